@@ -1,11 +1,53 @@
 package com.keep_accounts.app
 
+import android.content.Intent
+import android.net.Uri
 import android.os.Bundle
+import android.webkit.JavascriptInterface
+import android.webkit.WebView
 import androidx.activity.enableEdgeToEdge
+import androidx.core.content.FileProvider
+import java.io.File
+import org.json.JSONArray
 
 class MainActivity : TauriActivity() {
   override fun onCreate(savedInstanceState: Bundle?) {
     enableEdgeToEdge()
     super.onCreate(savedInstanceState)
   }
+
+  override fun onWebViewCreate(webView: WebView) {
+    webView.addJavascriptInterface(ShareBridge(), "KeepAccountsAndroid")
+  }
+
+  // Lets the web view hand exported files (in the app cache dir) to the share sheet
+  inner class ShareBridge {
+    @JavascriptInterface
+    fun shareFile(path: String, mimeType: String) {
+      val file = File(path)
+      val uri = FileProvider.getUriForFile(this@MainActivity, "$packageName.fileprovider", file)
+      val intent = Intent(Intent.ACTION_SEND).apply {
+        type = mimeType
+        putExtra(Intent.EXTRA_STREAM, uri)
+        addFlags(Intent.FLAG_GRANT_READ_URI_PERMISSION)
+      }
+      runOnUiThread { startActivity(Intent.createChooser(intent, file.name)) }
+    }
+
+    // `pathsJson` is a JSON array of paths, shared together (e.g. a CSV export and its installment schedule)
+    @JavascriptInterface
+    fun shareFiles(pathsJson: String, mimeType: String) {
+      val paths = JSONArray(pathsJson)
+      val uris = ArrayList<Uri>()
+      for (i in 0 until paths.length()) {
+        uris.add(FileProvider.getUriForFile(this@MainActivity, "$packageName.fileprovider", File(paths.getString(i))))
+      }
+      val intent = Intent(Intent.ACTION_SEND_MULTIPLE).apply {
+        type = mimeType
+        putParcelableArrayListExtra(Intent.EXTRA_STREAM, uris)
+        addFlags(Intent.FLAG_GRANT_READ_URI_PERMISSION)
+      }
+      runOnUiThread { startActivity(Intent.createChooser(intent, null)) }
+    }
+  }
 }
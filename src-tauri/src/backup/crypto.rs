@@ -0,0 +1,251 @@
+//! Password-protected backups.
+//!
+//! An encrypted backup is still a JSON document, so it can be picked, read and
+//! shared like a plain one. The backup text is sealed with XChaCha20-Poly1305
+//! under a key derived from the password with Argon2id; everything except the
+//! ciphertext (format, version, KDF parameters, nonce) is the associated data,
+//! so tampering with the header fails authentication just like tampering with
+//! the data. The two cases that cannot be told apart are a wrong password and
+//! a damaged ciphertext.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+
+/// Value of the `format` field identifying encrypted backups
+pub const ENCRYPTED_FORMAT: &str = "keep-accounts-encrypted";
+
+/// Current envelope version; older versions stay readable
+pub const ENCRYPTED_VERSION: u32 = 1;
+
+const KDF_ALGORITHM: &str = "argon2id";
+const CIPHER: &str = "xchacha20poly1305";
+
+/// Argon2id cost for new backups (64 MiB, 3 passes), fine on phones too
+const MEMORY_KIB: u32 = 64 * 1024;
+const ITERATIONS: u32 = 3;
+const PARALLELISM: u32 = 1;
+
+/// Upper bounds accepted when reading, so a crafted file cannot make the
+/// KDF take all memory or run for minutes
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_ITERATIONS: u32 = 16;
+const MAX_PARALLELISM: u32 = 16;
+
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+/// Minimum password length accepted when encrypting
+pub const MIN_PASSWORD_LEN: usize = 6;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KdfParams {
+    algorithm: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    /// Base64
+    salt: String,
+}
+
+/// Authenticated part of the envelope
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Header {
+    format: String,
+    version: u32,
+    kdf: KdfParams,
+    cipher: String,
+    /// Base64
+    nonce: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Envelope {
+    #[serde(flatten)]
+    header: Header,
+    /// Base64
+    ciphertext: String,
+}
+
+impl Header {
+    /// Associated data: the header serialized in field order
+    fn aad(&self) -> Result<Vec<u8>, String> {
+        serde_json::to_vec(self).map_err(|e| e.to_string())
+    }
+}
+
+fn derive_key(password: &str, kdf: &KdfParams, salt: &[u8]) -> Result<[u8; KEY_LEN], String> {
+    let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(KEY_LEN))
+        .map_err(|e| format!("加密参数无效: {}", e))?;
+    let mut key = [0u8; KEY_LEN];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("密钥生成失败: {}", e))?;
+    Ok(key)
+}
+
+/// Random hex string of `bytes` bytes, e.g. for device ids
+pub fn random_hex(bytes: usize) -> String {
+    let mut buffer = vec![0u8; bytes];
+    OsRng.fill_bytes(&mut buffer);
+    buffer.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Whether `content` is an encrypted backup (without checking it further)
+pub fn is_encrypted(content: &str) -> bool {
+    #[derive(Deserialize)]
+    struct Probe {
+        format: Option<String>,
+    }
+    serde_json::from_str::<Probe>(content)
+        .map(|probe| probe.format.as_deref() == Some(ENCRYPTED_FORMAT))
+        .unwrap_or(false)
+}
+
+/// Seal `plaintext` with `password` into an encrypted backup document
+pub fn encrypt(plaintext: &[u8], password: &str) -> Result<String, String> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(format!("密码至少需要 {} 个字符", MIN_PASSWORD_LEN));
+    }
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let header = Header {
+        format: ENCRYPTED_FORMAT.to_string(),
+        version: ENCRYPTED_VERSION,
+        kdf: KdfParams {
+            algorithm: KDF_ALGORITHM.to_string(),
+            memory_kib: MEMORY_KIB,
+            iterations: ITERATIONS,
+            parallelism: PARALLELISM,
+            salt: BASE64.encode(salt),
+        },
+        cipher: CIPHER.to_string(),
+        nonce: BASE64.encode(nonce),
+    };
+    let key = derive_key(password, &header.kdf, &salt)?;
+    let aad = header.aad()?;
+    let ciphertext = XChaCha20Poly1305::new(&key.into())
+        .encrypt(&nonce, Payload { msg: plaintext, aad: &aad })
+        .map_err(|_| "加密失败".to_string())?;
+
+    let envelope = Envelope {
+        header,
+        ciphertext: BASE64.encode(ciphertext),
+    };
+    serde_json::to_string_pretty(&envelope).map_err(|e| e.to_string())
+}
+
+/// Open an encrypted backup document; fails on a wrong password, a damaged
+/// file or an envelope from a newer app version
+pub fn decrypt(content: &str, password: &str) -> Result<Vec<u8>, String> {
+    let envelope: Envelope =
+        serde_json::from_str(content).map_err(|e| format!("加密备份文件已损坏: {}", e))?;
+    let header = envelope.header;
+    if header.format != ENCRYPTED_FORMAT {
+        return Err("不是加密备份文件".to_string());
+    }
+    if header.version > ENCRYPTED_VERSION {
+        return Err(format!(
+            "加密备份的版本 {} 高于当前支持的 {}，请先升级应用",
+            header.version, ENCRYPTED_VERSION
+        ));
+    }
+    if header.kdf.algorithm != KDF_ALGORITHM || header.cipher != CIPHER {
+        return Err(format!("不支持的加密方式: {} / {}", header.kdf.algorithm, header.cipher));
+    }
+    if header.kdf.memory_kib > MAX_MEMORY_KIB
+        || header.kdf.iterations > MAX_ITERATIONS
+        || header.kdf.parallelism > MAX_PARALLELISM
+    {
+        return Err("加密备份文件已损坏: 密钥参数超出范围".to_string());
+    }
+
+    let decode = |field: &str, value: &str| {
+        BASE64
+            .decode(value)
+            .map_err(|_| format!("加密备份文件已损坏: {} 无效", field))
+    };
+    let salt = decode("salt", &header.kdf.salt)?;
+    let nonce = decode("nonce", &header.nonce)?;
+    let ciphertext = decode("ciphertext", &envelope.ciphertext)?;
+    if nonce.len() != 24 {
+        return Err("加密备份文件已损坏: nonce 无效".to_string());
+    }
+
+    let key = derive_key(password, &header.kdf, &salt)?;
+    let aad = header.aad()?;
+    XChaCha20Poly1305::new(&key.into())
+        .decrypt(XNonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: &aad })
+        .map_err(|_| "密码错误或备份文件已损坏".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWORD: &str = "correct horse";
+    const PLAINTEXT: &[u8] = "{\"format\":\"keep-accounts-backup\",\"note\":\"午饭\"}".as_bytes();
+
+    /// Change one field of the envelope JSON
+    fn edit(content: &str, change: impl FnOnce(&mut serde_json::Value)) -> String {
+        let mut value: serde_json::Value = serde_json::from_str(content).unwrap();
+        change(&mut value);
+        value.to_string()
+    }
+
+    #[test]
+    fn round_trip() {
+        let sealed = encrypt(PLAINTEXT, PASSWORD).unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.contains("午饭"));
+        assert_eq!(decrypt(&sealed, PASSWORD).unwrap(), PLAINTEXT);
+        // Fresh salt and nonce every time
+        assert_ne!(sealed, encrypt(PLAINTEXT, PASSWORD).unwrap());
+    }
+
+    #[test]
+    fn short_password_is_refused() {
+        assert!(encrypt(PLAINTEXT, "12345").is_err());
+    }
+
+    #[test]
+    fn wrong_password() {
+        let sealed = encrypt(PLAINTEXT, PASSWORD).unwrap();
+        assert_eq!(decrypt(&sealed, "wrong horse").unwrap_err(), "密码错误或备份文件已损坏");
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let sealed = encrypt(PLAINTEXT, PASSWORD).unwrap();
+
+        // The header is authenticated even where the change is harmless
+        let header = edit(&sealed, |v| v["kdf"]["iterations"] = 2.into());
+        assert_eq!(decrypt(&header, PASSWORD).unwrap_err(), "密码错误或备份文件已损坏");
+
+        let ciphertext = edit(&sealed, |v| {
+            let mut bytes = BASE64.decode(v["ciphertext"].as_str().unwrap()).unwrap();
+            bytes[0] ^= 1;
+            v["ciphertext"] = BASE64.encode(bytes).into();
+        });
+        assert_eq!(decrypt(&ciphertext, PASSWORD).unwrap_err(), "密码错误或备份文件已损坏");
+    }
+
+    #[test]
+    fn unsupported_envelopes_are_refused_before_the_kdf() {
+        let sealed = encrypt(PLAINTEXT, PASSWORD).unwrap();
+        let newer = edit(&sealed, |v| v["version"] = (ENCRYPTED_VERSION + 1).into());
+        assert!(decrypt(&newer, PASSWORD).unwrap_err().contains("请先升级应用"));
+        let costly = edit(&sealed, |v| v["kdf"]["memory_kib"] = (MAX_MEMORY_KIB + 1).into());
+        assert!(decrypt(&costly, PASSWORD).unwrap_err().contains("超出范围"));
+        let cipher = edit(&sealed, |v| v["cipher"] = "aes-256-gcm".into());
+        assert!(decrypt(&cipher, PASSWORD).unwrap_err().contains("不支持的加密方式"));
+        assert!(!is_encrypted("{\"format\":\"keep-accounts-backup\"}"));
+        assert!(!is_encrypted("not json"));
+    }
+}
@@ -0,0 +1,260 @@
+//! Backup document format, creation and validation.
+
+use super::crypto;
+use crate::models::{
+    BackupCounts, BackupInfo, Category, CategoryRule, CsvPreset, ImportedLine, Installment, InstallmentDetail,
+    RecurringBill, Setting, SmartView, Transaction,
+};
+use crate::repository::{
+    category_repo, import_repo, installment_repo, recurring_bill_repo, rule_repo, settings_repo, smart_view_repo,
+    transaction_repo,
+};
+use libsql::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Value of the `format` field identifying our backups
+pub const BACKUP_FORMAT: &str = "keep-accounts-backup";
+
+/// Current document version; bump it when the layout changes and convert
+/// older documents in `parse_backup`
+///
+/// 2: category rules and CSV import presets.
+/// 3: recurring bills.
+///
+/// Older documents simply lack the newer tables and read as having none.
+pub const BACKUP_VERSION: u32 = 3;
+
+/// Maximum number of validation problems reported at once
+const MAX_REPORTED_ERRORS: usize = 5;
+
+/// A full backup of the ledger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupDocument {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    pub app_version: String,
+    pub categories: Vec<Category>,
+    pub transactions: Vec<Transaction>,
+    pub installments: Vec<Installment>,
+    pub installment_details: Vec<InstallmentDetail>,
+    pub settings: Vec<Setting>,
+    #[serde(default)]
+    pub smart_views: Vec<SmartView>,
+    /// Statement lines already imported, so re-importing a bill after a
+    /// restore still skips them
+    #[serde(default)]
+    pub imported_lines: Vec<ImportedLine>,
+    #[serde(default)]
+    pub rules: Vec<CategoryRule>,
+    #[serde(default)]
+    pub csv_presets: Vec<CsvPreset>,
+    #[serde(default)]
+    pub recurring_bills: Vec<RecurringBill>,
+}
+
+impl BackupDocument {
+    pub fn counts(&self) -> BackupCounts {
+        BackupCounts {
+            categories: self.categories.len() as i64,
+            transactions: self.transactions.len() as i64,
+            installments: self.installments.len() as i64,
+            installment_details: self.installment_details.len() as i64,
+            settings: self.settings.len() as i64,
+            smart_views: self.smart_views.len() as i64,
+            rules: self.rules.len() as i64,
+            csv_presets: self.csv_presets.len() as i64,
+            recurring_bills: self.recurring_bills.len() as i64,
+        }
+    }
+
+    pub fn info(&self) -> BackupInfo {
+        BackupInfo {
+            version: self.version,
+            exported_at: self.exported_at.clone(),
+            counts: self.counts(),
+        }
+    }
+}
+
+/// Read the whole ledger into a backup document
+pub async fn create_backup(conn: &Connection) -> Result<BackupDocument, String> {
+    let mut transactions = transaction_repo::get_all_transactions(conn).await?;
+    transactions.sort_by_key(|tx| tx.id);
+
+    Ok(BackupDocument {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        exported_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        categories: category_repo::get_all_categories(conn).await?,
+        transactions,
+        installments: installment_repo::get_all_installments(conn).await?,
+        installment_details: installment_repo::get_all_installment_details(conn).await?,
+        settings: settings_repo::get_all_settings(conn).await?,
+        smart_views: smart_view_repo::get_smart_views(conn).await?,
+        imported_lines: import_repo::get_all_imported_lines(conn).await?,
+        rules: rule_repo::get_rules(conn).await?,
+        csv_presets: import_repo::get_csv_presets(conn).await?,
+        recurring_bills: recurring_bill_repo::get_recurring_bills(conn).await?,
+    })
+}
+
+/// Parse and validate a backup document
+pub fn parse_backup(content: &str) -> Result<BackupDocument, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("备份文件不是有效的 JSON: {}", e))?;
+
+    if value.get("format").and_then(|f| f.as_str()) != Some(BACKUP_FORMAT) {
+        return Err("这不是简易记账的备份文件".to_string());
+    }
+    let version = value
+        .get("version")
+        .and_then(|v| v.as_u64())
+        .ok_or("备份文件缺少版本号")?;
+    if version == 0 || version > BACKUP_VERSION as u64 {
+        return Err(format!(
+            "备份文件版本 {} 不受支持（当前支持到 {}），请先升级应用",
+            version, BACKUP_VERSION
+        ));
+    }
+
+    let document: BackupDocument =
+        serde_json::from_value(value).map_err(|e| format!("备份文件内容不完整: {}", e))?;
+    validate(&document)?;
+    Ok(document)
+}
+
+/// Parse a backup that may be password-protected
+pub async fn open_backup(content: &str, password: Option<&str>) -> Result<BackupDocument, String> {
+    if !crypto::is_encrypted(content) {
+        return parse_backup(content);
+    }
+    let password = password.filter(|p| !p.is_empty()).ok_or("备份已加密，请输入密码")?.to_string();
+    let content = content.to_string();
+    // The key derivation takes a good part of a second; keep it off the async workers
+    let plaintext = tauri::async_runtime::spawn_blocking(move || crypto::decrypt(&content, &password))
+        .await
+        .map_err(|e| e.to_string())??;
+    let content = String::from_utf8(plaintext).map_err(|_| "备份文件已损坏".to_string())?;
+    parse_backup(&content)
+}
+
+/// Serialize a backup, sealed with `password` when one is given
+pub async fn write_backup(document: &BackupDocument, password: Option<&str>) -> Result<String, String> {
+    let content = serde_json::to_string_pretty(document).map_err(|e| e.to_string())?;
+    match password {
+        Some(password) => {
+            let password = password.to_string();
+            tauri::async_runtime::spawn_blocking(move || crypto::encrypt(content.as_bytes(), &password))
+                .await
+                .map_err(|e| e.to_string())?
+        }
+        None => Ok(content),
+    }
+}
+
+fn is_valid_date(value: &str) -> bool {
+    value
+        .get(..10)
+        .is_some_and(|day| chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d").is_ok())
+}
+
+/// Collect the ids of a table, reporting duplicates
+fn unique_ids(table: &str, ids: impl Iterator<Item = i64>, errors: &mut Vec<String>) -> HashSet<i64> {
+    let mut seen = HashSet::new();
+    for id in ids {
+        if !seen.insert(id) {
+            errors.push(format!("{} 中 id {} 重复", table, id));
+        }
+    }
+    seen
+}
+
+/// Check ids are unique and every reference points into the document
+fn validate(document: &BackupDocument) -> Result<(), String> {
+    let mut errors = Vec::new();
+
+    let category_ids = unique_ids("categories", document.categories.iter().map(|c| c.id), &mut errors);
+    let transaction_ids = unique_ids("transactions", document.transactions.iter().map(|t| t.id), &mut errors);
+    let installment_ids = unique_ids("installments", document.installments.iter().map(|i| i.id), &mut errors);
+    unique_ids("installment_details", document.installment_details.iter().map(|d| d.id), &mut errors);
+
+    for tx in &document.transactions {
+        if !category_ids.contains(&tx.category_id) {
+            errors.push(format!("交易 {} 引用了不存在的分类 {}", tx.id, tx.category_id));
+        }
+        if !is_valid_date(&tx.transaction_date) {
+            errors.push(format!("交易 {} 的日期无效: {}", tx.id, tx.transaction_date));
+        }
+    }
+    for installment in &document.installments {
+        if !category_ids.contains(&installment.category_id) {
+            errors.push(format!("分期 {} 引用了不存在的分类 {}", installment.id, installment.category_id));
+        }
+        if installment.installment_count <= 0 {
+            errors.push(format!("分期 {} 的期数无效: {}", installment.id, installment.installment_count));
+        }
+    }
+    for detail in &document.installment_details {
+        if !installment_ids.contains(&detail.installment_id) {
+            errors.push(format!("分期明细 {} 引用了不存在的分期 {}", detail.id, detail.installment_id));
+        }
+        if !is_valid_date(&detail.due_date) {
+            errors.push(format!("分期明细 {} 的到期日无效: {}", detail.id, detail.due_date));
+        }
+    }
+
+    let mut lines = HashSet::new();
+    for line in &document.imported_lines {
+        if !lines.insert((&line.source, &line.account, &line.external_id)) {
+            errors.push(format!("已导入的账单行 {} 重复", line.external_id));
+        }
+        if let Some(id) = line.transaction_id.filter(|id| !transaction_ids.contains(id)) {
+            errors.push(format!("已导入的账单行 {} 引用了不存在的交易 {}", line.external_id, id));
+        }
+    }
+
+    let mut keys = HashSet::new();
+    for setting in &document.settings {
+        if !keys.insert(setting.key.as_str()) {
+            errors.push(format!("设置项 {} 重复", setting.key));
+        }
+    }
+    let mut names = HashSet::new();
+    for view in &document.smart_views {
+        if !names.insert(view.name.as_str()) {
+            errors.push(format!("智能视图 {} 重复", view.name));
+        }
+    }
+    unique_ids("category_rules", document.rules.iter().map(|r| r.id), &mut errors);
+    for rule in &document.rules {
+        if let Some(id) = rule.category_id.filter(|id| !category_ids.contains(id)) {
+            errors.push(format!("分类规则 {} 引用了不存在的分类 {}", rule.name, id));
+        }
+    }
+    let mut names = HashSet::new();
+    for preset in &document.csv_presets {
+        if !names.insert(preset.name.as_str()) {
+            errors.push(format!("CSV 预设 {} 重复", preset.name));
+        }
+    }
+    unique_ids("recurring_bills", document.recurring_bills.iter().map(|b| b.id), &mut errors);
+    for bill in &document.recurring_bills {
+        if !is_valid_date(&bill.start_date) {
+            errors.push(format!("周期账单 {} 的开始日期无效: {}", bill.name, bill.start_date));
+        }
+    }
+
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let more = errors.len().saturating_sub(MAX_REPORTED_ERRORS);
+    errors.truncate(MAX_REPORTED_ERRORS);
+    let mut message = format!("备份校验失败: {}", errors.join("；"));
+    if more > 0 {
+        message.push_str(&format!("；另有 {} 处问题", more));
+    }
+    Err(message)
+}
@@ -0,0 +1,284 @@
+//! Backups to a folder of the user's choice, typically one kept in sync
+//! between devices by Nextcloud, Syncthing or a cloud drive.
+//!
+//! Each device writes `keep-accounts-<UTC time>-<device id>.json` files (plain
+//! or password-protected JSON backups) when the ledger changed, keeps its own
+//! latest `keep` and never touches the files of other devices. A backup from
+//! another device newer than the last one handled here is offered for restore
+//! or merge.
+//!
+//! The settings live in `backup_folder.json` next to the database rather than
+//! in the settings table: the folder path is different on every device, and
+//! the password must not end up inside the backups it protects.
+//!
+//! The password is kept there in plain text (readable only by the user on
+//! Unix), not in the OS keychain: there is no keychain backend for Android,
+//! and backups are written unattended, so it has to be at hand without a
+//! prompt. It protects the copies in the synced folder, not this device. It is
+//! dropped as soon as encryption or folder backups are turned off.
+
+use super::{create_backup, crypto, open_backup, write_backup, BackupDocument};
+use crate::models::{BackupFolderSettings, FolderBackup};
+use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+use libsql::Connection;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const CONFIG_FILE: &str = "backup_folder.json";
+const FILE_PREFIX: &str = "keep-accounts-";
+const FILE_EXTENSION: &str = ".json";
+const TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// Backups of this device kept in the folder unless configured otherwise
+const DEFAULT_KEEP: u32 = 10;
+
+/// Device-local state, stored as JSON
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct FolderConfig {
+    /// Random id naming this device's files
+    #[serde(default)]
+    device_id: String,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    keep: Option<u32>,
+    #[serde(default)]
+    password: Option<String>,
+    /// SHA-256 of the ledger as last written, to skip unchanged backups
+    #[serde(default)]
+    last_hash: Option<String>,
+    /// Newest backup of another device that was restored, merged or dismissed
+    #[serde(default)]
+    handled: Option<String>,
+}
+
+/// Path of the folder backup settings of the database at `db_path`
+pub fn config_path(db_path: &Path) -> PathBuf {
+    db_path.with_file_name(CONFIG_FILE)
+}
+
+fn load_config(config_path: &Path) -> Result<FolderConfig, String> {
+    let mut config = match std::fs::read_to_string(config_path) {
+        Ok(text) => serde_json::from_str(&text).map_err(|e| format!("备份文件夹设置已损坏: {}", e))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => FolderConfig::default(),
+        Err(e) => return Err(e.to_string()),
+    };
+    if config.device_id.is_empty() {
+        config.device_id = crypto::random_hex(4);
+        save_config(config_path, &config)?;
+    }
+    Ok(config)
+}
+
+/// Write the settings through a new file, renamed into place
+///
+/// The file may hold the backup password, so on Unix it is created readable
+/// by the user only and never exists with wider permissions.
+fn save_config(config_path: &Path, config: &FolderConfig) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    let partial = config_path.with_extension("json.partial");
+    let _ = std::fs::remove_file(&partial);
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&partial).map_err(|e| e.to_string())?;
+    file.write_all(text.as_bytes()).map_err(|e| e.to_string())?;
+    drop(file);
+    std::fs::rename(&partial, config_path).map_err(|e| e.to_string())
+}
+
+/// Parse a backup file name into its time (UTC) and device id
+fn parse_file_name(file_name: &str) -> Option<(NaiveDateTime, &str)> {
+    let stem = file_name.strip_prefix(FILE_PREFIX)?.strip_suffix(FILE_EXTENSION)?;
+    let (time, device_id) = (stem.get(..15)?, stem.get(16..)?);
+    let time = NaiveDateTime::parse_from_str(time, TIME_FORMAT).ok()?;
+    if device_id.is_empty() || !device_id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some((time, device_id))
+}
+
+pub fn get_settings(config_path: &Path) -> Result<BackupFolderSettings, String> {
+    let config = load_config(config_path)?;
+    Ok(BackupFolderSettings {
+        path: config.path,
+        keep: config.keep.unwrap_or(DEFAULT_KEEP),
+        encrypted: config.password.is_some(),
+    })
+}
+
+/// Choose the folder (None turns folder backups off and forgets the
+/// password). `password` None keeps the current one; an empty password turns
+/// encryption off.
+pub fn set_settings(
+    config_path: &Path,
+    path: Option<String>,
+    keep: u32,
+    password: Option<String>,
+) -> Result<(), String> {
+    if keep == 0 {
+        return Err("至少要保留 1 个备份".to_string());
+    }
+    let path = path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    if let Some(path) = &path {
+        check_writable(Path::new(path))?;
+    }
+    if let Some(password) = password.as_deref().filter(|p| !p.is_empty()) {
+        if password.chars().count() < crypto::MIN_PASSWORD_LEN {
+            return Err(format!("密码至少需要 {} 个字符", crypto::MIN_PASSWORD_LEN));
+        }
+    }
+
+    let mut config = load_config(config_path)?;
+    if config.path != path {
+        // A different folder may hold other backups
+        config.handled = None;
+    }
+    if let Some(password) = password {
+        config.password = Some(password).filter(|p| !p.is_empty());
+    }
+    if path.is_none() {
+        config.password = None;
+    }
+    config.path = path;
+    config.keep = Some(keep);
+    // Settings changed, so the next backup is written even if the data did not
+    config.last_hash = None;
+    save_config(config_path, &config)
+}
+
+fn check_writable(dir: &Path) -> Result<(), String> {
+    if !dir.is_dir() {
+        return Err(format!("文件夹不存在: {}", dir.display()));
+    }
+    let probe = dir.join(format!(".{}write-test", FILE_PREFIX));
+    std::fs::write(&probe, b"").map_err(|e| format!("无法写入 {}: {}", dir.display(), e))?;
+    let _ = std::fs::remove_file(probe);
+    Ok(())
+}
+
+/// All backups in the configured folder, newest first
+pub fn list_backups(config_path: &Path) -> Result<Vec<FolderBackup>, String> {
+    let config = load_config(config_path)?;
+    let Some(dir) = &config.path else { return Ok(Vec::new()) };
+    list_dir(Path::new(dir), &config.device_id)
+}
+
+fn list_dir(dir: &Path, own_device: &str) -> Result<Vec<FolderBackup>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("无法读取 {}: {}", dir.display(), e))?;
+    let mut backups = Vec::new();
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some((time, device_id)) = parse_file_name(&file_name) else { continue };
+        backups.push(FolderBackup {
+            created_at: Utc
+                .from_utc_datetime(&time)
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            device_id: device_id.to_string(),
+            from_this_device: device_id == own_device,
+            size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+            file_name,
+        });
+    }
+    // The timestamp leads the name, so names sort by time
+    backups.sort_by(|a, b| b.file_name.cmp(&a.file_name));
+    Ok(backups)
+}
+
+/// SHA-256 (hex) of the ledger content, ignoring when the document was made
+///
+/// Stored in the settings, so it has to stay the same across app versions.
+fn content_hash(document: &BackupDocument) -> Result<String, String> {
+    let mut document = document.clone();
+    document.exported_at.clear();
+    let content = serde_json::to_string(&document).map_err(|e| e.to_string())?;
+    Ok(Sha256::digest(content.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Write a backup into the folder and rotate this device's files. Unless
+/// `force`, nothing is written when the ledger is unchanged since the last
+/// one. Returns None when no folder is configured or nothing was written.
+pub async fn write_folder_backup(
+    conn: &Connection,
+    config_path: &Path,
+    force: bool,
+) -> Result<Option<FolderBackup>, String> {
+    let mut config = load_config(config_path)?;
+    let Some(dir) = config.path.clone().map(PathBuf::from) else { return Ok(None) };
+
+    let document = create_backup(conn).await?;
+    let hash = content_hash(&document)?;
+    if !force && config.last_hash.as_ref() == Some(&hash) {
+        return Ok(None);
+    }
+    let content = write_backup(&document, config.password.as_deref()).await?;
+
+    let file_name = format!(
+        "{}{}-{}{}",
+        FILE_PREFIX,
+        Utc::now().format(TIME_FORMAT),
+        config.device_id,
+        FILE_EXTENSION
+    );
+    // Written under another name first so sync tools never pick up half a file
+    let partial = dir.join(format!(".{}.partial", file_name));
+    std::fs::write(&partial, content.as_bytes()).map_err(|e| format!("写入备份失败: {}", e))?;
+    std::fs::rename(&partial, dir.join(&file_name)).map_err(|e| format!("写入备份失败: {}", e))?;
+
+    config.last_hash = Some(hash);
+    save_config(config_path, &config)?;
+
+    let keep = config.keep.unwrap_or(DEFAULT_KEEP) as usize;
+    let backups = list_dir(&dir, &config.device_id)?;
+    for old in backups.iter().filter(|b| b.from_this_device).skip(keep) {
+        if let Err(e) = std::fs::remove_file(dir.join(&old.file_name)) {
+            log::warn!("Failed to remove old folder backup {}: {}", old.file_name, e);
+        }
+    }
+    Ok(backups.into_iter().find(|b| b.file_name == file_name))
+}
+
+/// The newest backup written by another device, if it is newer than the
+/// last one restored, merged or dismissed here
+pub fn newer_backup(config_path: &Path) -> Result<Option<FolderBackup>, String> {
+    let config = load_config(config_path)?;
+    let Some(dir) = &config.path else { return Ok(None) };
+    let newest = list_dir(Path::new(dir), &config.device_id)?
+        .into_iter()
+        .find(|b| !b.from_this_device);
+    Ok(newest.filter(|b| config.handled.as_ref().map_or(true, |handled| &b.file_name > handled)))
+}
+
+/// Read and open a backup from the folder, with the configured password
+pub async fn read_backup(config_path: &Path, file_name: &str) -> Result<BackupDocument, String> {
+    let config = load_config(config_path)?;
+    let dir = config.path.as_ref().ok_or("未设置备份文件夹")?;
+    if parse_file_name(file_name).is_none() {
+        return Err(format!("无效的备份文件: {}", file_name));
+    }
+    let content = std::fs::read_to_string(Path::new(dir).join(file_name))
+        .map_err(|e| format!("读取备份失败: {}", e))?;
+    if crypto::is_encrypted(&content) && config.password.is_none() {
+        return Err("该备份已加密，请在备份文件夹设置中填写写入它的设备所用的密码".to_string());
+    }
+    open_backup(&content, config.password.as_deref()).await
+}
+
+/// Remember that a backup of another device has been dealt with
+pub fn mark_handled(config_path: &Path, file_name: &str) -> Result<(), String> {
+    let mut config = load_config(config_path)?;
+    if config.handled.as_deref().map_or(true, |handled| file_name > handled) {
+        config.handled = Some(file_name.to_string());
+        save_config(config_path, &config)?;
+    }
+    Ok(())
+}
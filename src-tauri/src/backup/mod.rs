@@ -0,0 +1,25 @@
+//! Backup
+//!
+//! Lossless JSON backups of the whole ledger: categories, transactions,
+//! installments with their schedules, settings, smart views, category rules,
+//! CSV import presets and the record of imported statement lines. Unlike the
+//! `accounts.db` file (and the `.db.legacy` copy handled by
+//! `db::migrate_from_legacy`), a backup is a single versioned document that
+//! can be validated and restored into an empty database or merged into an
+//! existing one.
+//!
+//! Local snapshots (`snapshot`) are the automatic safety net: copies of the
+//! database file itself, rotated and restorable with one click.
+//!
+//! A backup can be sealed with a password (`crypto`); `open_backup` reads
+//! both kinds. `folder` writes backups into a folder synced by a third-party
+//! tool and picks up the ones other devices left there.
+
+pub mod crypto;
+mod document;
+pub mod folder;
+mod restore;
+pub mod snapshot;
+
+pub use document::{create_backup, open_backup, write_backup, BackupDocument};
+pub use restore::restore_backup;
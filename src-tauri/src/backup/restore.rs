@@ -0,0 +1,492 @@
+//! Restoring a backup document into the database.
+
+use super::BackupDocument;
+use crate::models::{
+    CategoryRule, CsvPreset, ImportedLine, Installment, InstallmentDetail, RecurringBill, RestoreMode, RestoreSummary,
+    Setting, SmartView, Transaction,
+};
+use libsql::Connection;
+use std::collections::HashMap;
+
+/// Restore a validated backup, all or nothing
+///
+/// `Replace` empties the ledger and imports the backup with its original ids.
+/// `Merge` keeps existing data: categories are matched by name, and
+/// transactions, installments, settings, smart views, rules, CSV presets and
+/// recurring bills already present are skipped, so merging the same backup
+/// twice adds nothing. Merged rules are matched after the existing ones. Imported
+/// statement lines follow their transactions in both modes.
+pub async fn restore_backup(
+    conn: &Connection,
+    document: &BackupDocument,
+    mode: RestoreMode,
+) -> Result<RestoreSummary, String> {
+    let tx = conn.transaction().await.map_err(|e| e.to_string())?;
+    let result = match mode {
+        RestoreMode::Replace => replace(&tx, document).await,
+        RestoreMode::Merge => merge(&tx, document).await,
+    };
+    match result {
+        Ok(summary) => {
+            tx.commit().await.map_err(|e| e.to_string())?;
+            Ok(summary)
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            Err(e)
+        }
+    }
+}
+
+async fn replace(conn: &Connection, document: &BackupDocument) -> Result<RestoreSummary, String> {
+    for table in [
+        // Dismissed duplicate pairs refer to transaction ids, which now belong
+        // to other transactions; they are not part of the backup
+        "duplicate_dismissals",
+        "imported_lines",
+        "installment_details",
+        "installments",
+        "transactions",
+        "categories",
+        "settings",
+        "smart_views",
+        "category_rules",
+        "csv_import_presets",
+        "recurring_bills",
+    ] {
+        conn.execute(&format!("DELETE FROM {}", table), ())
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    for category in &document.categories {
+        conn.execute(
+            "INSERT INTO categories (id, name, icon, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
+            libsql::params![
+                category.id,
+                category.name.clone(),
+                category.icon.clone(),
+                category.created_at.clone(),
+                category.updated_at.clone()
+            ],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+    for transaction in &document.transactions {
+        insert_transaction(conn, Some(transaction.id), transaction.category_id, transaction).await?;
+    }
+    for line in &document.imported_lines {
+        insert_imported_line(conn, line, line.transaction_id).await?;
+    }
+    for installment in &document.installments {
+        insert_installment(conn, Some(installment.id), installment.category_id, installment).await?;
+    }
+    for detail in &document.installment_details {
+        insert_installment_detail(conn, Some(detail.id), detail.installment_id, detail).await?;
+    }
+    for setting in &document.settings {
+        insert_setting(conn, setting).await?;
+    }
+    for view in &document.smart_views {
+        insert_smart_view(conn, Some(view.id), view).await?;
+    }
+    for rule in &document.rules {
+        insert_rule(conn, Some(rule.id), Some(rule.priority), rule.category_id, rule).await?;
+    }
+    for preset in &document.csv_presets {
+        insert_csv_preset(conn, Some(preset.id), preset).await?;
+    }
+    for bill in &document.recurring_bills {
+        insert_recurring_bill(conn, Some(bill.id), bill).await?;
+    }
+
+    Ok(RestoreSummary {
+        imported: document.counts(),
+        skipped: Default::default(),
+    })
+}
+
+async fn merge(conn: &Connection, document: &BackupDocument) -> Result<RestoreSummary, String> {
+    let mut summary = RestoreSummary::default();
+
+    // Backup category id -> id in this database
+    let mut category_ids = HashMap::new();
+    let existing = category_ids_by_name(conn).await?;
+    for category in &document.categories {
+        if let Some(id) = existing.get(&category.name) {
+            category_ids.insert(category.id, *id);
+            summary.skipped.categories += 1;
+        } else {
+            conn.execute(
+                "INSERT INTO categories (name, icon, created_at, updated_at) VALUES (?, ?, ?, ?)",
+                libsql::params![
+                    category.name.clone(),
+                    category.icon.clone(),
+                    category.created_at.clone(),
+                    category.updated_at.clone()
+                ],
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+            category_ids.insert(category.id, conn.last_insert_rowid());
+            summary.imported.categories += 1;
+        }
+    }
+    let category_id = |id: i64| category_ids.get(&id).copied().ok_or(format!("未知分类 {}", id));
+
+    // Existing rows are counted per key, so repeated identical transactions in
+    // the backup (two coffees on the same day) are only skipped as often as
+    // they already exist.
+    let mut existing_transactions = count_keys(
+        conn,
+        "SELECT category_id, printf('%.2f', amount), transaction_date, COALESCE(note, '') FROM transactions",
+    )
+    .await?;
+    // Backup transaction id -> id of the new row; skipped transactions are
+    // left out, their lines are still recorded so they are not imported again
+    let mut transaction_ids = HashMap::new();
+    let mut transactions: Vec<_> = document.transactions.iter().collect();
+    transactions.sort_by_key(|t| t.id);
+    for transaction in transactions {
+        let category = category_id(transaction.category_id)?;
+        let key = vec![
+            category.to_string(),
+            format!("{:.2}", transaction.amount),
+            transaction.transaction_date.clone(),
+            transaction.note.clone().unwrap_or_default(),
+        ];
+        if take_key(&mut existing_transactions, &key) {
+            summary.skipped.transactions += 1;
+        } else {
+            insert_transaction(conn, None, category, transaction).await?;
+            transaction_ids.insert(transaction.id, conn.last_insert_rowid());
+            summary.imported.transactions += 1;
+        }
+    }
+    for line in &document.imported_lines {
+        let transaction_id = line.transaction_id.and_then(|id| transaction_ids.get(&id).copied());
+        insert_imported_line(conn, line, transaction_id).await?;
+    }
+
+    let mut existing_installments = count_keys(
+        conn,
+        "SELECT category_id, printf('%.2f', total_amount), installment_count, start_date, COALESCE(note, '')
+         FROM installments",
+    )
+    .await?;
+    for installment in &document.installments {
+        let category = category_id(installment.category_id)?;
+        let key = vec![
+            category.to_string(),
+            format!("{:.2}", installment.total_amount),
+            installment.installment_count.to_string(),
+            installment.start_date.clone(),
+            installment.note.clone().unwrap_or_default(),
+        ];
+        let details: Vec<_> = document
+            .installment_details
+            .iter()
+            .filter(|d| d.installment_id == installment.id)
+            .collect();
+        if take_key(&mut existing_installments, &key) {
+            summary.skipped.installments += 1;
+            summary.skipped.installment_details += details.len() as i64;
+            continue;
+        }
+        insert_installment(conn, None, category, installment).await?;
+        let installment_id = conn.last_insert_rowid();
+        summary.imported.installments += 1;
+        for detail in details {
+            insert_installment_detail(conn, None, installment_id, detail).await?;
+            summary.imported.installment_details += 1;
+        }
+    }
+
+    let existing_settings = count_keys(conn, "SELECT key FROM settings").await?;
+    for setting in &document.settings {
+        if existing_settings.contains_key(&vec![setting.key.clone()]) {
+            summary.skipped.settings += 1;
+        } else {
+            insert_setting(conn, setting).await?;
+            summary.imported.settings += 1;
+        }
+    }
+
+    let existing_views = count_keys(conn, "SELECT name FROM smart_views").await?;
+    for view in &document.smart_views {
+        if existing_views.contains_key(&vec![view.name.clone()]) {
+            summary.skipped.smart_views += 1;
+        } else {
+            insert_smart_view(conn, None, view).await?;
+            summary.imported.smart_views += 1;
+        }
+    }
+
+    let existing_rules = count_keys(conn, "SELECT name FROM category_rules").await?;
+    let mut rules: Vec<_> = document.rules.iter().collect();
+    rules.sort_by_key(|r| (r.priority, r.id));
+    for rule in rules {
+        if existing_rules.contains_key(&vec![rule.name.clone()]) {
+            summary.skipped.rules += 1;
+        } else {
+            let category = rule.category_id.map(category_id).transpose()?;
+            insert_rule(conn, None, None, category, rule).await?;
+            summary.imported.rules += 1;
+        }
+    }
+
+    let existing_presets = count_keys(conn, "SELECT name FROM csv_import_presets").await?;
+    for preset in &document.csv_presets {
+        if existing_presets.contains_key(&vec![preset.name.clone()]) {
+            summary.skipped.csv_presets += 1;
+        } else {
+            insert_csv_preset(conn, None, preset).await?;
+            summary.imported.csv_presets += 1;
+        }
+    }
+
+    let mut existing_bills = count_keys(
+        conn,
+        "SELECT name, printf('%.2f', amount), interval_months, start_date FROM recurring_bills",
+    )
+    .await?;
+    for bill in &document.recurring_bills {
+        let key = vec![
+            bill.name.clone(),
+            format!("{:.2}", bill.amount),
+            bill.interval_months.to_string(),
+            bill.start_date.clone(),
+        ];
+        if take_key(&mut existing_bills, &key) {
+            summary.skipped.recurring_bills += 1;
+        } else {
+            insert_recurring_bill(conn, None, bill).await?;
+            summary.imported.recurring_bills += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
+async fn category_ids_by_name(conn: &Connection) -> Result<HashMap<String, i64>, String> {
+    let mut rows = conn
+        .query("SELECT name, id FROM categories", ())
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut ids = HashMap::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        ids.insert(
+            row.get::<String>(0).map_err(|e| e.to_string())?,
+            row.get::<i64>(1).map_err(|e| e.to_string())?,
+        );
+    }
+    Ok(ids)
+}
+
+/// Count how often each row (all columns rendered as text) occurs
+async fn count_keys(conn: &Connection, sql: &str) -> Result<HashMap<Vec<String>, usize>, String> {
+    let mut rows = conn.query(sql, ()).await.map_err(|e| e.to_string())?;
+    let mut counts = HashMap::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        let mut key = Vec::new();
+        for i in 0..row.column_count() {
+            key.push(match row.get_value(i).map_err(|e| e.to_string())? {
+                libsql::Value::Integer(n) => n.to_string(),
+                libsql::Value::Real(n) => n.to_string(),
+                libsql::Value::Text(s) => s,
+                _ => String::new(),
+            });
+        }
+        *counts.entry(key).or_insert(0) += 1;
+    }
+    Ok(counts)
+}
+
+/// Consume one occurrence of `key`, returning whether it existed
+fn take_key(counts: &mut HashMap<Vec<String>, usize>, key: &[String]) -> bool {
+    match counts.get_mut(key) {
+        Some(count) if *count > 0 => {
+            *count -= 1;
+            true
+        }
+        _ => false,
+    }
+}
+
+async fn insert_transaction(
+    conn: &Connection,
+    id: Option<i64>,
+    category_id: i64,
+    transaction: &Transaction,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO transactions (id, category_id, amount, transaction_date, note, created_at) VALUES (?, ?, ?, ?, ?, ?)",
+        libsql::params![
+            id,
+            category_id,
+            transaction.amount,
+            transaction.transaction_date.clone(),
+            transaction.note.clone(),
+            transaction.created_at.clone()
+        ],
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Record an imported line unless the same line is already recorded
+async fn insert_imported_line(
+    conn: &Connection,
+    line: &ImportedLine,
+    transaction_id: Option<i64>,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO imported_lines (source, account, external_id, transaction_id, imported_at)
+         VALUES (?, ?, ?, ?, ?)",
+        libsql::params![
+            line.source.clone(),
+            line.account.clone(),
+            line.external_id.clone(),
+            transaction_id,
+            line.imported_at.clone()
+        ],
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn insert_installment(
+    conn: &Connection,
+    id: Option<i64>,
+    category_id: i64,
+    installment: &Installment,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO installments (id, category_id, total_amount, installment_count, start_date, note, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+        libsql::params![
+            id,
+            category_id,
+            installment.total_amount,
+            installment.installment_count,
+            installment.start_date.clone(),
+            installment.note.clone(),
+            installment.created_at.clone()
+        ],
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn insert_installment_detail(
+    conn: &Connection,
+    id: Option<i64>,
+    installment_id: i64,
+    detail: &InstallmentDetail,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO installment_details (id, installment_id, sequence_number, amount, due_date, is_paid, paid_date)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+        libsql::params![
+            id,
+            installment_id,
+            detail.sequence_number,
+            detail.amount,
+            detail.due_date.clone(),
+            detail.is_paid as i32,
+            detail.paid_date.clone()
+        ],
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn insert_setting(conn: &Connection, setting: &Setting) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES (?, ?)",
+        libsql::params![setting.key.clone(), setting.value.clone()],
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn insert_smart_view(conn: &Connection, id: Option<i64>, view: &SmartView) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO smart_views (id, name, query, created_at) VALUES (?, ?, ?, ?)",
+        libsql::params![id, view.name.clone(), view.query.clone(), view.created_at.clone()],
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Insert a rule; without a priority it is matched after the existing ones
+async fn insert_rule(
+    conn: &Connection,
+    id: Option<i64>,
+    priority: Option<i64>,
+    category_id: Option<i64>,
+    rule: &CategoryRule,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO category_rules
+            (id, name, priority, enabled, pattern, is_regex, min_amount, max_amount, account, category_id, tags,
+             note_rewrite, created_at)
+         VALUES (?, ?, COALESCE(?, (SELECT COALESCE(MAX(priority), 0) + 1 FROM category_rules)),
+                 ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        libsql::params![
+            id,
+            rule.name.clone(),
+            priority,
+            rule.enabled,
+            rule.pattern.clone(),
+            rule.is_regex,
+            rule.min_amount,
+            rule.max_amount,
+            rule.account.clone(),
+            category_id,
+            rule.tags.join(" "),
+            rule.note_rewrite.clone(),
+            rule.created_at.clone()
+        ],
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn insert_csv_preset(conn: &Connection, id: Option<i64>, preset: &CsvPreset) -> Result<(), String> {
+    let mapping = serde_json::to_string(&preset.mapping).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO csv_import_presets (id, name, mapping, created_at) VALUES (?, ?, ?, ?)",
+        libsql::params![id, preset.name.clone(), mapping, preset.created_at.clone()],
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn insert_recurring_bill(conn: &Connection, id: Option<i64>, bill: &RecurringBill) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO recurring_bills (id, name, amount, interval_months, start_date, end_date, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+        libsql::params![
+            id,
+            bill.name.clone(),
+            bill.amount,
+            bill.interval_months,
+            bill.start_date.clone(),
+            bill.end_date.clone(),
+            bill.created_at.clone()
+        ],
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,319 @@
+//! Local snapshots: full copies of `accounts.db` in a `backups/` directory
+//! next to it, written with `VACUUM INTO`.
+//!
+//! Snapshots are taken on start (before migrations run), once a day while the
+//! app is open, on demand and before operations that rewrite or delete a lot
+//! of data. After each one the directory is rotated by the `SnapshotPolicy`.
+//! File names carry the time and reason, e.g.
+//! `accounts-20241018-093000-before-import.db`.
+
+use crate::models::{SnapshotInfo, SnapshotPolicy, SnapshotReason};
+use crate::repository::settings_repo;
+use chrono::{Datelike, Local, NaiveDateTime};
+use libsql::Connection;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+const FILE_PREFIX: &str = "accounts-";
+const FILE_EXTENSION: &str = ".db";
+const TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// Directory holding the snapshots of the database at `db_path`
+pub fn snapshot_dir(db_path: &Path) -> PathBuf {
+    db_path.with_file_name("backups")
+}
+
+/// Parse a snapshot file name; None for anything else in the directory
+fn parse_file_name(file_name: &str) -> Option<(NaiveDateTime, SnapshotReason)> {
+    let stem = file_name.strip_prefix(FILE_PREFIX)?.strip_suffix(FILE_EXTENSION)?;
+    let (time, reason) = (stem.get(..15)?, stem.get(16..)?);
+    let time = NaiveDateTime::parse_from_str(time, TIME_FORMAT).ok()?;
+    let reason = SnapshotReason::ALL.into_iter().find(|r| r.key() == reason)?;
+    Some((time, reason))
+}
+
+/// All snapshots in `dir`, newest first
+pub fn list_snapshots(dir: &Path) -> Result<Vec<SnapshotInfo>, String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut snapshots = Vec::new();
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some((time, reason)) = parse_file_name(&file_name) else { continue };
+        snapshots.push(SnapshotInfo {
+            file_name,
+            reason,
+            created_at: time.format("%Y-%m-%d %H:%M:%S").to_string(),
+            size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+        });
+    }
+    // The timestamp leads the name, so names sort by time
+    snapshots.sort_by(|a, b| b.file_name.cmp(&a.file_name));
+    Ok(snapshots)
+}
+
+/// The rotation policy saved in settings, or the default
+pub async fn get_policy(conn: &Connection) -> SnapshotPolicy {
+    settings_repo::get_setting(conn, settings_repo::SNAPSHOT_POLICY_KEY)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+pub async fn set_policy(conn: &Connection, policy: SnapshotPolicy) -> Result<(), String> {
+    if policy.keep_last == 0 {
+        return Err("至少要保留最近 1 个快照".to_string());
+    }
+    let value = serde_json::to_string(&policy).map_err(|e| e.to_string())?;
+    settings_repo::set_setting(conn, settings_repo::SNAPSHOT_POLICY_KEY, &value).await
+}
+
+/// Write a snapshot of the database behind `conn` into `dir`, then rotate
+pub async fn create_snapshot(conn: &Connection, dir: &Path, reason: SnapshotReason) -> Result<SnapshotInfo, String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let mut time = Local::now().naive_local();
+    // Two snapshots in the same second (e.g. a restore right after an
+    // import) get distinct names
+    let mut path;
+    loop {
+        path = dir.join(format!("{}{}-{}{}", FILE_PREFIX, time.format(TIME_FORMAT), reason.key(), FILE_EXTENSION));
+        if !path.exists() {
+            break;
+        }
+        time += chrono::Duration::seconds(1);
+    }
+
+    let target = path.to_str().ok_or("Invalid snapshot path")?;
+    conn.execute("VACUUM INTO ?", libsql::params![target])
+        .await
+        .map_err(|e| format!("创建快照失败: {}", e))?;
+    let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+
+    let policy = get_policy(conn).await;
+    if let Err(e) = rotate(dir, policy) {
+        log::warn!("Failed to rotate snapshots: {}", e);
+    }
+    Ok(SnapshotInfo {
+        file_name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        reason,
+        created_at: time.format("%Y-%m-%d %H:%M:%S").to_string(),
+        size,
+    })
+}
+
+/// Delete the snapshots the policy does not keep; returns how many
+pub fn rotate(dir: &Path, policy: SnapshotPolicy) -> Result<usize, String> {
+    let snapshots = list_snapshots(dir)?;
+    let mut keep: HashSet<&str> = HashSet::new();
+
+    keep.extend(snapshots.iter().take(policy.keep_last.max(1) as usize).map(|s| s.file_name.as_str()));
+    // Newest first, so the first snapshot seen of a day (week) is its latest
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+    for snapshot in &snapshots {
+        let Some((time, _)) = parse_file_name(&snapshot.file_name) else { continue };
+        let day = time.date();
+        if days.len() < policy.keep_daily as usize && days.insert(day) {
+            keep.insert(&snapshot.file_name);
+        }
+        let week = (day.iso_week().year(), day.iso_week().week());
+        if weeks.len() < policy.keep_weekly as usize && weeks.insert(week) {
+            keep.insert(&snapshot.file_name);
+        }
+    }
+
+    let mut removed = 0;
+    for snapshot in snapshots.iter().filter(|s| !keep.contains(s.file_name.as_str())) {
+        std::fs::remove_file(dir.join(&snapshot.file_name)).map_err(|e| e.to_string())?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Whether the newest snapshot is older than a day (or there is none)
+pub fn daily_snapshot_due(dir: &Path) -> bool {
+    let newest = list_snapshots(dir)
+        .ok()
+        .and_then(|snapshots| snapshots.first().and_then(|s| parse_file_name(&s.file_name)))
+        .map(|(time, _)| time);
+    match newest {
+        Some(time) => Local::now().naive_local() - time >= chrono::Duration::days(1),
+        None => true,
+    }
+}
+
+/// Snapshot the database file at `db_path` before the app opens it
+///
+/// Uses its own connection, so the copy is taken before migrations touch the
+/// schema; it is marked as a pre-migration snapshot when some are pending. A
+/// missing or empty database (first start) is not snapshotted.
+pub async fn snapshot_before_open(db_path: &Path) -> Result<Option<SnapshotInfo>, String> {
+    if std::fs::metadata(db_path).map(|m| m.len()).unwrap_or(0) == 0 {
+        return Ok(None);
+    }
+    let db = libsql::Builder::new_local(db_path)
+        .build()
+        .await
+        .map_err(|e| e.to_string())?;
+    let conn = db.connect().map_err(|e| e.to_string())?;
+    let reason = if crate::db::schema_version(&conn).await? < crate::db::SCHEMA_VERSION {
+        SnapshotReason::BeforeMigration
+    } else {
+        SnapshotReason::Startup
+    };
+    let snapshot = create_snapshot(&conn, &snapshot_dir(db_path), reason).await?;
+    Ok(Some(snapshot))
+}
+
+/// Replace the ledger with the contents of a snapshot, all or nothing
+///
+/// Tables are copied column by column, so a snapshot taken before a
+/// migration added a column restores with that column's default. The full
+/// text index is rebuilt afterwards.
+pub async fn restore_snapshot(conn: &Connection, dir: &Path, file_name: &str) -> Result<(), String> {
+    // Only names from `list_snapshots`, never a path elsewhere
+    if parse_file_name(file_name).is_none() || file_name.contains(['/', '\\']) {
+        return Err(format!("无效的快照: {}", file_name));
+    }
+    let path = dir.join(file_name);
+    if !path.is_file() {
+        return Err(format!("快照不存在: {}", file_name));
+    }
+    let source = path.to_str().ok_or("Invalid snapshot path")?;
+
+    conn.execute("ATTACH DATABASE ? AS snapshot", libsql::params![source])
+        .await
+        .map_err(|e| format!("无法打开快照: {}", e))?;
+    let result = copy_from_snapshot(conn).await;
+    if let Err(e) = conn.execute("DETACH DATABASE snapshot", ()).await {
+        log::warn!("Failed to detach snapshot: {}", e);
+    }
+    result
+}
+
+async fn copy_from_snapshot(conn: &Connection) -> Result<(), String> {
+    let mut rows = conn
+        .query("PRAGMA snapshot.quick_check", ())
+        .await
+        .map_err(|e| e.to_string())?;
+    let check: String = match rows.next().await.map_err(|e| e.to_string())? {
+        Some(row) => row.get(0).map_err(|e| e.to_string())?,
+        None => String::new(),
+    };
+    drop(rows);
+    if check != "ok" {
+        return Err(format!("快照已损坏: {}", check));
+    }
+
+    let tables = tables(conn, "main").await?;
+    let snapshot_tables = tables_set(conn).await?;
+    let tx = conn.transaction().await.map_err(|e| e.to_string())?;
+    let result = async {
+        tx.execute("PRAGMA defer_foreign_keys = ON", ()).await.map_err(|e| e.to_string())?;
+        for table in &tables {
+            tx.execute(&format!("DELETE FROM main.\"{}\"", table), ())
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        for table in tables.iter().filter(|t| snapshot_tables.contains(*t)) {
+            let main_columns = columns(&tx, "main", table).await?;
+            let snapshot_columns = columns(&tx, "snapshot", table).await?;
+            let shared: Vec<String> = main_columns
+                .into_iter()
+                .filter(|c| snapshot_columns.contains(c))
+                .map(|c| format!("\"{}\"", c))
+                .collect();
+            if shared.is_empty() {
+                continue;
+            }
+            let list = shared.join(", ");
+            tx.execute(
+                &format!("INSERT INTO main.\"{table}\" ({list}) SELECT {list} FROM snapshot.\"{table}\""),
+                (),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+        // Keep AUTOINCREMENT counters in step with the restored ids
+        if snapshot_tables.contains("sqlite_sequence") {
+            tx.execute("DELETE FROM main.sqlite_sequence", ()).await.map_err(|e| e.to_string())?;
+            tx.execute(
+                "INSERT INTO main.sqlite_sequence (name, seq) SELECT name, seq FROM snapshot.sqlite_sequence",
+                (),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+        tx.execute("INSERT INTO transactions_fts(transactions_fts) VALUES ('rebuild')", ())
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok::<(), String>(())
+    }
+    .await;
+
+    match result {
+        Ok(()) => tx.commit().await.map_err(|e| e.to_string()),
+        Err(e) => {
+            let _ = tx.rollback().await;
+            Err(format!("恢复快照失败: {}", e))
+        }
+    }
+}
+
+/// Ordinary tables of a schema, leaving out SQLite's own tables, virtual
+/// tables and the shadow tables behind them
+async fn tables(conn: &Connection, schema: &str) -> Result<Vec<String>, String> {
+    let mut rows = conn
+        .query(
+            &format!(
+                "SELECT name, sql FROM {schema}.sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name"
+            ),
+            (),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut all = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        let name: String = row.get(0).map_err(|e| e.to_string())?;
+        let sql: String = row.get(1).unwrap_or_default();
+        all.push((name, sql.trim_start().to_uppercase().starts_with("CREATE VIRTUAL TABLE")));
+    }
+    let virtual_tables: Vec<String> = all.iter().filter(|(_, v)| *v).map(|(name, _)| format!("{}_", name)).collect();
+    Ok(all
+        .into_iter()
+        .filter(|(name, is_virtual)| !is_virtual && !virtual_tables.iter().any(|prefix| name.starts_with(prefix.as_str())))
+        // The schema is not restored, so neither is the record of its migrations
+        .filter(|(name, _)| name != "schema_version")
+        .map(|(name, _)| name)
+        .collect())
+}
+
+/// Tables of the attached snapshot, including `sqlite_sequence`
+async fn tables_set(conn: &Connection) -> Result<HashSet<String>, String> {
+    let mut set: HashSet<String> = tables(conn, "snapshot").await?.into_iter().collect();
+    let mut rows = conn
+        .query("SELECT 1 FROM snapshot.sqlite_master WHERE name = 'sqlite_sequence'", ())
+        .await
+        .map_err(|e| e.to_string())?;
+    if rows.next().await.map_err(|e| e.to_string())?.is_some() {
+        set.insert("sqlite_sequence".to_string());
+    }
+    Ok(set)
+}
+
+async fn columns(conn: &Connection, schema: &str, table: &str) -> Result<Vec<String>, String> {
+    let mut rows = conn
+        .query(&format!("PRAGMA {}.table_info(\"{}\")", schema, table), ())
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut columns = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        columns.push(row.get::<String>(1).map_err(|e| e.to_string())?);
+    }
+    Ok(columns)
+}
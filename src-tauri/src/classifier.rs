@@ -0,0 +1,267 @@
+//! Learned Category Suggestions
+//!
+//! A naive Bayes classifier trained from the categorised transactions. The
+//! features of a transaction are the words of its note (Chinese text as single
+//! characters and character pairs), an amount bucket and, when known, the time
+//! of day. The model learns new transactions incrementally; anything that
+//! changes or removes existing ones resets it and it is retrained on next use.
+
+use crate::models::CategorySuggestion;
+use chrono::{Local, NaiveDateTime, TimeZone, Timelike, Utc};
+use libsql::Connection;
+use std::collections::{HashMap, HashSet};
+
+/// Suggestions below this probability are not used to categorise imports
+pub const IMPORT_CONFIDENCE: f64 = 0.5;
+
+#[derive(Default)]
+pub struct Classifier {
+    /// Transactions per category
+    documents: HashMap<i64, u32>,
+    /// Feature counts per category
+    features: HashMap<i64, HashMap<String, u32>>,
+    /// Sum of the feature counts per category
+    feature_totals: HashMap<i64, u32>,
+    vocabulary: HashSet<String>,
+    /// Highest transaction id learned; None until the first training
+    last_id: Option<i64>,
+}
+
+impl Classifier {
+    /// Forget everything; the next `sync` retrains from all transactions
+    pub fn reset(&mut self) {
+        *self = Classifier::default();
+    }
+
+    /// Learn the transactions saved since the last sync
+    pub async fn sync(&mut self, conn: &Connection) -> Result<(), String> {
+        let mut rows = conn
+            .query(
+                "SELECT t.id, t.category_id, t.amount, t.transaction_date, t.note, t.created_at
+                 FROM transactions t
+                 JOIN categories c ON c.id = t.category_id
+                 WHERE t.id > ?
+                 ORDER BY t.id",
+                libsql::params![self.last_id.unwrap_or(0)],
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+
+        while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+            let id: i64 = row.get(0).map_err(|e| e.to_string())?;
+            let category_id: i64 = row.get(1).map_err(|e| e.to_string())?;
+            let amount: f64 = row.get(2).map_err(|e| e.to_string())?;
+            let transaction_date: String = row.get(3).map_err(|e| e.to_string())?;
+            let note: Option<String> = row.get(4).ok();
+            let created_at: Option<String> = row.get(5).ok();
+
+            let hour = hour_of(&transaction_date, created_at.as_deref());
+            self.learn(category_id, features(note.as_deref().unwrap_or_default(), amount, hour));
+            self.last_id = Some(id);
+        }
+        // An empty ledger is trained too
+        self.last_id.get_or_insert(0);
+        Ok(())
+    }
+
+    fn learn(&mut self, category_id: i64, features: Vec<String>) {
+        *self.documents.entry(category_id).or_insert(0) += 1;
+        let counts = self.features.entry(category_id).or_default();
+        for feature in features {
+            *counts.entry(feature.clone()).or_insert(0) += 1;
+            *self.feature_totals.entry(category_id).or_insert(0) += 1;
+            self.vocabulary.insert(feature);
+        }
+    }
+
+    /// Whether any word of the note was seen in training; without one a
+    /// suggestion only reflects the amount and time
+    pub fn recognizes(&self, note: &str) -> bool {
+        features(note, 0.0, None)
+            .iter()
+            .any(|f| f.starts_with("w:") && self.vocabulary.contains(f))
+    }
+
+    /// The `limit` most likely categories, most likely first
+    pub fn suggest(&self, note: &str, amount: f64, hour: Option<u32>, limit: usize) -> Vec<CategorySuggestion> {
+        let total: u32 = self.documents.values().sum();
+        if total == 0 {
+            return Vec::new();
+        }
+        let features = features(note, amount, hour);
+        let categories = self.documents.len() as f64;
+        let vocabulary = self.vocabulary.len() as f64;
+
+        // Log probabilities with add-one smoothing
+        let mut scores: Vec<(i64, f64)> = self
+            .documents
+            .iter()
+            .map(|(&category_id, &documents)| {
+                let counts = &self.features[&category_id];
+                let feature_total = f64::from(self.feature_totals.get(&category_id).copied().unwrap_or(0));
+                let prior = ((f64::from(documents) + 1.0) / (f64::from(total) + categories)).ln();
+                let likelihood: f64 = features
+                    .iter()
+                    .filter(|f| self.vocabulary.contains(*f))
+                    .map(|f| {
+                        let count = f64::from(counts.get(f).copied().unwrap_or(0));
+                        ((count + 1.0) / (feature_total + vocabulary)).ln()
+                    })
+                    .sum();
+                (category_id, prior + likelihood)
+            })
+            .collect();
+
+        // Normalise into probabilities
+        let max = scores.iter().map(|(_, s)| *s).fold(f64::NEG_INFINITY, f64::max);
+        let sum: f64 = scores.iter().map(|(_, s)| (s - max).exp()).sum();
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        scores
+            .into_iter()
+            .take(limit)
+            .map(|(category_id, score)| CategorySuggestion {
+                category_id,
+                probability: (score - max).exp() / sum,
+            })
+            .collect()
+    }
+}
+
+/// Hour of day a transaction happened, if known
+///
+/// Imported bills carry the time in `transaction_date`. Entries made by hand
+/// only have a date, so the time they were saved (`created_at`, UTC) is used
+/// when they were saved on that same day.
+pub fn hour_of(transaction_date: &str, created_at: Option<&str>) -> Option<u32> {
+    if let Ok(time) = NaiveDateTime::parse_from_str(transaction_date, "%Y-%m-%d %H:%M:%S") {
+        // Statements without a time of day come in at midnight
+        return Some(time.hour()).filter(|_| time.time() != chrono::NaiveTime::MIN);
+    }
+    let saved = NaiveDateTime::parse_from_str(created_at?, "%Y-%m-%d %H:%M:%S").ok()?;
+    let saved = Utc.from_utc_datetime(&saved).with_timezone(&Local);
+    (saved.format("%Y-%m-%d").to_string() == transaction_date.get(..10)?).then_some(saved.hour())
+}
+
+/// The current local hour, for transactions being entered now
+pub fn current_hour() -> u32 {
+    Local::now().hour()
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '\u{F900}'..='\u{FAFF}')
+}
+
+/// A run of Chinese characters gives its characters and character pairs,
+/// any other word is used whole
+fn push_word(word: &str, cjk: bool, features: &mut Vec<String>) {
+    if cjk {
+        let chars: Vec<char> = word.chars().collect();
+        features.extend(chars.iter().map(|c| format!("w:{}", c)));
+        features.extend(chars.windows(2).map(|pair| format!("w:{}{}", pair[0], pair[1])));
+    } else if !word.is_empty() {
+        features.push(format!("w:{}", word.to_lowercase()));
+    }
+}
+
+/// Feature strings of a transaction
+fn features(note: &str, amount: f64, hour: Option<u32>) -> Vec<String> {
+    let mut features = Vec::new();
+
+    // Runs of Chinese characters and runs of letters/digits; everything else
+    // (spaces, punctuation, the # of tags) separates words
+    let mut run = String::new();
+    let mut run_is_cjk = false;
+    for c in note.chars() {
+        let cjk = is_cjk(c);
+        if !cjk && !c.is_alphanumeric() {
+            push_word(&std::mem::take(&mut run), run_is_cjk, &mut features);
+            continue;
+        }
+        if !run.is_empty() && cjk != run_is_cjk {
+            push_word(&std::mem::take(&mut run), run_is_cjk, &mut features);
+        }
+        run_is_cjk = cjk;
+        run.push(c);
+    }
+    push_word(&run, run_is_cjk, &mut features);
+
+    let direction = if amount < 0.0 { "out" } else { "in" };
+    let bucket = [10.0, 30.0, 100.0, 300.0, 1000.0, 3000.0]
+        .iter()
+        .position(|limit| amount.abs() < *limit)
+        .unwrap_or(6);
+    features.push(format!("amount:{}:{}", direction, bucket));
+
+    if let Some(hour) = hour {
+        let part = match hour {
+            5..=9 => "morning",
+            10..=13 => "noon",
+            14..=16 => "afternoon",
+            17..=20 => "evening",
+            _ => "night",
+        };
+        features.push(format!("hour:{}", part));
+    }
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FOOD: i64 = 1;
+    const TRANSPORT: i64 = 2;
+
+    fn trained() -> Classifier {
+        let mut classifier = Classifier::default();
+        for (category_id, note, amount, hour) in [
+            (FOOD, "瑞幸咖啡 生椰拿铁", -19.9, Some(8)),
+            (FOOD, "星巴克 拿铁", -32.0, Some(9)),
+            (FOOD, "肯德基 午餐", -35.5, Some(12)),
+            (TRANSPORT, "滴滴快车", -25.0, Some(19)),
+            (TRANSPORT, "地铁 Metro", -4.0, Some(8)),
+        ] {
+            classifier.learn(category_id, features(note, amount, hour));
+        }
+        classifier
+    }
+
+    #[test]
+    fn chinese_words_and_buckets() {
+        assert_eq!(
+            features("拿铁#咖啡 Latte2", -19.9, Some(8)),
+            vec!["w:拿", "w:铁", "w:拿铁", "w:咖", "w:啡", "w:咖啡", "w:latte2", "amount:out:1", "hour:morning"]
+        );
+        assert_eq!(features("", 8000.0, None), vec!["amount:in:6"]);
+    }
+
+    #[test]
+    fn suggests_most_likely_category() {
+        let classifier = trained();
+        let suggestions = classifier.suggest("拿铁", -28.0, Some(9), 2);
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].category_id, FOOD);
+        assert!(suggestions[0].probability > IMPORT_CONFIDENCE);
+        let total: f64 = suggestions.iter().map(|s| s.probability).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        assert_eq!(classifier.suggest("滴滴 快车", -30.0, Some(20), 1)[0].category_id, TRANSPORT);
+        assert_eq!(classifier.suggest("METRO", -4.0, None, 1)[0].category_id, TRANSPORT);
+    }
+
+    #[test]
+    fn unknown_words() {
+        let classifier = trained();
+        assert!(classifier.recognizes("今天的拿铁"));
+        assert!(!classifier.recognizes("电影票"));
+        assert!(Classifier::default().suggest("拿铁", -30.0, None, 3).is_empty());
+    }
+
+    #[test]
+    fn hour_of_day() {
+        assert_eq!(hour_of("2024-03-05 12:30:00", None), Some(12));
+        assert_eq!(hour_of("2024-03-05 00:00:00", None), None);
+        assert_eq!(hour_of("2024-03-05", None), None);
+        assert_eq!(hour_of("2020-01-01", Some("2024-03-05 12:30:00")), None);
+    }
+}
@@ -1,6 +1,11 @@
 //! Database Connection and Setup
 //!
 //! Manages SQLite database connection and migrations.
+//!
+//! The schema is changed only by numbered migrations (`MIGRATIONS`), applied
+//! in order, each in its own transaction, and recorded in `schema_version`.
+//! A database already migrated past `SCHEMA_VERSION` by a newer build is
+//! refused rather than opened with a schema this build does not know.
 
 use std::sync::Arc;
 use libsql::{Connection, Builder};
@@ -21,6 +26,11 @@ pub use tauri_sync_db_backend::{
 
 /// Initialize database and run migrations
 pub async fn init_db(db_path: &PathBuf) -> Result<DbState, String> {
+    // Safety net before the migrations below touch the schema
+    if let Err(e) = crate::backup::snapshot::snapshot_before_open(db_path).await {
+        log::warn!("Failed to snapshot the database before opening: {}", e);
+    }
+
     // Use shared crate's init_db with our migration callback
     tauri_sync_db_backend::init_db(db_path, |conn| {
         Box::pin(async {
@@ -29,23 +39,101 @@ pub async fn init_db(db_path: &PathBuf) -> Result<DbState, String> {
     }).await
 }
 
-/// Check if a column exists in a table
-async fn column_exists(conn: &Connection, table: &str, column: &str) -> bool {
-    let query = format!("PRAGMA table_info({})", table);
-    if let Ok(mut rows) = conn.query(&query, ()).await {
-        while let Ok(Some(row)) = rows.next().await {
-            if let Ok(name) = row.get::<String>(1) {
-                if name == column {
-                    return true;
-                }
-            }
-        }
+/// Ordered schema migrations: (version, description). Append new ones with
+/// the next number and their step in `apply_migration`; never edit a
+/// released migration, databases that ran it will not run it again.
+const MIGRATIONS: &[(i64, &str)] = &[(1, "initial schema"), (2, "recurring bills")];
+
+/// Schema version this build migrates databases to
+pub const SCHEMA_VERSION: i64 = MIGRATIONS[MIGRATIONS.len() - 1].0;
+
+/// Check if a table (or virtual table) exists
+async fn table_exists(conn: &Connection, table: &str) -> bool {
+    if let Ok(mut rows) = conn
+        .query("SELECT 1 FROM sqlite_master WHERE name = ?", libsql::params![table])
+        .await
+    {
+        return matches!(rows.next().await, Ok(Some(_)));
     }
     false
 }
 
-/// Run database migrations
+/// Highest migration applied to the database, 0 for one from before
+/// versioning (or a new one)
+pub async fn schema_version(conn: &Connection) -> Result<i64, String> {
+    if !table_exists(conn, "schema_version").await {
+        return Ok(0);
+    }
+    let mut rows = conn
+        .query("SELECT COALESCE(MAX(version), 0) FROM schema_version", ())
+        .await
+        .map_err(|e| e.to_string())?;
+    match rows.next().await.map_err(|e| e.to_string())? {
+        Some(row) => row.get::<i64>(0).map_err(|e| e.to_string()),
+        None => Ok(0),
+    }
+}
+
+/// Run the migrations the database has not seen yet
 async fn run_migrations(conn: &Connection) -> Result<(), String> {
+    let current = schema_version(conn).await?;
+    if current > SCHEMA_VERSION {
+        return Err(format!(
+            "数据库结构版本 {} 高于当前应用支持的 {}，它已被更新版本的应用打开过，请先升级应用",
+            current, SCHEMA_VERSION
+        ));
+    }
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        (),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    for &(version, name) in MIGRATIONS.iter().filter(|(version, _)| *version > current) {
+        let tx = conn.transaction().await.map_err(|e| e.to_string())?;
+        let result = async {
+            apply_migration(&tx, version).await?;
+            tx.execute(
+                "INSERT INTO schema_version (version, name) VALUES (?, ?)",
+                libsql::params![version, name],
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+            Ok::<(), String>(())
+        }
+        .await;
+
+        match result {
+            Ok(()) => tx.commit().await.map_err(|e| e.to_string())?,
+            Err(e) => {
+                let _ = tx.rollback().await;
+                return Err(format!("数据库迁移 {}（{}）失败: {}", version, name, e));
+            }
+        }
+        log::info!("Applied migration {}: {}", version, name);
+    }
+    Ok(())
+}
+
+/// The step of one migration
+async fn apply_migration(conn: &Connection, version: i64) -> Result<(), String> {
+    match version {
+        1 => initial_schema(conn).await,
+        2 => recurring_bills(conn).await,
+        _ => Err(format!("Unknown migration {}", version)),
+    }
+}
+
+/// Migration 1: the schema as it was when versioning was introduced. Every
+/// statement tolerates existing objects, so databases from before versioning
+/// go through it too.
+async fn initial_schema(conn: &Connection) -> Result<(), String> {
     // Categories table (消费项目/分类)
     conn.execute(
         "CREATE TABLE IF NOT EXISTS categories (
@@ -154,6 +242,85 @@ async fn run_migrations(conn: &Connection) -> Result<(), String> {
     .await
     .map_err(|e| e.to_string())?;
 
+    // Smart views table (保存的查询)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS smart_views (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            query TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        (),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Imported statement lines (已导入的账单行), keyed by the source's own id
+    // (FITID, 交易订单号 ...) so re-importing a statement skips them
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS imported_lines (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source TEXT NOT NULL,
+            account TEXT NOT NULL DEFAULT '',
+            external_id TEXT NOT NULL,
+            transaction_id INTEGER,
+            imported_at TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE(source, account, external_id)
+        )",
+        (),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Column mapping presets of the generic CSV import (CSV 导入预设)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS csv_import_presets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            mapping TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        (),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Transaction pairs the user marked as not duplicates (不是重复), smaller id first
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS duplicate_dismissals (
+            first_id INTEGER NOT NULL,
+            second_id INTEGER NOT NULL,
+            dismissed_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY(first_id, second_id)
+        )",
+        (),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Auto-categorization rules (自动分类规则), matched in priority order
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS category_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            priority INTEGER NOT NULL DEFAULT 0,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            pattern TEXT,
+            is_regex INTEGER NOT NULL DEFAULT 0,
+            min_amount REAL,
+            max_amount REAL,
+            account TEXT,
+            category_id INTEGER,
+            tags TEXT NOT NULL DEFAULT '',
+            note_rewrite TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY(category_id) REFERENCES categories(id) ON DELETE SET NULL
+        )",
+        (),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
     // Create indexes for better query performance
     conn.execute(
         "CREATE INDEX IF NOT EXISTS idx_transactions_date ON transactions(transaction_date)",
@@ -176,6 +343,62 @@ async fn run_migrations(conn: &Connection) -> Result<(), String> {
     .await
     .map_err(|e| e.to_string())?;
 
+    // Full-text index over transaction notes (备注搜索)
+    // The trigram tokenizer matches substrings, which suits Chinese text without word breaks
+    let fts_is_new = !table_exists(conn, "transactions_fts").await;
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS transactions_fts USING fts5(
+            note,
+            content='transactions',
+            content_rowid='id',
+            tokenize='trigram'
+        )",
+        (),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    for trigger in [
+        "CREATE TRIGGER IF NOT EXISTS transactions_fts_insert AFTER INSERT ON transactions BEGIN
+            INSERT INTO transactions_fts(rowid, note) VALUES (new.id, new.note);
+        END",
+        "CREATE TRIGGER IF NOT EXISTS transactions_fts_delete AFTER DELETE ON transactions BEGIN
+            INSERT INTO transactions_fts(transactions_fts, rowid, note) VALUES ('delete', old.id, old.note);
+        END",
+        "CREATE TRIGGER IF NOT EXISTS transactions_fts_update AFTER UPDATE OF note ON transactions BEGIN
+            INSERT INTO transactions_fts(transactions_fts, rowid, note) VALUES ('delete', old.id, old.note);
+            INSERT INTO transactions_fts(rowid, note) VALUES (new.id, new.note);
+        END",
+    ] {
+        conn.execute(trigger, ()).await.map_err(|e| e.to_string())?;
+    }
+
+    // Index notes that existed before the FTS table
+    if fts_is_new {
+        conn.execute("INSERT INTO transactions_fts(transactions_fts) VALUES ('rebuild')", ())
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Migration 2: recurring bills (周期账单), exported as calendar events
+async fn recurring_bills(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE recurring_bills (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            amount REAL NOT NULL,
+            interval_months INTEGER NOT NULL DEFAULT 1,
+            start_date TEXT NOT NULL,
+            end_date TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        (),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
     Ok(())
 }
 
@@ -0,0 +1,291 @@
+//! Duplicate Detection
+//!
+//! Two transactions are likely duplicates when they have the same amount,
+//! dates at most `DATE_WINDOW_DAYS` apart and, ideally, the same category and
+//! a similar note. Imports and manual entry check new transactions against
+//! the ledger; `find_duplicate_pairs` scans the whole ledger for the review
+//! screen, minus pairs the user dismissed.
+
+use crate::models::{DuplicatePair, TransactionWithCategory};
+use crate::repository::transaction_repo;
+use chrono::NaiveDate;
+use libsql::Connection;
+use std::collections::HashSet;
+
+/// Largest distance in days between the dates of two duplicates; statements
+/// often book a payment a day or two after it was made
+pub const DATE_WINDOW_DAYS: i64 = 3;
+
+/// Score from which two transactions are reported as duplicates
+const THRESHOLD: f64 = 0.6;
+
+/// The parts of a transaction that are compared
+pub struct Fingerprint<'a> {
+    pub transaction_date: &'a str,
+    pub amount: f64,
+    pub category_id: Option<i64>,
+    pub note: Option<&'a str>,
+}
+
+impl<'a> Fingerprint<'a> {
+    pub fn of(transaction: &'a TransactionWithCategory) -> Self {
+        Fingerprint {
+            transaction_date: &transaction.transaction_date,
+            amount: transaction.amount,
+            category_id: Some(transaction.category_id),
+            note: transaction.note.as_deref(),
+        }
+    }
+}
+
+fn date_of(transaction_date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(transaction_date.get(..10)?, "%Y-%m-%d").ok()
+}
+
+fn cents(amount: f64) -> i64 {
+    (amount * 100.0).round() as i64
+}
+
+/// How alike two transactions are, from 0 to 1; None when they cannot be
+/// duplicates (different amounts or too far apart)
+pub fn score(a: &Fingerprint, b: &Fingerprint) -> Option<f64> {
+    if cents(a.amount) != cents(b.amount) {
+        return None;
+    }
+    let days = (date_of(a.transaction_date)? - date_of(b.transaction_date)?).num_days().abs();
+    if days > DATE_WINDOW_DAYS {
+        return None;
+    }
+
+    let date_score = 1.0 - days as f64 / (DATE_WINDOW_DAYS + 1) as f64;
+    let category_score = match (a.category_id, b.category_id) {
+        (Some(x), Some(y)) if x == y => 1.0,
+        _ => 0.0,
+    };
+    Some(0.4 * date_score + 0.2 * category_score + 0.4 * note_similarity(a.note, b.note))
+}
+
+/// Character bigram overlap of two notes; a missing note says nothing either way
+fn note_similarity(a: Option<&str>, b: Option<&str>) -> f64 {
+    let normalize = |s: &str| s.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect::<Vec<_>>();
+    let a = a.map(normalize).filter(|s| !s.is_empty());
+    let b = b.map(normalize).filter(|s| !s.is_empty());
+    let (a, b) = match (a, b) {
+        (Some(a), Some(b)) => (a, b),
+        (None, None) => return 0.5,
+        // Manual entries often have no note while the statement has one
+        _ => return 0.3,
+    };
+    if a == b {
+        return 1.0;
+    }
+    // One note containing the other: `美团` vs `美团外卖 午饭`
+    let (short, long) = if a.len() <= b.len() { (&a, &b) } else { (&b, &a) };
+    if long.windows(short.len()).any(|w| w == short.as_slice()) {
+        return 0.9;
+    }
+
+    let bigrams = |s: &[char]| -> HashSet<(char, char)> {
+        if s.len() == 1 {
+            return HashSet::from([(s[0], s[0])]);
+        }
+        s.windows(2).map(|w| (w[0], w[1])).collect()
+    };
+    let (x, y) = (bigrams(&a), bigrams(&b));
+    2.0 * x.intersection(&y).count() as f64 / (x.len() + y.len()) as f64
+}
+
+/// Best match for `candidate` among `ledger`, if any scores above the threshold
+pub fn best_match<'a>(
+    candidate: &Fingerprint,
+    ledger: &'a [TransactionWithCategory],
+) -> Option<&'a TransactionWithCategory> {
+    ledger
+        .iter()
+        .filter_map(|t| score(candidate, &Fingerprint::of(t)).map(|s| (t, s)))
+        .filter(|(_, s)| *s >= THRESHOLD)
+        .max_by(|x, y| x.1.total_cmp(&y.1))
+        .map(|(t, _)| t)
+}
+
+/// Short description of a transaction for duplicate warnings
+pub fn describe(transaction: &TransactionWithCategory) -> String {
+    let mut text = format!(
+        "{} {:+.2} {}",
+        transaction.transaction_date.get(..10).unwrap_or(&transaction.transaction_date),
+        transaction.amount,
+        transaction.category_name
+    );
+    if let Some(note) = transaction.note.as_deref().filter(|n| !n.is_empty()) {
+        text.push(' ');
+        text.push_str(note);
+    }
+    text
+}
+
+/// Ledger transactions dated from `first` - window to `last` + window
+pub async fn load_window(conn: &Connection, first: &str, last: &str) -> Result<Vec<TransactionWithCategory>, String> {
+    let (Some(first), Some(last)) = (date_of(first), date_of(last)) else {
+        return Ok(Vec::new());
+    };
+    let window = chrono::Duration::days(DATE_WINDOW_DAYS);
+    // Plain comparisons keep the date index usable; dates may carry a time,
+    // so the end is the (exclusive) day after `last` + window
+    let end = transaction_repo::next_day(&(last + window).format("%Y-%m-%d").to_string())?;
+    load(
+        conn,
+        "WHERE t.transaction_date >= ? AND t.transaction_date < ?",
+        vec![
+            libsql::Value::Text((first - window).format("%Y-%m-%d").to_string()),
+            libsql::Value::Text(end),
+        ],
+    )
+    .await
+}
+
+/// Ledger transactions that look like `candidate`, most alike first
+pub async fn find_similar(conn: &Connection, candidate: &Fingerprint<'_>) -> Result<Vec<TransactionWithCategory>, String> {
+    let ledger = load_window(conn, candidate.transaction_date, candidate.transaction_date).await?;
+    let mut similar: Vec<(TransactionWithCategory, f64)> = ledger
+        .into_iter()
+        .filter_map(|t| score(candidate, &Fingerprint::of(&t)).map(|s| (t, s)))
+        .filter(|(_, s)| *s >= THRESHOLD)
+        .collect();
+    similar.sort_by(|x, y| y.1.total_cmp(&x.1));
+    Ok(similar.into_iter().map(|(t, _)| t).collect())
+}
+
+/// All likely duplicate pairs in the ledger that were not dismissed, newest first
+pub async fn find_duplicate_pairs(conn: &Connection) -> Result<Vec<DuplicatePair>, String> {
+    let mut ledger = load(conn, "", Vec::new()).await?;
+    ledger.sort_by(|a, b| {
+        cents(a.amount)
+            .cmp(&cents(b.amount))
+            .then_with(|| a.transaction_date.cmp(&b.transaction_date))
+    });
+    let dismissed = dismissed_pairs(conn).await?;
+
+    let mut pairs = Vec::new();
+    for (i, first) in ledger.iter().enumerate() {
+        let first_date = date_of(&first.transaction_date);
+        // Sorted by amount then date, so the later candidates come right after
+        for second in &ledger[i + 1..] {
+            if cents(second.amount) != cents(first.amount) {
+                break;
+            }
+            let days = first_date
+                .zip(date_of(&second.transaction_date))
+                .map(|(a, b)| (b - a).num_days());
+            if days.is_some_and(|d| d > DATE_WINDOW_DAYS) {
+                break;
+            }
+            let key = (first.id.min(second.id), first.id.max(second.id));
+            if dismissed.contains(&key) {
+                continue;
+            }
+            if let Some(score) = score(&Fingerprint::of(first), &Fingerprint::of(second)).filter(|s| *s >= THRESHOLD) {
+                pairs.push(DuplicatePair {
+                    first: first.clone(),
+                    second: second.clone(),
+                    score,
+                });
+            }
+        }
+    }
+    pairs.sort_by(|a, b| b.second.transaction_date.cmp(&a.second.transaction_date));
+    Ok(pairs)
+}
+
+/// Keep `keep_id` and delete `remove_id`, keeping the removed note if the kept
+/// one has none and pointing imported statement lines at the kept transaction
+pub async fn merge(conn: &Connection, keep_id: i64, remove_id: i64) -> Result<(), String> {
+    if keep_id == remove_id {
+        return Err("不能与自身合并".to_string());
+    }
+    let tx = conn.transaction().await.map_err(|e| e.to_string())?;
+    let result = async {
+        tx.execute(
+            "UPDATE transactions SET note = (SELECT note FROM transactions WHERE id = ?)
+             WHERE id = ? AND (note IS NULL OR note = '')",
+            libsql::params![remove_id, keep_id],
+        )
+        .await?;
+        tx.execute(
+            "UPDATE imported_lines SET transaction_id = ? WHERE transaction_id = ?",
+            libsql::params![keep_id, remove_id],
+        )
+        .await?;
+        tx.execute(
+            "DELETE FROM duplicate_dismissals WHERE first_id = ? OR second_id = ?",
+            libsql::params![remove_id, remove_id],
+        )
+        .await?;
+        tx.execute("DELETE FROM transactions WHERE id = ?", libsql::params![remove_id]).await?;
+        Ok::<_, libsql::Error>(())
+    }
+    .await;
+    match result {
+        Ok(()) => tx.commit().await.map_err(|e| e.to_string()),
+        Err(e) => {
+            let _ = tx.rollback().await;
+            Err(e.to_string())
+        }
+    }
+}
+
+/// Remember that two transactions are not duplicates
+pub async fn dismiss(conn: &Connection, first_id: i64, second_id: i64) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO duplicate_dismissals (first_id, second_id) VALUES (?, ?)",
+        libsql::params![first_id.min(second_id), first_id.max(second_id)],
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn dismissed_pairs(conn: &Connection) -> Result<HashSet<(i64, i64)>, String> {
+    let mut rows = conn
+        .query("SELECT first_id, second_id FROM duplicate_dismissals", ())
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut pairs = HashSet::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        pairs.insert((
+            row.get(0).map_err(|e| e.to_string())?,
+            row.get(1).map_err(|e| e.to_string())?,
+        ));
+    }
+    Ok(pairs)
+}
+
+async fn load(conn: &Connection, where_sql: &str, params: Vec<libsql::Value>) -> Result<Vec<TransactionWithCategory>, String> {
+    let mut rows = conn
+        .query(
+            &format!(
+                "SELECT t.id, t.category_id, c.name, c.icon, t.amount, t.transaction_date, t.note, t.created_at
+                 FROM transactions t
+                 INNER JOIN categories c ON t.category_id = c.id
+                 {}",
+                where_sql
+            ),
+            libsql::params_from_iter(params),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut transactions = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        transactions.push(TransactionWithCategory {
+            id: row.get(0).map_err(|e| e.to_string())?,
+            category_id: row.get(1).map_err(|e| e.to_string())?,
+            category_name: row.get(2).map_err(|e| e.to_string())?,
+            category_icon: row.get(3).ok(),
+            amount: row.get(4).map_err(|e| e.to_string())?,
+            transaction_date: row.get(5).map_err(|e| e.to_string())?,
+            note: row.get(6).ok(),
+            created_at: row.get(7).map_err(|e| e.to_string())?,
+        });
+    }
+    Ok(transactions)
+}
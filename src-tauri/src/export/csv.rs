@@ -0,0 +1,93 @@
+//! CSV export of transactions and installment schedules.
+
+use crate::models::{CsvColumn, CsvEncoding, InstallmentScheduleRow, TransactionWithCategory};
+use std::path::{Path, PathBuf};
+
+/// Suffix of the installment schedule file written next to the transactions file
+const INSTALLMENTS_SUFFIX: &str = "-分期";
+
+fn column_header(column: CsvColumn) -> &'static str {
+    match column {
+        CsvColumn::Date => "日期",
+        CsvColumn::Category => "分类",
+        CsvColumn::Kind => "收支",
+        CsvColumn::Amount => "金额",
+        CsvColumn::Note => "备注",
+        CsvColumn::CreatedAt => "创建时间",
+    }
+}
+
+fn column_value(column: CsvColumn, tx: &TransactionWithCategory) -> String {
+    match column {
+        CsvColumn::Date => tx.transaction_date.clone(),
+        CsvColumn::Category => tx.category_name.clone(),
+        CsvColumn::Kind => if tx.amount >= 0.0 { "收入" } else { "支出" }.to_string(),
+        CsvColumn::Amount => format!("{:.2}", tx.amount),
+        CsvColumn::Note => tx.note.clone().unwrap_or_default(),
+        CsvColumn::CreatedAt => tx.created_at.clone(),
+    }
+}
+
+/// Render transactions with the chosen columns (all columns when empty)
+pub fn render_transactions(transactions: &[TransactionWithCategory], columns: &[CsvColumn]) -> Result<String, String> {
+    let columns = if columns.is_empty() { &CsvColumn::ALL[..] } else { columns };
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(columns.iter().map(|c| column_header(*c)))
+        .map_err(|e| e.to_string())?;
+    for tx in transactions {
+        writer
+            .write_record(columns.iter().map(|c| column_value(*c, tx)))
+            .map_err(|e| e.to_string())?;
+    }
+    finish(writer)
+}
+
+/// Render installment payments, one row per period
+pub fn render_installments(schedule: &[InstallmentScheduleRow]) -> Result<String, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(["分期编号", "分类", "备注", "总金额", "期数", "第几期", "到期日", "本期金额", "状态", "还款日期"])
+        .map_err(|e| e.to_string())?;
+    for row in schedule {
+        writer
+            .write_record([
+                row.installment_id.to_string(),
+                row.category_name.clone(),
+                row.note.clone().unwrap_or_default(),
+                format!("{:.2}", row.total_amount),
+                row.installment_count.to_string(),
+                row.sequence_number.to_string(),
+                row.due_date.clone(),
+                format!("{:.2}", row.amount),
+                if row.is_paid { "已还款" } else { "待还款" }.to_string(),
+                row.paid_date.clone().unwrap_or_default(),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    finish(writer)
+}
+
+fn finish(writer: csv::Writer<Vec<u8>>) -> Result<String, String> {
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+/// Encode CSV text for writing
+pub fn encode(text: &str, encoding: CsvEncoding) -> Vec<u8> {
+    match encoding {
+        CsvEncoding::Utf8Bom => {
+            let mut bytes = "\u{FEFF}".as_bytes().to_vec();
+            bytes.extend_from_slice(text.as_bytes());
+            bytes
+        }
+        CsvEncoding::Gb18030 => encoding_rs::GB18030.encode(text).0.into_owned(),
+    }
+}
+
+/// Path of the installment schedule written alongside `path`, e.g. `账单.csv` → `账单-分期.csv`
+pub fn installments_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    path.with_file_name(format!("{}{}.csv", stem, INSTALLMENTS_SUFFIX))
+}
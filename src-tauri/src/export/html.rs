@@ -0,0 +1,338 @@
+//! Standalone HTML rendering of reports.
+//!
+//! The output is a single file with inline CSS so it can be opened, printed or
+//! shared without the app.
+
+use crate::models::{AnnualReport, MonthlyStatement};
+use std::fmt::Write;
+
+const DEFAULT_ICON: &str = "📝";
+
+/// Escape text for HTML content and attribute values
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Shared page style of exported reports
+pub const REPORT_STYLE: &str = "
+body { font-family: -apple-system, 'PingFang SC', 'Microsoft YaHei', sans-serif; background: #f5f5f5; color: #2c3e50; margin: 0; padding: 24px; }
+.page { max-width: 720px; margin: 0 auto; }
+h1 { text-align: center; margin: 0 0 4px 0; }
+.subtitle { text-align: center; color: #7f8c8d; margin-bottom: 20px; }
+.card { background: white; border-radius: 10px; padding: 16px 20px; margin-bottom: 16px; box-shadow: 0 1px 3px rgba(0,0,0,0.08); }
+.card h2 { font-size: 16px; margin: 0 0 12px 0; }
+.stats { display: flex; gap: 12px; text-align: center; }
+.stats div { flex: 1; }
+.stats .label { font-size: 12px; color: #7f8c8d; }
+.stats .value { font-size: 20px; font-weight: bold; }
+.income { color: #27ae60; }
+.expense { color: #e74c3c; }
+table { width: 100%; border-collapse: collapse; font-size: 14px; }
+td, th { padding: 6px 4px; border-bottom: 1px solid #eee; text-align: left; }
+th { color: #7f8c8d; font-weight: normal; font-size: 12px; }
+.num { text-align: right; white-space: nowrap; }
+.bar { height: 8px; background: #e74c3c; border-radius: 4px; }
+.muted { color: #7f8c8d; font-size: 13px; }
+.footer { text-align: center; color: #aaa; font-size: 12px; margin-top: 24px; }
+@media print { body { background: white; padding: 0; } .card { box-shadow: none; border: 1px solid #ddd; break-inside: avoid; } }
+";
+
+fn icon(icon: &Option<String>) -> String {
+    escape_html(icon.as_deref().unwrap_or(DEFAULT_ICON))
+}
+
+/// Render the annual report as a standalone HTML document
+pub fn render_annual_report(report: &AnnualReport) -> String {
+    let mut html = String::new();
+    let title = format!("{}年度账单", report.year);
+
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n<style>{REPORT_STYLE}</style>\n</head>\n<body>\n<div class=\"page\">\n\
+         <h1>{title}</h1>\n<div class=\"subtitle\">共记账 {} 笔</div>\n",
+        report.transaction_count
+    );
+
+    // Totals
+    let _ = write!(
+        html,
+        "<div class=\"card\"><div class=\"stats\">\
+         <div><div class=\"label\">全年收入</div><div class=\"value income\">¥{:.2}</div></div>\
+         <div><div class=\"label\">全年支出</div><div class=\"value expense\">¥{:.2}</div></div>\
+         <div><div class=\"label\">结余</div><div class=\"value\">¥{:.2}</div></div>\
+         </div>",
+        report.total_income, report.total_expense, report.net_amount
+    );
+    if report.installment_expense > 0.0 {
+        let _ = write!(html, "<p class=\"muted\">支出中含分期还款 ¥{:.2}</p>", report.installment_expense);
+    }
+    html.push_str("</div>\n");
+
+    // Top categories
+    html.push_str("<div class=\"card\"><h2>支出最多的分类</h2>");
+    if report.top_categories.is_empty() {
+        html.push_str("<p class=\"muted\">暂无支出</p>");
+    } else {
+        let max = report.top_categories.first().map(|c| c.total).unwrap_or(0.0);
+        html.push_str("<table>");
+        for category in &report.top_categories {
+            let width = if max > 0.0 { category.total / max * 100.0 } else { 0.0 };
+            let _ = write!(
+                html,
+                "<tr><td>{} {}</td><td style=\"width: 40%\"><div class=\"bar\" style=\"width: {:.1}%\"></div></td>\
+                 <td class=\"num\">¥{:.2}</td><td class=\"num muted\">{}笔</td></tr>",
+                icon(&category.category_icon),
+                escape_html(&category.category_name),
+                width,
+                category.total,
+                category.transaction_count
+            );
+        }
+        html.push_str("</table>");
+    }
+    html.push_str("</div>\n");
+
+    // Largest purchases
+    html.push_str("<div class=\"card\"><h2>最大的几笔支出</h2>");
+    if report.largest_expenses.is_empty() {
+        html.push_str("<p class=\"muted\">暂无支出</p>");
+    } else {
+        html.push_str("<table>");
+        for tx in &report.largest_expenses {
+            let _ = write!(
+                html,
+                "<tr><td class=\"muted\">{}</td><td>{} {}</td><td>{}</td><td class=\"num expense\">¥{:.2}</td></tr>",
+                escape_html(tx.transaction_date.get(..10).unwrap_or(&tx.transaction_date)),
+                icon(&tx.category_icon),
+                escape_html(&tx.category_name),
+                escape_html(tx.note.as_deref().unwrap_or("")),
+                tx.amount.abs()
+            );
+        }
+        html.push_str("</table>");
+    }
+    html.push_str("</div>\n");
+
+    // Months
+    html.push_str("<div class=\"card\"><h2>每月收支</h2>");
+    if let Some(month) = &report.most_expensive_month {
+        let _ = write!(
+            html,
+            "<p>花钱最多的月份是 <b>{}月</b>，支出 ¥{:.2}</p>",
+            month.month, month.total_expense
+        );
+    }
+    html.push_str("<table><tr><th>月份</th><th class=\"num\">收入</th><th class=\"num\">支出</th><th class=\"num\">底线</th></tr>");
+    for month in &report.months {
+        let budget = match month.within_budget {
+            Some(true) => "✅",
+            Some(false) => "❌",
+            None => "",
+        };
+        let _ = write!(
+            html,
+            "<tr><td>{}月</td><td class=\"num income\">¥{:.2}</td><td class=\"num expense\">¥{:.2}</td><td class=\"num\">{}</td></tr>",
+            month.month, month.total_income, month.total_expense, budget
+        );
+    }
+    html.push_str("</table></div>\n");
+
+    // Budget
+    html.push_str("<div class=\"card\"><h2>底线达成</h2>");
+    match (report.budget, report.budget_hit_rate) {
+        (Some(budget), Some(rate)) => {
+            let _ = write!(
+                html,
+                "<p>月度底线 ¥{:.2}，{} 个月中有 {} 个月未超支，达成率 <b>{:.0}%</b></p>",
+                budget, report.budget_months_counted, report.budget_months_within, rate
+            );
+        }
+        (Some(budget), None) => {
+            let _ = write!(html, "<p class=\"muted\">月度底线 ¥{:.2}，本年度尚无可统计的月份</p>", budget);
+        }
+        _ => html.push_str("<p class=\"muted\">未设置月度底线</p>"),
+    }
+    html.push_str("</div>\n");
+
+    // Installments
+    html.push_str("<div class=\"card\"><h2>分期</h2>");
+    for (label, items) in [
+        ("今年新开的分期", &report.installments_started),
+        ("今年还清的分期", &report.installments_finished),
+    ] {
+        let _ = write!(html, "<p><b>{}</b>（{}笔）</p>", label, items.len());
+        if !items.is_empty() {
+            html.push_str("<table>");
+            for item in items {
+                let _ = write!(
+                    html,
+                    "<tr><td>{} {}</td><td>{}</td><td class=\"num\">¥{:.2} / {}期</td></tr>",
+                    icon(&item.category_icon),
+                    escape_html(&item.category_name),
+                    escape_html(item.note.as_deref().unwrap_or("")),
+                    item.total_amount,
+                    item.installment_count
+                );
+            }
+            html.push_str("</table>");
+        }
+    }
+    html.push_str("</div>\n");
+
+    let _ = write!(
+        html,
+        "<div class=\"footer\">生成于 {}</div>\n</div>\n</body>\n</html>\n",
+        chrono::Local::now().format("%Y-%m-%d %H:%M")
+    );
+
+    html
+}
+
+/// Extra style of the monthly statement: a denser transaction table and A4 pages
+const STATEMENT_STYLE: &str = "
+.tx td { font-size: 13px; padding: 4px; }
+.tx td.date { white-space: nowrap; color: #7f8c8d; }
+@page { size: A4; margin: 14mm; }
+";
+
+/// Render a monthly statement as a standalone HTML document
+pub fn render_monthly_statement(statement: &MonthlyStatement) -> String {
+    let summary = &statement.summary;
+    let mut html = String::new();
+    let title = format!("{}年{}月对账单", summary.year, summary.month);
+    let total_expense = summary.total_expense + statement.installment_expense;
+
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n<style>{REPORT_STYLE}{STATEMENT_STYLE}</style>\n</head>\n<body>\n<div class=\"page\">\n\
+         <h1>{title}</h1>\n<div class=\"subtitle\">共记账 {} 笔</div>\n",
+        summary.transaction_count
+    );
+
+    // Totals
+    let _ = write!(
+        html,
+        "<div class=\"card\"><div class=\"stats\">\
+         <div><div class=\"label\">收入</div><div class=\"value income\">¥{:.2}</div></div>\
+         <div><div class=\"label\">支出</div><div class=\"value expense\">¥{:.2}</div></div>\
+         <div><div class=\"label\">结余</div><div class=\"value\">¥{:.2}</div></div>\
+         </div>",
+        summary.total_income,
+        total_expense,
+        summary.total_income - total_expense
+    );
+    if statement.installment_expense > 0.0 {
+        let _ = write!(html, "<p class=\"muted\">支出中含分期还款 ¥{:.2}</p>", statement.installment_expense);
+    }
+    html.push_str("</div>\n");
+
+    // Categories
+    html.push_str("<div class=\"card\"><h2>分类支出</h2>");
+    if statement.categories.is_empty() {
+        html.push_str("<p class=\"muted\">暂无支出</p>");
+    } else {
+        let max = statement.categories.first().map(|c| c.total).unwrap_or(0.0);
+        html.push_str("<table>");
+        for category in &statement.categories {
+            let width = if max > 0.0 { category.total / max * 100.0 } else { 0.0 };
+            let share = if total_expense > 0.0 { category.total / total_expense * 100.0 } else { 0.0 };
+            let _ = write!(
+                html,
+                "<tr><td>{} {}</td><td style=\"width: 35%\"><div class=\"bar\" style=\"width: {:.1}%\"></div></td>\
+                 <td class=\"num\">¥{:.2}</td><td class=\"num muted\">{:.1}%</td><td class=\"num muted\">{}笔</td></tr>",
+                icon(&category.category_icon),
+                escape_html(&category.category_name),
+                width,
+                category.total,
+                share,
+                category.transaction_count
+            );
+        }
+        html.push_str("</table>");
+    }
+    html.push_str("</div>\n");
+
+    // Transactions
+    html.push_str("<div class=\"card\"><h2>交易明细</h2>");
+    if statement.transactions.is_empty() {
+        html.push_str("<p class=\"muted\">本月没有记账</p>");
+    } else {
+        html.push_str(
+            "<table class=\"tx\"><tr><th>日期</th><th>分类</th><th>备注</th><th class=\"num\">金额</th></tr>",
+        );
+        for tx in &statement.transactions {
+            // `MM-DD`, with the time when one was recorded
+            let date = match tx.transaction_date.get(11..16).filter(|t| *t != "00:00") {
+                Some(time) => format!("{} {}", tx.transaction_date.get(5..10).unwrap_or_default(), time),
+                None => tx.transaction_date.get(5..10).unwrap_or(&tx.transaction_date).to_string(),
+            };
+            let _ = write!(
+                html,
+                "<tr><td class=\"date\">{}</td><td>{} {}</td><td>{}</td><td class=\"num {}\">{}¥{:.2}</td></tr>",
+                escape_html(&date),
+                icon(&tx.category_icon),
+                escape_html(&tx.category_name),
+                escape_html(tx.note.as_deref().unwrap_or("")),
+                if tx.amount >= 0.0 { "income" } else { "expense" },
+                if tx.amount >= 0.0 { "+" } else { "-" },
+                tx.amount.abs()
+            );
+        }
+        html.push_str("</table>");
+    }
+    html.push_str("</div>\n");
+
+    // Installments
+    html.push_str("<div class=\"card\"><h2>本月分期还款</h2>");
+    if statement.installments.is_empty() {
+        html.push_str("<p class=\"muted\">本月没有到期的分期</p>");
+    } else {
+        html.push_str(
+            "<table><tr><th>到期日</th><th>分期</th><th>期数</th><th class=\"num\">金额</th><th class=\"num\">状态</th></tr>",
+        );
+        for item in &statement.installments {
+            let status = match (item.is_paid, &item.paid_date) {
+                (true, Some(date)) => format!("已还 {}", date.get(5..10).unwrap_or(date)),
+                (true, None) => "已还".to_string(),
+                (false, _) => "待还".to_string(),
+            };
+            let _ = write!(
+                html,
+                "<tr><td class=\"muted\">{}</td><td>{} {}</td><td>{}/{}</td><td class=\"num expense\">¥{:.2}</td><td class=\"num {}\">{}</td></tr>",
+                escape_html(item.due_date.get(5..10).unwrap_or(&item.due_date)),
+                escape_html(&item.category_name),
+                escape_html(item.note.as_deref().unwrap_or("")),
+                item.sequence_number,
+                item.installment_count,
+                item.amount.abs(),
+                if item.is_paid { "muted" } else { "expense" },
+                escape_html(&status)
+            );
+        }
+        html.push_str("</table>");
+    }
+    html.push_str("</div>\n");
+
+    let _ = write!(
+        html,
+        "<div class=\"footer\">生成于 {}</div>\n</div>\n</body>\n</html>\n",
+        chrono::Local::now().format("%Y-%m-%d %H:%M")
+    );
+
+    html
+}
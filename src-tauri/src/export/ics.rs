@@ -0,0 +1,232 @@
+//! Calendar (iCalendar) export: one all-day event per unpaid installment
+//! period, on its due date, and one repeating all-day event per recurring
+//! bill.
+//!
+//! UIDs are derived from the plan id and period number, or the bill id, so
+//! importing a newer file into the same calendar updates the events instead
+//! of duplicating them.
+
+use crate::models::{InstallmentScheduleRow, RecurringBill};
+use chrono::{Datelike, Duration, NaiveDate};
+
+const PRODUCT_ID: &str = "-//keep-accounts//记账//ZH";
+const UID_DOMAIN: &str = "com.keep-accounts.app";
+/// Reminders go off at this hour of the reminder day
+const REMINDER_HOUR: i64 = 9;
+pub const MAX_REMINDER_DAYS: u32 = 60;
+
+/// Escape a TEXT value (RFC 5545 §3.3.11)
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c if c.is_control() => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Append a content line, folded at 75 octets without splitting a character
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// Alarm trigger relative to the start of an all-day event (midnight):
+/// `days` before the due date at `REMINDER_HOUR`
+fn trigger(days: u32) -> String {
+    let minutes = REMINDER_HOUR * 60 - i64::from(days) * 24 * 60;
+    let sign = if minutes < 0 { "-" } else { "" };
+    let minutes = minutes.abs();
+    let (days, hours) = (minutes / (24 * 60), minutes % (24 * 60) / 60);
+    match (days, hours) {
+        (0, hours) => format!("{}PT{}H", sign, hours),
+        (days, 0) => format!("{}P{}D", sign, days),
+        (days, hours) => format!("{}P{}DT{}H", sign, days, hours),
+    }
+}
+
+/// `RRULE` of a bill: every `interval_months` months on the day of its first
+/// date, or on the last day of months too short for that day
+fn recurrence(bill: &RecurringBill, start: NaiveDate) -> String {
+    let day = start.day();
+    let days = if day > 28 {
+        let days: Vec<String> = (28..=day).map(|d| d.to_string()).collect();
+        format!("{};BYSETPOS=-1", days.join(","))
+    } else {
+        day.to_string()
+    };
+    let mut rule = format!("RRULE:FREQ=MONTHLY;INTERVAL={};BYMONTHDAY={}", bill.interval_months.max(1), days);
+    if let Some(end) = bill.end_date.as_deref().and_then(parse_date) {
+        rule.push_str(&format!(";UNTIL={}", end.format("%Y%m%d")));
+    }
+    rule
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..10).unwrap_or(value), "%Y-%m-%d").ok()
+}
+
+/// An all-day event; `rrule` makes it repeat
+struct Event {
+    uid: String,
+    date: NaiveDate,
+    rrule: Option<String>,
+    summary: String,
+    description: String,
+}
+
+fn push_event(out: &mut String, event: &Event, stamp: &str, reminder_days: Option<u32>) {
+    push_line(out, "BEGIN:VEVENT");
+    push_line(out, &format!("UID:{}@{}", event.uid, UID_DOMAIN));
+    push_line(out, &format!("DTSTAMP:{}", stamp));
+    push_line(out, &format!("DTSTART;VALUE=DATE:{}", event.date.format("%Y%m%d")));
+    push_line(out, &format!("DTEND;VALUE=DATE:{}", (event.date + Duration::days(1)).format("%Y%m%d")));
+    if let Some(rrule) = &event.rrule {
+        push_line(out, rrule);
+    }
+    push_line(out, &format!("SUMMARY:{}", escape(&event.summary)));
+    push_line(out, &format!("DESCRIPTION:{}", escape(&event.description)));
+    push_line(out, "TRANSP:TRANSPARENT");
+    if let Some(days) = reminder_days {
+        push_line(out, "BEGIN:VALARM");
+        push_line(out, "ACTION:DISPLAY");
+        push_line(out, &format!("DESCRIPTION:{}", escape(&event.summary)));
+        push_line(out, &format!("TRIGGER:{}", trigger(days)));
+        push_line(out, "END:VALARM");
+    }
+    push_line(out, "END:VEVENT");
+}
+
+/// Render the calendar
+///
+/// `stamp` is the export time as `YYYYMMDDTHHMMSSZ`; `reminder_days` adds an
+/// alarm that many days before each due date.
+pub fn render(
+    schedule: &[InstallmentScheduleRow],
+    bills: &[RecurringBill],
+    reminder_days: Option<u32>,
+    stamp: &str,
+) -> String {
+    let mut out = String::new();
+    for line in [
+        "BEGIN:VCALENDAR",
+        "VERSION:2.0",
+        &format!("PRODID:{}", PRODUCT_ID),
+        "CALSCALE:GREGORIAN",
+        "METHOD:PUBLISH",
+        "X-WR-CALNAME:记账 - 还款提醒",
+    ] {
+        push_line(&mut out, line);
+    }
+
+    for item in schedule.iter().filter(|item| !item.is_paid) {
+        let Some(due) = parse_date(&item.due_date) else { continue };
+        let title = item.note.clone().filter(|n| !n.trim().is_empty()).unwrap_or_else(|| item.category_name.clone());
+        let summary = format!(
+            "分期还款：{} 第 {}/{} 期 ¥{:.2}",
+            title,
+            item.sequence_number,
+            item.installment_count,
+            item.amount.abs()
+        );
+        let description = format!(
+            "分类：{}\n本期金额：¥{:.2}\n分期总额：¥{:.2}（{} 期）",
+            item.category_name,
+            item.amount.abs(),
+            item.total_amount.abs(),
+            item.installment_count
+        );
+        let event = Event {
+            uid: format!("installment-{}-{}", item.installment_id, item.sequence_number),
+            date: due,
+            rrule: None,
+            summary,
+            description,
+        };
+        push_event(&mut out, &event, stamp, reminder_days);
+    }
+
+    for bill in bills {
+        let Some(start) = parse_date(&bill.start_date) else { continue };
+        let summary = format!("周期账单：{} ¥{:.2}", bill.name, bill.amount.abs());
+        let every = match bill.interval_months {
+            1 => "每月".to_string(),
+            12 => "每年".to_string(),
+            months => format!("每 {} 个月", months),
+        };
+        let description = match &bill.end_date {
+            Some(end) => format!("{}，¥{:.2}，至 {}", every, bill.amount.abs(), end),
+            None => format!("{}，¥{:.2}", every, bill.amount.abs()),
+        };
+        let event = Event {
+            uid: format!("recurring-{}", bill.id),
+            date: start,
+            rrule: Some(recurrence(bill, start)),
+            summary,
+            description,
+        };
+        push_event(&mut out, &event, stamp, reminder_days);
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bill(start_date: &str, interval_months: i32, end_date: Option<&str>) -> RecurringBill {
+        RecurringBill {
+            id: 7,
+            name: "房租".to_string(),
+            amount: 3200.0,
+            interval_months,
+            start_date: start_date.to_string(),
+            end_date: end_date.map(str::to_string),
+            created_at: "2024-01-01 00:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn monthly_on_the_start_day() {
+        let bill = bill("2024-03-05", 1, None);
+        let start = parse_date(&bill.start_date).unwrap();
+        assert_eq!(recurrence(&bill, start), "RRULE:FREQ=MONTHLY;INTERVAL=1;BYMONTHDAY=5");
+    }
+
+    #[test]
+    fn late_days_fall_back_to_the_last_day_of_the_month() {
+        let bill = bill("2024-01-31", 3, Some("2024-12-31"));
+        let start = parse_date(&bill.start_date).unwrap();
+        assert_eq!(
+            recurrence(&bill, start),
+            "RRULE:FREQ=MONTHLY;INTERVAL=3;BYMONTHDAY=28,29,30,31;BYSETPOS=-1;UNTIL=20241231"
+        );
+    }
+
+    #[test]
+    fn bills_are_repeating_events() {
+        let calendar = render(&[], &[bill("2024-03-05", 12, None)], Some(1), "20240101T000000Z");
+        assert!(calendar.contains("UID:recurring-7@com.keep-accounts.app\r\n"));
+        assert!(calendar.contains("DTSTART;VALUE=DATE:20240305\r\n"));
+        assert!(calendar.contains("RRULE:FREQ=MONTHLY;INTERVAL=12;BYMONTHDAY=5\r\n"));
+        assert!(calendar.contains("SUMMARY:周期账单：房租 ¥3200.00\r\n"));
+        assert!(calendar.contains("TRIGGER:-PT15H\r\n"));
+    }
+}
@@ -0,0 +1,40 @@
+//! Export
+//!
+//! Renders app data into files (HTML reports, CSV, Excel, calendars, ...). Commands either write to a
+//! path chosen by the user (desktop save dialog) or, when no path is given,
+//! into the app cache dir so the file can be handed to the share sheet (Android).
+
+pub mod csv;
+pub mod html;
+pub mod ics;
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+pub mod pdf;
+pub mod plaintext;
+pub mod xlsx;
+
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// Resolve where an export should be written
+///
+/// `path` comes from the save dialog; `None` means "export for sharing".
+pub fn resolve_export_path(app: &AppHandle, path: Option<String>, file_name: &str) -> Result<PathBuf, String> {
+    match path {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => {
+            let dir = app
+                .path()
+                .app_cache_dir()
+                .map_err(|e| e.to_string())?
+                .join("exports");
+            std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            Ok(dir.join(file_name))
+        }
+    }
+}
+
+/// Write an exported file and return its path as a string
+pub fn write_export(path: &Path, content: &[u8]) -> Result<String, String> {
+    std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path.to_string_lossy().to_string())
+}
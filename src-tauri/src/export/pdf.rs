@@ -0,0 +1,80 @@
+//! PDF rendering of HTML reports (desktop only).
+//!
+//! There is no PDF engine in the app; the HTML is printed by a Chromium-based
+//! browser installed on the machine (Chrome, Edge or Chromium) running
+//! headless. Without one, the HTML export is the fallback.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Well-known install locations, tried after the names on `PATH`
+#[cfg(target_os = "windows")]
+const BROWSER_PATHS: &[&str] = &[
+    r"C:\Program Files\Google\Chrome\Application\chrome.exe",
+    r"C:\Program Files (x86)\Google\Chrome\Application\chrome.exe",
+    r"C:\Program Files (x86)\Microsoft\Edge\Application\msedge.exe",
+    r"C:\Program Files\Microsoft\Edge\Application\msedge.exe",
+];
+#[cfg(target_os = "macos")]
+const BROWSER_PATHS: &[&str] = &[
+    "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+    "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+    "/Applications/Chromium.app/Contents/MacOS/Chromium",
+];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const BROWSER_PATHS: &[&str] = &["/usr/bin/google-chrome", "/usr/bin/chromium", "/snap/bin/chromium"];
+
+const BROWSER_NAMES: &[&str] = &["google-chrome", "google-chrome-stable", "chromium", "chromium-browser", "microsoft-edge", "msedge", "chrome"];
+
+/// Find a browser that can print to PDF
+fn find_browser() -> Option<PathBuf> {
+    let path_var = std::env::var_os("PATH").unwrap_or_default();
+    let on_path = BROWSER_NAMES.iter().find_map(|name| {
+        std::env::split_paths(&path_var)
+            .map(|dir| dir.join(if cfg!(windows) { format!("{}.exe", name) } else { name.to_string() }))
+            .find(|candidate| candidate.is_file())
+    });
+    on_path.or_else(|| BROWSER_PATHS.iter().map(PathBuf::from).find(|p| p.is_file()))
+}
+
+/// Keeps the console window of the browser from flashing up on Windows
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+/// Print a standalone HTML file to `pdf_path`. The browser takes a few
+/// seconds, so it runs on a blocking thread.
+pub async fn html_to_pdf(html_path: &Path, pdf_path: &Path) -> Result<(), String> {
+    let browser = find_browser().ok_or("未找到可生成 PDF 的浏览器（Chrome / Edge / Chromium），请导出 HTML 后用浏览器打印为 PDF")?;
+    // The browser resolves a bare path relative to its own working directory,
+    // so it gets a file:// URL. dunce drops the `\\?\` prefix Windows adds to
+    // canonical paths, which no URL can carry.
+    let html_path = dunce::canonicalize(html_path).map_err(|e| e.to_string())?;
+    let html_url = url::Url::from_file_path(&html_path)
+        .map_err(|_| format!("Invalid file path: {}", html_path.display()))?;
+    // So a file left from an earlier export is not mistaken for the result
+    let _ = std::fs::remove_file(pdf_path);
+
+    let mut command = Command::new(&browser);
+    command
+        .arg("--headless")
+        .arg("--disable-gpu")
+        .arg("--no-pdf-header-footer")
+        .arg(format!("--print-to-pdf={}", pdf_path.display()))
+        .arg(html_url.as_str());
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = tauri::async_runtime::spawn_blocking(move || command.output())
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Failed to run {}: {}", browser.display(), e))?;
+    if !output.status.success() || !pdf_path.is_file() {
+        return Err(format!(
+            "生成 PDF 失败: {}",
+            String::from_utf8_lossy(&output.stderr).lines().last().unwrap_or_default()
+        ));
+    }
+    Ok(())
+}
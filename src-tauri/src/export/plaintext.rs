@@ -0,0 +1,246 @@
+//! Plain-text accounting export (Beancount / ledger-cli).
+//!
+//! Categories become `Expenses:` accounts, or `Income:` accounts when their
+//! transactions add up to income. Every transaction is posted against one
+//! asset (or liability) account chosen by the user. An installment plan is
+//! booked in full on its start date against `Liabilities:Installments`, and
+//! each paid period moves money from the asset account to the liability.
+//!
+//! The output has no timestamps and is sorted by date and id, with the ids as
+//! metadata, so re-exporting an unchanged ledger gives the same file.
+
+use crate::models::{InstallmentDetail, InstallmentWithCategory, PlainTextFormat, TransactionWithCategory};
+use std::collections::{BTreeMap, HashMap};
+
+pub const DEFAULT_ASSET_ACCOUNT: &str = "Assets:Cash";
+pub const DEFAULT_CURRENCY: &str = "CNY";
+const INSTALLMENT_ACCOUNT: &str = "Liabilities:Installments";
+
+/// A balanced transaction with two postings
+struct Entry {
+    /// `YYYY-MM-DD`
+    date: String,
+    /// Sort key after the date: (kind, id, sequence)
+    order: (u8, i64, i32),
+    narration: String,
+    meta: Vec<(&'static str, String)>,
+    /// The first account receives `amount`, the second `-amount`
+    accounts: (String, String),
+    amount: f64,
+}
+
+/// Turn a name into an account name component
+///
+/// Beancount allows letters (including Chinese), digits and dashes; the first
+/// character must not be a lowercase ASCII letter or a dash.
+fn component(name: &str) -> String {
+    let mut cleaned = String::new();
+    for c in name.trim().chars() {
+        let c = if c.is_alphanumeric() { c } else { '-' };
+        if !(c == '-' && cleaned.ends_with('-')) {
+            cleaned.push(c);
+        }
+    }
+    let cleaned = cleaned.trim_matches('-');
+    let mut chars = cleaned.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// Check the user's asset account, e.g. `Assets:Bank:Checking` or
+/// `Liabilities:CreditCard`
+pub fn parse_asset_account(account: Option<&str>) -> Result<String, String> {
+    let account = account.map(str::trim).filter(|a| !a.is_empty()).unwrap_or(DEFAULT_ASSET_ACCOUNT);
+    let parts: Vec<&str> = account.split(':').collect();
+    if !matches!(parts[0], "Assets" | "Liabilities") || parts.len() < 2 {
+        return Err("账户须以 Assets: 或 Liabilities: 开头，例如 Assets:Cash".to_string());
+    }
+    if parts.iter().any(|part| component(part) != *part) {
+        return Err(format!("账户名称无效: {}", account));
+    }
+    Ok(account.to_string())
+}
+
+/// Check a commodity name such as `CNY`
+pub fn parse_currency(currency: Option<&str>) -> Result<String, String> {
+    let currency = currency.map(str::trim).filter(|c| !c.is_empty()).unwrap_or(DEFAULT_CURRENCY);
+    let valid = currency.len() >= 2
+        && currency.len() <= 24
+        && currency.starts_with(|c: char| c.is_ascii_uppercase())
+        && currency.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+    if valid {
+        Ok(currency.to_string())
+    } else {
+        Err(format!("币种无效: {}（应为大写字母，如 CNY）", currency))
+    }
+}
+
+/// Account of every category that has transactions or installments
+fn category_accounts(
+    transactions: &[TransactionWithCategory],
+    installments: &[InstallmentWithCategory],
+) -> BTreeMap<i64, String> {
+    let mut categories: BTreeMap<i64, (&str, f64)> = BTreeMap::new();
+    for tx in transactions {
+        categories.entry(tx.category_id).or_insert((&tx.category_name, 0.0)).1 += tx.amount;
+    }
+    for plan in installments {
+        categories.entry(plan.category_id).or_insert((&plan.category_name, 0.0)).1 -= plan.total_amount.abs();
+    }
+
+    let mut used: HashMap<String, usize> = HashMap::new();
+    let mut accounts = BTreeMap::new();
+    for (id, (name, total)) in categories {
+        let root = if total > 0.0 { "Income" } else { "Expenses" };
+        // `餐饮:午餐` (as created by a journal import) becomes a sub-account
+        let parts: Vec<String> = name.split(':').map(component).filter(|part| !part.is_empty()).collect();
+        let name = match parts.join(":") {
+            name if name.is_empty() => format!("Category{}", id),
+            name => name,
+        };
+        let mut account = format!("{}:{}", root, name);
+        // Two categories with the same cleaned name get the id appended
+        let seen = used.entry(account.clone()).or_insert(0);
+        *seen += 1;
+        if *seen > 1 {
+            account = format!("{}-{}", account, id);
+        }
+        accounts.insert(id, account);
+    }
+    accounts
+}
+
+fn entries(
+    transactions: &[TransactionWithCategory],
+    installments: &[InstallmentWithCategory],
+    details: &[InstallmentDetail],
+    accounts: &BTreeMap<i64, String>,
+    asset_account: &str,
+) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for tx in transactions {
+        let date = tx.transaction_date.get(..10).unwrap_or(&tx.transaction_date).to_string();
+        let mut meta = vec![("id", format!("tx-{}", tx.id))];
+        if let Some(time) = tx.transaction_date.get(11..16).filter(|t| *t != "00:00") {
+            meta.push(("time", time.to_string()));
+        }
+        entries.push(Entry {
+            date,
+            order: (0, tx.id, 0),
+            narration: tx.note.clone().filter(|n| !n.trim().is_empty()).unwrap_or_else(|| tx.category_name.clone()),
+            meta,
+            accounts: (accounts[&tx.category_id].clone(), asset_account.to_string()),
+            amount: -tx.amount,
+        });
+    }
+
+    let plans: HashMap<i64, &InstallmentWithCategory> = installments.iter().map(|p| (p.id, p)).collect();
+    for plan in installments {
+        let title = plan.note.clone().filter(|n| !n.trim().is_empty()).unwrap_or_else(|| plan.category_name.clone());
+        entries.push(Entry {
+            date: plan.start_date.get(..10).unwrap_or(&plan.start_date).to_string(),
+            order: (1, plan.id, 0),
+            narration: format!("分期：{}（{} 期）", title, plan.installment_count),
+            meta: vec![("id", format!("inst-{}", plan.id))],
+            accounts: (accounts[&plan.category_id].clone(), INSTALLMENT_ACCOUNT.to_string()),
+            amount: plan.total_amount.abs(),
+        });
+    }
+    for detail in details.iter().filter(|d| d.is_paid) {
+        let Some(plan) = plans.get(&detail.installment_id) else { continue };
+        let title = plan.note.clone().filter(|n| !n.trim().is_empty()).unwrap_or_else(|| plan.category_name.clone());
+        let date = detail.paid_date.as_deref().unwrap_or(&detail.due_date);
+        entries.push(Entry {
+            date: date.get(..10).unwrap_or(date).to_string(),
+            order: (2, plan.id, detail.sequence_number),
+            narration: format!("分期还款：{} 第 {}/{} 期", title, detail.sequence_number, plan.installment_count),
+            meta: vec![("id", format!("inst-{}-{}", plan.id, detail.sequence_number))],
+            accounts: (INSTALLMENT_ACCOUNT.to_string(), asset_account.to_string()),
+            amount: detail.amount.abs(),
+        });
+    }
+
+    entries.sort_by(|a, b| a.date.cmp(&b.date).then(a.order.cmp(&b.order)));
+    entries
+}
+
+/// Two decimals, without a negative zero
+fn money(value: f64) -> String {
+    let value = (value * 100.0).round() / 100.0;
+    format!("{:.2}", if value == 0.0 { 0.0 } else { value })
+}
+
+fn quote(text: &str) -> String {
+    let text: String = text.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Render the ledger in the chosen format
+pub fn render(
+    format: PlainTextFormat,
+    transactions: &[TransactionWithCategory],
+    installments: &[InstallmentWithCategory],
+    details: &[InstallmentDetail],
+    asset_account: &str,
+    currency: &str,
+) -> String {
+    let accounts = category_accounts(transactions, installments);
+    let entries = entries(transactions, installments, details, &accounts, asset_account);
+
+    // Every account is opened on the first day of the ledger
+    let mut used: Vec<&str> = vec![asset_account];
+    used.extend(accounts.values().map(String::as_str));
+    if !installments.is_empty() {
+        used.push(INSTALLMENT_ACCOUNT);
+    }
+    used.sort();
+    used.dedup();
+    let categories: HashMap<&str, i64> = accounts.iter().map(|(id, account)| (account.as_str(), *id)).collect();
+
+    let mut out = String::new();
+    match format {
+        PlainTextFormat::Beancount => {
+            out.push_str(";; 记账 (KeepAccounts) export\n");
+            out.push_str(&format!("option \"operating_currency\" \"{}\"\n", currency));
+            if let Some(first) = entries.first() {
+                out.push('\n');
+                for account in &used {
+                    out.push_str(&format!("{} open {} {}\n", first.date, account, currency));
+                    if let Some(id) = categories.get(account) {
+                        out.push_str(&format!("  category_id: {}\n", id));
+                    }
+                }
+            }
+            for entry in &entries {
+                out.push_str(&format!("\n{} * {}\n", entry.date, quote(&entry.narration)));
+                for (key, value) in &entry.meta {
+                    out.push_str(&format!("  {}: {}\n", key, quote(value)));
+                }
+                out.push_str(&format!("  {}  {} {}\n", entry.accounts.0, money(entry.amount), currency));
+                out.push_str(&format!("  {}  {} {}\n", entry.accounts.1, money(-entry.amount), currency));
+            }
+        }
+        PlainTextFormat::Ledger => {
+            out.push_str("; 记账 (KeepAccounts) export\n");
+            out.push_str(&format!("\ncommodity {}\n", currency));
+            for account in &used {
+                out.push_str(&format!("account {}\n", account));
+                if let Some(id) = categories.get(account) {
+                    out.push_str(&format!("    ; category_id: {}\n", id));
+                }
+            }
+            for entry in &entries {
+                let narration: String = entry.narration.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+                out.push_str(&format!("\n{} * {}\n", entry.date.replace('-', "/"), narration));
+                for (key, value) in &entry.meta {
+                    out.push_str(&format!("    ; {}: {}\n", key, value));
+                }
+                out.push_str(&format!("    {}  {} {}\n", entry.accounts.0, money(entry.amount), currency));
+                out.push_str(&format!("    {}  {} {}\n", entry.accounts.1, money(-entry.amount), currency));
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,193 @@
+//! Excel export: a summary sheet of category totals per month, one sheet of
+//! transactions per month and the installment schedule.
+
+use crate::models::{InstallmentScheduleRow, TransactionWithCategory};
+use rust_xlsxwriter::{ExcelDateTime, Format, FormatAlign, Workbook, Worksheet, XlsxError};
+use std::collections::{BTreeMap, HashMap};
+
+const MONEY_FORMAT: &str = "#,##0.00;[Red]-#,##0.00";
+
+/// Formats shared by all sheets
+struct Formats {
+    header: Format,
+    money: Format,
+    date: Format,
+    datetime: Format,
+    total_label: Format,
+    total_money: Format,
+}
+
+impl Formats {
+    fn new() -> Self {
+        let header = Format::new().set_bold().set_background_color("#EEEEEE").set_align(FormatAlign::Center);
+        Formats {
+            header,
+            money: Format::new().set_num_format(MONEY_FORMAT),
+            date: Format::new().set_num_format("yyyy-mm-dd"),
+            datetime: Format::new().set_num_format("yyyy-mm-dd hh:mm"),
+            total_label: Format::new().set_bold(),
+            total_money: Format::new().set_bold().set_num_format(MONEY_FORMAT),
+        }
+    }
+}
+
+/// Render the workbook
+///
+/// Transactions may come in any order; each month sheet lists its
+/// transactions oldest first.
+pub fn render_workbook(
+    transactions: &[TransactionWithCategory],
+    schedule: &[InstallmentScheduleRow],
+) -> Result<Vec<u8>, String> {
+    build(transactions, schedule).map_err(|e| e.to_string())
+}
+
+fn build(transactions: &[TransactionWithCategory], schedule: &[InstallmentScheduleRow]) -> Result<Vec<u8>, XlsxError> {
+    let formats = Formats::new();
+
+    // "YYYY-MM" → transactions
+    let mut months: BTreeMap<&str, Vec<&TransactionWithCategory>> = BTreeMap::new();
+    for tx in transactions {
+        months.entry(tx.transaction_date.get(..7).unwrap_or(&tx.transaction_date)).or_default().push(tx);
+    }
+    for list in months.values_mut() {
+        list.sort_by(|a, b| a.transaction_date.cmp(&b.transaction_date).then(a.id.cmp(&b.id)));
+    }
+
+    let mut workbook = Workbook::new();
+    workbook.push_worksheet(summary_sheet(&months, &formats)?);
+    for (month, list) in &months {
+        workbook.push_worksheet(month_sheet(month, list, &formats)?);
+    }
+    workbook.push_worksheet(installments_sheet(schedule, &formats)?);
+    workbook.save_to_buffer()
+}
+
+fn write_header(sheet: &mut Worksheet, headers: &[&str], formats: &Formats) -> Result<(), XlsxError> {
+    for (col, header) in headers.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *header, &formats.header)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    Ok(())
+}
+
+/// Write a `YYYY-MM-DD[ HH:MM:SS]` date as an Excel date; anything else as text
+fn write_date(sheet: &mut Worksheet, row: u32, col: u16, value: &str, formats: &Formats) -> Result<(), XlsxError> {
+    let has_time = value.len() > 10 && !value.ends_with("00:00:00");
+    let date = if has_time { ExcelDateTime::parse_from_str(value) } else { ExcelDateTime::parse_from_str(value.get(..10).unwrap_or(value)) };
+    match date {
+        Ok(date) => sheet.write_datetime_with_format(row, col, &date, if has_time { &formats.datetime } else { &formats.date })?,
+        Err(_) => sheet.write_string(row, col, value)?,
+    };
+    Ok(())
+}
+
+/// Category totals per month, with income, expense and balance rows
+fn summary_sheet(months: &BTreeMap<&str, Vec<&TransactionWithCategory>>, formats: &Formats) -> Result<Worksheet, XlsxError> {
+    let mut sheet = Worksheet::new();
+    sheet.set_name("汇总")?;
+
+    let month_names: Vec<&str> = months.keys().copied().collect();
+    let mut headers = vec!["分类"];
+    headers.extend(&month_names);
+    headers.push("合计");
+    write_header(&mut sheet, &headers, formats)?;
+    sheet.set_freeze_panes(1, 1)?;
+    sheet.set_column_width(0, 16)?;
+    for col in 1..headers.len() {
+        sheet.set_column_width(col as u16, 12)?;
+    }
+
+    // category → month index → total
+    let mut totals: HashMap<&str, Vec<f64>> = HashMap::new();
+    let mut income = vec![0.0; month_names.len()];
+    let mut expense = vec![0.0; month_names.len()];
+    for (index, list) in months.values().enumerate() {
+        for tx in list {
+            totals.entry(tx.category_name.as_str()).or_insert_with(|| vec![0.0; month_names.len()])[index] += tx.amount;
+            if tx.amount >= 0.0 {
+                income[index] += tx.amount;
+            } else {
+                expense[index] += tx.amount;
+            }
+        }
+    }
+    // Biggest spending first, income categories last
+    let mut categories: Vec<(&str, Vec<f64>)> = totals.into_iter().collect();
+    categories.sort_by(|a, b| a.1.iter().sum::<f64>().total_cmp(&b.1.iter().sum::<f64>()).then(a.0.cmp(b.0)));
+
+    let mut row = 1;
+    for (name, values) in &categories {
+        sheet.write_string(row, 0, *name)?;
+        write_values(&mut sheet, row, values, &formats.money)?;
+        row += 1;
+    }
+    row += 1;
+    let balance: Vec<f64> = income.iter().zip(&expense).map(|(i, e)| i + e).collect();
+    for (label, values) in [("收入合计", &income), ("支出合计", &expense), ("结余", &balance)] {
+        sheet.write_string_with_format(row, 0, label, &formats.total_label)?;
+        write_values(&mut sheet, row, values, &formats.total_money)?;
+        row += 1;
+    }
+    Ok(sheet)
+}
+
+/// The values of a summary row, one per month, followed by the row total
+fn write_values(sheet: &mut Worksheet, row: u32, values: &[f64], format: &Format) -> Result<(), XlsxError> {
+    for (index, value) in values.iter().enumerate() {
+        sheet.write_number_with_format(row, index as u16 + 1, *value, format)?;
+    }
+    sheet.write_number_with_format(row, values.len() as u16 + 1, values.iter().sum::<f64>(), format)?;
+    Ok(())
+}
+
+fn month_sheet(month: &str, transactions: &[&TransactionWithCategory], formats: &Formats) -> Result<Worksheet, XlsxError> {
+    let mut sheet = Worksheet::new();
+    sheet.set_name(month)?;
+    write_header(&mut sheet, &["日期", "分类", "收支", "金额", "备注"], formats)?;
+    for (col, width) in [16, 12, 8, 12, 40].into_iter().enumerate() {
+        sheet.set_column_width(col as u16, width)?;
+    }
+
+    for (index, tx) in transactions.iter().enumerate() {
+        let row = index as u32 + 1;
+        write_date(&mut sheet, row, 0, &tx.transaction_date, formats)?;
+        sheet.write_string(row, 1, &tx.category_name)?;
+        sheet.write_string(row, 2, if tx.amount >= 0.0 { "收入" } else { "支出" })?;
+        sheet.write_number_with_format(row, 3, tx.amount, &formats.money)?;
+        sheet.write_string(row, 4, tx.note.as_deref().unwrap_or_default())?;
+    }
+    sheet.autofilter(0, 0, transactions.len() as u32, 4)?;
+    Ok(sheet)
+}
+
+fn installments_sheet(schedule: &[InstallmentScheduleRow], formats: &Formats) -> Result<Worksheet, XlsxError> {
+    let mut sheet = Worksheet::new();
+    sheet.set_name("分期")?;
+    write_header(
+        &mut sheet,
+        &["分期编号", "分类", "备注", "总金额", "期数", "第几期", "到期日", "本期金额", "状态", "还款日期"],
+        formats,
+    )?;
+    for (col, width) in [10, 12, 30, 12, 8, 8, 12, 12, 10, 12].into_iter().enumerate() {
+        sheet.set_column_width(col as u16, width)?;
+    }
+
+    for (index, item) in schedule.iter().enumerate() {
+        let row = index as u32 + 1;
+        sheet.write_number(row, 0, item.installment_id as f64)?;
+        sheet.write_string(row, 1, &item.category_name)?;
+        sheet.write_string(row, 2, item.note.as_deref().unwrap_or_default())?;
+        sheet.write_number_with_format(row, 3, item.total_amount, &formats.money)?;
+        sheet.write_number(row, 4, item.installment_count)?;
+        sheet.write_number(row, 5, item.sequence_number)?;
+        write_date(&mut sheet, row, 6, &item.due_date, formats)?;
+        sheet.write_number_with_format(row, 7, item.amount, &formats.money)?;
+        sheet.write_string(row, 8, if item.is_paid { "已还款" } else { "待还款" })?;
+        if let Some(paid_date) = &item.paid_date {
+            write_date(&mut sheet, row, 9, paid_date, formats)?;
+        }
+    }
+    sheet.autofilter(0, 0, schedule.len() as u32, 9)?;
+    Ok(sheet)
+}
@@ -0,0 +1,197 @@
+//! 支付宝 bill CSV
+//!
+//! The export starts with a block of account information, followed by the
+//! column header (交易时间, 交易分类, 交易对方, 商品说明, 收/支, 金额, 收/付款方式,
+//! 交易状态, ...) and one line per trade. Older exports name some columns
+//! differently (交易创建时间, 商品名称, 金额（元）) and end with a footer block.
+
+use super::table::TableRow;
+use super::{join_note, parse_amount, parse_datetime, ParsedRow};
+
+const TIME_COLUMNS: &[&str] = &["交易时间", "交易创建时间", "付款时间"];
+const DIRECTION_COLUMNS: &[&str] = &["收/支"];
+const AMOUNT_COLUMNS: &[&str] = &["金额", "金额（元）", "金额(元)"];
+const STATUS_COLUMNS: &[&str] = &["交易状态"];
+const COUNTERPARTY_COLUMNS: &[&str] = &["交易对方"];
+const GOODS_COLUMNS: &[&str] = &["商品说明", "商品名称"];
+const CATEGORY_COLUMNS: &[&str] = &["交易分类"];
+const REMARK_COLUMNS: &[&str] = &["备注"];
+const PAYMENT_COLUMNS: &[&str] = &["收/付款方式"];
+const ORDER_COLUMNS: &[&str] = &["交易订单号", "交易号"];
+
+/// Column positions found in the header line
+struct Columns {
+    time: usize,
+    direction: usize,
+    amount: usize,
+    status: usize,
+    counterparty: Option<usize>,
+    goods: Option<usize>,
+    category: Option<usize>,
+    remark: Option<usize>,
+    payment: Option<usize>,
+    order: Option<usize>,
+}
+
+impl Columns {
+    fn find(header: &TableRow) -> Option<Columns> {
+        Some(Columns {
+            time: header.position(TIME_COLUMNS)?,
+            direction: header.position(DIRECTION_COLUMNS)?,
+            amount: header.position(AMOUNT_COLUMNS)?,
+            status: header.position(STATUS_COLUMNS)?,
+            counterparty: header.position(COUNTERPARTY_COLUMNS),
+            goods: header.position(GOODS_COLUMNS),
+            category: header.position(CATEGORY_COLUMNS),
+            remark: header.position(REMARK_COLUMNS),
+            payment: header.position(PAYMENT_COLUMNS),
+            order: header.position(ORDER_COLUMNS),
+        })
+    }
+}
+
+/// Parse the rows of a 支付宝 bill
+pub fn parse(table: &[TableRow]) -> Result<Vec<ParsedRow>, String> {
+    let (header, columns) = table
+        .iter()
+        .enumerate()
+        .find_map(|(i, row)| Columns::find(row).map(|columns| (i, columns)))
+        .ok_or("没有找到支付宝账单的表头（交易时间、收/支、金额、交易状态），请确认选择的是支付宝导出的 CSV 账单")?;
+
+    Ok(table[header + 1..]
+        .iter()
+        .filter_map(|row| parse_row(&columns, row))
+        .collect())
+}
+
+/// Parse a trade line; lines without a valid time (footer, separators) are not trades
+fn parse_row(columns: &Columns, row: &TableRow) -> Option<ParsedRow> {
+    let transaction_date = parse_datetime(row.cell(Some(columns.time))?)?;
+    let note = join_note(&[row.cell(columns.counterparty), row.cell(columns.goods), row.cell(columns.remark)]);
+    let status = row.cell(Some(columns.status)).unwrap_or_default();
+    let direction = row.cell(Some(columns.direction)).unwrap_or_default();
+
+    let mut skip_reason = skip_reason(status, direction);
+    let amount = match row.cell(Some(columns.amount)).and_then(parse_amount) {
+        Some(amount) if amount != 0.0 => {
+            if direction == "支出" { -amount } else { amount }
+        }
+        _ => {
+            skip_reason.get_or_insert_with(|| "金额无效".to_string());
+            0.0
+        }
+    };
+
+    Some(ParsedRow {
+        line: row.line,
+        transaction_date,
+        amount,
+        note,
+        category_hint: row.cell(columns.category).map(str::to_string),
+        external_id: row.cell(columns.order).map(str::to_string),
+        // 花呗, 余额, a bank card ...; empty for most income
+        account: row.cell(columns.payment).map(str::to_string),
+        category_name: None,
+        skip_reason,
+    })
+}
+
+/// Why a trade is not imported by default
+///
+/// Refunds and closed trades never moved money (or moved it back), and
+/// 不计收支 lines are transfers between the user's own accounts (余额宝,
+/// 信用卡还款, 提现...). Transfers to other people are ordinary 支出/收入.
+fn skip_reason(status: &str, direction: &str) -> Option<String> {
+    if status.contains("退款") {
+        Some("退款".to_string())
+    } else if status.contains("关闭") {
+        Some("交易关闭".to_string())
+    } else if status.contains("失败") {
+        Some("交易失败".to_string())
+    } else if status.contains("等待") || status.contains("处理中") {
+        Some("交易未完成".to_string())
+    } else if direction != "支出" && direction != "收入" {
+        Some("不计收支".to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::table::read_table;
+
+    const BILL: &str = "\
+------------------------------------------------------------------------------------
+导出信息：
+姓名：张三
+支付宝账户：zhang***@example.com
+起始时间：[2024-03-01 00:00:00]    终止时间：[2024-03-31 23:59:59]
+------------------------支付宝（中国）网络技术有限公司  电子客户回单------------------------
+交易时间,交易分类,交易对方,对方账号,商品说明,收/支,金额,收/付款方式,交易状态,交易订单号,商家订单号,备注,
+2024-03-05 12:30:00,餐饮美食,肯德基,kfc***@yum.com,午餐套餐,支出,35.50,花呗,交易成功,2024030522001,T001,,
+2024-03-06 09:00:00,转账红包,李四,li***@qq.com,转账,收入,200.00,,交易成功,2024030622002,/,,
+2024-03-07 18:00:00,投资理财,余额宝,/,余额宝-自动转入,不计收支,100.00,余额,交易成功,2024030722003,/,,
+2024-03-08 20:00:00,日用百货,淘宝商家,/,纸巾,支出,20.00,余额,退款成功,2024030822004,/,,
+";
+
+    fn parse_bill(content: &[u8]) -> Vec<ParsedRow> {
+        parse(&read_table(content).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn header_after_preamble() {
+        let rows = parse_bill(BILL.as_bytes());
+        assert_eq!(rows.len(), 4);
+        let row = &rows[0];
+        assert_eq!(row.line, 8);
+        assert_eq!(row.transaction_date, "2024-03-05 12:30:00");
+        assert_eq!(row.amount, -35.5);
+        assert_eq!(row.note.as_deref(), Some("肯德基 午餐套餐"));
+        assert_eq!(row.category_hint.as_deref(), Some("餐饮美食"));
+        assert_eq!(row.account.as_deref(), Some("花呗"));
+        assert_eq!(row.skip_reason, None);
+        assert_eq!(rows[1].amount, 200.0);
+        assert_eq!(rows[1].account, None);
+        assert_eq!(rows[1].skip_reason, None);
+    }
+
+    #[test]
+    fn transfers_and_refunds_are_skipped() {
+        let rows = parse_bill(BILL.as_bytes());
+        assert_eq!(rows[2].skip_reason.as_deref(), Some("不计收支"));
+        assert_eq!(rows[3].skip_reason.as_deref(), Some("退款"));
+    }
+
+    #[test]
+    fn gbk_bill() {
+        let (content, _, _) = encoding_rs::GBK.encode(BILL);
+        let rows = parse_bill(&content);
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0].note.as_deref(), Some("肯德基 午餐套餐"));
+    }
+
+    #[test]
+    fn older_export_columns() {
+        let bill = "\
+支付宝交易记录明细查询
+账号:[zhang***@example.com]
+交易号,商户订单号,交易创建时间,付款时间,最近修改时间,交易来源地,类型,交易对方,商品名称,金额（元）,收/支,交易状态,服务费（元）,成功退款（元）,备注,资金状态,
+2024030522001 ,T001 ,2024/3/5 12:30 ,2024/3/5 12:30 ,2024/3/5 12:31 ,其他 ,即时到账交易 ,肯德基 ,午餐套餐 ,\"1,035.50\" ,支出 ,交易成功 ,0.00 ,0.00 , ,已支出 ,
+------------------------------------------------------------------------------------
+共1笔记录
+";
+        let rows = parse_bill(bill.as_bytes());
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].transaction_date, "2024-03-05 12:30:00");
+        assert_eq!(rows[0].amount, -1035.5);
+        assert_eq!(rows[0].note.as_deref(), Some("肯德基 午餐套餐"));
+        assert_eq!(rows[0].account, None);
+    }
+
+    #[test]
+    fn missing_header() {
+        assert!(parse(&read_table("日期,金额\n2024-03-05,10\n".as_bytes()).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,223 @@
+//! Generic CSV
+//!
+//! Files from other bookkeeping apps (随手记, 鲨鱼记账) and spreadsheets have
+//! no fixed layout, so the user maps the columns with a `CsvMapping` after
+//! `sniff` has guessed the encoding and delimiter.
+
+use super::table::{read_csv, TableRow};
+use super::{join_note, ParsedRow};
+use crate::models::{CsvMapping, CsvSniff, SignConvention};
+
+/// Delimiters tried by `sniff`
+const DELIMITERS: [char; 4] = [',', '\t', ';', '|'];
+
+/// Number of lines `sniff` looks at and returns
+const SNIFF_ROWS: usize = 10;
+
+/// Guess the encoding and delimiter and return the first rows
+pub fn sniff(content: &[u8]) -> Result<CsvSniff, String> {
+    let text = super::decode_text(content);
+    let stripped = content.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(content);
+    let encoding = if std::str::from_utf8(stripped).is_ok() { "UTF-8" } else { "GB18030" };
+
+    let delimiter = guess_delimiter(&text);
+    let rows = read_csv(&text, delimiter as u8)?
+        .into_iter()
+        .take(SNIFF_ROWS)
+        .map(|row| row.cells)
+        .collect();
+
+    Ok(CsvSniff {
+        encoding: encoding.to_string(),
+        delimiter,
+        rows,
+    })
+}
+
+/// The delimiter that splits the most lines into the same number of fields
+fn guess_delimiter(text: &str) -> char {
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).take(SNIFF_ROWS).collect();
+    DELIMITERS
+        .into_iter()
+        .max_by_key(|delimiter| {
+            let counts: Vec<usize> = lines.iter().map(|l| l.matches(*delimiter).count()).collect();
+            let most_common = counts
+                .iter()
+                .filter(|c| **c > 0)
+                .max_by_key(|c| counts.iter().filter(|o| o == c).count());
+            // Lines agreeing with the most common count, then the count itself
+            most_common.map_or((0, 0), |c| (counts.iter().filter(|o| *o == c).count(), *c))
+        })
+        .unwrap_or(',')
+}
+
+/// Parse a CSV file with the user's column mapping
+pub fn parse(content: &[u8], mapping: &CsvMapping) -> Result<Vec<ParsedRow>, String> {
+    if !mapping.delimiter.is_ascii() {
+        return Err("分隔符必须是单个 ASCII 字符".to_string());
+    }
+    if mapping.sign == SignConvention::TypeColumn && mapping.type_column.is_none() {
+        return Err("请指定收支类型所在的列".to_string());
+    }
+
+    let table = read_csv(&super::decode_text(content), mapping.delimiter as u8)?;
+    let skip = mapping.skip_rows + usize::from(mapping.has_header);
+    Ok(table
+        .iter()
+        .skip(skip)
+        .filter(|row| row.cells.iter().any(|c| !c.trim().is_empty()))
+        .map(|row| parse_row(mapping, row))
+        .collect())
+}
+
+fn parse_row(mapping: &CsvMapping, row: &TableRow) -> ParsedRow {
+    let transaction_date = row
+        .cell(Some(mapping.date_column))
+        .and_then(|value| parse_date(value, &mapping.date_format));
+    let amount = row.cell(Some(mapping.amount_column)).and_then(parse_signed_amount);
+    let kind = row.cell(mapping.type_column).unwrap_or_default();
+
+    let mut skip_reason = if transaction_date.is_none() {
+        Some("日期无效".to_string())
+    } else if !amount.is_some_and(|a| a != 0.0) {
+        Some("金额无效".to_string())
+    } else {
+        None
+    };
+
+    let amount = amount.unwrap_or(0.0);
+    let amount = match mapping.sign {
+        SignConvention::Signed => amount,
+        SignConvention::Inverted => -amount,
+        SignConvention::TypeColumn => {
+            if kind.contains("收入") {
+                amount.abs()
+            } else if kind.contains("支出") {
+                -amount.abs()
+            } else {
+                skip_reason.get_or_insert_with(|| "非收支记录（如转账）".to_string());
+                amount
+            }
+        }
+    };
+
+    ParsedRow {
+        line: row.line,
+        transaction_date: transaction_date.unwrap_or_default(),
+        amount,
+        note: join_note(&[row.cell(mapping.note_column)]),
+        category_hint: None,
+        category_name: row.cell(mapping.category_column).map(str::to_string),
+        external_id: None,
+        account: None,
+        skip_reason,
+    }
+}
+
+/// Parse a date with the chosen format, keeping the time if the format has one
+fn parse_date(value: &str, format: &str) -> Option<String> {
+    if let Ok(time) = chrono::NaiveDateTime::parse_from_str(value, format) {
+        return Some(time.format("%Y-%m-%d %H:%M:%S").to_string());
+    }
+    chrono::NaiveDate::parse_from_str(value, format)
+        .ok()
+        .map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+/// Parse a signed amount: `-12.50`, `¥1,234`, accounting-style `(12.50)`
+fn parse_signed_amount(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('(').and_then(|v| v.strip_suffix(')')) {
+        Some(inner) => (true, inner),
+        None => (false, value),
+    };
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (!negative, rest),
+        None => (negative, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let amount = super::parse_amount(value)?;
+    Some(if negative { -amount } else { amount })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping() -> CsvMapping {
+        CsvMapping {
+            delimiter: ',',
+            skip_rows: 0,
+            has_header: true,
+            date_column: 0,
+            date_format: "%Y-%m-%d".to_string(),
+            amount_column: 1,
+            sign: SignConvention::Signed,
+            type_column: None,
+            category_column: Some(2),
+            note_column: Some(3),
+        }
+    }
+
+    #[test]
+    fn sniff_gbk_semicolons() {
+        let text = "日期;金额;分类;备注\n2024-03-05;-12.50;餐饮;午饭\n2024-03-06;8000;工资;三月\n";
+        let (content, _, _) = encoding_rs::GBK.encode(text);
+        let sniff = sniff(&content).unwrap();
+        assert_eq!(sniff.encoding, "GB18030");
+        assert_eq!(sniff.delimiter, ';');
+        assert_eq!(sniff.rows.len(), 3);
+        assert_eq!(sniff.rows[1], vec!["2024-03-05", "-12.50", "餐饮", "午饭"]);
+    }
+
+    #[test]
+    fn sniff_utf8_tabs() {
+        let sniff = sniff("\u{feff}日期\t金额\n2024-03-05\t1,200\n".as_bytes()).unwrap();
+        assert_eq!(sniff.encoding, "UTF-8");
+        assert_eq!(sniff.delimiter, '\t');
+        assert_eq!(sniff.rows[0], vec!["日期", "金额"]);
+    }
+
+    #[test]
+    fn signed_amounts() {
+        let text = "日期,金额,分类,备注\n2024-03-05,-12.50,餐饮,午饭\n2024-03-06,(3.00),,公交\n2024-03-07,abc,,\n\n2024-13-01,5,,\n";
+        let rows = parse(text.as_bytes(), &mapping()).unwrap();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0].line, 2);
+        assert_eq!(rows[0].transaction_date, "2024-03-05");
+        assert_eq!(rows[0].amount, -12.5);
+        assert_eq!(rows[0].category_name.as_deref(), Some("餐饮"));
+        assert_eq!(rows[0].note.as_deref(), Some("午饭"));
+        assert_eq!(rows[1].amount, -3.0);
+        assert_eq!(rows[2].skip_reason.as_deref(), Some("金额无效"));
+        assert_eq!(rows[3].skip_reason.as_deref(), Some("日期无效"));
+    }
+
+    #[test]
+    fn type_column() {
+        let mapping = CsvMapping {
+            skip_rows: 1,
+            date_format: "%Y/%m/%d %H:%M".to_string(),
+            sign: SignConvention::TypeColumn,
+            type_column: Some(2),
+            category_column: None,
+            note_column: None,
+            ..mapping()
+        };
+        let text = "随手记导出\n时间,金额,类型\n2024/03/05 12:30,12.50,支出\n2024/03/06 09:00,8000,收入\n2024/03/07 10:00,100,转账\n";
+        let rows = parse(text.as_bytes(), &mapping).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].transaction_date, "2024-03-05 12:30:00");
+        assert_eq!(rows[0].amount, -12.5);
+        assert_eq!(rows[1].amount, 8000.0);
+        assert_eq!(rows[2].skip_reason.as_deref(), Some("非收支记录（如转账）"));
+    }
+
+    #[test]
+    fn type_column_required() {
+        let mapping = CsvMapping {
+            sign: SignConvention::TypeColumn,
+            ..mapping()
+        };
+        assert!(parse(b"", &mapping).is_err());
+    }
+}
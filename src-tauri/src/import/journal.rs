@@ -0,0 +1,519 @@
+//! Plain-text accounting journals (Beancount and ledger-cli)
+//!
+//! Every posting to an `Expenses:` or `Income:` account becomes a
+//! transaction, with the rest of the account name as its category
+//! (`Expenses:Food:Lunch` → `Food:Lunch`); the other side of the transaction
+//! is recorded as the row's account. Transactions between asset, liability
+//! and equity accounts only, amounts in a commodity other than CNY and
+//! directives this app has no use for (balance, pad, price, ...) come back as
+//! skipped rows so the user sees what was left out.
+
+use super::ParsedRow;
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    Beancount,
+    Ledger,
+}
+
+const CURRENCY: &str = "CNY";
+
+/// Declarations that need no import
+const IGNORED_BEANCOUNT: &[&str] = &["open", "close", "commodity", "option", "pushtag", "poptag", "pushmeta", "popmeta"];
+const IGNORED_LEDGER: &[&str] = &["account", "commodity", "payee", "tag"];
+
+#[derive(Debug)]
+struct Posting {
+    line: u64,
+    account: String,
+    /// Amount and commodity; None when left for the journal to balance
+    amount: Option<(f64, String)>,
+}
+
+#[derive(Debug, Default)]
+struct Transaction {
+    line: u64,
+    date: String,
+    note: Option<String>,
+    /// `id` metadata, written by this app's own export
+    id: Option<String>,
+    /// `time` metadata, `HH:MM`
+    time: Option<String>,
+    postings: Vec<Posting>,
+}
+
+/// Whether an account is an expense or income category
+fn is_category(account: &str) -> bool {
+    matches!(
+        account.split(':').next(),
+        Some("Expenses" | "Expense" | "Income" | "Revenue" | "Revenues")
+    )
+}
+
+/// Parse a journal
+pub fn parse(text: &str, dialect: Dialect) -> Result<Vec<ParsedRow>, String> {
+    let mut rows = Vec::new();
+    let mut current: Option<Transaction> = None;
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    // Inside a ledger `comment` / `test` block
+    let mut in_block_comment = false;
+    let mut transactions = 0;
+
+    for (index, raw) in text.lines().enumerate() {
+        let line_no = index as u64 + 1;
+        let line = raw.trim_end();
+
+        if in_block_comment {
+            in_block_comment = !matches!(line.trim(), "end comment" | "end test");
+            continue;
+        }
+
+        // Indented lines belong to the entry above
+        if line.starts_with([' ', '\t']) {
+            if let Some(tx) = current.as_mut() {
+                read_detail(tx, line.trim(), line_no, dialect);
+            }
+            continue;
+        }
+
+        if let Some(tx) = current.take() {
+            to_rows(tx, &mut occurrences, &mut rows);
+        }
+        let line = line.trim();
+        if line.is_empty() || line.starts_with([';', '#', '%', '|', '*']) {
+            continue;
+        }
+
+        let header = match dialect {
+            Dialect::Beancount => beancount_header(line),
+            Dialect::Ledger => ledger_header(line),
+        };
+        match header {
+            Header::Transaction(mut tx) => {
+                tx.line = line_no;
+                transactions += 1;
+                current = Some(tx);
+            }
+            Header::Ignored => {}
+            Header::BlockComment => in_block_comment = true,
+            Header::Unsupported { date, keyword } => rows.push(skipped(
+                line_no,
+                date,
+                line,
+                format!("不支持的指令（{}），已跳过", keyword),
+            )),
+            Header::InvalidDate => rows.push(skipped(line_no, None, line, "日期无效".to_string())),
+        }
+    }
+    if let Some(tx) = current.take() {
+        to_rows(tx, &mut occurrences, &mut rows);
+    }
+
+    if transactions == 0 {
+        return Err(match dialect {
+            Dialect::Beancount => "没有找到交易，不是有效的 Beancount 文件".to_string(),
+            Dialect::Ledger => "没有找到交易，不是有效的 ledger 文件".to_string(),
+        });
+    }
+    Ok(rows)
+}
+
+enum Header {
+    Transaction(Transaction),
+    Ignored,
+    BlockComment,
+    Unsupported { date: Option<String>, keyword: String },
+    InvalidDate,
+}
+
+/// A reported line that is not imported
+fn skipped(line_no: u64, date: Option<String>, text: &str, reason: String) -> ParsedRow {
+    ParsedRow {
+        line: line_no,
+        transaction_date: date.unwrap_or_default(),
+        amount: 0.0,
+        note: Some(text.chars().take(80).collect()),
+        category_hint: None,
+        category_name: None,
+        external_id: None,
+        account: None,
+        skip_reason: Some(reason),
+    }
+}
+
+fn beancount_header(line: &str) -> Header {
+    static DATED: OnceLock<Regex> = OnceLock::new();
+    let dated = DATED.get_or_init(|| Regex::new(r#"^(\d{4}-\d{2}-\d{2})\s+(\S+)\s*(.*)$"#).unwrap());
+
+    let Some(caps) = dated.captures(line) else {
+        let keyword = line.split_whitespace().next().unwrap_or_default();
+        return if IGNORED_BEANCOUNT.contains(&keyword) {
+            Header::Ignored
+        } else {
+            Header::Unsupported { date: None, keyword: keyword.to_string() }
+        };
+    };
+    let date = caps[1].to_string();
+    let keyword = &caps[2];
+    if IGNORED_BEANCOUNT.contains(&keyword) {
+        return Header::Ignored;
+    }
+    let is_transaction = matches!(keyword, "*" | "!" | "txn") || keyword.starts_with('"');
+    if !is_transaction {
+        return Header::Unsupported { date: Some(date), keyword: keyword.to_string() };
+    }
+
+    // `"payee" "narration"` or just `"narration"`, then tags and links
+    let rest = if keyword.starts_with('"') { &line[caps.get(2).unwrap().start()..] } else { &caps[3] };
+    let strings = quoted_strings(rest);
+    let note = super::join_note(&strings.iter().map(|s| Some(s.as_str()).filter(|s| !s.is_empty())).collect::<Vec<_>>());
+    Header::Transaction(Transaction { date, note, ..Default::default() })
+}
+
+/// The contents of the double-quoted strings in `text`, unescaped
+fn quoted_strings(text: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == ';' {
+            break;
+        }
+        if c != '"' {
+            continue;
+        }
+        let mut value = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => value.extend(chars.next()),
+                '"' => break,
+                c => value.push(c),
+            }
+        }
+        strings.push(value);
+    }
+    strings
+}
+
+fn ledger_header(line: &str) -> Header {
+    static DATED: OnceLock<Regex> = OnceLock::new();
+    let dated = DATED.get_or_init(|| {
+        Regex::new(r"^(\d{4}[/.-]\d{1,2}[/.-]\d{1,2})(?:=\S+)?\s*(?:[*!]\s*)?(?:\([^)]*\)\s*)?(.*)$").unwrap()
+    });
+
+    if let Some(caps) = dated.captures(line) {
+        let Some(date) = ledger_date(&caps[1]) else {
+            return Header::InvalidDate;
+        };
+        // A payee may carry a note after `;`
+        let payee = caps[2].split(';').next().unwrap_or_default().trim();
+        return Header::Transaction(Transaction {
+            date,
+            note: Some(payee.to_string()).filter(|p| !p.is_empty()),
+            ..Default::default()
+        });
+    }
+
+    let keyword = line.split_whitespace().next().unwrap_or_default();
+    match keyword {
+        "comment" | "test" => Header::BlockComment,
+        k if IGNORED_LEDGER.contains(&k) => Header::Ignored,
+        k => Header::Unsupported { date: None, keyword: k.to_string() },
+    }
+}
+
+fn ledger_date(value: &str) -> Option<String> {
+    let value = value.replace(['/', '.'], "-");
+    chrono::NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+        .ok()
+        .map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+/// A posting, metadata or comment line of a transaction
+fn read_detail(tx: &mut Transaction, line: &str, line_no: u64, dialect: Dialect) {
+    static META: OnceLock<Regex> = OnceLock::new();
+    let meta = META.get_or_init(|| Regex::new(r#"^;?\s*([a-z][A-Za-z0-9_-]*):\s*"?([^"]*)"?\s*$"#).unwrap());
+
+    let is_comment = line.starts_with(';');
+    if is_comment || (dialect == Dialect::Beancount && line.starts_with(|c: char| c.is_ascii_lowercase())) {
+        if let Some(caps) = meta.captures(line) {
+            match &caps[1] {
+                "id" => tx.id = Some(caps[2].trim().to_string()),
+                "time" => tx.time = Some(caps[2].trim().to_string()),
+                _ => {}
+            }
+        }
+        return;
+    }
+
+    // Optional posting flag, then the account; ledger account names may
+    // contain single spaces and end at two spaces or a tab
+    let line = line.trim_start_matches(['*', '!']).trim_start();
+    let (account, rest) = match dialect {
+        Dialect::Beancount => line.split_once(char::is_whitespace).unwrap_or((line, "")),
+        Dialect::Ledger => {
+            let end = line.find("  ").into_iter().chain(line.find('\t')).min().unwrap_or(line.len());
+            (&line[..end], &line[end..])
+        }
+    };
+    // Virtual ledger postings, `(Account)` / `[Account]`
+    let account = account.trim_matches(['(', ')', '[', ']']).to_string();
+    let rest = rest.split(';').next().unwrap_or_default();
+    tx.postings.push(Posting {
+        line: line_no,
+        account,
+        amount: parse_amount(rest),
+    });
+}
+
+/// `25.00 CNY`, `CNY 25`, `¥25.00`, `-$3.5`; costs, prices and balance
+/// assertions after the amount are ignored. A bare number is taken as CNY.
+fn parse_amount(text: &str) -> Option<(f64, String)> {
+    static AMOUNT: OnceLock<Regex> = OnceLock::new();
+    let amount = AMOUNT.get_or_init(|| {
+        Regex::new(r"^(-)?\s*([^\d\s.,+-]*)\s*([-+])?\s*(\d[\d,]*(?:\.\d+)?|\.\d+)\s*([^\d\s]*)").unwrap()
+    });
+
+    let text = text.split(['@', '{', '=']).next().unwrap_or_default().trim();
+    if text.is_empty() {
+        return None;
+    }
+    let caps = amount.captures(text)?;
+    let mut value: f64 = caps[4].replace(',', "").parse().ok()?;
+    if caps.get(1).is_some() || caps.get(3).is_some_and(|s| s.as_str() == "-") {
+        value = -value;
+    }
+    let commodity = match (caps[2].trim(), caps[5].trim()) {
+        ("", "") => CURRENCY,
+        ("", post) => post,
+        (pre, _) => pre,
+    };
+    let commodity = match commodity {
+        "¥" | "￥" | "RMB" | "CNY" | "元" => CURRENCY,
+        "$" => "USD",
+        "€" => "EUR",
+        "£" => "GBP",
+        other => other,
+    };
+    Some((value, commodity.to_string()))
+}
+
+/// Turn a finished transaction into rows, one per category posting
+fn to_rows(mut tx: Transaction, occurrences: &mut HashMap<String, usize>, rows: &mut Vec<ParsedRow>) {
+    // Fill in the one posting left without an amount
+    let missing: Vec<usize> = (0..tx.postings.len()).filter(|i| tx.postings[*i].amount.is_none()).collect();
+    if let [index] = missing[..] {
+        let mut commodities = tx.postings.iter().filter_map(|p| p.amount.as_ref().map(|(_, c)| c.clone()));
+        if let Some(commodity) = commodities.next() {
+            if commodities.all(|c| c == commodity) {
+                let sum: f64 = tx.postings.iter().filter_map(|p| p.amount.as_ref().map(|(v, _)| *v)).sum();
+                tx.postings[index].amount = Some((-sum, commodity));
+            }
+        }
+    }
+
+    let transaction_date = match &tx.time {
+        Some(time) if time.len() == 5 => format!("{} {}:00", tx.date, time),
+        _ => tx.date.clone(),
+    };
+    let account = tx
+        .postings
+        .iter()
+        .find(|p| !is_category(&p.account))
+        .map(|p| p.account.clone());
+    let categories: Vec<&Posting> = tx.postings.iter().filter(|p| is_category(&p.account)).collect();
+
+    if categories.is_empty() {
+        // Only moves money between the user's own accounts
+        let amount = tx.postings.first().and_then(|p| p.amount.as_ref()).map_or(0.0, |(v, _)| *v);
+        rows.push(ParsedRow {
+            line: tx.line,
+            transaction_date,
+            amount,
+            note: tx.note.clone(),
+            category_hint: None,
+            category_name: None,
+            external_id: None,
+            account,
+            skip_reason: Some("账户间转账".to_string()),
+        });
+        return;
+    }
+
+    let split = categories.len() > 1;
+    for (index, posting) in categories.into_iter().enumerate() {
+        let (amount, skip_reason) = match &posting.amount {
+            Some((value, commodity)) if commodity != CURRENCY => (-value, Some(format!("币种为 {}，未换算", commodity))),
+            Some((value, _)) if *value != 0.0 => (-value, None),
+            _ => (0.0, Some("金额无效".to_string())),
+        };
+        let category_name = posting.account.split_once(':').map(|(_, path)| path.to_string());
+
+        let external_id = match &tx.id {
+            Some(id) if split => format!("{}#{}", id, index + 1),
+            Some(id) => id.clone(),
+            None => {
+                let key = format!("{}|{:.2}|{}|{}", transaction_date, amount, posting.account, tx.note.as_deref().unwrap_or_default());
+                let occurrence = occurrences.entry(key.clone()).or_insert(0);
+                *occurrence += 1;
+                format!("{}#{}", key, occurrence)
+            }
+        };
+
+        rows.push(ParsedRow {
+            line: posting.line,
+            transaction_date: transaction_date.clone(),
+            amount,
+            note: tx.note.clone(),
+            category_hint: category_name.as_ref().map(|c| c.replace(':', " ")),
+            category_name,
+            external_id: Some(external_id),
+            account: account.clone(),
+            skip_reason,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rows that would be imported
+    fn imported(rows: &[ParsedRow]) -> Vec<&ParsedRow> {
+        rows.iter().filter(|r| r.skip_reason.is_none()).collect()
+    }
+
+    #[test]
+    fn beancount_elided_amount() {
+        let text = r#"
+option "operating_currency" "CNY"
+2024-03-01 open Assets:Bank:CMB
+
+2024-03-05 * "星巴克" "拿铁"
+  id: "tx-42"
+  time: "08:30"
+  Expenses:Food:Coffee   32.00 CNY
+  Assets:Bank:CMB
+"#;
+        let rows = parse(text, Dialect::Beancount).unwrap();
+        assert_eq!(rows.len(), 1);
+        let row = &rows[0];
+        assert_eq!(row.transaction_date, "2024-03-05 08:30:00");
+        assert_eq!(row.amount, -32.0);
+        assert_eq!(row.note.as_deref(), Some("星巴克 拿铁"));
+        assert_eq!(row.category_name.as_deref(), Some("Food:Coffee"));
+        assert_eq!(row.account.as_deref(), Some("Assets:Bank:CMB"));
+        assert_eq!(row.external_id.as_deref(), Some("tx-42"));
+        assert_eq!(row.skip_reason, None);
+    }
+
+    #[test]
+    fn elided_category_amount_is_balanced() {
+        let text = "2024/03/06 工资\n    Assets:Bank  ¥8,000.00\n    Income:Salary\n";
+        let rows = parse(text, Dialect::Ledger).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].amount, 8000.0);
+        assert_eq!(rows[0].transaction_date, "2024-03-06");
+        assert_eq!(rows[0].category_name.as_deref(), Some("Salary"));
+    }
+
+    #[test]
+    fn split_transaction_gives_one_row_per_category() {
+        let text = r#"
+2024-03-07 * "超市"
+  id: "tx-7"
+  Expenses:Food      50 CNY
+  Expenses:Home      20 CNY
+  Liabilities:CreditCard
+"#;
+        let rows = parse(text, Dialect::Beancount).unwrap();
+        let amounts: Vec<f64> = rows.iter().map(|r| r.amount).collect();
+        assert_eq!(amounts, vec![-50.0, -20.0]);
+        let ids: Vec<_> = rows.iter().map(|r| r.external_id.clone().unwrap()).collect();
+        assert_eq!(ids, vec!["tx-7#1", "tx-7#2"]);
+        assert!(rows.iter().all(|r| r.account.as_deref() == Some("Liabilities:CreditCard")));
+    }
+
+    #[test]
+    fn other_currencies_are_skipped() {
+        let text = r#"
+2024-03-08 * "Amazon"
+  Expenses:Shopping   12.50 USD @ 7.2 CNY
+  Assets:Bank
+2024-03-09 * "Lunch"
+  Expenses:Food   -$3.5
+  Assets:Cash
+"#;
+        let rows = parse(text, Dialect::Beancount).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].amount, -12.5);
+        assert_eq!(rows[0].skip_reason.as_deref(), Some("币种为 USD，未换算"));
+        assert_eq!(rows[1].skip_reason.as_deref(), Some("币种为 USD，未换算"));
+        assert!(imported(&rows).is_empty());
+    }
+
+    #[test]
+    fn transfers_are_skipped() {
+        let text = r#"
+2024-03-10 * "还信用卡"
+  Liabilities:CreditCard   1000.00 CNY
+  Assets:Bank             -1000.00 CNY
+"#;
+        let rows = parse(text, Dialect::Beancount).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].skip_reason.as_deref(), Some("账户间转账"));
+        assert_eq!(rows[0].amount, 1000.0);
+        assert_eq!(rows[0].account.as_deref(), Some("Liabilities:CreditCard"));
+    }
+
+    #[test]
+    fn unsupported_directives_are_reported() {
+        let text = r#"
+2024-03-01 balance Assets:Bank  100 CNY
+2024-03-02 * "午饭"
+  Expenses:Food  25 CNY
+  Assets:Bank
+"#;
+        let rows = parse(text, Dialect::Beancount).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].skip_reason.as_deref(), Some("不支持的指令（balance），已跳过"));
+        assert_eq!(imported(&rows).len(), 1);
+    }
+
+    #[test]
+    fn ledger_comments_and_account_names_with_spaces() {
+        let text = r#"
+comment
+2024/01/01 ignored
+    Expenses:Food  1
+end comment
+2024-03-11 * (42) 午饭 ; 公司楼下
+    Expenses:Dining Out  ¥25.00  ; 备注
+    Assets:Cash
+"#;
+        let rows = parse(text, Dialect::Ledger).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].note.as_deref(), Some("午饭"));
+        assert_eq!(rows[0].category_name.as_deref(), Some("Dining Out"));
+        assert_eq!(rows[0].amount, -25.0);
+    }
+
+    #[test]
+    fn identical_entries_get_distinct_ids() {
+        let entry = "2024-03-12 * \"咖啡\"\n  Expenses:Food  10 CNY\n  Assets:Cash\n";
+        let rows = parse(&format!("{}\n{}", entry, entry), Dialect::Beancount).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_ne!(rows[0].external_id, rows[1].external_id);
+        // The same file yields the same ids, so a re-import is recognised
+        let again = parse(&format!("{}\n{}", entry, entry), Dialect::Beancount).unwrap();
+        assert_eq!(rows[1].external_id, again[1].external_id);
+    }
+
+    #[test]
+    fn no_transactions_is_an_error() {
+        assert!(parse("2024-01-01 open Assets:Bank\n", Dialect::Beancount).is_err());
+        assert!(parse("hello world\n", Dialect::Ledger).is_err());
+    }
+}
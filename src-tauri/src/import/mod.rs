@@ -0,0 +1,382 @@
+//! Import
+//!
+//! Reads bills exported by other apps into transactions. Every source parser
+//! produces `ParsedRow`s; the shared pipeline then assigns categories by rule,
+//! flags rows that are already in the ledger and returns an `ImportRow`
+//! preview. Nothing is written until the user confirms the preview and the
+//! chosen rows come back through `commit`, which also records each line's
+//! external id in `imported_lines`.
+
+pub mod alipay;
+pub mod generic;
+pub mod journal;
+pub mod ofx;
+pub mod qif;
+mod rules;
+pub mod table;
+pub mod wechat;
+
+use crate::models::{CsvMapping, ImportResult, ImportRow, ImportSource, NewCategory, NewTransaction};
+use crate::classifier::{self, Classifier};
+use crate::duplicates::{self, Fingerprint};
+use crate::repository::{category_repo, import_repo, transaction_repo};
+use crate::rules::{apply_note, RuleSet};
+use libsql::Connection;
+use std::collections::HashMap;
+
+pub use rules::CategoryRules;
+
+/// A bill line as read from the source file, before categorisation
+#[derive(Debug, Clone)]
+pub struct ParsedRow {
+    pub line: u64,
+    /// `YYYY-MM-DD HH:MM:SS`
+    pub transaction_date: String,
+    /// Negative for expenses
+    pub amount: f64,
+    pub note: Option<String>,
+    /// The source's own classification (e.g. 支付宝 交易分类), used by the rules
+    pub category_hint: Option<String>,
+    /// Category named by the file itself, used as-is (and created if missing)
+    pub category_name: Option<String>,
+    /// The source's id of the line (FITID, 交易订单号 ...)
+    pub external_id: Option<String>,
+    /// Account named in the file (OFX ACCTID, 支付宝 收/付款方式, 微信 支付方式)
+    pub account: Option<String>,
+    pub skip_reason: Option<String>,
+}
+
+/// Decode a bill file: UTF-8 (with or without BOM), otherwise GB18030
+///
+/// GB18030 is a superset of GBK, the encoding Chinese payment apps export in.
+pub fn decode_text(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => encoding_rs::GB18030.decode(bytes).0.into_owned(),
+    }
+}
+
+/// Normalise a bill time to `YYYY-MM-DD HH:MM:SS`
+///
+/// Bills re-saved by Excel use slashes and drop the seconds.
+pub fn parse_datetime(value: &str) -> Option<String> {
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y/%m/%d %H:%M:%S", "%Y/%m/%d %H:%M"]
+        .iter()
+        .find_map(|format| chrono::NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+/// Parse an amount like `¥1,234.50`, ignoring the sign
+pub fn parse_amount(value: &str) -> Option<f64> {
+    value
+        .trim()
+        .trim_start_matches(['¥', '￥'])
+        .replace(',', "")
+        .parse::<f64>()
+        .ok()
+        .map(f64::abs)
+}
+
+/// Join the non-empty descriptive fields of a line into a note, dropping
+/// parts already contained in an earlier one
+pub fn join_note(parts: &[Option<&str>]) -> Option<String> {
+    let mut note: Vec<&str> = Vec::new();
+    for part in parts.iter().flatten() {
+        if !note.iter().any(|p| p.contains(part)) {
+            note.push(part);
+        }
+    }
+    if note.is_empty() { None } else { Some(note.join(" ")) }
+}
+
+/// Parse a bill file of the given source
+pub fn parse(source: ImportSource, content: &[u8]) -> Result<Vec<ParsedRow>, String> {
+    match source {
+        ImportSource::Alipay => alipay::parse(&table::read_table(content)?),
+        ImportSource::Wechat => wechat::parse(&table::read_table(content)?),
+        ImportSource::Ofx => ofx::parse(&decode_text(content)),
+        ImportSource::Qif => qif::parse(&decode_text(content)),
+        ImportSource::Beancount => journal::parse(&decode_text(content), journal::Dialect::Beancount),
+        ImportSource::Ledger => journal::parse(&decode_text(content), journal::Dialect::Ledger),
+        ImportSource::Csv => Err("CSV 导入需要先设置列映射".to_string()),
+    }
+}
+
+/// Parse a bill file and build the import preview
+///
+/// `account` binds the rows to an account; without it the account named in
+/// the file (if any) is used. `classifier` must be synced with the ledger.
+pub async fn preview(
+    conn: &Connection,
+    classifier: &Classifier,
+    source: ImportSource,
+    content: &[u8],
+    account: Option<String>,
+) -> Result<Vec<ImportRow>, String> {
+    build_preview(conn, classifier, source, parse(source, content)?, account).await
+}
+
+/// Parse a generic CSV file with a column mapping and build the import preview
+pub async fn preview_csv(
+    conn: &Connection,
+    classifier: &Classifier,
+    content: &[u8],
+    mapping: &CsvMapping,
+    account: Option<String>,
+) -> Result<Vec<ImportRow>, String> {
+    build_preview(conn, classifier, ImportSource::Csv, generic::parse(content, mapping)?, account).await
+}
+
+async fn build_preview(
+    conn: &Connection,
+    classifier: &Classifier,
+    source: ImportSource,
+    parsed: Vec<ParsedRow>,
+    account: Option<String>,
+) -> Result<Vec<ImportRow>, String> {
+    let categories = category_repo::get_all_categories(conn).await?;
+    let rules = CategoryRules::new(&categories);
+    let user_rules = RuleSet::load(conn).await?;
+    let imported = import_repo::get_imported_lines(conn, source.key()).await?;
+    let mut existing = existing_keys(conn, &parsed).await?;
+    let ledger = match date_range(&parsed) {
+        Some((first, last)) => duplicates::load_window(conn, first, last).await?,
+        None => Vec::new(),
+    };
+    let account = account.map(|a| a.trim().to_string()).filter(|a| !a.is_empty());
+
+    let mut rows = Vec::with_capacity(parsed.len());
+    for mut row in parsed {
+        let text = [row.category_hint.as_deref(), row.note.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let account = account.clone().or(row.account.clone());
+        // The user's rules rewrite the note before it is compared with the ledger,
+        // where earlier imports already carry the rewritten note
+        let rule = user_rules.find(&text, row.amount, account.as_deref());
+        if let Some(rule) = rule {
+            row.note = apply_note(rule, row.note.as_deref());
+        }
+        let already_imported = row.external_id.as_ref().is_some_and(|id| {
+            imported.contains(&(account.clone().unwrap_or_default(), id.clone()))
+        });
+        let skip_reason = if row.skip_reason.is_none() && (already_imported || take_existing(&mut existing, &row)) {
+            Some("已导入".to_string())
+        } else {
+            row.skip_reason.clone()
+        };
+        let hour = classifier::hour_of(&row.transaction_date, None);
+        let suggestions = classifier.suggest(&text, row.amount, hour, 3);
+        // A category named in the file wins, then the user's rules, then a
+        // confident suggestion learned from the ledger, then the keyword
+        // guesses; unknown names are created on commit
+        let learned = suggestions
+            .first()
+            .filter(|s| s.probability >= classifier::IMPORT_CONFIDENCE && classifier.recognizes(&text))
+            .map(|s| s.category_id);
+        let (category_id, category_name) = match row.category_name {
+            Some(name) => match categories.iter().find(|c| c.name == name) {
+                Some(category) => (Some(category.id), None),
+                None => (None, Some(name)),
+            },
+            None => match rule.and_then(|r| r.category_id).or(learned) {
+                Some(id) => (Some(id), None),
+                None => (rules.categorize(&text), None),
+            },
+        };
+        // Not the same line as before, but close enough to be the same payment
+        // (e.g. entered by hand, or imported from the card's statement)
+        let duplicate_of = if skip_reason.is_none() {
+            let fingerprint = Fingerprint {
+                transaction_date: &row.transaction_date,
+                amount: row.amount,
+                category_id,
+                note: row.note.as_deref(),
+            };
+            duplicates::best_match(&fingerprint, &ledger).map(duplicates::describe)
+        } else {
+            None
+        };
+        rows.push(ImportRow {
+            line: row.line,
+            transaction_date: row.transaction_date,
+            amount: row.amount,
+            note: row.note,
+            category_id,
+            category_name,
+            external_id: row.external_id,
+            account,
+            duplicate_of,
+            suggestions,
+            skip_reason,
+        });
+    }
+    Ok(rows)
+}
+
+/// Insert the rows the user confirmed, all or nothing
+pub async fn commit(conn: &Connection, source: ImportSource, rows: Vec<ImportRow>) -> Result<ImportResult, String> {
+    let tx = conn.transaction().await.map_err(|e| e.to_string())?;
+    let mut result = ImportResult::default();
+    let mut created = HashMap::new();
+    for mut row in rows {
+        let line = row.line;
+        if row.category_id.is_none() {
+            if let Some(name) = row.category_name.clone() {
+                match category_for_name(&tx, &mut created, name).await {
+                    Ok(id) => row.category_id = Some(id),
+                    Err(e) => {
+                        let _ = tx.rollback().await;
+                        return Err(format!("第 {} 行导入失败: {}", line, e));
+                    }
+                }
+            }
+        }
+        if let Err(e) = insert_row(&tx, source, row).await {
+            let _ = tx.rollback().await;
+            return Err(format!("第 {} 行导入失败: {}", line, e));
+        }
+        result.imported += 1;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(result)
+}
+
+/// Create the category named by an imported row, once per name
+async fn category_for_name(conn: &Connection, created: &mut HashMap<String, i64>, name: String) -> Result<i64, String> {
+    if let Some(id) = created.get(&name) {
+        return Ok(*id);
+    }
+    let category = category_repo::create_category(conn, NewCategory { name: name.clone(), icon: None }).await?;
+    created.insert(name, category.id);
+    Ok(category.id)
+}
+
+async fn insert_row(conn: &Connection, source: ImportSource, row: ImportRow) -> Result<(), String> {
+    let category_id = row.category_id.ok_or("未选择分类")?;
+    let transaction = transaction_repo::create_transaction(
+        conn,
+        NewTransaction {
+            category_id,
+            amount: row.amount,
+            transaction_date: row.transaction_date,
+            note: row.note,
+        },
+    )
+    .await?;
+    if let Some(external_id) = &row.external_id {
+        import_repo::record_imported_line(
+            conn,
+            source.key(),
+            row.account.as_deref().unwrap_or_default(),
+            external_id,
+            transaction.id,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+type RowKey = (String, String, String);
+
+fn row_key(transaction_date: &str, amount: f64, note: Option<&str>) -> RowKey {
+    (
+        transaction_date.to_string(),
+        format!("{:.2}", amount),
+        note.unwrap_or_default().to_string(),
+    )
+}
+
+/// First and last date of the bill's importable rows
+///
+/// Skipped rows are left out: they are never compared with the ledger, and
+/// some (directives, lines with an invalid date) have no date at all.
+fn date_range(rows: &[ParsedRow]) -> Option<(&str, &str)> {
+    let mut dates = rows
+        .iter()
+        .filter(|r| r.skip_reason.is_none())
+        .map(|r| r.transaction_date.as_str())
+        .filter(|date| {
+            date.get(..10)
+                .is_some_and(|day| chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d").is_ok())
+        });
+    let first = dates.next()?;
+    Some(dates.fold((first, first), |(min, max), date| (min.min(date), max.max(date))))
+}
+
+/// Count the ledger's transactions in the bill's date range by (date, amount, note)
+async fn existing_keys(conn: &Connection, rows: &[ParsedRow]) -> Result<HashMap<RowKey, usize>, String> {
+    let mut counts = HashMap::new();
+    let Some((first, last)) = date_range(rows) else {
+        return Ok(counts);
+    };
+
+    let mut result = conn
+        .query(
+            "SELECT transaction_date, amount, note FROM transactions
+             WHERE transaction_date >= ? AND transaction_date <= ?",
+            libsql::params![first, last],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    while let Some(row) = result.next().await.map_err(|e| e.to_string())? {
+        let date: String = row.get(0).map_err(|e| e.to_string())?;
+        let amount: f64 = row.get(1).map_err(|e| e.to_string())?;
+        let note: Option<String> = row.get(2).ok();
+        *counts.entry(row_key(&date, amount, note.as_deref())).or_insert(0) += 1;
+    }
+    Ok(counts)
+}
+
+/// Consume one matching ledger transaction, so identical bill lines are only
+/// flagged as often as they were imported before
+fn take_existing(existing: &mut HashMap<RowKey, usize>, row: &ParsedRow) -> bool {
+    let key = row_key(&row.transaction_date, row.amount, row.note.as_deref());
+    match existing.get_mut(&key) {
+        Some(count) if *count > 0 => {
+            *count -= 1;
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(transaction_date: &str, skip_reason: Option<&str>) -> ParsedRow {
+        ParsedRow {
+            line: 1,
+            transaction_date: transaction_date.to_string(),
+            amount: -10.0,
+            note: None,
+            category_hint: None,
+            category_name: None,
+            external_id: None,
+            account: None,
+            skip_reason: skip_reason.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn date_range_of_importable_rows() {
+        let rows = vec![
+            row("2024-03-05 12:30:00", None),
+            row("", Some("不支持的指令")),
+            row("2024-03-01", Some("退款")),
+            row("2024-03-09", None),
+            row("2024-03-07 08:00:00", None),
+        ];
+        assert_eq!(date_range(&rows), Some(("2024-03-05 12:30:00", "2024-03-09")));
+    }
+
+    #[test]
+    fn date_range_without_dates() {
+        assert_eq!(date_range(&[]), None);
+        assert_eq!(date_range(&[row("", Some("日期无效")), row("03/05/2024", None)]), None);
+    }
+}
@@ -0,0 +1,239 @@
+//! OFX bank and credit card statements
+//!
+//! Handles both OFX 1.x (SGML, leaf elements without closing tags) and 2.x
+//! (XML) by scanning tags instead of parsing a document tree. Each
+//! `<STMTTRN>` becomes a row; `<FITID>` is the line's external id and the
+//! statement's `<ACCTID>` its account.
+
+use super::{join_note, ParsedRow};
+
+/// Fields of the `<STMTTRN>` being read
+#[derive(Default)]
+struct StatementLine {
+    line: u64,
+    posted: Option<String>,
+    amount: Option<f64>,
+    fitid: Option<String>,
+    name: Option<String>,
+    memo: Option<String>,
+}
+
+/// Parse an OFX statement
+pub fn parse(text: &str) -> Result<Vec<ParsedRow>, String> {
+    let start = text
+        .to_ascii_uppercase()
+        .find("<OFX>")
+        .ok_or("不是有效的 OFX 文件（没有找到 <OFX> 元素）")?;
+
+    let mut account = None;
+    let mut current: Option<StatementLine> = None;
+    let mut rows = Vec::new();
+    let mut line = text[..start].matches('\n').count() as u64 + 1;
+    let mut last = start;
+
+    for token in tags(&text[start..]) {
+        let offset = start + token.offset;
+        line += text[last..offset].matches('\n').count() as u64;
+        last = offset;
+
+        match token.name.as_str() {
+            "ACCTID" => account = Some(token.value.clone()).filter(|v| !v.is_empty()),
+            "STMTTRN" => {
+                current = Some(StatementLine {
+                    line,
+                    ..Default::default()
+                })
+            }
+            "/STMTTRN" => {
+                if let Some(statement_line) = current.take() {
+                    rows.push(to_row(statement_line, account.clone()));
+                }
+            }
+            name => {
+                if let Some(statement_line) = current.as_mut() {
+                    let value = Some(token.value.clone()).filter(|v| !v.is_empty());
+                    match name {
+                        "DTPOSTED" => statement_line.posted = value,
+                        "TRNAMT" => statement_line.amount = value.and_then(|v| v.replace(',', "").parse().ok()),
+                        "FITID" => statement_line.fitid = value,
+                        "NAME" => statement_line.name = value,
+                        "MEMO" => statement_line.memo = value,
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    if rows.is_empty() && text.to_ascii_uppercase().contains("<STMTTRN>") {
+        return Err("OFX 文件中的交易记录不完整".to_string());
+    }
+    Ok(rows)
+}
+
+fn to_row(statement_line: StatementLine, account: Option<String>) -> ParsedRow {
+    let transaction_date = statement_line.posted.as_deref().and_then(parse_ofx_date);
+    let amount = statement_line.amount.unwrap_or(0.0);
+    let skip_reason = if transaction_date.is_none() {
+        Some("日期无效".to_string())
+    } else if amount == 0.0 {
+        Some("金额无效".to_string())
+    } else {
+        None
+    };
+
+    ParsedRow {
+        line: statement_line.line,
+        transaction_date: transaction_date.unwrap_or_default(),
+        // OFX amounts are signed from the account holder's view: debits are negative
+        amount,
+        note: join_note(&[statement_line.name.as_deref(), statement_line.memo.as_deref()]),
+        category_hint: None,
+        external_id: statement_line.fitid,
+        account,
+        category_name: None,
+        skip_reason,
+    }
+}
+
+struct Tag {
+    /// Byte offset of the `<`
+    offset: usize,
+    /// Upper-cased tag name, closing tags keep their `/`
+    name: String,
+    /// Text up to the next tag, trimmed and unescaped
+    value: String,
+}
+
+fn tags(text: &str) -> Vec<Tag> {
+    let starts: Vec<usize> = text.match_indices('<').map(|(i, _)| i).collect();
+    starts
+        .iter()
+        .enumerate()
+        .filter_map(|(n, &i)| {
+            let end = starts.get(n + 1).copied().unwrap_or(text.len());
+            let (name, value) = text[i + 1..end].split_once('>')?;
+            Some(Tag {
+                offset: i,
+                name: name.trim().to_ascii_uppercase(),
+                value: unescape(value.trim()),
+            })
+        })
+        .collect()
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// `YYYYMMDD[HHMMSS[.XXX]][[-8:PST]]` to `YYYY-MM-DD[ HH:MM:SS]`
+///
+/// A midnight time is what banks send when they only know the day, so it is
+/// dropped like in manually entered transactions.
+fn parse_ofx_date(value: &str) -> Option<String> {
+    let digits: String = value.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let date = chrono::NaiveDate::parse_from_str(digits.get(..8)?, "%Y%m%d").ok()?;
+    match digits.get(8..14) {
+        Some(time) if time != "000000" => {
+            let time = chrono::NaiveTime::parse_from_str(time, "%H%M%S").ok()?;
+            Some(date.and_time(time).format("%Y-%m-%d %H:%M:%S").to_string())
+        }
+        _ => Some(date.format("%Y-%m-%d").to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sgml_statement() {
+        let text = "\
+OFXHEADER:100
+DATA:OFXSGML
+VERSION:102
+
+<OFX>
+<BANKMSGSRSV1><STMTTRNRS><STMTRS>
+<CURDEF>CNY
+<BANKACCTFROM><BANKID>308<ACCTID>6225880012345678<ACCTTYPE>CHECKING</BANKACCTFROM>
+<BANKTRANLIST>
+<STMTTRN>
+<TRNTYPE>DEBIT
+<DTPOSTED>20240305123000[+8:CST]
+<TRNAMT>-1,035.50
+<FITID>2024030501
+<NAME>Starbucks &amp; Co
+<MEMO>Latte
+</STMTTRN>
+<STMTTRN>
+<TRNTYPE>CREDIT
+<DTPOSTED>20240306000000
+<TRNAMT>8000.00
+<FITID>2024030601
+<NAME>Salary
+</STMTTRN>
+</BANKTRANLIST>
+</STMTRS></STMTTRNRS></BANKMSGSRSV1>
+</OFX>
+";
+        let rows = parse(text).unwrap();
+        assert_eq!(rows.len(), 2);
+        let row = &rows[0];
+        assert_eq!(row.line, 10);
+        assert_eq!(row.transaction_date, "2024-03-05 12:30:00");
+        assert_eq!(row.amount, -1035.5);
+        assert_eq!(row.note.as_deref(), Some("Starbucks & Co Latte"));
+        assert_eq!(row.external_id.as_deref(), Some("2024030501"));
+        assert_eq!(row.account.as_deref(), Some("6225880012345678"));
+        assert_eq!(row.skip_reason, None);
+        // Midnight means the bank only knows the day
+        assert_eq!(rows[1].transaction_date, "2024-03-06");
+        assert_eq!(rows[1].amount, 8000.0);
+    }
+
+    #[test]
+    fn xml_statement() {
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<?OFX OFXHEADER="200" VERSION="220"?>
+<OFX>
+  <CREDITCARDMSGSRSV1><CCSTMTTRNRS><CCSTMTRS>
+    <CCACCTFROM><ACCTID>4000123412341234</ACCTID></CCACCTFROM>
+    <BANKTRANLIST>
+      <STMTTRN>
+        <TRNTYPE>DEBIT</TRNTYPE>
+        <DTPOSTED>20240307</DTPOSTED>
+        <TRNAMT>-42.00</TRNAMT>
+        <FITID>CC-7</FITID>
+        <NAME>Didi</NAME>
+      </STMTTRN>
+      <STMTTRN>
+        <TRNTYPE>DEBIT</TRNTYPE>
+        <DTPOSTED>2024</DTPOSTED>
+        <TRNAMT>-1.00</TRNAMT>
+        <FITID>CC-8</FITID>
+      </STMTTRN>
+    </BANKTRANLIST>
+  </CCSTMTRS></CCSTMTTRNRS></CREDITCARDMSGSRSV1>
+</OFX>
+"#;
+        let rows = parse(text).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].transaction_date, "2024-03-07");
+        assert_eq!(rows[0].amount, -42.0);
+        assert_eq!(rows[0].note.as_deref(), Some("Didi"));
+        assert_eq!(rows[0].external_id.as_deref(), Some("CC-7"));
+        assert_eq!(rows[0].account.as_deref(), Some("4000123412341234"));
+        assert_eq!(rows[1].skip_reason.as_deref(), Some("日期无效"));
+    }
+
+    #[test]
+    fn not_ofx() {
+        assert!(parse("Date,Amount\n2024-03-05,10\n").is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! QIF bank and credit card statements
+//!
+//! Records are groups of lines starting with a field code (`D` date, `T`/`U`
+//! amount, `P` payee, `M` memo, `L` category) ended by `^`. QIF has no line
+//! ids, so the external id is derived from the record's content and its
+//! occurrence in the file.
+
+use super::{join_note, ParsedRow};
+use std::collections::HashMap;
+
+/// Fields of the record being read
+#[derive(Default)]
+struct Record {
+    line: u64,
+    date: Option<String>,
+    amount: Option<f64>,
+    payee: Option<String>,
+    memo: Option<String>,
+    category: Option<String>,
+}
+
+/// Parse a QIF file
+pub fn parse(text: &str) -> Result<Vec<ParsedRow>, String> {
+    if !text.lines().any(|l| l.trim_start().starts_with("!Type:") || l.trim_start().starts_with("!Account")) {
+        return Err("不是有效的 QIF 文件（没有找到 !Type 行）".to_string());
+    }
+
+    let mut account = None;
+    let mut in_account_block = false;
+    let mut record = Record::default();
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let mut rows = Vec::new();
+
+    for (index, raw) in text.lines().enumerate() {
+        let line = raw.trim();
+        let Some(code) = line.chars().next() else { continue };
+        let value = line[code.len_utf8()..].trim();
+
+        if code == '!' {
+            in_account_block = line.eq_ignore_ascii_case("!Account");
+            continue;
+        }
+        if in_account_block {
+            // `N` names the account of the records that follow
+            match code {
+                'N' => account = Some(value.to_string()).filter(|v| !v.is_empty()),
+                '^' => in_account_block = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        if record.line == 0 {
+            record.line = index as u64 + 1;
+        }
+        match code {
+            'D' => record.date = Some(value.to_string()),
+            'T' | 'U' => record.amount = value.replace(',', "").parse().ok(),
+            'P' => record.payee = Some(value.to_string()).filter(|v| !v.is_empty()),
+            'M' => record.memo = Some(value.to_string()).filter(|v| !v.is_empty()),
+            'L' => record.category = Some(value.to_string()).filter(|v| !v.is_empty()),
+            '^' => {
+                let finished = std::mem::take(&mut record);
+                if finished.date.is_some() || finished.amount.is_some() {
+                    rows.push(to_row(finished, account.clone(), &mut occurrences));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(rows)
+}
+
+fn to_row(record: Record, account: Option<String>, occurrences: &mut HashMap<String, usize>) -> ParsedRow {
+    let transaction_date = record.date.as_deref().and_then(parse_qif_date);
+    let amount = record.amount.unwrap_or(0.0);
+    let note = join_note(&[record.payee.as_deref(), record.memo.as_deref()]);
+
+    // `[Account]` in the category field is a transfer between the user's accounts
+    let is_transfer = record.category.as_deref().is_some_and(|c| c.starts_with('['));
+    let skip_reason = if transaction_date.is_none() {
+        Some("日期无效".to_string())
+    } else if amount == 0.0 {
+        Some("金额无效".to_string())
+    } else if is_transfer {
+        Some("账户间转账".to_string())
+    } else {
+        None
+    };
+
+    let transaction_date = transaction_date.unwrap_or_default();
+    let key = format!("{}|{:.2}|{}", transaction_date, amount, note.as_deref().unwrap_or_default());
+    let occurrence = occurrences.entry(key.clone()).or_insert(0);
+    *occurrence += 1;
+
+    ParsedRow {
+        line: record.line,
+        transaction_date,
+        amount,
+        note,
+        // `Food:Groceries` -> `Food Groceries`, so either level can match a category
+        category_hint: record.category.filter(|_| !is_transfer).map(|c| c.replace(':', " ")),
+        external_id: Some(format!("{}#{}", key, occurrence)),
+        account,
+        category_name: None,
+        skip_reason,
+    }
+}
+
+/// QIF dates are written in the exporting program's locale; ISO dates and
+/// the US forms (`03/02/2024`, `3/ 2'24`) are accepted
+fn parse_qif_date(value: &str) -> Option<String> {
+    let value: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    let value = value.replace('\'', "/");
+    let formats: &[&str] = if value.get(..4).is_some_and(|y| y.chars().all(|c| c.is_ascii_digit())) {
+        &["%Y-%m-%d", "%Y/%m/%d"]
+    } else if value.rsplit(['/', '-', '.']).next().is_some_and(|y| y.len() <= 2) {
+        &["%m/%d/%y", "%m-%d-%y", "%d.%m.%y"]
+    } else {
+        &["%m/%d/%Y", "%m-%d-%Y", "%d.%m.%Y"]
+    };
+    formats
+        .iter()
+        .find_map(|format| chrono::NaiveDate::parse_from_str(&value, format).ok())
+        .map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bank_records() {
+        let text = "\
+!Account
+NChecking
+TBank
+^
+!Type:Bank
+D03/05/2024
+T-1,035.50
+PSupermarket
+MWeekly shopping
+LFood:Groceries
+^
+D3/ 6'24
+U8000.00
+PSalary
+^
+D2024-03-07
+T-200.00
+PTransfer
+L[Savings]
+^
+";
+        let rows = parse(text).unwrap();
+        assert_eq!(rows.len(), 3);
+        let row = &rows[0];
+        assert_eq!(row.line, 6);
+        assert_eq!(row.transaction_date, "2024-03-05");
+        assert_eq!(row.amount, -1035.5);
+        assert_eq!(row.note.as_deref(), Some("Supermarket Weekly shopping"));
+        assert_eq!(row.category_hint.as_deref(), Some("Food Groceries"));
+        assert_eq!(row.account.as_deref(), Some("Checking"));
+        assert_eq!(row.skip_reason, None);
+        assert_eq!(rows[1].transaction_date, "2024-03-06");
+        assert_eq!(rows[1].amount, 8000.0);
+        assert_eq!(rows[2].skip_reason.as_deref(), Some("账户间转账"));
+        assert_eq!(rows[2].category_hint, None);
+    }
+
+    #[test]
+    fn date_formats() {
+        assert_eq!(parse_qif_date("2024/03/05").as_deref(), Some("2024-03-05"));
+        assert_eq!(parse_qif_date("03/05/2024").as_deref(), Some("2024-03-05"));
+        assert_eq!(parse_qif_date("3/5'24").as_deref(), Some("2024-03-05"));
+        assert_eq!(parse_qif_date("03-05-24").as_deref(), Some("2024-03-05"));
+        assert_eq!(parse_qif_date("05.03.2024").as_deref(), Some("2024-03-05"));
+        assert_eq!(parse_qif_date("13/45/2024"), None);
+    }
+
+    #[test]
+    fn identical_records_get_distinct_ids() {
+        let text = "!Type:CCard\nD2024-03-05\nT-3.00\nPBus\n^\nD2024-03-05\nT-3.00\nPBus\n^\n";
+        let rows = parse(text).unwrap();
+        let ids: Vec<_> = rows.iter().map(|r| r.external_id.clone().unwrap()).collect();
+        assert_eq!(ids, vec!["2024-03-05|-3.00|Bus#1", "2024-03-05|-3.00|Bus#2"]);
+    }
+
+    #[test]
+    fn not_qif() {
+        assert!(parse("Date,Amount\n2024-03-05,10\n").is_err());
+    }
+}
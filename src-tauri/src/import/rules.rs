@@ -0,0 +1,111 @@
+//! Category rules for imported bills.
+
+use crate::models::Category;
+
+/// Keywords matched against the bill's own classification and the note,
+/// mapped to the default category names created by `db::run_migrations`
+const KEYWORD_RULES: &[(&[&str], &str)] = &[
+    (
+        &["餐饮美食", "美团", "饿了么", "肯德基", "麦当劳", "星巴克", "瑞幸", "外卖", "餐厅", "饭店", "奶茶", "水果", "买菜"],
+        "食物",
+    ),
+    (
+        &["交通出行", "爱车养车", "滴滴", "高德打车", "地铁", "公交", "12306", "铁路", "航空", "加油", "停车", "哈啰", "ETC"],
+        "交通",
+    ),
+    (
+        &["日用百货", "家居家装", "超市", "便利店", "淘宝", "天猫", "京东", "拼多多", "盒马"],
+        "日用品",
+    ),
+    (&["母婴亲子", "童装", "玩具", "奶粉", "幼儿园"], "孩子"),
+    (&["教育培训", "书店", "图书", "课程", "培训", "知识付费"], "学习"),
+];
+
+/// Category used when no rule matches
+const FALLBACK_CATEGORY: &str = "其它";
+
+/// Rules resolved against the current categories
+pub struct CategoryRules {
+    /// (category name, id), longest names first so "日用品" wins over a "日用" category
+    names: Vec<(String, i64)>,
+    keywords: Vec<(&'static [&'static str], i64)>,
+    fallback: Option<i64>,
+}
+
+impl CategoryRules {
+    pub fn new(categories: &[Category]) -> Self {
+        let id_of = |name: &str| categories.iter().find(|c| c.name == name).map(|c| c.id);
+
+        let mut names: Vec<_> = categories
+            .iter()
+            .filter(|c| !c.name.is_empty())
+            .map(|c| (c.name.clone(), c.id))
+            .collect();
+        names.sort_by_key(|(name, _)| std::cmp::Reverse(name.chars().count()));
+
+        let keywords = KEYWORD_RULES
+            .iter()
+            .filter_map(|(keywords, name)| id_of(name).map(|id| (*keywords, id)))
+            .collect();
+
+        CategoryRules {
+            names,
+            keywords,
+            fallback: id_of(FALLBACK_CATEGORY).or_else(|| categories.last().map(|c| c.id)),
+        }
+    }
+
+    /// Pick a category for a bill line; a category whose name appears in the
+    /// text wins over keyword rules
+    pub fn categorize(&self, text: &str) -> Option<i64> {
+        self.names
+            .iter()
+            .find(|(name, _)| text.contains(name.as_str()))
+            .map(|(_, id)| *id)
+            .or_else(|| {
+                self.keywords
+                    .iter()
+                    .find(|(keywords, _)| keywords.iter().any(|k| text.contains(k)))
+                    .map(|(_, id)| *id)
+            })
+            .or(self.fallback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn categories(names: &[&str]) -> Vec<Category> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Category {
+                id: i as i64 + 1,
+                name: name.to_string(),
+                icon: None,
+                created_at: String::new(),
+                updated_at: String::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn category_names_before_keywords() {
+        let rules = CategoryRules::new(&categories(&["食物", "交通", "日用", "日用品", "其它"]));
+        assert_eq!(rules.categorize("餐饮美食 肯德基"), Some(1));
+        assert_eq!(rules.categorize("滴滴出行 快车"), Some(2));
+        // The longest category name wins
+        assert_eq!(rules.categorize("日用品 纸巾"), Some(4));
+        // A category named in the text wins over a keyword
+        assert_eq!(rules.categorize("交通 美团单车"), Some(2));
+        assert_eq!(rules.categorize("电影票"), Some(5));
+    }
+
+    #[test]
+    fn fallback_without_other_category() {
+        let rules = CategoryRules::new(&categories(&["食物", "旅行"]));
+        assert_eq!(rules.categorize("电影票"), Some(2));
+        assert_eq!(CategoryRules::new(&[]).categorize("电影票"), None);
+    }
+}
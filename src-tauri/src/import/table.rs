@@ -0,0 +1,92 @@
+//! Reading bill files (CSV in any of the usual encodings, or xlsx) into rows
+//! of text cells.
+
+use calamine::{Data, Reader, Xlsx};
+use std::io::Cursor;
+
+/// A row of a bill file
+#[derive(Debug, Clone)]
+pub struct TableRow {
+    /// Line (CSV) or row (xlsx) number, starting at 1
+    pub line: u64,
+    pub cells: Vec<String>,
+}
+
+impl TableRow {
+    /// Trimmed cell value; empty cells and "/" placeholders are `None`
+    pub fn cell(&self, index: Option<usize>) -> Option<&str> {
+        index
+            .and_then(|i| self.cells.get(i))
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty() && *value != "/")
+    }
+
+    /// Position of the first cell whose trimmed text is one of `names`
+    pub fn position(&self, names: &[&str]) -> Option<usize> {
+        self.cells.iter().position(|cell| names.contains(&cell.trim()))
+    }
+}
+
+/// Read a bill file, detecting xlsx by its zip signature
+pub fn read_table(content: &[u8]) -> Result<Vec<TableRow>, String> {
+    if content.starts_with(b"PK\x03\x04") {
+        read_xlsx(content)
+    } else {
+        read_csv(&super::decode_text(content), b',')
+    }
+}
+
+/// Read CSV text; rows may have different lengths (preambles, footers)
+pub fn read_csv(text: &str, delimiter: u8) -> Result<Vec<TableRow>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(text.as_bytes());
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("账单文件格式错误: {}", e))?;
+        rows.push(TableRow {
+            line: record.position().map(|p| p.line()).unwrap_or_default(),
+            cells: record.iter().map(str::to_string).collect(),
+        });
+    }
+    Ok(rows)
+}
+
+/// Read the first worksheet of an xlsx workbook
+fn read_xlsx(content: &[u8]) -> Result<Vec<TableRow>, String> {
+    let mut workbook = Xlsx::new(Cursor::new(content)).map_err(|e| format!("无法读取 Excel 文件: {}", e))?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or("Excel 文件中没有工作表")?
+        .map_err(|e| format!("无法读取 Excel 工作表: {}", e))?;
+
+    let first_row = range.start().map(|(row, _)| row as u64).unwrap_or_default();
+    Ok(range
+        .rows()
+        .enumerate()
+        .map(|(i, cells)| TableRow {
+            line: first_row + i as u64 + 1,
+            cells: cells.iter().map(cell_text).collect(),
+        })
+        .collect())
+}
+
+/// Render an xlsx cell the way it would appear in the CSV export
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        Data::String(s) | Data::DateTimeIso(s) | Data::DurationIso(s) => s.clone(),
+        Data::Float(f) if f.fract() == 0.0 => format!("{}", *f as i64),
+        Data::Float(f) => f.to_string(),
+        Data::Int(n) => n.to_string(),
+        Data::Bool(b) => b.to_string(),
+        Data::DateTime(dt) => dt
+            .as_datetime()
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| dt.as_f64().to_string()),
+        Data::Error(e) => e.to_string(),
+    }
+}
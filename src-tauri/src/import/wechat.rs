@@ -0,0 +1,194 @@
+//! 微信支付 bill (xlsx or CSV)
+//!
+//! The export starts with a preamble (微信昵称, 起始时间, 统计 ...), followed by
+//! the column header (交易时间, 交易类型, 交易对方, 商品, 收/支, 金额(元), 支付方式,
+//! 当前状态, 交易单号, 商户单号, 备注) and one line per trade. Amounts carry a
+//! `¥` prefix.
+
+use super::table::TableRow;
+use super::{join_note, parse_amount, parse_datetime, ParsedRow};
+
+const TIME_COLUMNS: &[&str] = &["交易时间"];
+const TYPE_COLUMNS: &[&str] = &["交易类型"];
+const DIRECTION_COLUMNS: &[&str] = &["收/支"];
+const AMOUNT_COLUMNS: &[&str] = &["金额(元)", "金额（元）", "金额"];
+const STATUS_COLUMNS: &[&str] = &["当前状态"];
+const COUNTERPARTY_COLUMNS: &[&str] = &["交易对方"];
+const GOODS_COLUMNS: &[&str] = &["商品"];
+const REMARK_COLUMNS: &[&str] = &["备注"];
+const PAYMENT_COLUMNS: &[&str] = &["支付方式"];
+const ORDER_COLUMNS: &[&str] = &["交易单号"];
+
+/// Column positions found in the header line
+struct Columns {
+    time: usize,
+    kind: usize,
+    direction: usize,
+    amount: usize,
+    status: usize,
+    counterparty: Option<usize>,
+    goods: Option<usize>,
+    remark: Option<usize>,
+    payment: Option<usize>,
+    order: Option<usize>,
+}
+
+impl Columns {
+    fn find(header: &TableRow) -> Option<Columns> {
+        Some(Columns {
+            time: header.position(TIME_COLUMNS)?,
+            kind: header.position(TYPE_COLUMNS)?,
+            direction: header.position(DIRECTION_COLUMNS)?,
+            amount: header.position(AMOUNT_COLUMNS)?,
+            status: header.position(STATUS_COLUMNS)?,
+            counterparty: header.position(COUNTERPARTY_COLUMNS),
+            goods: header.position(GOODS_COLUMNS),
+            remark: header.position(REMARK_COLUMNS),
+            payment: header.position(PAYMENT_COLUMNS),
+            order: header.position(ORDER_COLUMNS),
+        })
+    }
+}
+
+/// Parse the rows of a 微信支付 bill
+pub fn parse(table: &[TableRow]) -> Result<Vec<ParsedRow>, String> {
+    let (header, columns) = table
+        .iter()
+        .enumerate()
+        .find_map(|(i, row)| Columns::find(row).map(|columns| (i, columns)))
+        .ok_or("没有找到微信支付账单的表头（交易时间、交易类型、收/支、金额(元)、当前状态），请确认选择的是微信导出的账单")?;
+
+    Ok(table[header + 1..]
+        .iter()
+        .filter_map(|row| parse_row(&columns, row))
+        .collect())
+}
+
+/// Parse a trade line; lines without a valid time are not trades
+fn parse_row(columns: &Columns, row: &TableRow) -> Option<ParsedRow> {
+    let transaction_date = parse_datetime(row.cell(Some(columns.time))?)?;
+    let kind = row.cell(Some(columns.kind)).unwrap_or_default();
+    let note = join_note(&[row.cell(columns.counterparty), row.cell(columns.goods), row.cell(columns.remark)]);
+    let status = row.cell(Some(columns.status)).unwrap_or_default();
+    let direction = row.cell(Some(columns.direction)).unwrap_or_default();
+
+    let mut skip_reason = skip_reason(kind, status, direction);
+    let amount = match row.cell(Some(columns.amount)).and_then(parse_amount) {
+        // A partially refunded payment only cost what was not refunded
+        Some(amount) => amount - partial_refund(status).unwrap_or(0.0),
+        None => 0.0,
+    };
+    if amount <= 0.0 {
+        skip_reason.get_or_insert_with(|| "金额无效".to_string());
+    }
+
+    Some(ParsedRow {
+        line: row.line,
+        transaction_date,
+        amount: if direction == "支出" { -amount } else { amount },
+        note,
+        category_hint: Some(kind.to_string()).filter(|k| !k.is_empty()),
+        external_id: row.cell(columns.order).map(str::to_string),
+        // 零钱, 零钱通 or a bank card; "/" for income
+        account: row.cell(columns.payment).map(str::to_string),
+        category_name: None,
+        skip_reason,
+    })
+}
+
+/// Why a trade is not imported by default
+///
+/// Refund lines (交易类型 "...-退款") are skipped because the refund is
+/// already netted off the original payment: fully refunded payments are
+/// skipped, partially refunded ones are imported with the remaining amount.
+/// Lines with 收/支 "/" move money between 零钱, 零钱通 and the user's bank
+/// cards (充值, 提现, 信用卡还款) and are not income or spending.
+fn skip_reason(kind: &str, status: &str, direction: &str) -> Option<String> {
+    if kind.contains("退款") {
+        Some("退款".to_string())
+    } else if status.contains("全额退款") {
+        Some("已全额退款".to_string())
+    } else if status.contains("退还") {
+        Some("对方已退还".to_string())
+    } else if status.contains("关闭") || status.contains("失败") {
+        Some("交易未成功".to_string())
+    } else if direction != "支出" && direction != "收入" {
+        if kind.contains("零钱") || kind.contains("提现") || kind.contains("充值") || kind.contains("还款") {
+            Some("零钱/银行卡间转账".to_string())
+        } else {
+            Some("不计收支".to_string())
+        }
+    } else {
+        None
+    }
+}
+
+/// Refunded amount of a partially refunded payment, e.g. `已退款(￥10.00)`
+fn partial_refund(status: &str) -> Option<f64> {
+    let start = status.find(['(', '（'])?;
+    let end = status.rfind([')', '）'])?;
+    let inner = status.get(start..end)?.trim_start_matches(['(', '（']);
+    parse_amount(inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::table::read_table;
+
+    const BILL: &str = "\
+微信支付账单明细,,,,,,,,,,
+微信昵称：[张三],,,,,,,,,,
+起始时间：[2024-03-01 00:00:00] 终止时间：[2024-03-31 23:59:59],,,,,,,,,,
+共4笔记录,,,,,,,,,,
+----------------------微信支付账单明细列表--------------------,,,,,,,,,,
+交易时间,交易类型,交易对方,商品,收/支,金额(元),支付方式,当前状态,交易单号,商户单号,备注
+2024-03-05 12:30:00,商户消费,瑞幸咖啡,生椰拿铁,支出,¥19.90,零钱,支付成功,4200001,10001,/
+2024-03-06 19:00:00,商户消费,盒马,买菜,支出,¥100.00,招商银行储蓄卡(1234),已退款(￥10.00),4200002,10002,/
+2024-03-07 10:00:00,零钱提现,招商银行(1234),/,/,¥500.00,招商银行(1234),提现已到账,4200003,/,/
+2024-03-08 08:00:00,转账,李四,转账备注:午饭,收入,¥30.00,/,已收钱,4200004,/,/
+2024-03-09 09:00:00,商户消费-退款,盒马,买菜,收入,¥10.00,招商银行储蓄卡(1234),已退款,4200005,/,/
+";
+
+    fn parse_bill(content: &[u8]) -> Vec<ParsedRow> {
+        parse(&read_table(content).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn header_after_preamble() {
+        let rows = parse_bill(BILL.as_bytes());
+        assert_eq!(rows.len(), 5);
+        let row = &rows[0];
+        assert_eq!(row.line, 7);
+        assert_eq!(row.transaction_date, "2024-03-05 12:30:00");
+        assert_eq!(row.amount, -19.9);
+        assert_eq!(row.note.as_deref(), Some("瑞幸咖啡 生椰拿铁"));
+        assert_eq!(row.category_hint.as_deref(), Some("商户消费"));
+        assert_eq!(row.account.as_deref(), Some("零钱"));
+        assert_eq!(row.skip_reason, None);
+        assert_eq!(rows[1].account.as_deref(), Some("招商银行储蓄卡(1234)"));
+        assert_eq!(rows[3].amount, 30.0);
+        assert_eq!(rows[3].account, None);
+        assert_eq!(rows[3].skip_reason, None);
+    }
+
+    #[test]
+    fn partial_refund_is_netted() {
+        let rows = parse_bill(BILL.as_bytes());
+        assert_eq!(rows[1].amount, -90.0);
+        assert_eq!(rows[1].skip_reason, None);
+        assert_eq!(rows[4].skip_reason.as_deref(), Some("退款"));
+    }
+
+    #[test]
+    fn transfers_are_skipped() {
+        let rows = parse_bill(BILL.as_bytes());
+        assert_eq!(rows[2].skip_reason.as_deref(), Some("零钱/银行卡间转账"));
+    }
+
+    #[test]
+    fn missing_header() {
+        let table = read_table("交易时间,金额\n2024-03-05 12:30:00,10\n".as_bytes()).unwrap();
+        assert!(parse(&table).is_err());
+    }
+}
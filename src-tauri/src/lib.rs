@@ -3,18 +3,47 @@ use tauri::State;
 use tauri::Manager;
 use tauri::Emitter;
 
+mod backup;
+mod classifier;
 mod db;
+mod duplicates;
+mod export;
+mod import;
 mod models;
+mod query;
 mod repository;
+mod rules;
 
 use db::DbState;
 use models::*;
-use repository::{category_repo, installment_repo, transaction_repo};
+use repository::{category_repo, import_repo, installment_repo, recurring_bill_repo, report_repo, rule_repo, settings_repo, smart_view_repo, stats_repo, transaction_repo};
 
 /// Global database state
 pub struct AppState {
     pub db: DbState,
     pub db_path: PathBuf,
+    /// Category suggestions learned from the ledger
+    pub classifier: tokio::sync::Mutex<classifier::Classifier>,
+}
+
+impl AppState {
+    /// Directory of the local snapshots
+    fn snapshot_dir(&self) -> PathBuf {
+        backup::snapshot::snapshot_dir(&self.db_path)
+    }
+
+    /// This device's settings for backups to a synced folder
+    fn backup_folder_config(&self) -> PathBuf {
+        backup::folder::config_path(&self.db_path)
+    }
+
+    /// Snapshot the database before an operation that rewrites or deletes a
+    /// lot of data; a failure is logged and does not stop the operation
+    async fn safety_snapshot(&self, conn: &libsql::Connection, reason: SnapshotReason) {
+        if let Err(e) = backup::snapshot::create_snapshot(conn, &self.snapshot_dir(), reason).await {
+            log::warn!("Failed to take {} snapshot: {}", reason.key(), e);
+        }
+    }
 }
 
 // ============================================================================
@@ -57,7 +86,11 @@ async fn delete_category(
     id: i64,
 ) -> Result<(), String> {
     let conn = state.db.get_connection().await?;
-    category_repo::delete_category(&conn, id).await
+    // Deleting a category deletes its transactions and installments too
+    state.safety_snapshot(&conn, SnapshotReason::BeforeDelete).await;
+    category_repo::delete_category(&conn, id).await?;
+    state.classifier.lock().await.reset();
+    Ok(())
 }
 
 // ============================================================================
@@ -82,6 +115,82 @@ async fn get_transactions_by_month(
     transaction_repo::get_transactions_by_month(&conn, year, month).await
 }
 
+#[tauri::command]
+async fn get_transactions_by_date(
+    state: State<'_, AppState>,
+    date: String,
+) -> Result<Vec<TransactionWithCategory>, String> {
+    let conn = state.db.get_connection().await?;
+    transaction_repo::get_transactions_by_date(&conn, &date).await
+}
+
+/// Page through transactions newest first; pass the previous page's
+/// `next_cursor` to continue, and `year`/`month` to stay within one month
+#[tauri::command]
+async fn get_transactions_page(
+    state: State<'_, AppState>,
+    year: Option<i32>,
+    month: Option<i32>,
+    cursor: Option<TransactionCursor>,
+    limit: i64,
+) -> Result<TransactionCursorPage, String> {
+    let conn = state.db.get_connection().await?;
+    transaction_repo::get_transactions_page(&conn, year, month, cursor.as_ref(), limit).await
+}
+
+#[tauri::command]
+async fn search_transactions(
+    state: State<'_, AppState>,
+    filter: TransactionFilter,
+    limit: i64,
+    offset: i64,
+) -> Result<TransactionPage, String> {
+    let conn = state.db.get_connection().await?;
+    transaction_repo::search_transactions(&conn, &filter, limit, offset).await
+}
+
+#[tauri::command]
+async fn query_transactions(
+    state: State<'_, AppState>,
+    query: String,
+    limit: i64,
+    offset: i64,
+) -> Result<TransactionPage, String> {
+    let conn = state.db.get_connection().await?;
+    transaction_repo::query_transactions(&conn, &query, limit, offset).await
+}
+
+#[tauri::command]
+async fn get_smart_views(
+    state: State<'_, AppState>,
+) -> Result<Vec<SmartView>, String> {
+    let conn = state.db.get_connection().await?;
+    smart_view_repo::get_smart_views(&conn).await
+}
+
+#[tauri::command]
+async fn save_smart_view(
+    state: State<'_, AppState>,
+    name: String,
+    query: String,
+) -> Result<SmartView, String> {
+    let conn = state.db.get_connection().await?;
+    smart_view_repo::save_smart_view(&conn, &name, &query).await
+}
+
+#[tauri::command]
+async fn delete_smart_view(
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<(), String> {
+    let conn = state.db.get_connection().await?;
+    smart_view_repo::delete_smart_view(&conn, id).await
+}
+
+/// Create a transaction; unless `allow_duplicate` is set, a transaction that
+/// looks like one already in the ledger is not saved and the similar ones are
+/// returned for the user to confirm. With the rules-on-entry setting on, the
+/// category rules may change the category and note first.
 #[tauri::command]
 async fn create_transaction(
     state: State<'_, AppState>,
@@ -89,15 +198,48 @@ async fn create_transaction(
     amount: f64,
     transaction_date: String,
     note: Option<String>,
-) -> Result<Transaction, String> {
+    allow_duplicate: Option<bool>,
+) -> Result<CreateTransactionResult, String> {
     let conn = state.db.get_connection().await?;
+    let (category_id, note) = if settings_repo::get_rules_on_entry(&conn).await? {
+        let rules = rules::RuleSet::load(&conn).await?;
+        match rules.find(note.as_deref().unwrap_or_default(), amount, None) {
+            Some(rule) => (rule.category_id.unwrap_or(category_id), rules::apply_note(rule, note.as_deref())),
+            None => (category_id, note),
+        }
+    } else {
+        (category_id, note)
+    };
+    if !allow_duplicate.unwrap_or(false) {
+        let fingerprint = duplicates::Fingerprint {
+            transaction_date: &transaction_date,
+            amount,
+            category_id: Some(category_id),
+            note: note.as_deref(),
+        };
+        let similar = duplicates::find_similar(&conn, &fingerprint).await?;
+        if !similar.is_empty() {
+            return Ok(CreateTransactionResult {
+                transaction: None,
+                duplicates: similar,
+            });
+        }
+    }
     let new_transaction = NewTransaction {
         category_id,
         amount,
         transaction_date,
         note,
     };
-    transaction_repo::create_transaction(&conn, new_transaction).await
+    let transaction = transaction_repo::create_transaction(&conn, new_transaction).await?;
+    // Learning is best effort; the transaction is saved either way
+    if let Err(e) = state.classifier.lock().await.sync(&conn).await {
+        log::warn!("Failed to update category suggestions: {}", e);
+    }
+    Ok(CreateTransactionResult {
+        transaction: Some(transaction),
+        duplicates: Vec::new(),
+    })
 }
 
 #[tauri::command]
@@ -106,7 +248,9 @@ async fn delete_transaction(
     id: i64,
 ) -> Result<(), String> {
     let conn = state.db.get_connection().await?;
-    transaction_repo::delete_transaction(&conn, id).await
+    transaction_repo::delete_transaction(&conn, id).await?;
+    state.classifier.lock().await.reset();
+    Ok(())
 }
 
 #[tauri::command]
@@ -189,13 +333,626 @@ async fn delete_installment(
     installment_repo::delete_installment(&conn, id).await
 }
 
+// ============================================================================
+// Recurring Bill Commands
+// ============================================================================
+
+#[tauri::command]
+async fn get_recurring_bills(state: State<'_, AppState>) -> Result<Vec<RecurringBill>, String> {
+    let conn = state.db.get_connection().await?;
+    recurring_bill_repo::get_recurring_bills(&conn).await
+}
+
+#[tauri::command]
+async fn create_recurring_bill(
+    state: State<'_, AppState>,
+    bill: NewRecurringBill,
+) -> Result<RecurringBill, String> {
+    let conn = state.db.get_connection().await?;
+    recurring_bill_repo::create_recurring_bill(&conn, bill).await
+}
+
+#[tauri::command]
+async fn delete_recurring_bill(state: State<'_, AppState>, id: i64) -> Result<(), String> {
+    let conn = state.db.get_connection().await?;
+    recurring_bill_repo::delete_recurring_bill(&conn, id).await
+}
+
+// ============================================================================
+// Statistics Commands
+// ============================================================================
+
+#[tauri::command]
+async fn get_category_breakdown(
+    state: State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<CategoryTotal>, String> {
+    let conn = state.db.get_connection().await?;
+    stats_repo::get_category_expense_breakdown(&conn, &start_date, &end_date).await
+}
+
+#[tauri::command]
+async fn get_monthly_trend(
+    state: State<'_, AppState>,
+    year: i32,
+    month: i32,
+    months: i32,
+) -> Result<Vec<MonthlyTrendPoint>, String> {
+    let conn = state.db.get_connection().await?;
+    stats_repo::get_monthly_trend(&conn, year, month, months).await
+}
+
+#[tauri::command]
+async fn get_daily_totals(
+    state: State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<DailyTotal>, String> {
+    let conn = state.db.get_connection().await?;
+    stats_repo::get_daily_totals(&conn, &start_date, &end_date).await
+}
+
+#[tauri::command]
+async fn get_month_totals(
+    state: State<'_, AppState>,
+) -> Result<Vec<MonthTotals>, String> {
+    let conn = state.db.get_connection().await?;
+    stats_repo::get_month_totals(&conn).await
+}
+
+#[tauri::command]
+async fn get_month_comparison(
+    state: State<'_, AppState>,
+    year: i32,
+    month: i32,
+) -> Result<MonthComparison, String> {
+    let conn = state.db.get_connection().await?;
+    stats_repo::get_month_comparison(&conn, year, month).await
+}
+
+// ============================================================================
+// Report Commands
+// ============================================================================
+
+#[tauri::command]
+async fn get_annual_report(
+    state: State<'_, AppState>,
+    year: i32,
+) -> Result<AnnualReport, String> {
+    let conn = state.db.get_connection().await?;
+    report_repo::get_annual_report(&conn, year).await
+}
+
+/// Export the annual report as a standalone HTML file, returns the written path
+#[tauri::command]
+async fn export_annual_report_html(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    year: i32,
+    path: Option<String>,
+) -> Result<String, String> {
+    let conn = state.db.get_connection().await?;
+    let report = report_repo::get_annual_report(&conn, year).await?;
+    let html = export::html::render_annual_report(&report);
+    let path = export::resolve_export_path(&app, path, &format!("年度账单-{}.html", year))?;
+    export::write_export(&path, html.as_bytes())
+}
+
+/// Export a month's statement as a standalone HTML file or, on desktop, a PDF.
+/// Returns the written path.
+#[tauri::command]
+async fn export_monthly_statement(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    year: i32,
+    month: i32,
+    format: StatementFormat,
+    path: Option<String>,
+) -> Result<String, String> {
+    let conn = state.db.get_connection().await?;
+    let statement = report_repo::get_monthly_statement(&conn, year, month).await?;
+    let html = export::html::render_monthly_statement(&statement);
+    let file_name = format!("对账单-{}-{:02}", year, month);
+    match format {
+        StatementFormat::Html => {
+            let path = export::resolve_export_path(&app, path, &format!("{}.html", file_name))?;
+            export::write_export(&path, html.as_bytes())
+        }
+        #[cfg(not(any(target_os = "android", target_os = "ios")))]
+        StatementFormat::Pdf => {
+            let path = export::resolve_export_path(&app, path, &format!("{}.pdf", file_name))?;
+            // The browser prints from a file, so the HTML goes to the cache first
+            let html_path = export::resolve_export_path(&app, None, &format!("{}.html", file_name))?;
+            export::write_export(&html_path, html.as_bytes())?;
+            let result = export::pdf::html_to_pdf(&html_path, &path).await;
+            let _ = std::fs::remove_file(&html_path);
+            result.map(|_| path.to_string_lossy().to_string())
+        }
+        #[cfg(any(target_os = "android", target_os = "ios"))]
+        StatementFormat::Pdf => Err("手机上暂不支持导出 PDF，请导出 HTML".to_string()),
+    }
+}
+
+// ============================================================================
+// Export Commands
+// ============================================================================
+
+/// Export transactions as CSV; with `include_installments` the installment
+/// schedule goes to a second file next to it. Returns the written paths.
+#[tauri::command]
+async fn export_csv(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    options: CsvExportOptions,
+    path: Option<String>,
+) -> Result<Vec<String>, String> {
+    let conn = state.db.get_connection().await?;
+    let filter = TransactionFilter {
+        start_date: options.start_date.clone(),
+        end_date: options.end_date.clone(),
+        category_ids: options.category_ids.clone(),
+        ..Default::default()
+    };
+    let transactions = transaction_repo::get_filtered_transactions(&conn, &filter).await?;
+    let content = export::csv::render_transactions(&transactions, &options.columns)?;
+    let file_name = format!("记账明细-{}.csv", chrono::Local::now().format("%Y%m%d"));
+    let path = export::resolve_export_path(&app, path, &file_name)?;
+    let mut written = vec![export::write_export(&path, &export::csv::encode(&content, options.encoding))?];
+
+    if options.include_installments {
+        let schedule = installment_repo::get_installment_schedule(
+            &conn,
+            options.start_date.as_deref(),
+            options.end_date.as_deref(),
+            &options.category_ids,
+        )
+        .await?;
+        let content = export::csv::render_installments(&schedule)?;
+        let installments_path = export::csv::installments_path(&path);
+        written.push(export::write_export(&installments_path, &export::csv::encode(&content, options.encoding))?);
+    }
+
+    Ok(written)
+}
+
+/// Export transactions as an Excel workbook: a summary of category totals per
+/// month, one sheet per month and the installment schedule. Returns the
+/// written path.
+#[tauri::command]
+async fn export_xlsx(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    options: XlsxExportOptions,
+    path: Option<String>,
+) -> Result<String, String> {
+    let conn = state.db.get_connection().await?;
+    let filter = TransactionFilter {
+        start_date: options.start_date.clone(),
+        end_date: options.end_date.clone(),
+        category_ids: options.category_ids.clone(),
+        ..Default::default()
+    };
+    let transactions = transaction_repo::get_filtered_transactions(&conn, &filter).await?;
+    let schedule = installment_repo::get_installment_schedule(
+        &conn,
+        options.start_date.as_deref(),
+        options.end_date.as_deref(),
+        &options.category_ids,
+    )
+    .await?;
+    let content = export::xlsx::render_workbook(&transactions, &schedule)?;
+    let file_name = format!("记账明细-{}.xlsx", chrono::Local::now().format("%Y%m%d"));
+    let path = export::resolve_export_path(&app, path, &file_name)?;
+    export::write_export(&path, &content)
+}
+
+/// Export the whole ledger for Beancount or ledger-cli. Returns the written path.
+#[tauri::command]
+async fn export_plaintext(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    options: PlainTextExportOptions,
+    path: Option<String>,
+) -> Result<String, String> {
+    let asset_account = export::plaintext::parse_asset_account(options.asset_account.as_deref())?;
+    let currency = export::plaintext::parse_currency(options.currency.as_deref())?;
+    let conn = state.db.get_connection().await?;
+    let transactions = transaction_repo::get_filtered_transactions(&conn, &TransactionFilter::default()).await?;
+    let installments = installment_repo::get_all_installments_with_category(&conn).await?;
+    let details = installment_repo::get_all_installment_details(&conn).await?;
+    let content = export::plaintext::render(options.format, &transactions, &installments, &details, &asset_account, &currency);
+    let extension = match options.format {
+        PlainTextFormat::Beancount => "beancount",
+        PlainTextFormat::Ledger => "ledger",
+    };
+    let file_name = format!("记账-{}.{}", chrono::Local::now().format("%Y%m%d"), extension);
+    let path = export::resolve_export_path(&app, path, &file_name)?;
+    export::write_export(&path, content.as_bytes())
+}
+
+/// Export the unpaid installment periods and the recurring bills as calendar
+/// events. Returns the written path.
+#[tauri::command]
+async fn export_ics(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    options: IcsExportOptions,
+    path: Option<String>,
+) -> Result<String, String> {
+    if options.reminder_days.is_some_and(|days| days > export::ics::MAX_REMINDER_DAYS) {
+        return Err(format!("提醒最多提前 {} 天", export::ics::MAX_REMINDER_DAYS));
+    }
+    let conn = state.db.get_connection().await?;
+    let schedule = installment_repo::get_installment_schedule(&conn, None, None, &[]).await?;
+    let bills = recurring_bill_repo::get_recurring_bills(&conn).await?;
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let content = export::ics::render(&schedule, &bills, options.reminder_days, &stamp);
+    let file_name = format!("记账-还款提醒-{}.ics", chrono::Local::now().format("%Y%m%d"));
+    let path = export::resolve_export_path(&app, path, &file_name)?;
+    export::write_export(&path, content.as_bytes())
+}
+
+// ============================================================================
+// Backup Commands
+// ============================================================================
+
+/// Write a full JSON backup of the ledger. Returns the written path.
+#[tauri::command]
+async fn export_json_backup(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    path: Option<String>,
+    password: Option<String>,
+) -> Result<String, String> {
+    let conn = state.db.get_connection().await?;
+    let document = backup::create_backup(&conn).await?;
+    let content = backup::write_backup(&document, password.as_deref()).await?;
+    let suffix = if password.is_some() { "-加密" } else { "" };
+    let file_name = format!("记账备份-{}{}.json", chrono::Local::now().format("%Y%m%d-%H%M%S"), suffix);
+    let path = export::resolve_export_path(&app, path, &file_name)?;
+    export::write_export(&path, content.as_bytes())
+}
+
+/// Validate a backup and describe what it contains, without touching the database
+#[tauri::command]
+async fn inspect_json_backup(content: String, password: Option<String>) -> Result<BackupInfo, String> {
+    Ok(backup::open_backup(&content, password.as_deref()).await?.info())
+}
+
+/// Restore a backup, replacing or merging into the current ledger
+#[tauri::command]
+async fn restore_json_backup(
+    state: State<'_, AppState>,
+    content: String,
+    mode: RestoreMode,
+    password: Option<String>,
+) -> Result<RestoreSummary, String> {
+    let document = backup::open_backup(&content, password.as_deref()).await?;
+    let conn = state.db.get_connection().await?;
+    state.safety_snapshot(&conn, SnapshotReason::BeforeRestore).await;
+    let summary = backup::restore_backup(&conn, &document, mode).await?;
+    state.classifier.lock().await.reset();
+    Ok(summary)
+}
+
+/// Local snapshots, newest first
+#[tauri::command]
+async fn list_snapshots(state: State<'_, AppState>) -> Result<Vec<SnapshotInfo>, String> {
+    backup::snapshot::list_snapshots(&state.snapshot_dir())
+}
+
+/// Take a snapshot now
+#[tauri::command]
+async fn create_snapshot(state: State<'_, AppState>) -> Result<SnapshotInfo, String> {
+    let conn = state.db.get_connection().await?;
+    backup::snapshot::create_snapshot(&conn, &state.snapshot_dir(), SnapshotReason::Manual).await
+}
+
+/// Replace the ledger with a snapshot; the current data is snapshotted first
+#[tauri::command]
+async fn restore_snapshot(state: State<'_, AppState>, file_name: String) -> Result<(), String> {
+    let conn = state.db.get_connection().await?;
+    let dir = state.snapshot_dir();
+    // Not a safety_snapshot: restoring without a way back is not allowed
+    backup::snapshot::create_snapshot(&conn, &dir, SnapshotReason::BeforeRestore).await?;
+    backup::snapshot::restore_snapshot(&conn, &dir, &file_name).await?;
+    state.classifier.lock().await.reset();
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_snapshot_policy(state: State<'_, AppState>) -> Result<SnapshotPolicy, String> {
+    let conn = state.db.get_connection().await?;
+    Ok(backup::snapshot::get_policy(&conn).await)
+}
+
+/// Save the rotation policy and apply it right away
+#[tauri::command]
+async fn set_snapshot_policy(state: State<'_, AppState>, policy: SnapshotPolicy) -> Result<(), String> {
+    let conn = state.db.get_connection().await?;
+    backup::snapshot::set_policy(&conn, policy).await?;
+    backup::snapshot::rotate(&state.snapshot_dir(), policy)?;
+    Ok(())
+}
+
+/// This device's backup folder settings
+#[tauri::command]
+async fn get_backup_folder(state: State<'_, AppState>) -> Result<BackupFolderSettings, String> {
+    backup::folder::get_settings(&state.backup_folder_config())
+}
+
+/// Choose the backup folder (None turns it off) and write a first backup into it
+#[tauri::command]
+async fn set_backup_folder(
+    state: State<'_, AppState>,
+    path: Option<String>,
+    keep: u32,
+    password: Option<String>,
+) -> Result<Option<FolderBackup>, String> {
+    let config = state.backup_folder_config();
+    backup::folder::set_settings(&config, path, keep, password)?;
+    let conn = state.db.get_connection().await?;
+    backup::folder::write_folder_backup(&conn, &config, true).await
+}
+
+/// Write a backup into the folder now, even if nothing changed
+#[tauri::command]
+async fn backup_to_folder(state: State<'_, AppState>) -> Result<Option<FolderBackup>, String> {
+    let conn = state.db.get_connection().await?;
+    backup::folder::write_folder_backup(&conn, &state.backup_folder_config(), true).await
+}
+
+/// Backups in the folder from all devices, newest first
+#[tauri::command]
+async fn list_folder_backups(state: State<'_, AppState>) -> Result<Vec<FolderBackup>, String> {
+    backup::folder::list_backups(&state.backup_folder_config())
+}
+
+/// A backup of another device not dealt with yet
+#[tauri::command]
+async fn check_folder_backup(state: State<'_, AppState>) -> Result<Option<FolderBackup>, String> {
+    backup::folder::newer_backup(&state.backup_folder_config())
+}
+
+/// Restore or merge a backup from the folder
+#[tauri::command]
+async fn restore_folder_backup(
+    state: State<'_, AppState>,
+    file_name: String,
+    mode: RestoreMode,
+) -> Result<RestoreSummary, String> {
+    let config = state.backup_folder_config();
+    let document = backup::folder::read_backup(&config, &file_name).await?;
+    let conn = state.db.get_connection().await?;
+    state.safety_snapshot(&conn, SnapshotReason::BeforeRestore).await;
+    let summary = backup::restore_backup(&conn, &document, mode).await?;
+    state.classifier.lock().await.reset();
+    backup::folder::mark_handled(&config, &file_name)?;
+    Ok(summary)
+}
+
+/// Stop offering a backup of another device
+#[tauri::command]
+async fn dismiss_folder_backup(state: State<'_, AppState>, file_name: String) -> Result<(), String> {
+    backup::folder::mark_handled(&state.backup_folder_config(), &file_name)
+}
+
+// ============================================================================
+// Import Commands
+// ============================================================================
+
+/// Parse a bill file and return the preview; nothing is written yet
+#[tauri::command]
+async fn preview_import(
+    state: State<'_, AppState>,
+    source: ImportSource,
+    content: Vec<u8>,
+    account: Option<String>,
+) -> Result<Vec<ImportRow>, String> {
+    let conn = state.db.get_connection().await?;
+    let mut classifier = state.classifier.lock().await;
+    classifier.sync(&conn).await?;
+    import::preview(&conn, &classifier, source, &content, account).await
+}
+
+/// Insert the preview rows the user confirmed
+#[tauri::command]
+async fn commit_import(
+    state: State<'_, AppState>,
+    source: ImportSource,
+    rows: Vec<ImportRow>,
+) -> Result<ImportResult, String> {
+    let conn = state.db.get_connection().await?;
+    state.safety_snapshot(&conn, SnapshotReason::BeforeImport).await;
+    let result = import::commit(&conn, source, rows).await?;
+    if let Err(e) = state.classifier.lock().await.sync(&conn).await {
+        log::warn!("Failed to update category suggestions: {}", e);
+    }
+    Ok(result)
+}
+
+/// Guess the encoding and delimiter of a CSV file and return its first rows
+#[tauri::command]
+async fn sniff_csv(content: Vec<u8>) -> Result<CsvSniff, String> {
+    import::generic::sniff(&content)
+}
+
+/// Parse a CSV file with the user's column mapping and return the preview
+#[tauri::command]
+async fn preview_csv_import(
+    state: State<'_, AppState>,
+    content: Vec<u8>,
+    mapping: CsvMapping,
+    account: Option<String>,
+) -> Result<Vec<ImportRow>, String> {
+    let conn = state.db.get_connection().await?;
+    let mut classifier = state.classifier.lock().await;
+    classifier.sync(&conn).await?;
+    import::preview_csv(&conn, &classifier, &content, &mapping, account).await
+}
+
+#[tauri::command]
+async fn get_csv_presets(state: State<'_, AppState>) -> Result<Vec<CsvPreset>, String> {
+    let conn = state.db.get_connection().await?;
+    import_repo::get_csv_presets(&conn).await
+}
+
+#[tauri::command]
+async fn save_csv_preset(
+    state: State<'_, AppState>,
+    name: String,
+    mapping: CsvMapping,
+) -> Result<CsvPreset, String> {
+    let conn = state.db.get_connection().await?;
+    import_repo::save_csv_preset(&conn, &name, &mapping).await
+}
+
+#[tauri::command]
+async fn delete_csv_preset(state: State<'_, AppState>, id: i64) -> Result<(), String> {
+    let conn = state.db.get_connection().await?;
+    import_repo::delete_csv_preset(&conn, id).await
+}
+
+// ============================================================================
+// Rule Commands
+// ============================================================================
+
+#[tauri::command]
+async fn get_category_rules(state: State<'_, AppState>) -> Result<Vec<CategoryRule>, String> {
+    let conn = state.db.get_connection().await?;
+    rule_repo::get_rules(&conn).await
+}
+
+#[tauri::command]
+async fn create_category_rule(
+    state: State<'_, AppState>,
+    rule: NewCategoryRule,
+) -> Result<CategoryRule, String> {
+    let conn = state.db.get_connection().await?;
+    rule_repo::create_rule(&conn, rule).await
+}
+
+#[tauri::command]
+async fn update_category_rule(
+    state: State<'_, AppState>,
+    id: i64,
+    rule: NewCategoryRule,
+) -> Result<(), String> {
+    let conn = state.db.get_connection().await?;
+    rule_repo::update_rule(&conn, id, rule).await
+}
+
+#[tauri::command]
+async fn delete_category_rule(state: State<'_, AppState>, id: i64) -> Result<(), String> {
+    let conn = state.db.get_connection().await?;
+    rule_repo::delete_rule(&conn, id).await
+}
+
+/// Set the matching order of the rules
+#[tauri::command]
+async fn reorder_category_rules(state: State<'_, AppState>, ids: Vec<i64>) -> Result<(), String> {
+    let conn = state.db.get_connection().await?;
+    rule_repo::reorder_rules(&conn, &ids).await
+}
+
+/// Dry run of re-applying the rules to existing transactions
+#[tauri::command]
+async fn preview_rule_changes(state: State<'_, AppState>) -> Result<Vec<RuleChange>, String> {
+    let conn = state.db.get_connection().await?;
+    rules::preview_changes(&conn).await
+}
+
+/// Apply the changes the user kept from the dry run
+#[tauri::command]
+async fn apply_rule_changes(
+    state: State<'_, AppState>,
+    changes: Vec<RuleChange>,
+) -> Result<i64, String> {
+    let conn = state.db.get_connection().await?;
+    state.safety_snapshot(&conn, SnapshotReason::BeforeRuleChanges).await;
+    let count = rules::apply_changes(&conn, &changes).await?;
+    state.classifier.lock().await.reset();
+    Ok(count)
+}
+
+#[tauri::command]
+async fn get_rules_on_entry(state: State<'_, AppState>) -> Result<bool, String> {
+    let conn = state.db.get_connection().await?;
+    settings_repo::get_rules_on_entry(&conn).await
+}
+
+#[tauri::command]
+async fn set_rules_on_entry(state: State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    let conn = state.db.get_connection().await?;
+    settings_repo::set_setting(&conn, settings_repo::RULES_ON_ENTRY_KEY, &enabled.to_string()).await
+}
+
+// ============================================================================
+// Suggestion Commands
+// ============================================================================
+
+/// The most likely categories for a transaction being entered, learned from
+/// the ledger
+#[tauri::command]
+async fn suggest_categories(
+    state: State<'_, AppState>,
+    note: String,
+    amount: f64,
+    limit: Option<usize>,
+) -> Result<Vec<CategorySuggestion>, String> {
+    let conn = state.db.get_connection().await?;
+    let mut classifier = state.classifier.lock().await;
+    classifier.sync(&conn).await?;
+    Ok(classifier.suggest(&note, amount, Some(classifier::current_hour()), limit.unwrap_or(3)))
+}
+
+// ============================================================================
+// Duplicate Commands
+// ============================================================================
+
+/// Likely duplicate pairs in the ledger, for the review screen
+#[tauri::command]
+async fn get_duplicate_pairs(state: State<'_, AppState>) -> Result<Vec<DuplicatePair>, String> {
+    let conn = state.db.get_connection().await?;
+    duplicates::find_duplicate_pairs(&conn).await
+}
+
+/// Keep one transaction of a duplicate pair and delete the other
+#[tauri::command]
+async fn merge_duplicates(
+    state: State<'_, AppState>,
+    keep_id: i64,
+    remove_id: i64,
+) -> Result<(), String> {
+    let conn = state.db.get_connection().await?;
+    state.safety_snapshot(&conn, SnapshotReason::BeforeMerge).await;
+    duplicates::merge(&conn, keep_id, remove_id).await?;
+    state.classifier.lock().await.reset();
+    Ok(())
+}
+
+/// Mark a pair as not duplicates so it is no longer reported
+#[tauri::command]
+async fn dismiss_duplicate(
+    state: State<'_, AppState>,
+    first_id: i64,
+    second_id: i64,
+) -> Result<(), String> {
+    let conn = state.db.get_connection().await?;
+    duplicates::dismiss(&conn, first_id, second_id).await
+}
+
 // ============================================================================
 // Sync Commands
 // ============================================================================
 
 #[tauri::command]
 async fn sync_database(state: State<'_, AppState>) -> Result<(), String> {
-    state.db.sync().await
+    state.db.sync().await?;
+    // Other devices may have changed or removed transactions
+    state.classifier.lock().await.reset();
+    Ok(())
 }
 
 #[tauri::command]
@@ -233,7 +990,10 @@ async fn migrate_from_legacy(
     state: State<'_, AppState>,
 ) -> Result<String, String> {
     let conn = state.db.get_connection().await?;
-    db::migrate_from_legacy(&state.db_path, &conn).await
+    state.safety_snapshot(&conn, SnapshotReason::BeforeMigration).await;
+    let message = db::migrate_from_legacy(&state.db_path, &conn).await?;
+    state.classifier.lock().await.reset();
+    Ok(message)
 }
 
 #[tauri::command]
@@ -257,19 +1017,7 @@ async fn get_baseline(
     state: State<'_, AppState>,
 ) -> Result<Option<f64>, String> {
     let conn = state.db.get_connection().await?;
-    let mut rows = conn.query("SELECT value FROM settings WHERE key = 'baseline'", ())
-        .await
-        .map_err(|e| e.to_string())?;
-    
-    if let Ok(Some(row)) = rows.next().await {
-        if let Ok(value_str) = row.get::<String>(0) {
-            if let Ok(value) = value_str.parse::<f64>() {
-                return Ok(Some(value));
-            }
-        }
-    }
-    
-    Ok(None)
+    settings_repo::get_baseline(&conn).await
 }
 
 #[tauri::command]
@@ -278,14 +1026,7 @@ async fn set_baseline(
     baseline: f64,
 ) -> Result<(), String> {
     let conn = state.db.get_connection().await?;
-    conn.execute(
-        "INSERT OR REPLACE INTO settings (key, value) VALUES ('baseline', ?)",
-        libsql::params![baseline.to_string()],
-    )
-    .await
-    .map_err(|e| e.to_string())?;
-    
-    Ok(())
+    settings_repo::set_setting(&conn, settings_repo::BASELINE_KEY, &baseline.to_string()).await
 }
 
 // ============================================================================
@@ -297,6 +1038,7 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_http::init())
+        .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
             // Get database path
             let db_path = app
@@ -325,7 +1067,8 @@ pub fn run() {
             // Manage state IMMEDIATELY so app doesn't panic
             app.manage(AppState { 
                 db: db_state.clone(), // Clone the DbState so we can update it later
-                db_path: db_path.clone() 
+                db_path: db_path.clone(),
+                classifier: Default::default(),
             });
 
             // Initialize database asynchronously in bg
@@ -344,10 +1087,40 @@ pub fn run() {
                         if let Err(e) = app_handle.emit("db-initialized", ()) {
                             eprintln!("Failed to emit event: {}", e);
                         }
+
+                        // Daily snapshots and folder backups while the app stays open
+                        let snapshot_dir = backup::snapshot::snapshot_dir(&db_path_for_init);
+                        let folder_config = backup::folder::config_path(&db_path_for_init);
+                        loop {
+                            match db_state.get_connection().await {
+                                Ok(conn) => {
+                                    if backup::snapshot::daily_snapshot_due(&snapshot_dir) {
+                                        if let Err(e) = backup::snapshot::create_snapshot(&conn, &snapshot_dir, SnapshotReason::Daily).await {
+                                            log::warn!("Failed to take daily snapshot: {}", e);
+                                        }
+                                    }
+                                    if let Err(e) = backup::folder::write_folder_backup(&conn, &folder_config, false).await {
+                                        log::warn!("Failed to write folder backup: {}", e);
+                                    }
+                                }
+                                Err(e) => log::warn!("Failed to get a connection for backups: {}", e),
+                            }
+                            // Another device may have left a newer backup
+                            match backup::folder::newer_backup(&folder_config) {
+                                Ok(Some(newer)) => {
+                                    let _ = app_handle.emit("folder-backup-available", newer);
+                                }
+                                Ok(None) => {}
+                                Err(e) => log::warn!("Failed to check the backup folder: {}", e),
+                            }
+                            tokio::time::sleep(std::time::Duration::from_secs(15 * 60)).await;
+                        }
                     }
                     Err(e) => {
                         eprintln!("Failed to initialize database (async): {}", e);
                         let _ = rolling_logger::error(&format!("Async DB init failed: {}", e));
+                        // E.g. a database from a newer app version
+                        let _ = app_handle.emit("db-init-failed", e);
                     }
                 }
             });
@@ -395,6 +1168,13 @@ pub fn run() {
             // Transaction commands
             get_transactions,
             get_transactions_by_month,
+            get_transactions_by_date,
+            get_transactions_page,
+            search_transactions,
+            query_transactions,
+            get_smart_views,
+            save_smart_view,
+            delete_smart_view,
             create_transaction,
             delete_transaction,
             get_monthly_summary,
@@ -405,6 +1185,65 @@ pub fn run() {
             get_due_installments_by_month,
             mark_installment_paid,
             delete_installment,
+            // Recurring bill commands
+            get_recurring_bills,
+            create_recurring_bill,
+            delete_recurring_bill,
+            // Statistics commands
+            get_category_breakdown,
+            get_monthly_trend,
+            get_daily_totals,
+            get_month_totals,
+            get_month_comparison,
+            // Report commands
+            get_annual_report,
+            export_annual_report_html,
+            export_monthly_statement,
+            // Export commands
+            export_csv,
+            export_xlsx,
+            export_plaintext,
+            export_ics,
+            // Backup commands
+            export_json_backup,
+            inspect_json_backup,
+            restore_json_backup,
+            list_snapshots,
+            create_snapshot,
+            restore_snapshot,
+            get_snapshot_policy,
+            set_snapshot_policy,
+            get_backup_folder,
+            set_backup_folder,
+            backup_to_folder,
+            list_folder_backups,
+            check_folder_backup,
+            restore_folder_backup,
+            dismiss_folder_backup,
+            // Import commands
+            preview_import,
+            commit_import,
+            sniff_csv,
+            preview_csv_import,
+            get_csv_presets,
+            save_csv_preset,
+            delete_csv_preset,
+            // Rule commands
+            get_category_rules,
+            create_category_rule,
+            update_category_rule,
+            delete_category_rule,
+            reorder_category_rules,
+            preview_rule_changes,
+            apply_rule_changes,
+            get_rules_on_entry,
+            set_rules_on_entry,
+            // Suggestion commands
+            suggest_categories,
+            // Duplicate commands
+            get_duplicate_pairs,
+            merge_duplicates,
+            dismiss_duplicate,
             // Sync commands
             sync_database,
             configure_sync,
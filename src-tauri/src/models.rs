@@ -41,6 +41,15 @@ pub struct NewTransaction {
     pub note: Option<String>,
 }
 
+/// 新建交易的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTransactionResult {
+    /// 保存的交易；发现疑似重复且未确认保存时为 None
+    pub transaction: Option<Transaction>,
+    /// 疑似重复的已有交易，最相似的在前
+    pub duplicates: Vec<TransactionWithCategory>,
+}
+
 /// 分期计划
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Installment {
@@ -75,6 +84,31 @@ pub struct InstallmentDetail {
     pub paid_date: Option<String>,
 }
 
+/// 周期账单（房租、订阅等），只用于日历提醒，不生成交易记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecurringBill {
+    pub id: i64,
+    pub name: String,
+    pub amount: f64,
+    /// 每隔几个月一次（1 为每月，12 为每年）
+    pub interval_months: i32,
+    /// 第一次的日期，之后按同一天重复（没有这一天的月份为月末）
+    pub start_date: String,
+    /// 最后一次不晚于此日期；为空表示一直重复
+    pub end_date: Option<String>,
+    pub created_at: String,
+}
+
+/// 新建周期账单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewRecurringBill {
+    pub name: String,
+    pub amount: f64,
+    pub interval_months: i32,
+    pub start_date: String,
+    pub end_date: Option<String>,
+}
+
 /// 带分类信息的交易记录（用于前端展示）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransactionWithCategory {
@@ -112,3 +146,678 @@ pub struct MonthlySummary {
     pub net_amount: f64,
     pub transaction_count: i32,
 }
+
+/// 分类支出汇总（用于图表）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryTotal {
+    pub category_id: i64,
+    pub category_name: String,
+    pub category_icon: Option<String>,
+    pub total: f64,
+    pub transaction_count: i32,
+}
+
+/// 月度收支趋势数据点（用于图表）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthlyTrendPoint {
+    pub year: i32,
+    pub month: i32,
+    pub total_income: f64,
+    pub total_expense: f64,
+    pub installment_expense: f64,
+}
+
+/// 按日统计的收支（用于日历热力图）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyTotal {
+    pub date: String,
+    pub total_income: f64,
+    pub total_expense: f64,
+    pub transaction_count: i32,
+    pub installment_amount: f64,
+    pub installment_count: i32,
+}
+
+/// 对比报告中某个月的收支汇总（支出含分期）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComparisonPeriod {
+    pub year: i32,
+    pub month: i32,
+    pub total_income: f64,
+    pub total_expense: f64,
+    pub transaction_count: i32,
+}
+
+/// 分类支出的环比/同比变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryComparison {
+    pub category_id: i64,
+    pub category_name: String,
+    pub category_icon: Option<String>,
+    pub current: f64,
+    pub previous: f64,
+    pub last_year: f64,
+    pub mom_change: f64,
+    pub mom_percent: Option<f64>,
+    pub yoy_change: f64,
+    pub yoy_percent: Option<f64>,
+    /// 是否为本月支出变化的主要来源
+    pub is_driver: bool,
+}
+
+/// 本月 vs 上月 vs 去年同月 对比报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthComparison {
+    pub current: ComparisonPeriod,
+    pub previous: ComparisonPeriod,
+    pub last_year: ComparisonPeriod,
+    pub categories: Vec<CategoryComparison>,
+}
+
+/// 年度账单中的单月数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnualReportMonth {
+    pub month: i32,
+    pub total_income: f64,
+    /// 支出（含分期）
+    pub total_expense: f64,
+    /// 是否未超出月度底线（未设置底线时为 None）
+    pub within_budget: Option<bool>,
+}
+
+/// 年度账单（年度报告）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnualReport {
+    pub year: i32,
+    pub total_income: f64,
+    /// 全年支出（含分期）
+    pub total_expense: f64,
+    pub installment_expense: f64,
+    pub net_amount: f64,
+    pub transaction_count: i32,
+    /// 支出最多的分类
+    pub top_categories: Vec<CategoryTotal>,
+    /// 金额最大的几笔支出
+    pub largest_expenses: Vec<TransactionWithCategory>,
+    pub months: Vec<AnnualReportMonth>,
+    /// 支出最多的月份
+    pub most_expensive_month: Option<AnnualReportMonth>,
+    /// 当年开始的分期
+    pub installments_started: Vec<InstallmentWithCategory>,
+    /// 当年还清（最后一期到期）的分期
+    pub installments_finished: Vec<InstallmentWithCategory>,
+    /// 月度底线
+    pub budget: Option<f64>,
+    /// 未超出底线的月数 / 已统计月数
+    pub budget_months_within: i32,
+    pub budget_months_counted: i32,
+    pub budget_hit_rate: Option<f64>,
+}
+
+/// 月度对账单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthlyStatement {
+    /// 当月交易汇总（不含分期）
+    pub summary: MonthlySummary,
+    /// 当月到期的分期还款合计
+    pub installment_expense: f64,
+    /// 各分类支出（含分期），从多到少
+    pub categories: Vec<CategoryTotal>,
+    /// 当月全部交易，按时间先后
+    pub transactions: Vec<TransactionWithCategory>,
+    /// 当月到期的各期分期
+    pub installments: Vec<InstallmentScheduleRow>,
+}
+
+/// 对账单文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementFormat {
+    #[default]
+    Html,
+    /// 仅桌面端，需要本机装有 Chrome / Edge / Chromium
+    Pdf,
+}
+
+/// 收支方向
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionKind {
+    Income,
+    Expense,
+}
+
+/// 交易搜索条件（所有条件同时满足）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransactionFilter {
+    /// 备注关键字，空格分隔，需全部包含
+    pub text: Option<String>,
+    /// 分类（为空表示不限）
+    pub category_ids: Vec<i64>,
+    /// 金额（绝对值）范围
+    pub min_amount: Option<f64>,
+    pub max_amount: Option<f64>,
+    /// 日期范围 YYYY-MM-DD，包含首尾两天
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub kind: Option<TransactionKind>,
+}
+
+/// 分页的交易查询结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionPage {
+    pub transactions: Vec<TransactionWithCategory>,
+    /// 满足条件的总条数
+    pub total_count: i64,
+    pub has_more: bool,
+}
+
+/// 交易分页游标：上一页最后一条的日期与 id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionCursor {
+    pub transaction_date: String,
+    pub id: i64,
+}
+
+/// 按游标分页的交易列表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionCursorPage {
+    pub transactions: Vec<TransactionWithCategory>,
+    /// 下一页的游标，为空表示已加载完
+    pub next_cursor: Option<TransactionCursor>,
+}
+
+/// 单月收支合计（汇总页按月分组的表头）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthTotals {
+    pub year: i32,
+    pub month: i32,
+    pub total_income: f64,
+    pub total_expense: f64,
+    pub transaction_count: i32,
+    pub installment_expense: f64,
+    pub installment_count: i32,
+}
+
+/// CSV 导出的列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvColumn {
+    Date,
+    Category,
+    Kind,
+    Amount,
+    Note,
+    CreatedAt,
+}
+
+impl CsvColumn {
+    /// 默认导出的全部列
+    pub const ALL: [CsvColumn; 6] = [
+        CsvColumn::Date,
+        CsvColumn::Category,
+        CsvColumn::Kind,
+        CsvColumn::Amount,
+        CsvColumn::Note,
+        CsvColumn::CreatedAt,
+    ];
+}
+
+/// CSV 文件编码
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvEncoding {
+    /// 带 BOM 的 UTF-8，中文 Windows 上的 Excel 可直接打开
+    #[default]
+    Utf8Bom,
+    Gb18030,
+}
+
+/// CSV 导出选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvExportOptions {
+    /// 起始日期（含），YYYY-MM-DD
+    pub start_date: Option<String>,
+    /// 结束日期（含），YYYY-MM-DD
+    pub end_date: Option<String>,
+    /// 分类（为空表示不限）
+    pub category_ids: Vec<i64>,
+    /// 导出的列及顺序（为空表示全部）
+    pub columns: Vec<CsvColumn>,
+    pub encoding: CsvEncoding,
+    /// 是否另存一份分期还款计划
+    pub include_installments: bool,
+}
+
+/// Excel 导出选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XlsxExportOptions {
+    /// 起始日期（含），YYYY-MM-DD
+    pub start_date: Option<String>,
+    /// 结束日期（含），YYYY-MM-DD
+    pub end_date: Option<String>,
+    /// 分类（为空表示不限）
+    pub category_ids: Vec<i64>,
+}
+
+/// 纯文本记账格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PlainTextFormat {
+    #[default]
+    Beancount,
+    /// ledger-cli
+    Ledger,
+}
+
+/// 纯文本记账导出选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlainTextExportOptions {
+    pub format: PlainTextFormat,
+    /// 记账对应的资产或负债账户，默认 Assets:Cash
+    pub asset_account: Option<String>,
+    /// 币种，默认 CNY
+    pub currency: Option<String>,
+}
+
+/// 日历（ICS）导出选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IcsExportOptions {
+    /// 提前几天提醒（为空表示不提醒）
+    pub reminder_days: Option<u32>,
+}
+
+/// 分期还款计划中的一期（用于导出）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallmentScheduleRow {
+    pub installment_id: i64,
+    pub category_name: String,
+    pub note: Option<String>,
+    pub total_amount: f64,
+    pub installment_count: i32,
+    pub sequence_number: i32,
+    pub amount: f64,
+    pub due_date: String,
+    pub is_paid: bool,
+    pub paid_date: Option<String>,
+}
+
+/// 应用设置（键值对）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Setting {
+    pub key: String,
+    pub value: String,
+}
+
+/// 恢复备份的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RestoreMode {
+    /// 清空现有数据后按原样导入（保留原 id）
+    Replace,
+    /// 合并到现有数据：分类按名称对应，重复的记录跳过
+    Merge,
+}
+
+/// 备份中各类数据的条数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupCounts {
+    pub categories: i64,
+    pub transactions: i64,
+    pub installments: i64,
+    pub installment_details: i64,
+    pub settings: i64,
+    pub smart_views: i64,
+    pub rules: i64,
+    pub csv_presets: i64,
+    pub recurring_bills: i64,
+}
+
+/// 备份文件概要（恢复前预览）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub version: u32,
+    pub exported_at: String,
+    pub counts: BackupCounts,
+}
+
+/// 恢复结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RestoreSummary {
+    pub imported: BackupCounts,
+    /// 合并时因已存在而跳过的条数
+    pub skipped: BackupCounts,
+}
+
+/// 本地快照的触发原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotReason {
+    /// 启动时（没有待执行的数据库迁移）
+    Startup,
+    /// 每日定时
+    Daily,
+    /// 用户手动创建
+    Manual,
+    /// 导入账单之前
+    BeforeImport,
+    /// 恢复备份或快照之前
+    BeforeRestore,
+    /// 批量应用分类规则之前
+    BeforeRuleChanges,
+    /// 删除分类（连同其记录）之前
+    BeforeDelete,
+    /// 合并重复交易之前
+    BeforeMerge,
+    /// 迁移旧数据或升级数据库结构之前
+    BeforeMigration,
+}
+
+impl SnapshotReason {
+    pub const ALL: [SnapshotReason; 9] = [
+        SnapshotReason::Startup,
+        SnapshotReason::Daily,
+        SnapshotReason::Manual,
+        SnapshotReason::BeforeImport,
+        SnapshotReason::BeforeRestore,
+        SnapshotReason::BeforeRuleChanges,
+        SnapshotReason::BeforeDelete,
+        SnapshotReason::BeforeMerge,
+        SnapshotReason::BeforeMigration,
+    ];
+
+    /// 快照文件名中的标记
+    pub fn key(&self) -> &'static str {
+        match self {
+            SnapshotReason::Startup => "startup",
+            SnapshotReason::Daily => "daily",
+            SnapshotReason::Manual => "manual",
+            SnapshotReason::BeforeImport => "before-import",
+            SnapshotReason::BeforeRestore => "before-restore",
+            SnapshotReason::BeforeRuleChanges => "before-rules",
+            SnapshotReason::BeforeDelete => "before-delete",
+            SnapshotReason::BeforeMerge => "before-merge",
+            SnapshotReason::BeforeMigration => "before-migration",
+        }
+    }
+}
+
+/// 一个本地快照（`backups/` 目录下的数据库副本）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub file_name: String,
+    pub reason: SnapshotReason,
+    /// 本地时间，YYYY-MM-DD HH:MM:SS
+    pub created_at: String,
+    /// 文件大小（字节）
+    pub size: u64,
+}
+
+/// 快照保留策略：三项各自保留的快照都不删除
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotPolicy {
+    /// 保留最近的 N 个
+    pub keep_last: u32,
+    /// 最近 N 天每天保留最新的一个
+    pub keep_daily: u32,
+    /// 最近 N 周每周保留最新的一个
+    pub keep_weekly: u32,
+}
+
+impl Default for SnapshotPolicy {
+    fn default() -> Self {
+        SnapshotPolicy {
+            keep_last: 5,
+            keep_daily: 7,
+            keep_weekly: 4,
+        }
+    }
+}
+
+/// 备份文件夹设置（只保存在本机，不写入数据库，也不进入备份）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupFolderSettings {
+    /// 文件夹路径，None 表示未启用
+    pub path: Option<String>,
+    /// 本设备在文件夹中保留的备份个数
+    pub keep: u32,
+    /// 是否设置了加密密码
+    pub encrypted: bool,
+}
+
+/// 备份文件夹中的一个备份
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderBackup {
+    pub file_name: String,
+    /// 写入该备份的设备
+    pub device_id: String,
+    /// 本地时间，YYYY-MM-DD HH:MM:SS
+    pub created_at: String,
+    /// 文件大小（字节）
+    pub size: u64,
+    /// 是否由本设备写入
+    pub from_this_device: bool,
+}
+
+/// 保存的查询（智能视图）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmartView {
+    pub id: i64,
+    pub name: String,
+    pub query: String,
+    pub created_at: String,
+}
+
+/// 账单导入来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    /// 支付宝账单 CSV（GBK 编码）
+    Alipay,
+    /// 微信支付账单（xlsx 或 CSV）
+    Wechat,
+    /// 银行/信用卡 OFX 对账单
+    Ofx,
+    /// 银行/信用卡 QIF 对账单
+    Qif,
+    /// Beancount 账本
+    Beancount,
+    /// ledger-cli 账本
+    Ledger,
+    /// 其他 CSV（按 `CsvMapping` 指定列）
+    Csv,
+}
+
+impl ImportSource {
+    /// 存入 `imported_lines.source` 的名称
+    pub fn key(&self) -> &'static str {
+        match self {
+            ImportSource::Alipay => "alipay",
+            ImportSource::Wechat => "wechat",
+            ImportSource::Ofx => "ofx",
+            ImportSource::Qif => "qif",
+            ImportSource::Beancount => "beancount",
+            ImportSource::Ledger => "ledger",
+            ImportSource::Csv => "csv",
+        }
+    }
+}
+
+/// 已导入的账单行（备份用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedLine {
+    /// `ImportSource::key`
+    pub source: String,
+    pub account: String,
+    pub external_id: String,
+    /// 导入成的交易；交易已删除时为空
+    pub transaction_id: Option<i64>,
+    pub imported_at: String,
+}
+
+/// 导入预览中的一行账单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRow {
+    /// 在账单文件中的行号（从 1 开始）
+    pub line: u64,
+    pub transaction_date: String,
+    /// 支出为负，收入为正
+    pub amount: f64,
+    pub note: Option<String>,
+    /// 按规则匹配到的分类
+    pub category_id: Option<i64>,
+    /// 文件中写明、但当前没有的分类名；`category_id` 为空时导入会新建该分类
+    pub category_name: Option<String>,
+    /// 账单自带的流水号（OFX FITID、支付宝交易订单号等），用于识别已导入的行
+    pub external_id: Option<String>,
+    /// 所属账户（用户填写，或 OFX 中的账号）
+    pub account: Option<String>,
+    /// 账本中疑似与该行重复的交易（日期相近、金额相同）
+    #[serde(default)]
+    pub duplicate_of: Option<String>,
+    /// 根据历史记录推荐的分类，可能性从高到低
+    #[serde(default)]
+    pub suggestions: Vec<CategorySuggestion>,
+    /// 默认不导入的原因（退款、交易关闭、已导入等），None 表示可以导入
+    pub skip_reason: Option<String>,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub imported: i64,
+}
+
+/// CSV 金额的正负约定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SignConvention {
+    /// 负数为支出，正数为收入（与本应用相同）
+    #[default]
+    Signed,
+    /// 正数为支出，负数为收入
+    Inverted,
+    /// 金额不带符号，由 `type_column` 中的“收入/支出”决定
+    TypeColumn,
+}
+
+/// 通用 CSV 导入的列映射（列号从 0 开始）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvMapping {
+    pub delimiter: char,
+    /// 表头之前要跳过的行数
+    #[serde(default)]
+    pub skip_rows: usize,
+    pub has_header: bool,
+    pub date_column: usize,
+    /// chrono 格式，如 `%Y-%m-%d` 或 `%Y/%m/%d %H:%M`
+    pub date_format: String,
+    pub amount_column: usize,
+    #[serde(default)]
+    pub sign: SignConvention,
+    pub type_column: Option<usize>,
+    pub category_column: Option<usize>,
+    pub note_column: Option<usize>,
+}
+
+/// 探测到的 CSV 文件结构，供设置列映射
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvSniff {
+    /// "UTF-8" 或 "GB18030"
+    pub encoding: String,
+    pub delimiter: char,
+    /// 文件开头的若干行
+    pub rows: Vec<Vec<String>>,
+}
+
+/// 保存的 CSV 列映射预设
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvPreset {
+    pub id: i64,
+    pub name: String,
+    pub mapping: CsvMapping,
+    pub created_at: String,
+}
+
+/// 一对疑似重复的交易
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicatePair {
+    /// 日期较早的一笔
+    pub first: TransactionWithCategory,
+    pub second: TransactionWithCategory,
+    /// 相似度（0-1）
+    pub score: f64,
+}
+
+/// 自动分类规则，按 `priority` 从小到大匹配，第一条命中的规则生效
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryRule {
+    pub id: i64,
+    pub name: String,
+    pub priority: i64,
+    pub enabled: bool,
+    /// 在备注/商户中查找的文字；为空时不限
+    pub pattern: Option<String>,
+    /// `pattern` 是正则表达式，否则按包含匹配（不区分大小写）
+    pub is_regex: bool,
+    /// 金额（绝对值）下限
+    pub min_amount: Option<f64>,
+    /// 金额（绝对值）上限
+    pub max_amount: Option<f64>,
+    /// 账户，只对导入的记录有效
+    pub account: Option<String>,
+    /// 命中后设置的分类
+    pub category_id: Option<i64>,
+    /// 命中后追加到备注的标签（不含 #）
+    pub tags: Vec<String>,
+    /// 命中后替换整条备注
+    pub note_rewrite: Option<String>,
+    pub created_at: String,
+}
+
+/// 新建或修改的自动分类规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewCategoryRule {
+    pub name: String,
+    pub enabled: bool,
+    /// 在备注/商户中查找的文字；为空时不限
+    pub pattern: Option<String>,
+    /// `pattern` 是正则表达式，否则按包含匹配（不区分大小写）
+    pub is_regex: bool,
+    /// 金额（绝对值）下限
+    pub min_amount: Option<f64>,
+    /// 金额（绝对值）上限
+    pub max_amount: Option<f64>,
+    /// 账户，只对导入的记录有效
+    pub account: Option<String>,
+    /// 命中后设置的分类
+    pub category_id: Option<i64>,
+    /// 命中后追加到备注的标签（不含 #）
+    pub tags: Vec<String>,
+    /// 命中后替换整条备注
+    pub note_rewrite: Option<String>,
+}
+
+/// 重新应用规则时，一笔交易将发生的变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleChange {
+    pub transaction_id: i64,
+    pub transaction_date: String,
+    pub amount: f64,
+    /// 命中的规则名称
+    pub rule_name: String,
+    pub old_category_id: i64,
+    pub new_category_id: i64,
+    pub old_note: Option<String>,
+    pub new_note: Option<String>,
+}
+
+/// 根据历史记录推荐的分类
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategorySuggestion {
+    pub category_id: i64,
+    /// 模型给出的概率（0~1）
+    pub probability: f64,
+}
@@ -0,0 +1,57 @@
+//! Filter query AST.
+
+use crate::models::TransactionKind;
+use chrono::NaiveDate;
+
+/// A parsed filter expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// All sub-expressions match (an empty list matches everything)
+    And(Vec<Expr>),
+    /// Any sub-expression matches
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    Condition(Condition),
+}
+
+/// Comparison against the signed transaction amount
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AmountOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+}
+
+impl AmountOp {
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            AmountOp::Lt => "<",
+            AmountOp::Le => "<=",
+            AmountOp::Gt => ">",
+            AmountOp::Ge => ">=",
+            AmountOp::Eq => "=",
+        }
+    }
+}
+
+/// A single filter condition
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// Category name, case-insensitive exact match
+    Category(String),
+    Amount(AmountOp, f64),
+    /// Inclusive signed amount range
+    AmountRange(f64, f64),
+    /// Date range `[start, end)`, either side may be open
+    Date {
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+    },
+    /// Note contains the text
+    Note(String),
+    /// Note carries `#tag`
+    Tag(String),
+    Kind(TransactionKind),
+}
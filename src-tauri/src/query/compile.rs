@@ -0,0 +1,151 @@
+//! Compile filter expressions to parameterized SQL.
+
+use super::ast::{Condition, Expr};
+use crate::models::TransactionKind;
+use crate::repository::transaction_repo::{escape_like, note_contains_clause};
+
+/// Compile an expression into a SQL condition over `transactions t` /
+/// `categories c` and its positional parameters
+pub fn compile(expr: &Expr) -> (String, Vec<libsql::Value>) {
+    let mut params = Vec::new();
+    let sql = compile_expr(expr, &mut params);
+    (sql, params)
+}
+
+fn compile_expr(expr: &Expr, params: &mut Vec<libsql::Value>) -> String {
+    match expr {
+        Expr::And(items) if items.is_empty() => "1 = 1".to_string(),
+        Expr::And(items) => join(items, " AND ", params),
+        Expr::Or(items) => join(items, " OR ", params),
+        // A NULL note makes LIKE yield NULL, which NOT would keep as NULL (no match)
+        Expr::Not(inner) => format!("NOT COALESCE(({}), 0)", compile_expr(inner, params)),
+        Expr::Condition(condition) => compile_condition(condition, params),
+    }
+}
+
+fn join(items: &[Expr], separator: &str, params: &mut Vec<libsql::Value>) -> String {
+    let parts: Vec<String> = items.iter().map(|item| compile_expr(item, params)).collect();
+    format!("({})", parts.join(separator))
+}
+
+fn compile_condition(condition: &Condition, params: &mut Vec<libsql::Value>) -> String {
+    match condition {
+        Condition::Category(name) => {
+            params.push(libsql::Value::Text(name.clone()));
+            "c.name = ? COLLATE NOCASE".to_string()
+        }
+        Condition::Amount(op, value) => {
+            params.push(libsql::Value::Real(*value));
+            format!("t.amount {} ?", op.as_sql())
+        }
+        Condition::AmountRange(min, max) => {
+            params.push(libsql::Value::Real(*min));
+            params.push(libsql::Value::Real(*max));
+            "t.amount BETWEEN ? AND ?".to_string()
+        }
+        Condition::Date { start, end } => {
+            let mut clauses = Vec::new();
+            if let Some(start) = start {
+                params.push(libsql::Value::Text(start.format("%Y-%m-%d").to_string()));
+                clauses.push("t.transaction_date >= ?");
+            }
+            if let Some(end) = end {
+                params.push(libsql::Value::Text(end.format("%Y-%m-%d").to_string()));
+                clauses.push("t.transaction_date < ?");
+            }
+            format!("({})", clauses.join(" AND "))
+        }
+        Condition::Note(text) => {
+            let (clause, param) = note_contains_clause(text);
+            params.push(param);
+            clause
+        }
+        Condition::Tag(tag) => {
+            // Put a space before every '#' so "午饭#出差#报销" matches "% #出差 %"
+            params.push(libsql::Value::Text(format!("% #{} %", escape_like(tag))));
+            "(' ' || REPLACE(COALESCE(t.note, ''), '#', ' #') || ' ') LIKE ? ESCAPE '\\'".to_string()
+        }
+        Condition::Kind(TransactionKind::Income) => "t.amount >= 0".to_string(),
+        Condition::Kind(TransactionKind::Expense) => "t.amount < 0".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::parse;
+    use libsql::Value;
+
+    fn compile_query(input: &str) -> (String, Vec<Value>) {
+        compile(&parse(input).unwrap())
+    }
+
+    fn text(value: &str) -> Value {
+        Value::Text(value.to_string())
+    }
+
+    #[test]
+    fn empty_query() {
+        assert_eq!(compile_query(""), ("1 = 1".to_string(), Vec::new()));
+    }
+
+    #[test]
+    fn conditions() {
+        assert_eq!(compile_query("cat:食物"), ("c.name = ? COLLATE NOCASE".to_string(), vec![text("食物")]));
+        assert_eq!(compile_query("amt<-50"), ("t.amount < ?".to_string(), vec![Value::Real(-50.0)]));
+        assert_eq!(
+            compile_query("amt:-100..-50"),
+            ("t.amount BETWEEN ? AND ?".to_string(), vec![Value::Real(-100.0), Value::Real(-50.0)])
+        );
+        assert_eq!(compile_query("type:income"), ("t.amount >= 0".to_string(), Vec::new()));
+        assert_eq!(compile_query("type:expense"), ("t.amount < 0".to_string(), Vec::new()));
+    }
+
+    #[test]
+    fn date_ranges_are_half_open() {
+        assert_eq!(
+            compile_query("date:2024-03..2024-05"),
+            (
+                "(t.transaction_date >= ? AND t.transaction_date < ?)".to_string(),
+                vec![text("2024-03-01"), text("2024-06-01")]
+            )
+        );
+        assert_eq!(
+            compile_query("date<=2024-03-15"),
+            ("(t.transaction_date < ?)".to_string(), vec![text("2024-03-16")])
+        );
+    }
+
+    #[test]
+    fn notes_use_fts_or_escaped_like() {
+        // Quotes cannot be typed into a term, but are doubled inside the phrase
+        let (_, params) = compile(&Expr::Condition(Condition::Note(r#"say "hi""#.to_string())));
+        assert_eq!(params, vec![text(r#""say ""hi""""#)]);
+        let (sql, params) = compile_query("外卖单");
+        assert_eq!(sql, "t.id IN (SELECT rowid FROM transactions_fts WHERE transactions_fts MATCH ?)");
+        assert_eq!(params, vec![text("\"外卖单\"")]);
+
+        let (sql, params) = compile_query("5%");
+        assert_eq!(sql, "t.note LIKE ? ESCAPE '\\'");
+        assert_eq!(params, vec![text("%5\\%%")]);
+        assert_eq!(compile_query("a_").1, vec![text("%a\\_%")]);
+    }
+
+    #[test]
+    fn tags_are_escaped() {
+        let (sql, params) = compile_query("#100%");
+        assert_eq!(sql, "(' ' || REPLACE(COALESCE(t.note, ''), '#', ' #') || ' ') LIKE ? ESCAPE '\\'");
+        assert_eq!(params, vec![text("% #100\\% %")]);
+    }
+
+    #[test]
+    fn boolean_structure_and_parameter_order() {
+        let (sql, params) = compile_query("cat:食物 (amt<-50 OR -#报销)");
+        assert_eq!(
+            sql,
+            "(c.name = ? COLLATE NOCASE AND (t.amount < ? OR NOT COALESCE((\
+             (' ' || REPLACE(COALESCE(t.note, ''), '#', ' #') || ' ') LIKE ? ESCAPE '\\'), 0)))"
+        );
+        assert_eq!(params, vec![text("食物"), Value::Real(-50.0), text("% #报销 %")]);
+    }
+}
@@ -0,0 +1,26 @@
+//! Filter Query Language
+//!
+//! A compact search syntax typed into one box, e.g.
+//! `cat:食物 amt<-50 date:2024-03..2024-05 note:外卖 #出差`.
+//!
+//! Queries are parsed into a typed [`Expr`] AST and compiled to a
+//! parameterized condition over the `transactions t` / `categories c` join
+//! used by `transaction_repo`.
+//!
+//! Syntax:
+//! - `cat:食物` / `cat:食物,交通` — category name (any of)
+//! - `amt<-50`, `amt>=100`, `amt=12.5`, `amt:-100..-50` — signed amount (expenses are negative)
+//! - `date:2024`, `date:2024-03`, `date:2024-03..2024-05`, `date>=2024-03-15` — dates, open ranges allowed
+//! - `note:外卖` or a bare word — note contains the text
+//! - `#出差` or `tag:出差` — note carries the hashtag
+//! - `type:expense` / `type:income` (also `支出` / `收入`)
+//! - terms are combined with AND; `OR`, `-term` (NOT) and parentheses are supported
+//! - double quotes keep spaces in a value: `note:"午饭 外卖"`
+
+mod ast;
+mod compile;
+mod parser;
+
+pub use ast::{AmountOp, Condition, Expr};
+pub use compile::compile;
+pub use parser::{parse, QueryError};
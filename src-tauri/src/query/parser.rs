@@ -0,0 +1,537 @@
+//! Lexer and recursive-descent parser of the filter query language.
+//!
+//! Grammar (terms separated by whitespace):
+//!
+//! ```text
+//! query   := or
+//! or      := and ( "OR" and )*
+//! and     := unary+
+//! unary   := "-" unary | "(" or ")" | term
+//! ```
+
+use super::ast::{AmountOp, Condition, Expr};
+use crate::models::TransactionKind;
+use chrono::NaiveDate;
+use std::fmt;
+
+/// A parse error and the (1-based) character position it refers to
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl QueryError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "查询语法错误（第 {} 个字符）: {}", self.position, self.message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    LParen,
+    RParen,
+    Or,
+    Not,
+    Term(String),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+/// Split the input into tokens, handling quotes, parentheses and `-` negation
+fn tokenize(input: &str) -> Result<Vec<Token>, QueryError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let position = i + 1;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let kind = match c {
+            '(' => {
+                i += 1;
+                TokenKind::LParen
+            }
+            ')' => {
+                i += 1;
+                TokenKind::RParen
+            }
+            '|' => {
+                i += 1;
+                TokenKind::Or
+            }
+            '-' if chars.get(i + 1).is_some_and(|next| !next.is_whitespace()) => {
+                i += 1;
+                TokenKind::Not
+            }
+            _ => {
+                let mut text = String::new();
+                let mut quoted = false;
+                let mut in_quote = false;
+                while i < chars.len() {
+                    let c = chars[i];
+                    if in_quote {
+                        if c == '"' {
+                            in_quote = false;
+                        } else {
+                            text.push(c);
+                        }
+                    } else if c == '"' {
+                        in_quote = true;
+                        quoted = true;
+                    } else if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    } else {
+                        text.push(c);
+                    }
+                    i += 1;
+                }
+
+                if in_quote {
+                    return Err(QueryError::new(position, "引号没有闭合"));
+                }
+                if text.is_empty() {
+                    continue;
+                }
+                if !quoted && text.eq_ignore_ascii_case("or") {
+                    TokenKind::Or
+                } else {
+                    TokenKind::Term(text)
+                }
+            }
+        };
+
+        tokens.push(Token { kind, position });
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    end_position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, QueryError> {
+        let mut branches = vec![self.parse_and()?];
+        while matches!(self.peek().map(|t| &t.kind), Some(TokenKind::Or)) {
+            self.pos += 1;
+            branches.push(self.parse_and()?);
+        }
+        Ok(if branches.len() == 1 {
+            branches.remove(0)
+        } else {
+            Expr::Or(branches)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, QueryError> {
+        let mut terms = Vec::new();
+        while let Some(token) = self.peek() {
+            if matches!(token.kind, TokenKind::Or | TokenKind::RParen) {
+                break;
+            }
+            terms.push(self.parse_unary()?);
+        }
+
+        if terms.is_empty() {
+            let position = self.peek().map(|t| t.position).unwrap_or(self.end_position);
+            return Err(QueryError::new(position, "缺少搜索条件"));
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Expr::And(terms)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, QueryError> {
+        let Some(token) = self.next() else {
+            return Err(QueryError::new(self.end_position, "缺少搜索条件"));
+        };
+
+        match token.kind {
+            TokenKind::Not => {
+                if matches!(self.peek().map(|t| &t.kind), None | Some(TokenKind::Or) | Some(TokenKind::RParen)) {
+                    return Err(QueryError::new(token.position, "“-” 后面缺少条件"));
+                }
+                Ok(Expr::Not(Box::new(self.parse_unary()?)))
+            }
+            TokenKind::LParen => {
+                let inner = self.parse_or()?;
+                match self.next() {
+                    Some(Token { kind: TokenKind::RParen, .. }) => Ok(inner),
+                    _ => Err(QueryError::new(token.position, "括号没有闭合")),
+                }
+            }
+            TokenKind::Term(text) => parse_term(&text, token.position),
+            TokenKind::Or => Err(QueryError::new(token.position, "OR 前面缺少条件")),
+            TokenKind::RParen => Err(QueryError::new(token.position, "多余的右括号")),
+        }
+    }
+}
+
+/// Parse a query string into an expression; an empty query matches everything
+pub fn parse(input: &str) -> Result<Expr, QueryError> {
+    let tokens = tokenize(input)?;
+    if tokens.is_empty() {
+        return Ok(Expr::And(Vec::new()));
+    }
+
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        end_position: input.chars().count() + 1,
+    };
+    let expr = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+        return Err(QueryError::new(token.position, "多余的右括号"));
+    }
+    Ok(expr)
+}
+
+/// Comparison operators recognised after a field name, longest first
+const OPERATORS: [&str; 6] = ["<=", ">=", "<", ">", "=", ":"];
+
+/// Parse one term such as `cat:食物`, `amt<-50`, `#出差` or a bare word
+fn parse_term(text: &str, position: usize) -> Result<Expr, QueryError> {
+    if let Some(tag) = text.strip_prefix('#') {
+        return parse_tag(tag, position).map(Expr::Condition);
+    }
+
+    // Field names are ASCII, so the char count equals the byte length
+    let field_len = text.chars().take_while(|c| c.is_ascii_alphabetic()).count();
+    let (field, rest) = text.split_at(field_len);
+    let operator = OPERATORS.iter().find(|op| rest.starts_with(**op)).copied();
+
+    let (Some(operator), false) = (operator, field.is_empty()) else {
+        return Ok(Expr::Condition(Condition::Note(text.to_string())));
+    };
+
+    let value = &rest[operator.len()..];
+    let value_position = position + field_len + operator.len();
+    let field = field.to_ascii_lowercase();
+    if value.is_empty() {
+        return Err(QueryError::new(value_position, format!("字段 {} 缺少值", field)));
+    }
+
+    let expect_colon = |allowed: &[&str]| {
+        if allowed.contains(&operator) {
+            Ok(())
+        } else {
+            Err(QueryError::new(
+                position + field_len,
+                format!("字段 {} 不支持 “{}”，请使用 “{}”", field, operator, allowed.join("” 或 “")),
+            ))
+        }
+    };
+
+    match field.as_str() {
+        "cat" | "category" => {
+            expect_colon(&[":", "="])?;
+            let names: Vec<&str> = value.split(',').map(str::trim).filter(|n| !n.is_empty()).collect();
+            if names.is_empty() {
+                return Err(QueryError::new(value_position, "缺少分类名"));
+            }
+            let mut conditions: Vec<Expr> = names
+                .into_iter()
+                .map(|name| Expr::Condition(Condition::Category(name.to_string())))
+                .collect();
+            Ok(if conditions.len() == 1 {
+                conditions.remove(0)
+            } else {
+                Expr::Or(conditions)
+            })
+        }
+        "amt" | "amount" => parse_amount(operator, value, value_position).map(Expr::Condition),
+        "date" => parse_date(operator, value, value_position).map(Expr::Condition),
+        "note" => {
+            expect_colon(&[":", "="])?;
+            Ok(Expr::Condition(Condition::Note(value.to_string())))
+        }
+        "tag" => {
+            expect_colon(&[":", "="])?;
+            parse_tag(value.trim_start_matches('#'), value_position).map(Expr::Condition)
+        }
+        "type" => {
+            expect_colon(&[":", "="])?;
+            match value.to_lowercase().as_str() {
+                "income" | "in" | "收入" => Ok(Expr::Condition(Condition::Kind(TransactionKind::Income))),
+                "expense" | "out" | "支出" => Ok(Expr::Condition(Condition::Kind(TransactionKind::Expense))),
+                _ => Err(QueryError::new(
+                    value_position,
+                    format!("未知类型 “{}”，可用: income / expense（收入 / 支出）", value),
+                )),
+            }
+        }
+        _ => Err(QueryError::new(
+            position,
+            format!("未知字段 “{}”，可用字段: cat, amt, date, note, tag, type", field),
+        )),
+    }
+}
+
+fn parse_tag(tag: &str, position: usize) -> Result<Condition, QueryError> {
+    if tag.is_empty() || tag.contains('#') {
+        return Err(QueryError::new(position, "标签格式错误，应为 #标签"));
+    }
+    Ok(Condition::Tag(tag.to_string()))
+}
+
+fn parse_number(value: &str, position: usize) -> Result<f64, QueryError> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| QueryError::new(position, format!("“{}” 不是有效的金额", value)))
+}
+
+/// Parse `amt<-50`, `amt=12`, `amt:-100..-50`, `amt:..-50`
+fn parse_amount(operator: &str, value: &str, position: usize) -> Result<Condition, QueryError> {
+    let op = match operator {
+        "<" => AmountOp::Lt,
+        "<=" => AmountOp::Le,
+        ">" => AmountOp::Gt,
+        ">=" => AmountOp::Ge,
+        _ => {
+            let Some((min, max)) = value.split_once("..") else {
+                return Ok(Condition::Amount(AmountOp::Eq, parse_number(value, position)?));
+            };
+            let max_position = position + min.chars().count() + 2;
+            return match (min.is_empty(), max.is_empty()) {
+                (true, true) => Err(QueryError::new(position, "金额范围缺少上下限")),
+                (false, true) => Ok(Condition::Amount(AmountOp::Ge, parse_number(min, position)?)),
+                (true, false) => Ok(Condition::Amount(AmountOp::Le, parse_number(max, max_position)?)),
+                (false, false) => {
+                    let min = parse_number(min, position)?;
+                    let max = parse_number(max, max_position)?;
+                    if min > max {
+                        return Err(QueryError::new(position, "金额范围的下限大于上限"));
+                    }
+                    Ok(Condition::AmountRange(min, max))
+                }
+            };
+        }
+    };
+    Ok(Condition::Amount(op, parse_number(value, position)?))
+}
+
+/// Parse `YYYY`, `YYYY-MM` or `YYYY-MM-DD` into the `[start, end)` range it covers
+fn parse_date_bound(value: &str, position: usize) -> Result<(NaiveDate, NaiveDate), QueryError> {
+    let error = || QueryError::new(position, format!("“{}” 不是有效的日期，应为 2024、2024-03 或 2024-03-15", value));
+    let parts: Vec<&str> = value.split('-').collect();
+    let numbers: Vec<u32> = parts
+        .iter()
+        .map(|p| p.parse::<u32>().map_err(|_| error()))
+        .collect::<Result<_, _>>()?;
+    if parts[0].len() != 4 {
+        return Err(error());
+    }
+    let year = numbers[0] as i32;
+
+    let range = match numbers[..] {
+        [_] => NaiveDate::from_ymd_opt(year, 1, 1).zip(NaiveDate::from_ymd_opt(year + 1, 1, 1)),
+        [_, month] => {
+            let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+            NaiveDate::from_ymd_opt(year, month, 1).zip(NaiveDate::from_ymd_opt(next_year, next_month, 1))
+        }
+        [_, month, day] => NaiveDate::from_ymd_opt(year, month, day).and_then(|d| Some((d, d.succ_opt()?))),
+        _ => None,
+    };
+    range.ok_or_else(error)
+}
+
+/// Parse `date:2024-03`, `date:2024-03..2024-05`, `date>=2024-03-15`, ...
+fn parse_date(operator: &str, value: &str, position: usize) -> Result<Condition, QueryError> {
+    let (start, end) = match operator {
+        "<" => (None, Some(parse_date_bound(value, position)?.0)),
+        "<=" => (None, Some(parse_date_bound(value, position)?.1)),
+        ">" => (Some(parse_date_bound(value, position)?.1), None),
+        ">=" => (Some(parse_date_bound(value, position)?.0), None),
+        _ => match value.split_once("..") {
+            Some((from, to)) => {
+                if from.is_empty() && to.is_empty() {
+                    return Err(QueryError::new(position, "日期范围缺少起止日期"));
+                }
+                let to_position = position + from.chars().count() + 2;
+                let start = if from.is_empty() { None } else { Some(parse_date_bound(from, position)?.0) };
+                let end = if to.is_empty() { None } else { Some(parse_date_bound(to, to_position)?.1) };
+                if let (Some(start), Some(end)) = (start, end) {
+                    if start >= end {
+                        return Err(QueryError::new(position, "日期范围的开始晚于结束"));
+                    }
+                }
+                (start, end)
+            }
+            None => {
+                let (start, end) = parse_date_bound(value, position)?;
+                (Some(start), Some(end))
+            }
+        },
+    };
+    Ok(Condition::Date { start, end })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cond(condition: Condition) -> Expr {
+        Expr::Condition(condition)
+    }
+
+    fn note(text: &str) -> Expr {
+        cond(Condition::Note(text.to_string()))
+    }
+
+    fn date(start: Option<&str>, end: Option<&str>) -> Expr {
+        let day = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        cond(Condition::Date {
+            start: start.map(day),
+            end: end.map(day),
+        })
+    }
+
+    fn error_at(input: &str) -> usize {
+        parse(input).unwrap_err().position
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert_eq!(parse("").unwrap(), Expr::And(Vec::new()));
+        assert_eq!(parse("   ").unwrap(), Expr::And(Vec::new()));
+    }
+
+    #[test]
+    fn fields() {
+        assert_eq!(parse("cat:食物").unwrap(), cond(Condition::Category("食物".into())));
+        assert_eq!(
+            parse("category=食物,交通").unwrap(),
+            Expr::Or(vec![
+                cond(Condition::Category("食物".into())),
+                cond(Condition::Category("交通".into())),
+            ])
+        );
+        assert_eq!(parse("note:外卖").unwrap(), note("外卖"));
+        assert_eq!(parse("外卖").unwrap(), note("外卖"));
+        assert_eq!(parse("#出差").unwrap(), cond(Condition::Tag("出差".into())));
+        assert_eq!(parse("tag:#出差").unwrap(), cond(Condition::Tag("出差".into())));
+        assert_eq!(parse("type:支出").unwrap(), cond(Condition::Kind(TransactionKind::Expense)));
+        assert_eq!(parse("TYPE:Income").unwrap(), cond(Condition::Kind(TransactionKind::Income)));
+    }
+
+    #[test]
+    fn amounts() {
+        assert_eq!(parse("amt<-50").unwrap(), cond(Condition::Amount(AmountOp::Lt, -50.0)));
+        assert_eq!(parse("amt>=100").unwrap(), cond(Condition::Amount(AmountOp::Ge, 100.0)));
+        assert_eq!(parse("amount=12.5").unwrap(), cond(Condition::Amount(AmountOp::Eq, 12.5)));
+        assert_eq!(parse("amt:-100..-50").unwrap(), cond(Condition::AmountRange(-100.0, -50.0)));
+        assert_eq!(parse("amt:..-50").unwrap(), cond(Condition::Amount(AmountOp::Le, -50.0)));
+        assert_eq!(parse("amt:10..").unwrap(), cond(Condition::Amount(AmountOp::Ge, 10.0)));
+    }
+
+    #[test]
+    fn date_ranges() {
+        assert_eq!(parse("date:2024").unwrap(), date(Some("2024-01-01"), Some("2025-01-01")));
+        assert_eq!(parse("date:2024-12").unwrap(), date(Some("2024-12-01"), Some("2025-01-01")));
+        assert_eq!(parse("date:2024-02-29").unwrap(), date(Some("2024-02-29"), Some("2024-03-01")));
+        assert_eq!(
+            parse("date:2024-03..2024-05").unwrap(),
+            date(Some("2024-03-01"), Some("2024-06-01"))
+        );
+        assert_eq!(parse("date:..2024-05").unwrap(), date(None, Some("2024-06-01")));
+        assert_eq!(parse("date:2024-03..").unwrap(), date(Some("2024-03-01"), None));
+        assert_eq!(parse("date>=2024-03-15").unwrap(), date(Some("2024-03-15"), None));
+        assert_eq!(parse("date>2024-03").unwrap(), date(Some("2024-04-01"), None));
+        assert_eq!(parse("date<2024-03").unwrap(), date(None, Some("2024-03-01")));
+        assert_eq!(parse("date<=2024-03").unwrap(), date(None, Some("2024-04-01")));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            parse("a b OR c").unwrap(),
+            Expr::Or(vec![Expr::And(vec![note("a"), note("b")]), note("c")])
+        );
+        assert_eq!(
+            parse("a (b | c)").unwrap(),
+            Expr::And(vec![note("a"), Expr::Or(vec![note("b"), note("c")])])
+        );
+    }
+
+    #[test]
+    fn negation() {
+        assert_eq!(parse("-外卖").unwrap(), Expr::Not(Box::new(note("外卖"))));
+        assert_eq!(
+            parse("-(a OR b)").unwrap(),
+            Expr::Not(Box::new(Expr::Or(vec![note("a"), note("b")])))
+        );
+        assert_eq!(parse("--a").unwrap(), Expr::Not(Box::new(Expr::Not(Box::new(note("a"))))));
+        // A minus on its own or inside a value is not negation
+        assert_eq!(parse("- a").unwrap(), Expr::And(vec![note("-"), note("a")]));
+        assert_eq!(parse("amt<-5").unwrap(), cond(Condition::Amount(AmountOp::Lt, -5.0)));
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(parse(r#"note:"午饭 外卖""#).unwrap(), note("午饭 外卖"));
+        assert_eq!(parse(r#""a (b)""#).unwrap(), note("a (b)"));
+        // Quoted OR is a word, not the operator
+        assert_eq!(parse(r#""OR""#).unwrap(), note("OR"));
+        assert_eq!(parse(r#"a or b"#).unwrap(), Expr::Or(vec![note("a"), note("b")]));
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        assert_eq!(error_at(r#"note:"午饭"#), 1);
+        assert_eq!(error_at("a (b"), 3);
+        assert_eq!(error_at("a b)"), 4);
+        assert_eq!(error_at("OR a"), 1);
+        assert_eq!(error_at("a OR"), 5);
+        assert_eq!(error_at("a -)"), 3);
+        assert_eq!(error_at("foo:bar"), 1);
+        assert_eq!(error_at("cat:"), 5);
+        assert_eq!(error_at("cat<食物"), 4);
+        assert_eq!(error_at("amt<abc"), 5);
+        assert_eq!(error_at("amt:..-"), 7);
+        assert_eq!(error_at("amt:-50..-100"), 5);
+        assert_eq!(error_at("date:2024-13"), 6);
+        assert_eq!(error_at("date:24-03"), 6);
+        assert_eq!(error_at("date:2024-05..2024-03"), 6);
+        assert_eq!(error_at("type:loan"), 6);
+        assert_eq!(error_at("#a#b"), 1);
+        assert!(parse("()").is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! Import Repository
+//!
+//! Statement lines already imported (`imported_lines`), so importing the same
+//! bill twice skips them, and the column mapping presets of the CSV import.
+
+use crate::models::{CsvMapping, CsvPreset, ImportedLine};
+use libsql::Connection;
+use std::collections::HashSet;
+
+/// Get the (account, external id) pairs already imported from a source
+pub async fn get_imported_lines(conn: &Connection, source: &str) -> Result<HashSet<(String, String)>, String> {
+    let mut rows = conn
+        .query(
+            "SELECT account, external_id FROM imported_lines WHERE source = ?",
+            libsql::params![source],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut lines = HashSet::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        lines.insert((
+            row.get::<String>(0).map_err(|e| e.to_string())?,
+            row.get::<String>(1).map_err(|e| e.to_string())?,
+        ));
+    }
+    Ok(lines)
+}
+
+/// Record that a statement line was imported as a transaction
+pub async fn record_imported_line(
+    conn: &Connection,
+    source: &str,
+    account: &str,
+    external_id: &str,
+    transaction_id: i64,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO imported_lines (source, account, external_id, transaction_id) VALUES (?, ?, ?, ?)",
+        libsql::params![source, account, external_id, transaction_id],
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Get all imported statement lines, for backups. Lines whose transaction
+/// was deleted since keep a null transaction id.
+pub async fn get_all_imported_lines(conn: &Connection) -> Result<Vec<ImportedLine>, String> {
+    let mut rows = conn
+        .query(
+            "SELECT l.source, l.account, l.external_id, t.id, l.imported_at
+             FROM imported_lines l
+             LEFT JOIN transactions t ON t.id = l.transaction_id
+             ORDER BY l.id",
+            (),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut lines = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        lines.push(ImportedLine {
+            source: row.get(0).map_err(|e| e.to_string())?,
+            account: row.get(1).map_err(|e| e.to_string())?,
+            external_id: row.get(2).map_err(|e| e.to_string())?,
+            transaction_id: row.get(3).ok(),
+            imported_at: row.get(4).map_err(|e| e.to_string())?,
+        });
+    }
+    Ok(lines)
+}
+
+/// Get all CSV import presets ordered by name
+pub async fn get_csv_presets(conn: &Connection) -> Result<Vec<CsvPreset>, String> {
+    let mut rows = conn
+        .query("SELECT id, name, mapping, created_at FROM csv_import_presets ORDER BY name", ())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut presets = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        let mapping: String = row.get(2).map_err(|e| e.to_string())?;
+        presets.push(CsvPreset {
+            id: row.get(0).map_err(|e| e.to_string())?,
+            name: row.get(1).map_err(|e| e.to_string())?,
+            mapping: serde_json::from_str(&mapping).map_err(|e| e.to_string())?,
+            created_at: row.get(3).map_err(|e| e.to_string())?,
+        });
+    }
+    Ok(presets)
+}
+
+/// Save a preset, replacing the mapping of an existing preset with the same name
+pub async fn save_csv_preset(conn: &Connection, name: &str, mapping: &CsvMapping) -> Result<CsvPreset, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Preset name cannot be empty".to_string());
+    }
+    let json = serde_json::to_string(mapping).map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO csv_import_presets (name, mapping) VALUES (?, ?)
+         ON CONFLICT(name) DO UPDATE SET mapping = excluded.mapping",
+        libsql::params![name, json],
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    get_csv_presets(conn)
+        .await?
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| "Preset not found after saving".to_string())
+}
+
+/// Delete a preset
+pub async fn delete_csv_preset(conn: &Connection, id: i64) -> Result<(), String> {
+    conn.execute("DELETE FROM csv_import_presets WHERE id = ?", libsql::params![id])
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -3,7 +3,8 @@
 //! Manages installment (分期) operations.
 
 use crate::db::DbState;
-use crate::models::{Installment, InstallmentDetail, InstallmentWithCategory, NewInstallment};
+use crate::models::{Installment, InstallmentDetail, InstallmentScheduleRow, InstallmentWithCategory, NewInstallment};
+use crate::repository::transaction_repo;
 use libsql::Connection;
 use chrono::Datelike;
 
@@ -158,6 +159,64 @@ pub async fn get_all_installments_with_category(
     Ok(installments)
 }
 
+/// Get all installment plans
+pub async fn get_all_installments(conn: &Connection) -> Result<Vec<Installment>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, category_id, total_amount, installment_count, start_date, note, created_at
+             FROM installments
+             ORDER BY id"
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut rows = stmt.query(()).await.map_err(|e| e.to_string())?;
+
+    let mut installments = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        installments.push(Installment {
+            id: row.get(0).map_err(|e| e.to_string())?,
+            category_id: row.get(1).map_err(|e| e.to_string())?,
+            total_amount: row.get(2).map_err(|e| e.to_string())?,
+            installment_count: row.get(3).map_err(|e| e.to_string())?,
+            start_date: row.get(4).map_err(|e| e.to_string())?,
+            note: row.get(5).ok(),
+            created_at: row.get(6).map_err(|e| e.to_string())?,
+        });
+    }
+
+    Ok(installments)
+}
+
+/// Get the details of every installment plan
+pub async fn get_all_installment_details(conn: &Connection) -> Result<Vec<InstallmentDetail>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, installment_id, sequence_number, amount, due_date, is_paid, paid_date
+             FROM installment_details
+             ORDER BY installment_id, sequence_number"
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut rows = stmt.query(()).await.map_err(|e| e.to_string())?;
+
+    let mut details = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        details.push(InstallmentDetail {
+            id: row.get(0).map_err(|e| e.to_string())?,
+            installment_id: row.get(1).map_err(|e| e.to_string())?,
+            sequence_number: row.get(2).map_err(|e| e.to_string())?,
+            amount: row.get(3).map_err(|e| e.to_string())?,
+            due_date: row.get(4).map_err(|e| e.to_string())?,
+            is_paid: row.get::<i32>(5).map_err(|e| e.to_string())? != 0,
+            paid_date: row.get(6).ok(),
+        });
+    }
+
+    Ok(details)
+}
+
 /// Get installment details by installment ID
 pub async fn get_installment_details(
     conn: &Connection,
@@ -194,6 +253,70 @@ pub async fn get_installment_details(
     Ok(details)
 }
 
+/// Get every installment payment due in a date range (both ends inclusive)
+///
+/// Used for exports; rows are ordered by due date, then plan and sequence.
+pub async fn get_installment_schedule(
+    conn: &Connection,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+    category_ids: &[i64],
+) -> Result<Vec<InstallmentScheduleRow>, String> {
+    let mut clauses = Vec::new();
+    let mut params = Vec::new();
+    if let Some(start) = start_date.filter(|d| !d.is_empty()) {
+        clauses.push("d.due_date >= ?".to_string());
+        params.push(libsql::Value::Text(start.to_string()));
+    }
+    if let Some(end) = end_date.filter(|d| !d.is_empty()) {
+        clauses.push("d.due_date < ?".to_string());
+        params.push(libsql::Value::Text(transaction_repo::next_day(end)?));
+    }
+    if !category_ids.is_empty() {
+        clauses.push(format!("i.category_id IN ({})", vec!["?"; category_ids.len()].join(", ")));
+        params.extend(category_ids.iter().map(|id| libsql::Value::Integer(*id)));
+    }
+    let where_sql = if clauses.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", clauses.join(" AND "))
+    };
+
+    let mut rows = conn
+        .query(
+            &format!(
+                "SELECT i.id, c.name, i.note, i.total_amount, i.installment_count,
+                        d.sequence_number, d.amount, d.due_date, d.is_paid, d.paid_date
+                 FROM installment_details d
+                 INNER JOIN installments i ON d.installment_id = i.id
+                 INNER JOIN categories c ON i.category_id = c.id{}
+                 ORDER BY d.due_date, i.id, d.sequence_number",
+                where_sql
+            ),
+            libsql::params_from_iter(params),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut schedule = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        schedule.push(InstallmentScheduleRow {
+            installment_id: row.get(0).map_err(|e| e.to_string())?,
+            category_name: row.get(1).map_err(|e| e.to_string())?,
+            note: row.get(2).ok(),
+            total_amount: row.get(3).map_err(|e| e.to_string())?,
+            installment_count: row.get(4).map_err(|e| e.to_string())?,
+            sequence_number: row.get(5).map_err(|e| e.to_string())?,
+            amount: row.get(6).map_err(|e| e.to_string())?,
+            due_date: row.get(7).map_err(|e| e.to_string())?,
+            is_paid: row.get::<i32>(8).map_err(|e| e.to_string())? != 0,
+            paid_date: row.get(9).ok(),
+        });
+    }
+
+    Ok(schedule)
+}
+
 /// Get due installments for a specific month
 pub async fn get_due_installments_by_month(
     conn: &Connection,
@@ -3,5 +3,12 @@
 //! Contains all database repository modules.
 
 pub mod category_repo;
+pub mod import_repo;
 pub mod installment_repo;
+pub mod recurring_bill_repo;
+pub mod report_repo;
+pub mod rule_repo;
+pub mod settings_repo;
+pub mod smart_view_repo;
+pub mod stats_repo;
 pub mod transaction_repo;
@@ -0,0 +1,81 @@
+//! Recurring Bill Repository
+//!
+//! Bills that come back every month or every few months (房租, 订阅). They are
+//! not booked automatically; the calendar export reminds the user of them.
+
+use crate::models::{NewRecurringBill, RecurringBill};
+use chrono::NaiveDate;
+use libsql::Connection;
+
+/// Get all recurring bills ordered by their first date
+pub async fn get_recurring_bills(conn: &Connection) -> Result<Vec<RecurringBill>, String> {
+    let mut rows = conn
+        .query(
+            "SELECT id, name, amount, interval_months, start_date, end_date, created_at
+             FROM recurring_bills
+             ORDER BY start_date, id",
+            (),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut bills = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        bills.push(RecurringBill {
+            id: row.get(0).map_err(|e| e.to_string())?,
+            name: row.get(1).map_err(|e| e.to_string())?,
+            amount: row.get(2).map_err(|e| e.to_string())?,
+            interval_months: row.get(3).map_err(|e| e.to_string())?,
+            start_date: row.get(4).map_err(|e| e.to_string())?,
+            end_date: row.get(5).ok(),
+            created_at: row.get(6).map_err(|e| e.to_string())?,
+        });
+    }
+
+    Ok(bills)
+}
+
+/// Create a recurring bill
+pub async fn create_recurring_bill(conn: &Connection, bill: NewRecurringBill) -> Result<RecurringBill, String> {
+    let name = bill.name.trim();
+    if name.is_empty() {
+        return Err("请填写账单名称".to_string());
+    }
+    if !(bill.amount.is_finite() && bill.amount > 0.0) {
+        return Err("金额必须大于 0".to_string());
+    }
+    if !(1..=12).contains(&bill.interval_months) {
+        return Err("重复间隔必须在 1 到 12 个月之间".to_string());
+    }
+    let start = NaiveDate::parse_from_str(&bill.start_date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date: {}", bill.start_date))?;
+    let end_date = bill.end_date.as_deref().map(str::trim).filter(|d| !d.is_empty());
+    if let Some(end_date) = end_date {
+        let end = NaiveDate::parse_from_str(end_date, "%Y-%m-%d").map_err(|_| format!("Invalid date: {}", end_date))?;
+        if end < start {
+            return Err("结束日期不能早于开始日期".to_string());
+        }
+    }
+
+    conn.execute(
+        "INSERT INTO recurring_bills (name, amount, interval_months, start_date, end_date) VALUES (?, ?, ?, ?, ?)",
+        libsql::params![name, bill.amount, bill.interval_months, bill.start_date.clone(), end_date],
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let id = conn.last_insert_rowid();
+    get_recurring_bills(conn)
+        .await?
+        .into_iter()
+        .find(|b| b.id == id)
+        .ok_or_else(|| "Recurring bill not found after saving".to_string())
+}
+
+/// Delete a recurring bill
+pub async fn delete_recurring_bill(conn: &Connection, id: i64) -> Result<(), String> {
+    conn.execute("DELETE FROM recurring_bills WHERE id = ?", libsql::params![id])
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
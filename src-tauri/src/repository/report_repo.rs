@@ -0,0 +1,242 @@
+//! Report Repository
+//!
+//! Builds the annual report (年度账单) from transactions, installments and the
+//! monthly baseline, and the monthly statement (月度对账单).
+
+use crate::models::{AnnualReport, AnnualReportMonth, InstallmentWithCategory, MonthlyStatement, TransactionWithCategory};
+use crate::repository::{installment_repo, settings_repo, stats_repo, transaction_repo};
+use chrono::Datelike;
+use libsql::Connection;
+
+/// Number of categories listed in the annual report
+const TOP_CATEGORY_COUNT: usize = 5;
+/// Number of single purchases listed in the annual report
+const LARGEST_EXPENSE_COUNT: i64 = 5;
+
+/// Get the largest single expenses in `[start_date, end_date)`
+async fn get_largest_expenses(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+    limit: i64,
+) -> Result<Vec<TransactionWithCategory>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.id, t.category_id, c.name, c.icon, t.amount, t.transaction_date, t.note, t.created_at
+             FROM transactions t
+             INNER JOIN categories c ON t.category_id = c.id
+             WHERE t.amount < 0 AND t.transaction_date >= ? AND t.transaction_date < ?
+             ORDER BY t.amount ASC, t.transaction_date ASC
+             LIMIT ?"
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut rows = stmt
+        .query(libsql::params![start_date, end_date, limit])
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut transactions = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        transactions.push(TransactionWithCategory {
+            id: row.get(0).map_err(|e| e.to_string())?,
+            category_id: row.get(1).map_err(|e| e.to_string())?,
+            category_name: row.get(2).map_err(|e| e.to_string())?,
+            category_icon: row.get(3).ok(),
+            amount: row.get(4).map_err(|e| e.to_string())?,
+            transaction_date: row.get(5).map_err(|e| e.to_string())?,
+            note: row.get(6).ok(),
+            created_at: row.get(7).map_err(|e| e.to_string())?,
+        });
+    }
+
+    Ok(transactions)
+}
+
+/// Get installments matching a condition on the plan (`i`) and its last due date (`last_due`)
+async fn query_installments(
+    conn: &Connection,
+    condition: &str,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<InstallmentWithCategory>, String> {
+    let sql = format!(
+        "SELECT i.id, i.category_id, c.name, c.icon, i.total_amount, i.installment_count,
+                i.start_date, i.note, i.created_at
+         FROM installments i
+         INNER JOIN categories c ON i.category_id = c.id
+         LEFT JOIN (
+            SELECT installment_id, MAX(due_date) AS due_date
+            FROM installment_details
+            GROUP BY installment_id
+         ) last_due ON last_due.installment_id = i.id
+         WHERE {condition}
+         ORDER BY i.start_date ASC"
+    );
+    let mut stmt = conn.prepare(&sql).await.map_err(|e| e.to_string())?;
+
+    let mut rows = stmt
+        .query(libsql::params![start_date, end_date])
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut installments = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        installments.push(InstallmentWithCategory {
+            id: row.get(0).map_err(|e| e.to_string())?,
+            category_id: row.get(1).map_err(|e| e.to_string())?,
+            category_name: row.get(2).map_err(|e| e.to_string())?,
+            category_icon: row.get(3).ok(),
+            total_amount: row.get(4).map_err(|e| e.to_string())?,
+            installment_count: row.get(5).map_err(|e| e.to_string())?,
+            start_date: row.get(6).map_err(|e| e.to_string())?,
+            note: row.get(7).ok(),
+            created_at: row.get(8).map_err(|e| e.to_string())?,
+        });
+    }
+
+    Ok(installments)
+}
+
+/// Build the annual report of a year
+///
+/// Expenses include installment payments due in the year. The budget hit rate
+/// compares each elapsed month against the baseline setting.
+pub async fn get_annual_report(conn: &Connection, year: i32) -> Result<AnnualReport, String> {
+    let start = format!("{:04}-01-01", year);
+    let end = format!("{:04}-01-01", year + 1);
+
+    let mut rows = conn
+        .query(
+            "SELECT
+                CAST(COALESCE(SUM(CASE WHEN amount >= 0 THEN amount ELSE 0 END), 0) AS REAL),
+                CAST(COALESCE(SUM(CASE WHEN amount < 0 THEN ABS(amount) ELSE 0 END), 0) AS REAL),
+                COUNT(*)
+             FROM transactions
+             WHERE transaction_date >= ? AND transaction_date < ?",
+            libsql::params![start.clone(), end.clone()],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let (total_income, transaction_expense, transaction_count) =
+        match rows.next().await.map_err(|e| e.to_string())? {
+            Some(row) => (
+                row.get::<f64>(0).map_err(|e| e.to_string())?,
+                row.get::<f64>(1).map_err(|e| e.to_string())?,
+                row.get::<i64>(2).map_err(|e| e.to_string())? as i32,
+            ),
+            None => (0.0, 0.0, 0),
+        };
+    drop(rows);
+
+    let installment_expense = stats_repo::get_installment_total(conn, &start, &end).await?;
+    let total_expense = transaction_expense + installment_expense;
+
+    let mut top_categories = stats_repo::get_category_expense_breakdown(conn, &start, &end).await?;
+    top_categories.truncate(TOP_CATEGORY_COUNT);
+
+    let largest_expenses = get_largest_expenses(conn, &start, &end, LARGEST_EXPENSE_COUNT).await?;
+
+    let installments_started =
+        query_installments(conn, "i.start_date >= ?1 AND i.start_date < ?2", &start, &end).await?;
+    let installments_finished =
+        query_installments(conn, "last_due.due_date >= ?1 AND last_due.due_date < ?2", &start, &end).await?;
+
+    // Only months that have already started count towards the hit rate
+    let today = chrono::Local::now().date_naive();
+    let elapsed_months = if year < today.year() {
+        12
+    } else if year == today.year() {
+        today.month() as i32
+    } else {
+        0
+    };
+
+    let budget = settings_repo::get_baseline(conn).await?;
+    let months: Vec<AnnualReportMonth> = stats_repo::get_monthly_trend(conn, year, 12, 12)
+        .await?
+        .into_iter()
+        .map(|p| {
+            let expense = p.total_expense + p.installment_expense;
+            AnnualReportMonth {
+                month: p.month,
+                total_income: p.total_income,
+                total_expense: expense,
+                within_budget: budget
+                    .filter(|_| p.month <= elapsed_months)
+                    .map(|b| expense <= b),
+            }
+        })
+        .collect();
+
+    let most_expensive_month = months
+        .iter()
+        .filter(|m| m.total_expense > 0.0)
+        .max_by(|a, b| {
+            a.total_expense
+                .partial_cmp(&b.total_expense)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .cloned();
+
+    let budget_months_counted = months.iter().filter(|m| m.within_budget.is_some()).count() as i32;
+    let budget_months_within = months.iter().filter(|m| m.within_budget == Some(true)).count() as i32;
+    let budget_hit_rate = if budget_months_counted > 0 {
+        Some(budget_months_within as f64 / budget_months_counted as f64 * 100.0)
+    } else {
+        None
+    };
+
+    Ok(AnnualReport {
+        year,
+        total_income,
+        total_expense,
+        installment_expense,
+        net_amount: total_income - total_expense,
+        transaction_count,
+        top_categories,
+        largest_expenses,
+        months,
+        most_expensive_month,
+        installments_started,
+        installments_finished,
+        budget,
+        budget_months_within,
+        budget_months_counted,
+        budget_hit_rate,
+    })
+}
+
+/// Build the statement of a month
+///
+/// Uses the same queries as the summary views: transaction totals, category
+/// expenses including installments due in the month, and the installment
+/// periods due in the month.
+pub async fn get_monthly_statement(conn: &Connection, year: i32, month: i32) -> Result<MonthlyStatement, String> {
+    if !(1..=12).contains(&month) {
+        return Err(format!("Invalid month: {}", month));
+    }
+    let (start, end) = stats_repo::month_range(year, month);
+    let last_day = chrono::NaiveDate::parse_from_str(&end, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .ok_or_else(|| format!("Invalid month: {}-{}", year, month))?;
+
+    let summary = transaction_repo::get_monthly_summary(conn, year, month).await?;
+    let installment_expense = stats_repo::get_installment_total(conn, &start, &end).await?;
+    let categories = stats_repo::get_category_expense_breakdown(conn, &start, &end).await?;
+    let mut transactions = transaction_repo::get_transactions_by_month(conn, year, month).await?;
+    transactions.reverse();
+    let installments = installment_repo::get_installment_schedule(conn, Some(&start), Some(&last_day), &[]).await?;
+
+    Ok(MonthlyStatement {
+        summary,
+        installment_expense,
+        categories,
+        transactions,
+        installments,
+    })
+}
@@ -0,0 +1,147 @@
+//! Category Rule Repository
+//!
+//! Manages the auto-categorization rules (自动分类规则). Tags are stored as
+//! one space-separated string.
+
+use crate::models::{CategoryRule, NewCategoryRule};
+use libsql::Connection;
+
+const RULE_COLUMNS: &str = "id, name, priority, enabled, pattern, is_regex, min_amount, max_amount, account,
+     category_id, tags, note_rewrite, created_at";
+
+/// Get all rules in matching order
+pub async fn get_rules(conn: &Connection) -> Result<Vec<CategoryRule>, String> {
+    let mut rows = conn
+        .query(
+            &format!("SELECT {} FROM category_rules ORDER BY priority, id", RULE_COLUMNS),
+            (),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut rules = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        let tags: String = row.get(10).unwrap_or_default();
+        rules.push(CategoryRule {
+            id: row.get(0).map_err(|e| e.to_string())?,
+            name: row.get(1).map_err(|e| e.to_string())?,
+            priority: row.get(2).map_err(|e| e.to_string())?,
+            enabled: row.get::<i64>(3).map_err(|e| e.to_string())? != 0,
+            pattern: row.get(4).ok(),
+            is_regex: row.get::<i64>(5).map_err(|e| e.to_string())? != 0,
+            min_amount: row.get(6).ok(),
+            max_amount: row.get(7).ok(),
+            account: row.get(8).ok(),
+            category_id: row.get(9).ok(),
+            tags: tags.split_whitespace().map(str::to_string).collect(),
+            note_rewrite: row.get(11).ok(),
+            created_at: row.get(12).map_err(|e| e.to_string())?,
+        });
+    }
+    Ok(rules)
+}
+
+/// Check a rule before saving it
+fn validate(rule: &NewCategoryRule) -> Result<(), String> {
+    if rule.name.trim().is_empty() {
+        return Err("规则名称不能为空".to_string());
+    }
+    if rule.is_regex {
+        if let Some(pattern) = &rule.pattern {
+            regex::Regex::new(pattern).map_err(|e| format!("正则表达式无效: {}", e))?;
+        }
+    }
+    if let (Some(min), Some(max)) = (rule.min_amount, rule.max_amount) {
+        if min > max {
+            return Err("金额下限不能大于上限".to_string());
+        }
+    }
+    if rule.category_id.is_none() && rule.tags.is_empty() && rule.note_rewrite.is_none() {
+        return Err("规则至少要设置分类、标签或备注中的一项".to_string());
+    }
+    Ok(())
+}
+
+/// Create a rule, matched after the existing ones
+pub async fn create_rule(conn: &Connection, rule: NewCategoryRule) -> Result<CategoryRule, String> {
+    validate(&rule)?;
+    conn.execute(
+        "INSERT INTO category_rules
+            (name, priority, enabled, pattern, is_regex, min_amount, max_amount, account, category_id, tags, note_rewrite)
+         VALUES (?, (SELECT COALESCE(MAX(priority), 0) + 1 FROM category_rules), ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        libsql::params![
+            rule.name.trim(),
+            rule.enabled,
+            rule.pattern,
+            rule.is_regex,
+            rule.min_amount,
+            rule.max_amount,
+            rule.account,
+            rule.category_id,
+            rule.tags.join(" "),
+            rule.note_rewrite
+        ],
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let id = conn.last_insert_rowid();
+    get_rules(conn)
+        .await?
+        .into_iter()
+        .find(|r| r.id == id)
+        .ok_or_else(|| "Rule not found".to_string())
+}
+
+/// Update a rule, keeping its place in the order
+pub async fn update_rule(conn: &Connection, id: i64, rule: NewCategoryRule) -> Result<(), String> {
+    validate(&rule)?;
+    conn.execute(
+        "UPDATE category_rules SET
+            name = ?, enabled = ?, pattern = ?, is_regex = ?, min_amount = ?, max_amount = ?,
+            account = ?, category_id = ?, tags = ?, note_rewrite = ?
+         WHERE id = ?",
+        libsql::params![
+            rule.name.trim(),
+            rule.enabled,
+            rule.pattern,
+            rule.is_regex,
+            rule.min_amount,
+            rule.max_amount,
+            rule.account,
+            rule.category_id,
+            rule.tags.join(" "),
+            rule.note_rewrite,
+            id
+        ],
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Delete a rule
+pub async fn delete_rule(conn: &Connection, id: i64) -> Result<(), String> {
+    conn.execute("DELETE FROM category_rules WHERE id = ?", libsql::params![id])
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Set the matching order to the order of `ids`
+pub async fn reorder_rules(conn: &Connection, ids: &[i64]) -> Result<(), String> {
+    let tx = conn.transaction().await.map_err(|e| e.to_string())?;
+    for (priority, id) in ids.iter().enumerate() {
+        if let Err(e) = tx
+            .execute(
+                "UPDATE category_rules SET priority = ? WHERE id = ?",
+                libsql::params![priority as i64 + 1, *id],
+            )
+            .await
+        {
+            let _ = tx.rollback().await;
+            return Err(e.to_string());
+        }
+    }
+    tx.commit().await.map_err(|e| e.to_string())
+}
@@ -0,0 +1,69 @@
+//! Settings Repository
+//!
+//! Key/value app settings stored in the `settings` table.
+
+use crate::models::Setting;
+use libsql::Connection;
+
+/// Settings key of the monthly baseline (月度底线消费)
+pub const BASELINE_KEY: &str = "baseline";
+
+/// Settings key of applying category rules to manually entered transactions
+pub const RULES_ON_ENTRY_KEY: &str = "rules_on_entry";
+
+/// Settings key of the local snapshot rotation policy (JSON `SnapshotPolicy`)
+pub const SNAPSHOT_POLICY_KEY: &str = "snapshot_policy";
+
+/// Get a setting value
+pub async fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    let mut rows = conn
+        .query("SELECT value FROM settings WHERE key = ?", libsql::params![key])
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        Ok(row.get::<String>(0).ok())
+    } else {
+        Ok(None)
+    }
+}
+
+/// Get every setting
+pub async fn get_all_settings(conn: &Connection) -> Result<Vec<Setting>, String> {
+    let mut rows = conn
+        .query("SELECT key, value FROM settings ORDER BY key", ())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut settings = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        settings.push(Setting {
+            key: row.get(0).map_err(|e| e.to_string())?,
+            value: row.get(1).map_err(|e| e.to_string())?,
+        });
+    }
+    Ok(settings)
+}
+
+/// Insert or update a setting value
+pub async fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)",
+        libsql::params![key, value],
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Get the monthly baseline, if one has been set
+pub async fn get_baseline(conn: &Connection) -> Result<Option<f64>, String> {
+    Ok(get_setting(conn, BASELINE_KEY)
+        .await?
+        .and_then(|value| value.parse::<f64>().ok()))
+}
+
+/// Whether category rules also apply to manually entered transactions
+pub async fn get_rules_on_entry(conn: &Connection) -> Result<bool, String> {
+    Ok(get_setting(conn, RULES_ON_ENTRY_KEY).await?.as_deref() == Some("true"))
+}
@@ -0,0 +1,61 @@
+//! Smart View Repository
+//!
+//! Named filter queries (智能视图) saved by the user.
+
+use crate::models::SmartView;
+use libsql::Connection;
+
+/// Get all smart views ordered by name
+pub async fn get_smart_views(conn: &Connection) -> Result<Vec<SmartView>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, name, query, created_at FROM smart_views ORDER BY name")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut rows = stmt.query(()).await.map_err(|e| e.to_string())?;
+
+    let mut views = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        views.push(SmartView {
+            id: row.get(0).map_err(|e| e.to_string())?,
+            name: row.get(1).map_err(|e| e.to_string())?,
+            query: row.get(2).map_err(|e| e.to_string())?,
+            created_at: row.get(3).map_err(|e| e.to_string())?,
+        });
+    }
+
+    Ok(views)
+}
+
+/// Save a smart view, replacing the query of an existing view with the same name
+///
+/// The query is parsed first so only valid queries are stored.
+pub async fn save_smart_view(conn: &Connection, name: &str, query: &str) -> Result<SmartView, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("View name cannot be empty".to_string());
+    }
+    crate::query::parse(query).map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO smart_views (name, query) VALUES (?, ?)
+         ON CONFLICT(name) DO UPDATE SET query = excluded.query",
+        libsql::params![name, query.trim()],
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    get_smart_views(conn)
+        .await?
+        .into_iter()
+        .find(|v| v.name == name)
+        .ok_or_else(|| "Smart view not found after saving".to_string())
+}
+
+/// Delete a smart view
+pub async fn delete_smart_view(conn: &Connection, id: i64) -> Result<(), String> {
+    conn.execute("DELETE FROM smart_views WHERE id = ?", libsql::params![id])
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,423 @@
+//! Statistics Repository
+//!
+//! Aggregate queries used by charts and reports (统计图表).
+
+use crate::models::{
+    CategoryComparison, CategoryTotal, ComparisonPeriod, DailyTotal, MonthComparison, MonthTotals, MonthlyTrendPoint,
+};
+use crate::repository::transaction_repo;
+use libsql::Connection;
+use std::collections::HashMap;
+
+/// Get the `[start, end)` date range of a month
+pub fn month_range(year: i32, month: i32) -> (String, String) {
+    let month_start = format!("{:04}-{:02}-01", year, month);
+    let next_month = if month == 12 {
+        format!("{:04}-01-01", year + 1)
+    } else {
+        format!("{:04}-{:02}-01", year, month + 1)
+    };
+    (month_start, next_month)
+}
+
+/// Shift a (year, month) pair by a number of months
+pub fn shift_month(year: i32, month: i32, offset: i32) -> (i32, i32) {
+    let total_months = year * 12 + (month - 1) + offset;
+    (total_months.div_euclid(12), total_months.rem_euclid(12) + 1)
+}
+
+/// Get expense totals per category in `[start_date, end_date)`
+///
+/// Installment payments due in the range count towards their plan's category,
+/// matching how the summary view adds them to monthly expenses.
+pub async fn get_category_expense_breakdown(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<CategoryTotal>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT c.id, c.name, c.icon, CAST(SUM(x.amount) AS REAL) AS total, COUNT(*) AS count
+             FROM (
+                SELECT category_id, ABS(amount) AS amount
+                FROM transactions
+                WHERE amount < 0 AND transaction_date >= ?1 AND transaction_date < ?2
+                UNION ALL
+                SELECT i.category_id, d.amount
+                FROM installment_details d
+                INNER JOIN installments i ON d.installment_id = i.id
+                WHERE d.due_date >= ?1 AND d.due_date < ?2
+             ) x
+             INNER JOIN categories c ON x.category_id = c.id
+             GROUP BY c.id, c.name, c.icon
+             ORDER BY total DESC"
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut rows = stmt
+        .query(libsql::params![start_date, end_date])
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut totals = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        totals.push(CategoryTotal {
+            category_id: row.get(0).map_err(|e| e.to_string())?,
+            category_name: row.get(1).map_err(|e| e.to_string())?,
+            category_icon: row.get(2).ok(),
+            total: row.get(3).map_err(|e| e.to_string())?,
+            transaction_count: row.get::<i64>(4).map_err(|e| e.to_string())? as i32,
+        });
+    }
+
+    Ok(totals)
+}
+
+/// Get income/expense totals for the `months` months ending at (year, month)
+///
+/// Months without data are returned as zero so charts get a continuous axis.
+/// Points are ordered from oldest to newest.
+pub async fn get_monthly_trend(
+    conn: &Connection,
+    year: i32,
+    month: i32,
+    months: i32,
+) -> Result<Vec<MonthlyTrendPoint>, String> {
+    let months = months.max(1);
+    let (first_year, first_month) = shift_month(year, month, -(months - 1));
+    let (range_start, _) = month_range(first_year, first_month);
+    let (_, range_end) = month_range(year, month);
+
+    let mut points: Vec<MonthlyTrendPoint> = (0..months)
+        .map(|i| {
+            let (y, m) = shift_month(first_year, first_month, i);
+            MonthlyTrendPoint {
+                year: y,
+                month: m,
+                total_income: 0.0,
+                total_expense: 0.0,
+                installment_expense: 0.0,
+            }
+        })
+        .collect();
+    let index: HashMap<String, usize> = points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (format!("{:04}-{:02}", p.year, p.month), i))
+        .collect();
+
+    // Transactions grouped by month
+    let mut stmt = conn
+        .prepare(
+            "SELECT substr(transaction_date, 1, 7) AS ym,
+                CAST(COALESCE(SUM(CASE WHEN amount >= 0 THEN amount ELSE 0 END), 0) AS REAL) as income,
+                CAST(COALESCE(SUM(CASE WHEN amount < 0 THEN ABS(amount) ELSE 0 END), 0) AS REAL) as expense
+             FROM transactions
+             WHERE transaction_date >= ? AND transaction_date < ?
+             GROUP BY ym"
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut rows = stmt
+        .query(libsql::params![range_start.clone(), range_end.clone()])
+        .await
+        .map_err(|e| e.to_string())?;
+
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        let ym: String = row.get(0).map_err(|e| e.to_string())?;
+        if let Some(&i) = index.get(&ym) {
+            points[i].total_income = row.get(1).map_err(|e| e.to_string())?;
+            points[i].total_expense = row.get(2).map_err(|e| e.to_string())?;
+        }
+    }
+
+    // Installment payments grouped by due month
+    let mut stmt = conn
+        .prepare(
+            "SELECT substr(due_date, 1, 7) AS ym, CAST(COALESCE(SUM(amount), 0) AS REAL)
+             FROM installment_details
+             WHERE due_date >= ? AND due_date < ?
+             GROUP BY ym"
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut rows = stmt
+        .query(libsql::params![range_start, range_end])
+        .await
+        .map_err(|e| e.to_string())?;
+
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        let ym: String = row.get(0).map_err(|e| e.to_string())?;
+        if let Some(&i) = index.get(&ym) {
+            points[i].installment_expense = row.get(1).map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(points)
+}
+
+/// Get per-day totals in `[start_date, end_date)`
+///
+/// Only days with transactions or installment payments due are returned,
+/// ordered by date.
+pub async fn get_daily_totals(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<DailyTotal>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT day,
+                CAST(COALESCE(SUM(income), 0) AS REAL),
+                CAST(COALESCE(SUM(expense), 0) AS REAL),
+                SUM(tx_count),
+                CAST(COALESCE(SUM(installment_amount), 0) AS REAL),
+                SUM(installment_count)
+             FROM (
+                SELECT substr(transaction_date, 1, 10) AS day,
+                    CASE WHEN amount >= 0 THEN amount ELSE 0 END AS income,
+                    CASE WHEN amount < 0 THEN ABS(amount) ELSE 0 END AS expense,
+                    1 AS tx_count,
+                    0 AS installment_amount,
+                    0 AS installment_count
+                FROM transactions
+                WHERE transaction_date >= ?1 AND transaction_date < ?2
+                UNION ALL
+                SELECT substr(due_date, 1, 10), 0, 0, 0, amount, 1
+                FROM installment_details
+                WHERE due_date >= ?1 AND due_date < ?2
+             )
+             GROUP BY day
+             ORDER BY day"
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut rows = stmt
+        .query(libsql::params![start_date, end_date])
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut days = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        days.push(DailyTotal {
+            date: row.get(0).map_err(|e| e.to_string())?,
+            total_income: row.get(1).map_err(|e| e.to_string())?,
+            total_expense: row.get(2).map_err(|e| e.to_string())?,
+            transaction_count: row.get::<i64>(3).map_err(|e| e.to_string())? as i32,
+            installment_amount: row.get(4).map_err(|e| e.to_string())?,
+            installment_count: row.get::<i64>(5).map_err(|e| e.to_string())? as i32,
+        });
+    }
+
+    Ok(days)
+}
+
+/// Get income/expense totals of every month with transactions or installment payments
+///
+/// Months are ordered newest first, matching the order transactions are paged in.
+pub async fn get_month_totals(conn: &Connection) -> Result<Vec<MonthTotals>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT ym,
+                CAST(COALESCE(SUM(income), 0) AS REAL),
+                CAST(COALESCE(SUM(expense), 0) AS REAL),
+                SUM(tx_count),
+                CAST(COALESCE(SUM(installment_amount), 0) AS REAL),
+                SUM(installment_count)
+             FROM (
+                SELECT substr(transaction_date, 1, 7) AS ym,
+                    CASE WHEN amount >= 0 THEN amount ELSE 0 END AS income,
+                    CASE WHEN amount < 0 THEN ABS(amount) ELSE 0 END AS expense,
+                    1 AS tx_count,
+                    0 AS installment_amount,
+                    0 AS installment_count
+                FROM transactions
+                UNION ALL
+                SELECT substr(due_date, 1, 7), 0, 0, 0, amount, 1
+                FROM installment_details
+             )
+             GROUP BY ym
+             ORDER BY ym DESC"
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut rows = stmt.query(()).await.map_err(|e| e.to_string())?;
+
+    let mut months = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        let ym: String = row.get(0).map_err(|e| e.to_string())?;
+        let mut parts = ym.split('-');
+        let (Some(Ok(year)), Some(Ok(month))) = (
+            parts.next().map(str::parse::<i32>),
+            parts.next().map(str::parse::<i32>),
+        ) else {
+            continue;
+        };
+        months.push(MonthTotals {
+            year,
+            month,
+            total_income: row.get(1).map_err(|e| e.to_string())?,
+            total_expense: row.get(2).map_err(|e| e.to_string())?,
+            transaction_count: row.get::<i64>(3).map_err(|e| e.to_string())? as i32,
+            installment_expense: row.get(4).map_err(|e| e.to_string())?,
+            installment_count: row.get::<i64>(5).map_err(|e| e.to_string())? as i32,
+        });
+    }
+
+    Ok(months)
+}
+
+/// Maximum number of categories flagged as drivers of a month's change
+const MAX_DRIVERS: usize = 3;
+
+/// Get the total of installment payments due in `[start_date, end_date)`
+pub async fn get_installment_total(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+) -> Result<f64, String> {
+    let mut rows = conn
+        .query(
+            "SELECT CAST(COALESCE(SUM(amount), 0) AS REAL) FROM installment_details
+             WHERE due_date >= ? AND due_date < ?",
+            libsql::params![start_date, end_date],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match rows.next().await.map_err(|e| e.to_string())? {
+        Some(row) => row.get(0).map_err(|e| e.to_string()),
+        None => Ok(0.0),
+    }
+}
+
+/// Build the totals of one month for comparison (expense includes installments)
+async fn get_comparison_period(
+    conn: &Connection,
+    year: i32,
+    month: i32,
+) -> Result<ComparisonPeriod, String> {
+    let summary = transaction_repo::get_monthly_summary(conn, year, month).await?;
+    let (start, end) = month_range(year, month);
+    let installment_total = get_installment_total(conn, &start, &end).await?;
+
+    Ok(ComparisonPeriod {
+        year,
+        month,
+        total_income: summary.total_income,
+        total_expense: summary.total_expense + installment_total,
+        transaction_count: summary.transaction_count,
+    })
+}
+
+/// Percentage change from `base` to `value`, `None` when there is no base
+fn percent_change(value: f64, base: f64) -> Option<f64> {
+    if base.abs() < f64::EPSILON {
+        None
+    } else {
+        Some((value - base) / base * 100.0)
+    }
+}
+
+/// Merge three category breakdowns into per-category comparisons
+///
+/// Categories that moved the month's expense total the most, in the same
+/// direction as the total, are flagged as drivers.
+fn build_category_comparisons(
+    current: &[CategoryTotal],
+    previous: &[CategoryTotal],
+    last_year: &[CategoryTotal],
+    total_change: f64,
+) -> Vec<CategoryComparison> {
+    let mut merged: Vec<CategoryComparison> = Vec::new();
+    let mut index: HashMap<i64, usize> = HashMap::new();
+
+    for (slot, totals) in [current, previous, last_year].iter().enumerate() {
+        for item in totals.iter() {
+            let i = *index.entry(item.category_id).or_insert_with(|| {
+                merged.push(CategoryComparison {
+                    category_id: item.category_id,
+                    category_name: item.category_name.clone(),
+                    category_icon: item.category_icon.clone(),
+                    current: 0.0,
+                    previous: 0.0,
+                    last_year: 0.0,
+                    mom_change: 0.0,
+                    mom_percent: None,
+                    yoy_change: 0.0,
+                    yoy_percent: None,
+                    is_driver: false,
+                });
+                merged.len() - 1
+            });
+            match slot {
+                0 => merged[i].current = item.total,
+                1 => merged[i].previous = item.total,
+                _ => merged[i].last_year = item.total,
+            }
+        }
+    }
+
+    for item in merged.iter_mut() {
+        item.mom_change = item.current - item.previous;
+        item.mom_percent = percent_change(item.current, item.previous);
+        item.yoy_change = item.current - item.last_year;
+        item.yoy_percent = percent_change(item.current, item.last_year);
+    }
+
+    merged.sort_by(|a, b| {
+        b.mom_change
+            .abs()
+            .partial_cmp(&a.mom_change.abs())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    if total_change.abs() >= 0.01 {
+        merged
+            .iter_mut()
+            .filter(|c| c.mom_change.abs() >= 0.01 && c.mom_change.signum() == total_change.signum())
+            .take(MAX_DRIVERS)
+            .for_each(|c| c.is_driver = true);
+    }
+
+    merged
+}
+
+/// Compare a month against the previous month and the same month last year
+pub async fn get_month_comparison(
+    conn: &Connection,
+    year: i32,
+    month: i32,
+) -> Result<MonthComparison, String> {
+    let (prev_year, prev_month) = shift_month(year, month, -1);
+
+    let current = get_comparison_period(conn, year, month).await?;
+    let previous = get_comparison_period(conn, prev_year, prev_month).await?;
+    let last_year = get_comparison_period(conn, year - 1, month).await?;
+
+    let (start, end) = month_range(year, month);
+    let current_breakdown = get_category_expense_breakdown(conn, &start, &end).await?;
+    let (start, end) = month_range(prev_year, prev_month);
+    let previous_breakdown = get_category_expense_breakdown(conn, &start, &end).await?;
+    let (start, end) = month_range(year - 1, month);
+    let last_year_breakdown = get_category_expense_breakdown(conn, &start, &end).await?;
+
+    let categories = build_category_comparisons(
+        &current_breakdown,
+        &previous_breakdown,
+        &last_year_breakdown,
+        current.total_expense - previous.total_expense,
+    );
+
+    Ok(MonthComparison {
+        current,
+        previous,
+        last_year,
+        categories,
+    })
+}
@@ -3,7 +3,10 @@
 //! Manages transaction (交易记录) CRUD operations.
 
 use crate::db::DbState;
-use crate::models::{NewTransaction, Transaction, TransactionWithCategory};
+use crate::models::{
+    NewTransaction, Transaction, TransactionCursor, TransactionCursorPage, TransactionFilter, TransactionKind,
+    TransactionPage, TransactionWithCategory,
+};
 use libsql::Connection;
 
 /// Create a new transaction
@@ -165,6 +168,357 @@ pub async fn get_transactions_by_month(
     Ok(transactions)
 }
 
+/// Get transactions of a single day (YYYY-MM-DD)
+pub async fn get_transactions_by_date(
+    conn: &Connection,
+    date: &str,
+) -> Result<Vec<TransactionWithCategory>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.id, t.category_id, c.name, c.icon, t.amount, t.transaction_date, t.note, t.created_at
+             FROM transactions t
+             INNER JOIN categories c ON t.category_id = c.id
+             WHERE t.transaction_date >= ? AND t.transaction_date < ?
+             ORDER BY t.transaction_date DESC, t.id DESC"
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut rows = stmt
+        .query(libsql::params![date, next_day(date)?])
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut transactions = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        transactions.push(TransactionWithCategory {
+            id: row.get(0).map_err(|e| e.to_string())?,
+            category_id: row.get(1).map_err(|e| e.to_string())?,
+            category_name: row.get(2).map_err(|e| e.to_string())?,
+            category_icon: row.get(3).ok(),
+            amount: row.get(4).map_err(|e| e.to_string())?,
+            transaction_date: row.get(5).map_err(|e| e.to_string())?,
+            note: row.get(6).ok(),
+            created_at: row.get(7).map_err(|e| e.to_string())?,
+        });
+    }
+
+    Ok(transactions)
+}
+
+/// Parameterized WHERE conditions over `transactions t INNER JOIN categories c`
+///
+/// Clauses use positional `?` placeholders and are joined with AND; params are
+/// bound in the order the clauses were pushed.
+#[derive(Debug, Default)]
+pub struct TransactionWhere {
+    clauses: Vec<String>,
+    params: Vec<libsql::Value>,
+}
+
+impl TransactionWhere {
+    /// Add a clause with its parameters
+    pub fn push(&mut self, clause: impl Into<String>, params: impl IntoIterator<Item = libsql::Value>) {
+        self.clauses.push(clause.into());
+        self.params.extend(params);
+    }
+
+    /// Render as ` WHERE ...`, or an empty string without conditions
+    fn sql(&self) -> String {
+        if self.clauses.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", self.clauses.join(" AND "))
+        }
+    }
+}
+
+/// Escape `\`, `%` and `_` for a LIKE pattern using `ESCAPE '\'`
+pub fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// Build the condition matching notes that contain `term`
+///
+/// Terms of three or more characters use the trigram FTS index; shorter terms
+/// (e.g. "京东") cannot be matched by trigrams and fall back to LIKE.
+pub fn note_contains_clause(term: &str) -> (String, libsql::Value) {
+    if term.chars().count() >= 3 {
+        (
+            "t.id IN (SELECT rowid FROM transactions_fts WHERE transactions_fts MATCH ?)".to_string(),
+            libsql::Value::Text(format!("\"{}\"", term.replace('"', "\"\""))),
+        )
+    } else {
+        (
+            "t.note LIKE ? ESCAPE '\\'".to_string(),
+            libsql::Value::Text(format!("%{}%", escape_like(term))),
+        )
+    }
+}
+
+/// Get the day after a YYYY-MM-DD date, used for inclusive end dates
+pub fn next_day(date: &str) -> Result<String, String> {
+    let day = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date: {}", date))?;
+    day.succ_opt()
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .ok_or_else(|| format!("Invalid date: {}", date))
+}
+
+/// Translate a search filter into WHERE conditions
+fn filter_conditions(filter: &TransactionFilter) -> Result<TransactionWhere, String> {
+    let mut conditions = TransactionWhere::default();
+
+    if let Some(text) = &filter.text {
+        for term in text.split_whitespace() {
+            let (clause, param) = note_contains_clause(term);
+            conditions.push(clause, [param]);
+        }
+    }
+
+    if !filter.category_ids.is_empty() {
+        let placeholders = vec!["?"; filter.category_ids.len()].join(", ");
+        conditions.push(
+            format!("t.category_id IN ({})", placeholders),
+            filter.category_ids.iter().map(|id| libsql::Value::Integer(*id)),
+        );
+    }
+
+    if let Some(min) = filter.min_amount {
+        conditions.push("ABS(t.amount) >= ?", [libsql::Value::Real(min)]);
+    }
+    if let Some(max) = filter.max_amount {
+        conditions.push("ABS(t.amount) <= ?", [libsql::Value::Real(max)]);
+    }
+
+    if let Some(start) = filter.start_date.as_deref().filter(|d| !d.is_empty()) {
+        conditions.push("t.transaction_date >= ?", [libsql::Value::Text(start.to_string())]);
+    }
+    if let Some(end) = filter.end_date.as_deref().filter(|d| !d.is_empty()) {
+        conditions.push("t.transaction_date < ?", [libsql::Value::Text(next_day(end)?)]);
+    }
+
+    match filter.kind {
+        Some(TransactionKind::Income) => conditions.push("t.amount >= 0", []),
+        Some(TransactionKind::Expense) => conditions.push("t.amount < 0", []),
+        None => {}
+    }
+
+    Ok(conditions)
+}
+
+/// Get one page of transactions matching `conditions`, newest first
+pub async fn query_transaction_page(
+    conn: &Connection,
+    conditions: &TransactionWhere,
+    limit: i64,
+    offset: i64,
+) -> Result<TransactionPage, String> {
+    let where_sql = conditions.sql();
+
+    let mut rows = conn
+        .query(
+            &format!(
+                "SELECT COUNT(*) FROM transactions t INNER JOIN categories c ON t.category_id = c.id{}",
+                where_sql
+            ),
+            libsql::params_from_iter(conditions.params.clone()),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    let total_count: i64 = match rows.next().await.map_err(|e| e.to_string())? {
+        Some(row) => row.get(0).map_err(|e| e.to_string())?,
+        None => 0,
+    };
+    drop(rows);
+
+    let mut params = conditions.params.clone();
+    params.push(libsql::Value::Integer(limit));
+    params.push(libsql::Value::Integer(offset));
+
+    let mut rows = conn
+        .query(
+            &format!(
+                "SELECT t.id, t.category_id, c.name, c.icon, t.amount, t.transaction_date, t.note, t.created_at
+                 FROM transactions t
+                 INNER JOIN categories c ON t.category_id = c.id{}
+                 ORDER BY t.transaction_date DESC, t.id DESC
+                 LIMIT ? OFFSET ?",
+                where_sql
+            ),
+            libsql::params_from_iter(params),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut transactions = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        transactions.push(TransactionWithCategory {
+            id: row.get(0).map_err(|e| e.to_string())?,
+            category_id: row.get(1).map_err(|e| e.to_string())?,
+            category_name: row.get(2).map_err(|e| e.to_string())?,
+            category_icon: row.get(3).ok(),
+            amount: row.get(4).map_err(|e| e.to_string())?,
+            transaction_date: row.get(5).map_err(|e| e.to_string())?,
+            note: row.get(6).ok(),
+            created_at: row.get(7).map_err(|e| e.to_string())?,
+        });
+    }
+
+    let has_more = offset + (transactions.len() as i64) < total_count;
+    Ok(TransactionPage {
+        transactions,
+        total_count,
+        has_more,
+    })
+}
+
+/// Search transactions by note text, categories, amount, date range and kind
+pub async fn search_transactions(
+    conn: &Connection,
+    filter: &TransactionFilter,
+    limit: i64,
+    offset: i64,
+) -> Result<TransactionPage, String> {
+    let conditions = filter_conditions(filter)?;
+    query_transaction_page(conn, &conditions, limit, offset).await
+}
+
+/// Search transactions with a filter query string (see `crate::query`)
+pub async fn query_transactions(
+    conn: &Connection,
+    query: &str,
+    limit: i64,
+    offset: i64,
+) -> Result<TransactionPage, String> {
+    let expr = crate::query::parse(query).map_err(|e| e.to_string())?;
+    let (clause, params) = crate::query::compile(&expr);
+    let mut conditions = TransactionWhere::default();
+    conditions.push(clause, params);
+    query_transaction_page(conn, &conditions, limit, offset).await
+}
+
+/// Get the page of transactions matching `conditions` that follows `cursor`
+///
+/// Rows are ordered by (transaction_date, id) descending, so the cursor stays
+/// valid when transactions are added or deleted while paging, and every page
+/// is a range scan on the date index instead of an OFFSET skip.
+pub async fn query_transaction_cursor_page(
+    conn: &Connection,
+    conditions: TransactionWhere,
+    cursor: Option<&TransactionCursor>,
+    limit: i64,
+) -> Result<TransactionCursorPage, String> {
+    let mut conditions = conditions;
+    if let Some(cursor) = cursor {
+        conditions.push(
+            "(t.transaction_date < ? OR (t.transaction_date = ? AND t.id < ?))",
+            [
+                libsql::Value::Text(cursor.transaction_date.clone()),
+                libsql::Value::Text(cursor.transaction_date.clone()),
+                libsql::Value::Integer(cursor.id),
+            ],
+        );
+    }
+
+    let limit = limit.max(1);
+    let mut params = conditions.params.clone();
+    // One extra row tells whether another page exists
+    params.push(libsql::Value::Integer(limit + 1));
+
+    let mut rows = conn
+        .query(
+            &format!(
+                "SELECT t.id, t.category_id, c.name, c.icon, t.amount, t.transaction_date, t.note, t.created_at
+                 FROM transactions t
+                 INNER JOIN categories c ON t.category_id = c.id{}
+                 ORDER BY t.transaction_date DESC, t.id DESC
+                 LIMIT ?",
+                conditions.sql()
+            ),
+            libsql::params_from_iter(params),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut transactions = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        transactions.push(TransactionWithCategory {
+            id: row.get(0).map_err(|e| e.to_string())?,
+            category_id: row.get(1).map_err(|e| e.to_string())?,
+            category_name: row.get(2).map_err(|e| e.to_string())?,
+            category_icon: row.get(3).ok(),
+            amount: row.get(4).map_err(|e| e.to_string())?,
+            transaction_date: row.get(5).map_err(|e| e.to_string())?,
+            note: row.get(6).ok(),
+            created_at: row.get(7).map_err(|e| e.to_string())?,
+        });
+    }
+
+    let next_cursor = if transactions.len() as i64 > limit {
+        transactions.truncate(limit as usize);
+        transactions.last().map(|tx| TransactionCursor {
+            transaction_date: tx.transaction_date.clone(),
+            id: tx.id,
+        })
+    } else {
+        None
+    };
+
+    Ok(TransactionCursorPage {
+        transactions,
+        next_cursor,
+    })
+}
+
+/// Get a page of transactions, optionally limited to one month
+pub async fn get_transactions_page(
+    conn: &Connection,
+    year: Option<i32>,
+    month: Option<i32>,
+    cursor: Option<&TransactionCursor>,
+    limit: i64,
+) -> Result<TransactionCursorPage, String> {
+    let mut conditions = TransactionWhere::default();
+    if let (Some(year), Some(month)) = (year, month) {
+        let month_start = format!("{:04}-{:02}-01", year, month);
+        let next_month = if month == 12 {
+            format!("{:04}-01-01", year + 1)
+        } else {
+            format!("{:04}-{:02}-01", year, month + 1)
+        };
+        conditions.push(
+            "t.transaction_date >= ? AND t.transaction_date < ?",
+            [libsql::Value::Text(month_start), libsql::Value::Text(next_month)],
+        );
+    }
+    query_transaction_cursor_page(conn, conditions, cursor, limit).await
+}
+
+/// Number of rows fetched per batch when reading a whole result set
+const BATCH_SIZE: i64 = 1000;
+
+/// Get every transaction matching `filter`, newest first
+///
+/// Reads in cursor batches so exports of large ledgers don't hold one huge
+/// statement open.
+pub async fn get_filtered_transactions(
+    conn: &Connection,
+    filter: &TransactionFilter,
+) -> Result<Vec<TransactionWithCategory>, String> {
+    let mut transactions = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = query_transaction_cursor_page(conn, filter_conditions(filter)?, cursor.as_ref(), BATCH_SIZE).await?;
+        transactions.extend(page.transactions);
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+    Ok(transactions)
+}
+
 /// Delete transaction
 pub async fn delete_transaction(conn: &Connection, id: i64) -> Result<(), String> {
     conn.execute("DELETE FROM transactions WHERE id = ?", libsql::params![id])
@@ -0,0 +1,260 @@
+//! Auto-categorization Rules
+//!
+//! User rules from the `category_rules` table, as opposed to the built-in
+//! keyword guesses of `import::rules`. Enabled rules are tried in priority
+//! order and the first one whose conditions all hold applies: it sets the
+//! category, appends `#tags` to the note and/or replaces the note.
+
+use crate::models::{CategoryRule, RuleChange};
+use crate::repository::rule_repo;
+use libsql::Connection;
+use regex::Regex;
+
+/// What a rule looks for in the note
+enum Matcher {
+    Any,
+    /// Lower-cased text
+    Contains(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            Matcher::Any => true,
+            Matcher::Contains(needle) => text.to_lowercase().contains(needle.as_str()),
+            Matcher::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+/// The enabled rules, ready for matching
+pub struct RuleSet {
+    rules: Vec<(CategoryRule, Matcher)>,
+}
+
+impl RuleSet {
+    /// Rules must already be in priority order; rules with an invalid regex
+    /// never match
+    pub fn new(rules: Vec<CategoryRule>) -> Self {
+        let rules = rules
+            .into_iter()
+            .filter(|rule| rule.enabled)
+            .filter_map(|rule| {
+                let matcher = match rule.pattern.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+                    None => Matcher::Any,
+                    Some(pattern) if rule.is_regex => Matcher::Regex(Regex::new(pattern).ok()?),
+                    Some(pattern) => Matcher::Contains(pattern.to_lowercase()),
+                };
+                Some((rule, matcher))
+            })
+            .collect();
+        RuleSet { rules }
+    }
+
+    pub async fn load(conn: &Connection) -> Result<Self, String> {
+        Ok(RuleSet::new(rule_repo::get_rules(conn).await?))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The first rule matching a transaction; `text` is the note plus, for
+    /// imported rows, the bill's own classification
+    pub fn find(&self, text: &str, amount: f64, account: Option<&str>) -> Option<&CategoryRule> {
+        self.rules
+            .iter()
+            .find(|(rule, matcher)| {
+                rule.min_amount.map_or(true, |min| amount.abs() >= min)
+                    && rule.max_amount.map_or(true, |max| amount.abs() <= max)
+                    && rule
+                        .account
+                        .as_deref()
+                        .filter(|a| !a.is_empty())
+                        .map_or(true, |a| account == Some(a))
+                    && matcher.matches(text)
+            })
+            .map(|(rule, _)| rule)
+    }
+}
+
+/// The note after a rule's rewrite and tags
+pub fn apply_note(rule: &CategoryRule, note: Option<&str>) -> Option<String> {
+    let mut note = rule
+        .note_rewrite
+        .as_deref()
+        .filter(|n| !n.is_empty())
+        .or(note)
+        .unwrap_or_default()
+        .to_string();
+    for tag in &rule.tags {
+        let tag = format!("#{}", tag.trim_start_matches('#'));
+        let spaced = format!(" {} ", note.replace('#', " #"));
+        if !spaced.contains(&format!(" {} ", tag)) {
+            if !note.is_empty() {
+                note.push(' ');
+            }
+            note.push_str(&tag);
+        }
+    }
+    Some(note).filter(|n| !n.is_empty())
+}
+
+/// What re-running the rules would change on the existing transactions
+pub async fn preview_changes(conn: &Connection) -> Result<Vec<RuleChange>, String> {
+    let rules = RuleSet::load(conn).await?;
+    if rules.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Imported transactions know their account
+    let mut rows = conn
+        .query(
+            "SELECT t.id, t.transaction_date, t.amount, t.category_id, t.note,
+                    (SELECT MAX(account) FROM imported_lines l WHERE l.transaction_id = t.id)
+             FROM transactions t
+             ORDER BY t.transaction_date DESC, t.id DESC",
+            (),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut changes = Vec::new();
+    while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+        let amount: f64 = row.get(2).map_err(|e| e.to_string())?;
+        let category_id: i64 = row.get(3).map_err(|e| e.to_string())?;
+        let note: Option<String> = row.get(4).ok();
+        let account: Option<String> = row.get::<String>(5).ok().filter(|a| !a.is_empty());
+
+        let Some(rule) = rules.find(note.as_deref().unwrap_or_default(), amount, account.as_deref()) else {
+            continue;
+        };
+        let new_category_id = rule.category_id.unwrap_or(category_id);
+        let new_note = apply_note(rule, note.as_deref());
+        if new_category_id == category_id && new_note == note {
+            continue;
+        }
+        changes.push(RuleChange {
+            transaction_id: row.get(0).map_err(|e| e.to_string())?,
+            transaction_date: row.get(1).map_err(|e| e.to_string())?,
+            amount,
+            rule_name: rule.name.clone(),
+            old_category_id: category_id,
+            new_category_id,
+            old_note: note,
+            new_note,
+        });
+    }
+    Ok(changes)
+}
+
+/// Write the changes the user kept from `preview_changes`
+pub async fn apply_changes(conn: &Connection, changes: &[RuleChange]) -> Result<i64, String> {
+    let tx = conn.transaction().await.map_err(|e| e.to_string())?;
+    for change in changes {
+        if let Err(e) = tx
+            .execute(
+                "UPDATE transactions SET category_id = ?, note = ? WHERE id = ?",
+                libsql::params![change.new_category_id, change.new_note.clone(), change.transaction_id],
+            )
+            .await
+        {
+            let _ = tx.rollback().await;
+            return Err(e.to_string());
+        }
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(changes.len() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str, pattern: Option<&str>) -> CategoryRule {
+        CategoryRule {
+            id: 0,
+            name: name.to_string(),
+            priority: 0,
+            enabled: true,
+            pattern: pattern.map(str::to_string),
+            is_regex: false,
+            min_amount: None,
+            max_amount: None,
+            account: None,
+            category_id: Some(1),
+            tags: Vec::new(),
+            note_rewrite: None,
+            created_at: String::new(),
+        }
+    }
+
+    fn found<'a>(rules: &'a RuleSet, text: &str, amount: f64, account: Option<&str>) -> Option<&'a str> {
+        rules.find(text, amount, account).map(|rule| rule.name.as_str())
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules = RuleSet::new(vec![
+            CategoryRule {
+                min_amount: Some(100.0),
+                ..rule("big coffee", Some("Starbucks"))
+            },
+            rule("coffee", Some("starbucks")),
+            rule("anything", None),
+        ]);
+        assert_eq!(found(&rules, "STARBUCKS 拿铁", -32.0, None), Some("coffee"));
+        assert_eq!(found(&rules, "Starbucks 团购", -150.0, None), Some("big coffee"));
+        assert_eq!(found(&rules, "地铁", -3.0, None), Some("anything"));
+    }
+
+    #[test]
+    fn conditions() {
+        let rules = RuleSet::new(vec![
+            CategoryRule {
+                enabled: false,
+                ..rule("disabled", Some("滴滴"))
+            },
+            // An invalid regex never matches
+            CategoryRule {
+                is_regex: true,
+                ..rule("broken", Some("(滴滴"))
+            },
+            CategoryRule {
+                account: Some("招商银行".to_string()),
+                ..rule("card", None)
+            },
+            CategoryRule {
+                is_regex: true,
+                ..rule("regex", Some(r"^滴滴.*快车$"))
+            },
+            CategoryRule {
+                max_amount: Some(10.0),
+                ..rule("small", None)
+            },
+        ]);
+        assert_eq!(found(&rules, "滴滴 快车", -20.0, Some("招商银行")), Some("card"));
+        assert_eq!(found(&rules, "滴滴快车", -20.0, None), Some("regex"));
+        assert_eq!(found(&rules, "滴滴 专车", -8.0, None), Some("small"));
+        assert_eq!(found(&rules, "滴滴 专车", -20.0, None), None);
+    }
+
+    #[test]
+    fn note_rewrite_and_tags() {
+        let tagged = CategoryRule {
+            tags: vec!["咖啡".to_string(), "#工作日".to_string()],
+            ..rule("tagged", None)
+        };
+        assert_eq!(apply_note(&tagged, Some("拿铁")).as_deref(), Some("拿铁 #咖啡 #工作日"));
+        // Tags already in the note are not added again
+        assert_eq!(apply_note(&tagged, Some("拿铁#咖啡")).as_deref(), Some("拿铁#咖啡 #工作日"));
+
+        let rewrite = CategoryRule {
+            note_rewrite: Some("早餐".to_string()),
+            ..rule("rewrite", None)
+        };
+        assert_eq!(apply_note(&rewrite, Some("肯德基 订单 123")).as_deref(), Some("早餐"));
+        assert_eq!(apply_note(&rule("plain", None), None), None);
+    }
+}
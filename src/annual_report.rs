@@ -0,0 +1,188 @@
+//! Annual report (年度账单) view.
+//!
+//! A card-style, screenshot-friendly rendering of a year's report, with an
+//! export to a standalone HTML file.
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use chrono::Datelike;
+
+use crate::types::{AnnualReport, InstallmentWithCategory, MonthlyTrendPoint};
+use crate::shared::{export_file, fetch_annual_report, ExportFile, DEFAULT_ICON};
+use crate::charts::IncomeExpenseBarChart;
+
+/// List of installments started or finished in the year
+fn installment_list(label: &'static str, items: Vec<InstallmentWithCategory>) -> impl IntoView {
+    view! {
+        <div class="annual-subtitle">{format!("{}（{}笔）", label, items.len())}</div>
+        {items.into_iter().map(|item| view! {
+            <div class="annual-row">
+                <span>
+                    {format!(
+                        "{} {} {}",
+                        item.category_icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string()),
+                        item.category_name,
+                        item.note.clone().unwrap_or_default()
+                    )}
+                </span>
+                <span class="annual-row-value">{format!("¥{:.2} / {}期", item.total_amount, item.installment_count)}</span>
+            </div>
+        }).collect_view()}
+    }
+}
+
+#[component]
+pub fn AnnualReportView(
+    /// Set to false to leave the report
+    show_report: WriteSignal<bool>,
+) -> impl IntoView {
+    let (year, set_year) = create_signal(chrono::Local::now().year());
+    let (report, set_report) = create_signal(None::<AnnualReport>);
+    let (month_points, set_month_points) = create_signal(Vec::<MonthlyTrendPoint>::new());
+    let (message, set_message) = create_signal(None::<String>);
+
+    create_effect(move |_| {
+        let year = year.get();
+        spawn_local(async move {
+            match fetch_annual_report(year).await {
+                Ok(result) => {
+                    set_month_points.set(result.months.iter().map(|m| MonthlyTrendPoint {
+                        year: result.year,
+                        month: m.month,
+                        total_income: m.total_income,
+                        total_expense: m.total_expense,
+                        installment_expense: 0.0,
+                    }).collect());
+                    set_report.set(Some(result));
+                }
+                Err(e) => set_message.set(Some(format!("加载失败: {}", e))),
+            }
+        });
+    });
+
+    let export_html = move |_| {
+        let year = year.get_untracked();
+        spawn_local(async move {
+            let file_name = format!("年度账单-{}.html", year);
+            let result = export_file(
+                "export_annual_report_html",
+                serde_json::json!({ "year": year }),
+                ExportFile {
+                    file_name: &file_name,
+                    filter_name: "HTML",
+                    extensions: &["html"],
+                    mime_type: "text/html",
+                },
+            ).await;
+            match result {
+                Ok(Some(path)) => set_message.set(Some(format!("已导出: {}", path))),
+                Ok(None) => {}
+                Err(e) => set_message.set(Some(format!("导出失败: {}", e))),
+            }
+        });
+    };
+
+    view! {
+        <div class="annual-report">
+            <div class="month-selector">
+                <button on:click=move |_| show_report.set(false)>"← 返回"</button>
+                <button on:click=move |_| set_year.set(year.get_untracked() - 1)>"◀"</button>
+                <span class="month-display">{move || format!("{}年度账单", year.get())}</span>
+                <button on:click=move |_| set_year.set(year.get_untracked() + 1)>"▶"</button>
+                <button on:click=export_html>"导出HTML"</button>
+            </div>
+            {move || message.get().map(|m| view! { <div class="annual-message">{m}</div> })}
+
+            {move || report.get().map(|r| {
+                let max_category = r.top_categories.first().map(|c| c.total).unwrap_or(0.0);
+                view! {
+                    <div class="annual-card annual-hero">
+                        <div class="annual-hero-title">{format!("{}年，你一共记了 {} 笔账", r.year, r.transaction_count)}</div>
+                        <div class="annual-stats">
+                            <div>
+                                <div class="annual-stat-label">"收入"</div>
+                                <div class="annual-stat-value amount-positive">{format!("¥{:.2}", r.total_income)}</div>
+                            </div>
+                            <div>
+                                <div class="annual-stat-label">"支出"</div>
+                                <div class="annual-stat-value amount-negative">{format!("¥{:.2}", r.total_expense)}</div>
+                            </div>
+                            <div>
+                                <div class="annual-stat-label">"结余"</div>
+                                <div class="annual-stat-value">{format!("¥{:.2}", r.net_amount)}</div>
+                            </div>
+                        </div>
+                        {(r.installment_expense > 0.0).then(|| view! {
+                            <div class="annual-note">{format!("支出中含分期还款 ¥{:.2}", r.installment_expense)}</div>
+                        })}
+                    </div>
+
+                    <div class="annual-card">
+                        <div class="chart-title">"支出最多的分类"</div>
+                        {r.top_categories.is_empty().then(|| view! { <div class="chart-empty">"暂无支出"</div> })}
+                        {r.top_categories.iter().map(|c| {
+                            let width = if max_category > 0.0 { c.total / max_category * 100.0 } else { 0.0 };
+                            view! {
+                                <div class="annual-row">
+                                    <span>{format!("{} {}", c.category_icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string()), c.category_name)}</span>
+                                    <span class="annual-row-value">{format!("¥{:.2}", c.total)}</span>
+                                </div>
+                                <div class="annual-bar" style=format!("width: {:.1}%;", width)></div>
+                            }
+                        }).collect_view()}
+                    </div>
+
+                    <div class="annual-card">
+                        <div class="chart-title">"最大的几笔支出"</div>
+                        {r.largest_expenses.is_empty().then(|| view! { <div class="chart-empty">"暂无支出"</div> })}
+                        {r.largest_expenses.iter().map(|tx| view! {
+                            <div class="annual-row">
+                                <span>
+                                    {format!(
+                                        "{} {} {}",
+                                        tx.transaction_date.split('T').next().unwrap_or(&tx.transaction_date),
+                                        tx.category_icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string()),
+                                        tx.note.clone().unwrap_or_else(|| tx.category_name.clone())
+                                    )}
+                                </span>
+                                <span class="annual-row-value amount-negative">{format!("¥{:.2}", tx.amount.abs())}</span>
+                            </div>
+                        }).collect_view()}
+                    </div>
+
+                    <div class="annual-card">
+                        <div class="chart-title">"花钱最多的月份"</div>
+                        {match r.most_expensive_month.clone() {
+                            Some(m) => view! {
+                                <div class="annual-highlight">{format!("{}月 · ¥{:.2}", m.month, m.total_expense)}</div>
+                            }.into_any(),
+                            None => view! { <div class="chart-empty">"暂无支出"</div> }.into_any(),
+                        }}
+                    </div>
+
+                    <IncomeExpenseBarChart data=month_points />
+
+                    <div class="annual-card">
+                        <div class="chart-title">"底线达成"</div>
+                        {match (r.budget, r.budget_hit_rate) {
+                            (Some(budget), Some(rate)) => view! {
+                                <div class="annual-highlight">{format!("{:.0}%", rate)}</div>
+                                <div class="annual-note">
+                                    {format!("月度底线 ¥{:.2}，{} 个月中有 {} 个月未超支", budget, r.budget_months_counted, r.budget_months_within)}
+                                </div>
+                            }.into_any(),
+                            (Some(_), None) => view! { <div class="chart-empty">"本年度尚无可统计的月份"</div> }.into_any(),
+                            _ => view! { <div class="chart-empty">"未设置月度底线"</div> }.into_any(),
+                        }}
+                    </div>
+
+                    <div class="annual-card">
+                        <div class="chart-title">"分期"</div>
+                        {installment_list("今年新开的分期", r.installments_started.clone())}
+                        {installment_list("今年还清的分期", r.installments_finished.clone())}
+                    </div>
+                }
+            })}
+        </div>
+    }
+}
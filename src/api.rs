@@ -52,3 +52,109 @@ pub async fn invoke_safe(cmd: &str, args: JsValue) -> Result<JsValue, String> {
         }
     }
 }
+
+// Dialog plugin bindings (desktop save and folder dialogs)
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "dialog"], js_name = "save", catch)]
+    async fn dialog_save(options: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "dialog"], js_name = "open", catch)]
+    async fn dialog_open(options: JsValue) -> Result<JsValue, JsValue>;
+}
+
+/// Name of the JS interface injected by the Android MainActivity
+const ANDROID_BRIDGE: &str = "KeepAccountsAndroid";
+
+/// Get the Android bridge object, if running inside the Android app
+fn android_bridge() -> Option<JsValue> {
+    let window = web_sys::window()?;
+    js_sys::Reflect::get(&window, &JsValue::from_str(ANDROID_BRIDGE))
+        .ok()
+        .filter(|bridge| !bridge.is_undefined() && !bridge.is_null())
+}
+
+/// Whether exported files can be handed to the platform share sheet (Android)
+pub fn can_share_files() -> bool {
+    android_bridge().is_some()
+}
+
+/// Open the Android share sheet for a file written by the backend
+pub fn share_file(path: &str, mime_type: &str) -> Result<(), String> {
+    let bridge = android_bridge().ok_or("Sharing is not available on this platform")?;
+    let share = js_sys::Reflect::get(&bridge, &JsValue::from_str("shareFile"))
+        .map_err(|e| format!("{:?}", e))?
+        .dyn_into::<js_sys::Function>()
+        .map_err(|e| format!("{:?}", e))?;
+    share
+        .call2(&bridge, &JsValue::from_str(path), &JsValue::from_str(mime_type))
+        .map_err(|e| format!("{:?}", e))?;
+    Ok(())
+}
+
+/// Open the Android share sheet for several files at once
+pub fn share_files(paths: &[String], mime_type: &str) -> Result<(), String> {
+    let bridge = android_bridge().ok_or("Sharing is not available on this platform")?;
+    let share = js_sys::Reflect::get(&bridge, &JsValue::from_str("shareFiles"))
+        .map_err(|e| format!("{:?}", e))?
+        .dyn_into::<js_sys::Function>()
+        .map_err(|e| format!("{:?}", e))?;
+    let paths = serde_json::to_string(paths).map_err(|e| e.to_string())?;
+    share
+        .call2(&bridge, &JsValue::from_str(&paths), &JsValue::from_str(mime_type))
+        .map_err(|e| format!("{:?}", e))?;
+    Ok(())
+}
+
+/// Ask the user where to save a file, `None` if the dialog was cancelled
+pub async fn pick_save_path(default_name: &str, filter_name: &str, extensions: &[&str]) -> Result<Option<String>, String> {
+    let options = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "defaultPath": default_name,
+        "filters": [{ "name": filter_name, "extensions": extensions }],
+    }))
+    .map_err(|e| format!("{:?}", e))?;
+
+    let result = dialog_save(options).await.map_err(|e| {
+        e.as_string().unwrap_or_else(|| format!("{:?}", e))
+    })?;
+    Ok(result.as_string())
+}
+
+/// Ask the user for a folder, `None` if the dialog was cancelled
+pub async fn pick_folder(title: &str) -> Result<Option<String>, String> {
+    let options = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "directory": true,
+        "title": title,
+    }))
+    .map_err(|e| format!("{:?}", e))?;
+
+    let result = dialog_open(options).await.map_err(|e| {
+        e.as_string().unwrap_or_else(|| format!("{:?}", e))
+    })?;
+    Ok(result.as_string())
+}
+
+/// The file chosen in an `<input type="file">` change event
+pub fn picked_file(ev: &web_sys::Event) -> Option<web_sys::File> {
+    ev.target()?
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .ok()?
+        .files()?
+        .get(0)
+}
+
+/// Read a picked file as UTF-8 text
+pub async fn read_file_text(file: &web_sys::File) -> Result<String, String> {
+    let text = wasm_bindgen_futures::JsFuture::from(file.text())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    text.as_string().ok_or_else(|| "File is not text".to_string())
+}
+
+/// Read a picked file as raw bytes
+pub async fn read_file_bytes(file: &web_sys::File) -> Result<Vec<u8>, String> {
+    let buffer = wasm_bindgen_futures::JsFuture::from(file.array_buffer())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
@@ -5,7 +5,7 @@ use chrono::Datelike;
 // Import shared types and API
 use crate::types::Category;
 use crate::api::JsValue;
-use crate::components::{CategoryManager, DesktopTransactionView, InstallmentManager};
+use crate::components::{CategoryManager, DataManager, DesktopTransactionView, FolderBackupPrompt, InstallmentManager, RecurringBillManager, RuleManager};
 
 
 // ============================================================================
@@ -17,7 +17,16 @@ pub fn App() -> impl IntoView {
     // Categories state
     let (categories, set_categories) = create_signal(Vec::<Category>::new());
 
-    // Current view: "categories", "transactions", "installments"
+    // Set when the database could not be opened
+    let (db_error, set_db_error) = create_signal(None::<String>);
+    spawn_local(async move {
+        let _ = crate::api::listen_safe("db-init-failed", move |event| {
+            let payload = js_sys::Reflect::get(&event, &JsValue::from_str("payload")).ok();
+            set_db_error.set(Some(payload.and_then(|p| p.as_string()).unwrap_or_default()));
+        }).await;
+    });
+
+    // Current view: "categories", "transactions", "installments", "summary", "data"
     let (current_view, set_current_view) = create_signal("transactions".to_string());
 
     // Selected month for transaction view - use current date
@@ -65,6 +74,7 @@ pub fn App() -> impl IntoView {
     let show_transactions = move |_| set_current_view.set("transactions".to_string());
     let show_installments = move |_| set_current_view.set("installments".to_string());
     let show_summary = move |_| set_current_view.set("summary".to_string());
+    let show_data = move |_| set_current_view.set("data".to_string());
 
     view! {
         <main class="container">
@@ -95,12 +105,31 @@ pub fn App() -> impl IntoView {
                     >
                         "汇总"
                     </button>
+                    <button
+                        class=move || format!("nav-btn {}", if current_view.get() == "data" { "active" } else { "" })
+                        on:click=show_data
+                    >
+                        "数据"
+                    </button>
                 </nav>
             </div>
 
+            {move || db_error.get().map(|e| view! {
+                <div class="db-error">{format!("无法打开账本: {}", e)}</div>
+            })}
+
+            <FolderBackupPrompt on_changed=move || {
+                spawn_local(async move {
+                    if let Ok(cats) = crate::shared::fetch_categories().await {
+                        set_categories.set(cats);
+                    }
+                });
+            } />
+
             <div class="content">
                 <Show when=move || current_view.get() == "categories">
                     <CategoryManager categories=categories set_categories=set_categories />
+                    <RuleManager categories=categories />
                 </Show>
 
                 <Show when=move || current_view.get() == "transactions">
@@ -117,11 +146,22 @@ pub fn App() -> impl IntoView {
 
                 <Show when=move || current_view.get() == "installments">
                     <InstallmentManager categories=categories />
+                    <RecurringBillManager />
                 </Show>
 
                 <Show when=move || current_view.get() == "summary">
                     <crate::summary::SummaryView categories=categories />
                 </Show>
+
+                <Show when=move || current_view.get() == "data">
+                    <DataManager categories=categories on_changed=move || {
+                        spawn_local(async move {
+                            if let Ok(cats) = crate::shared::fetch_categories().await {
+                                set_categories.set(cats);
+                            }
+                        });
+                    } />
+                </Show>
             </div>
         </main>
     }
@@ -0,0 +1,286 @@
+//! SVG chart components for the summary view.
+//!
+//! Charts are drawn as plain SVG so no JS chart library is needed.
+
+use leptos::prelude::*;
+
+use crate::types::{CategoryTotal, MonthlyTrendPoint};
+use crate::shared::DEFAULT_ICON;
+
+/// Palette used for chart series and pie slices
+const CHART_COLORS: &[&str] = &[
+    "#3b82f6", "#e74c3c", "#27ae60", "#f39c12", "#9b59b6",
+    "#1abc9c", "#e67e22", "#34495e", "#e84393", "#7f8c8d",
+];
+
+const INCOME_COLOR: &str = "#27ae60";
+const EXPENSE_COLOR: &str = "#e74c3c";
+
+/// Trend and bar chart canvas size (viewBox units)
+const CHART_WIDTH: f64 = 340.0;
+const CHART_HEIGHT: f64 = 180.0;
+const CHART_PAD_LEFT: f64 = 40.0;
+const CHART_PAD_RIGHT: f64 = 8.0;
+const CHART_PAD_TOP: f64 = 12.0;
+const CHART_PAD_BOTTOM: f64 = 22.0;
+
+/// Pick a palette color by series index
+fn chart_color(index: usize) -> &'static str {
+    CHART_COLORS[index % CHART_COLORS.len()]
+}
+
+/// Build the SVG path of a donut slice between two angles (radians)
+fn donut_slice_path(cx: f64, cy: f64, r_outer: f64, r_inner: f64, start: f64, end: f64) -> String {
+    // A full circle cannot be drawn with one arc, so stop just short of it
+    let end = if end - start >= std::f64::consts::TAU { start + std::f64::consts::TAU - 0.0001 } else { end };
+    let large_arc = if end - start > std::f64::consts::PI { 1 } else { 0 };
+
+    let (x0, y0) = (cx + r_outer * start.cos(), cy + r_outer * start.sin());
+    let (x1, y1) = (cx + r_outer * end.cos(), cy + r_outer * end.sin());
+    let (x2, y2) = (cx + r_inner * end.cos(), cy + r_inner * end.sin());
+    let (x3, y3) = (cx + r_inner * start.cos(), cy + r_inner * start.sin());
+
+    format!(
+        "M {:.2} {:.2} A {r_outer} {r_outer} 0 {large_arc} 1 {:.2} {:.2} L {:.2} {:.2} A {r_inner} {r_inner} 0 {large_arc} 0 {:.2} {:.2} Z",
+        x0, y0, x1, y1, x2, y2, x3, y3
+    )
+}
+
+/// Round the axis maximum up to a readable value
+fn nice_max(value: f64) -> f64 {
+    if value <= 0.0 {
+        return 100.0;
+    }
+    let magnitude = 10f64.powf(value.log10().floor());
+    [1.0, 2.0, 2.5, 5.0, 10.0]
+        .iter()
+        .map(|s| s * magnitude)
+        .find(|s| *s >= value)
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// Format an axis label compactly (e.g. 12000 -> 1.2万)
+fn axis_label(value: f64) -> String {
+    if value >= 10000.0 {
+        format!("{:.1}万", value / 10000.0)
+    } else {
+        format!("{:.0}", value)
+    }
+}
+
+/// Horizontal grid lines with value labels shared by trend and bar charts
+fn chart_grid(max: f64) -> impl IntoView {
+    let plot_height = CHART_HEIGHT - CHART_PAD_TOP - CHART_PAD_BOTTOM;
+    (0..=4)
+        .map(|i| {
+            let value = max * i as f64 / 4.0;
+            let y = CHART_HEIGHT - CHART_PAD_BOTTOM - plot_height * i as f64 / 4.0;
+            view! {
+                <g>
+                    <line
+                        x1=CHART_PAD_LEFT.to_string()
+                        y1=format!("{:.1}", y)
+                        x2=(CHART_WIDTH - CHART_PAD_RIGHT).to_string()
+                        y2=format!("{:.1}", y)
+                        stroke="#e0e0e0"
+                        stroke-width="1"
+                    />
+                    <text
+                        x=(CHART_PAD_LEFT - 4.0).to_string()
+                        y=format!("{:.1}", y + 3.0)
+                        text-anchor="end"
+                        font-size="9"
+                        fill="#7f8c8d"
+                    >
+                        {axis_label(value)}
+                    </text>
+                </g>
+            }
+        })
+        .collect_view()
+}
+
+/// Category share donut chart with legend
+#[component]
+pub fn CategoryPieChart(
+    /// Expense totals per category
+    data: ReadSignal<Vec<CategoryTotal>>,
+) -> impl IntoView {
+    view! {
+        <div class="chart-card">
+            <div class="chart-title">"支出分类占比"</div>
+            {move || {
+                let items = data.get();
+                let total: f64 = items.iter().map(|c| c.total).sum();
+                if items.is_empty() || total <= 0.0 {
+                    return view! { <div class="chart-empty">"暂无支出数据"</div> }.into_any();
+                }
+
+                let mut angle = -std::f64::consts::FRAC_PI_2;
+                let slices = items.iter().enumerate().map(|(i, item)| {
+                    let sweep = item.total / total * std::f64::consts::TAU;
+                    let d = donut_slice_path(100.0, 100.0, 90.0, 55.0, angle, angle + sweep);
+                    angle += sweep;
+                    view! { <path d=d fill=chart_color(i) stroke="white" stroke-width="1" /> }
+                }).collect_view();
+
+                let legend = items.iter().enumerate().map(|(i, item)| {
+                    let percent = item.total / total * 100.0;
+                    view! {
+                        <div class="chart-legend-item">
+                            <span class="chart-legend-swatch" style=format!("background: {};", chart_color(i))></span>
+                            <span class="chart-legend-label">
+                                {format!("{} {}", item.category_icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string()), item.category_name)}
+                            </span>
+                            <span class="chart-legend-value">{format!("¥{:.2} · {:.1}%", item.total, percent)}</span>
+                        </div>
+                    }
+                }).collect_view();
+
+                view! {
+                    <div class="chart-pie">
+                        <svg viewBox="0 0 200 200" width="180" height="180">
+                            {slices}
+                            <text x="100" y="95" text-anchor="middle" font-size="12" fill="#7f8c8d">"总支出"</text>
+                            <text x="100" y="115" text-anchor="middle" font-size="16" font-weight="bold" fill="#2c3e50">
+                                {format!("¥{:.0}", total)}
+                            </text>
+                        </svg>
+                        <div class="chart-legend">{legend}</div>
+                    </div>
+                }.into_any()
+            }}
+        </div>
+    }
+}
+
+/// Monthly expense trend line (transactions plus installment payments)
+#[component]
+pub fn ExpenseTrendChart(
+    /// Monthly totals ordered from oldest to newest
+    data: ReadSignal<Vec<MonthlyTrendPoint>>,
+) -> impl IntoView {
+    view! {
+        <div class="chart-card">
+            <div class="chart-title">"近12个月支出趋势"</div>
+            {move || {
+                let points = data.get();
+                if points.is_empty() {
+                    return view! { <div class="chart-empty">"暂无数据"</div> }.into_any();
+                }
+
+                let expenses: Vec<f64> = points.iter().map(|p| p.total_expense + p.installment_expense).collect();
+                let max = nice_max(expenses.iter().cloned().fold(0.0, f64::max));
+                let plot_width = CHART_WIDTH - CHART_PAD_LEFT - CHART_PAD_RIGHT;
+                let plot_height = CHART_HEIGHT - CHART_PAD_TOP - CHART_PAD_BOTTOM;
+                let step = if points.len() > 1 { plot_width / (points.len() - 1) as f64 } else { 0.0 };
+
+                let coords: Vec<(f64, f64)> = expenses.iter().enumerate().map(|(i, value)| {
+                    let x = CHART_PAD_LEFT + step * i as f64;
+                    let y = CHART_HEIGHT - CHART_PAD_BOTTOM - value / max * plot_height;
+                    (x, y)
+                }).collect();
+                let polyline = coords.iter()
+                    .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                let dots = coords.iter().zip(expenses.iter()).map(|((x, y), value)| {
+                    view! {
+                        <circle cx=format!("{:.1}", x) cy=format!("{:.1}", y) r="3" fill=EXPENSE_COLOR>
+                            <title>{format!("¥{:.2}", value)}</title>
+                        </circle>
+                    }
+                }).collect_view();
+
+                let labels = points.iter().zip(coords.iter()).map(|(p, (x, _))| {
+                    view! {
+                        <text x=format!("{:.1}", x) y=(CHART_HEIGHT - 6.0).to_string() text-anchor="middle" font-size="9" fill="#7f8c8d">
+                            {format!("{}月", p.month)}
+                        </text>
+                    }
+                }).collect_view();
+
+                view! {
+                    <svg class="chart-svg" viewBox=format!("0 0 {} {}", CHART_WIDTH, CHART_HEIGHT) preserveAspectRatio="xMidYMid meet">
+                        {chart_grid(max)}
+                        <polyline points=polyline fill="none" stroke=EXPENSE_COLOR stroke-width="2" />
+                        {dots}
+                        {labels}
+                    </svg>
+                }.into_any()
+            }}
+        </div>
+    }
+}
+
+/// Grouped income vs expense bars per month
+#[component]
+pub fn IncomeExpenseBarChart(
+    /// Monthly totals ordered from oldest to newest
+    data: ReadSignal<Vec<MonthlyTrendPoint>>,
+) -> impl IntoView {
+    view! {
+        <div class="chart-card">
+            <div class="chart-title">"收支对比"</div>
+            {move || {
+                let points = data.get();
+                if points.is_empty() {
+                    return view! { <div class="chart-empty">"暂无数据"</div> }.into_any();
+                }
+
+                let max = nice_max(points.iter()
+                    .map(|p| p.total_income.max(p.total_expense + p.installment_expense))
+                    .fold(0.0, f64::max));
+                let plot_width = CHART_WIDTH - CHART_PAD_LEFT - CHART_PAD_RIGHT;
+                let plot_height = CHART_HEIGHT - CHART_PAD_TOP - CHART_PAD_BOTTOM;
+                let slot = plot_width / points.len() as f64;
+                let bar_width = (slot * 0.35).min(14.0);
+                let baseline = CHART_HEIGHT - CHART_PAD_BOTTOM;
+
+                let bars = points.iter().enumerate().map(|(i, p)| {
+                    let center = CHART_PAD_LEFT + slot * (i as f64 + 0.5);
+                    let expense = p.total_expense + p.installment_expense;
+                    let income_height = p.total_income / max * plot_height;
+                    let expense_height = expense / max * plot_height;
+                    view! {
+                        <g>
+                            <rect
+                                x=format!("{:.1}", center - bar_width)
+                                y=format!("{:.1}", baseline - income_height)
+                                width=format!("{:.1}", bar_width)
+                                height=format!("{:.1}", income_height)
+                                fill=INCOME_COLOR
+                            >
+                                <title>{format!("收入 ¥{:.2}", p.total_income)}</title>
+                            </rect>
+                            <rect
+                                x=format!("{:.1}", center)
+                                y=format!("{:.1}", baseline - expense_height)
+                                width=format!("{:.1}", bar_width)
+                                height=format!("{:.1}", expense_height)
+                                fill=EXPENSE_COLOR
+                            >
+                                <title>{format!("支出 ¥{:.2}", expense)}</title>
+                            </rect>
+                            <text x=format!("{:.1}", center) y=(CHART_HEIGHT - 6.0).to_string() text-anchor="middle" font-size="9" fill="#7f8c8d">
+                                {format!("{}月", p.month)}
+                            </text>
+                        </g>
+                    }
+                }).collect_view();
+
+                view! {
+                    <svg class="chart-svg" viewBox=format!("0 0 {} {}", CHART_WIDTH, CHART_HEIGHT) preserveAspectRatio="xMidYMid meet">
+                        {chart_grid(max)}
+                        {bars}
+                    </svg>
+                    <div class="chart-series-legend">
+                        <span><span class="chart-legend-swatch" style=format!("background: {};", INCOME_COLOR)></span>"收入"</span>
+                        <span><span class="chart-legend-swatch" style=format!("background: {};", EXPENSE_COLOR)></span>"支出（含分期）"</span>
+                    </div>
+                }.into_any()
+            }}
+        </div>
+    }
+}
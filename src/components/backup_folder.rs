@@ -0,0 +1,240 @@
+//! Backups to a synced folder: settings card, and the prompt shown when
+//! another device left a newer backup there.
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+use crate::api::pick_folder;
+use super::data_manager::describe_counts;
+use crate::types::{FolderBackup, RestoreMode};
+use crate::shared::{
+    backup_to_folder, check_folder_backup, dismiss_folder_backup, fetch_backup_folder, fetch_folder_backups,
+    restore_folder_backup, save_backup_folder,
+};
+
+#[component]
+pub fn BackupFolder() -> impl IntoView {
+    let (path, set_path) = create_signal(String::new());
+    let (keep, set_keep) = create_signal(String::new());
+    let (encrypted, set_encrypted) = create_signal(false);
+    // Empty keeps the saved password
+    let (password, set_password) = create_signal(String::new());
+    let (remove_password, set_remove_password) = create_signal(false);
+    let (backups, set_backups) = create_signal(Vec::<FolderBackup>::new());
+    let (busy, set_busy) = create_signal(false);
+    let (message, set_message) = create_signal(None::<String>);
+
+    let reload = move || {
+        spawn_local(async move {
+            match fetch_backup_folder().await {
+                Ok(settings) => {
+                    set_path.set(settings.path.unwrap_or_default());
+                    set_keep.set(settings.keep.to_string());
+                    set_encrypted.set(settings.encrypted);
+                }
+                Err(e) => set_message.set(Some(format!("加载设置失败: {}", e))),
+            }
+            match fetch_folder_backups().await {
+                Ok(list) => set_backups.set(list),
+                Err(e) => set_message.set(Some(e)),
+            }
+        });
+    };
+    reload();
+
+    let choose = move |_| {
+        spawn_local(async move {
+            match pick_folder("选择备份文件夹").await {
+                Ok(Some(folder)) => set_path.set(folder),
+                Ok(None) => {}
+                Err(e) => set_message.set(Some(format!("无法打开文件夹选择: {}", e))),
+            }
+        });
+    };
+
+    let save = move |_| {
+        let Ok(keep) = keep.get_untracked().trim().parse::<u32>() else {
+            set_message.set(Some("保留个数必须是正整数".to_string()));
+            return;
+        };
+        let path = path.get_untracked();
+        let password = if remove_password.get_untracked() {
+            Some(String::new())
+        } else {
+            Some(password.get_untracked()).filter(|p| !p.is_empty())
+        };
+        set_busy.set(true);
+        set_message.set(None);
+        spawn_local(async move {
+            let path = Some(path.trim()).filter(|p| !p.is_empty());
+            match save_backup_folder(path, keep, password.as_deref()).await {
+                Ok(Some(backup)) => set_message.set(Some(format!("已保存，并写入备份 {}", backup.file_name))),
+                Ok(None) => set_message.set(Some("已关闭文件夹备份".to_string())),
+                Err(e) => set_message.set(Some(format!("保存失败: {}", e))),
+            }
+            set_password.set(String::new());
+            set_remove_password.set(false);
+            reload();
+            set_busy.set(false);
+        });
+    };
+
+    let backup_now = move |_| {
+        set_busy.set(true);
+        set_message.set(None);
+        spawn_local(async move {
+            match backup_to_folder().await {
+                Ok(Some(backup)) => set_message.set(Some(format!("已写入备份 {}", backup.file_name))),
+                Ok(None) => set_message.set(Some("请先设置备份文件夹".to_string())),
+                Err(e) => set_message.set(Some(format!("备份失败: {}", e))),
+            }
+            reload();
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <div class="annual-card data-card">
+            <h3>"备份到文件夹"</h3>
+            <div class="annual-note">"选择一个由 Nextcloud、Syncthing 或网盘同步的文件夹，数据有变化时自动写入备份；其他设备写入更新的备份时会提示恢复或合并"</div>
+            <div class="data-actions">
+                <input type="text" class="backup-folder-path" placeholder="文件夹路径（留空表示关闭）" prop:value=path
+                    on:input=move |ev| set_path.set(event_target_value(&ev)) />
+                <button on:click=choose>"选择…"</button>
+            </div>
+            <div class="data-actions">
+                <span class="import-summary">"本设备保留最近"</span>
+                <input type="number" min="1" class="snapshot-keep" prop:value=keep
+                    on:input=move |ev| set_keep.set(event_target_value(&ev)) />
+                <span class="import-summary">"个备份"</span>
+            </div>
+            <div class="data-actions">
+                <input
+                    type="password"
+                    placeholder=move || if encrypted.get() { "已加密，输入新密码可更换" } else { "加密密码（可选，至少 6 位）" }
+                    prop:value=password
+                    on:input=move |ev| set_password.set(event_target_value(&ev))
+                />
+                <Show when=move || encrypted.get()>
+                    <label class="rule-option">
+                        <input type="checkbox" prop:checked=move || remove_password.get()
+                            on:change=move |ev| set_remove_password.set(event_target_checked(&ev)) />
+                        "取消加密"
+                    </label>
+                </Show>
+            </div>
+            <div class="annual-note">"各设备需使用相同的密码才能互相恢复。密码以明文保存在本机的 backup_folder.json 中（未使用系统钥匙串），只保护同步文件夹里的备份；取消加密或关闭文件夹备份时删除"</div>
+            <div class="data-actions">
+                <button class="btn-primary" disabled=move || busy.get() on:click=save>"保存设置"</button>
+                <button disabled=move || busy.get() on:click=backup_now>"立即备份"</button>
+            </div>
+
+            {move || {
+                let list = backups.get();
+                (!list.is_empty()).then(|| view! {
+                    <div class="snapshot-list">
+                        {list.into_iter().map(|backup| view! {
+                            <div class="snapshot-item">
+                                <span>{backup.created_at.clone()}</span>
+                                <span class="import-summary">
+                                    {if backup.from_this_device { "本设备".to_string() } else { format!("设备 {}", backup.device_id) }}
+                                </span>
+                            </div>
+                        }).collect_view()}
+                    </div>
+                })
+            }}
+
+            {move || message.get().map(|m| view! { <div class="annual-message">{m}</div> })}
+        </div>
+    }
+}
+
+#[component]
+pub fn FolderBackupPrompt(
+    /// Called after a restore or merge so the caller can reload its data
+    on_changed: impl Fn() + 'static + Copy,
+) -> impl IntoView {
+    let (newer, set_newer) = create_signal(None::<FolderBackup>);
+    let (confirm_replace, set_confirm_replace) = create_signal(false);
+    let (busy, set_busy) = create_signal(false);
+    let (message, set_message) = create_signal(None::<String>);
+
+    let check = move || {
+        spawn_local(async move {
+            if let Ok(found) = check_folder_backup().await {
+                set_newer.set(found);
+            }
+        });
+    };
+    check();
+    // The backend checks the folder periodically while the app is open
+    spawn_local(async move {
+        let _ = crate::api::listen_safe("folder-backup-available", move |_| check()).await;
+    });
+
+    let apply = move |mode: RestoreMode| {
+        let Some(backup) = newer.get_untracked() else { return };
+        set_busy.set(true);
+        set_message.set(None);
+        spawn_local(async move {
+            match restore_folder_backup(&backup.file_name, mode).await {
+                Ok(summary) => {
+                    let imported = describe_counts(&summary.imported);
+                    set_message.set(Some(if imported.is_empty() {
+                        "没有需要导入的新数据".to_string()
+                    } else {
+                        format!("已导入: {}", imported.join("，"))
+                    }));
+                    set_newer.set(None);
+                    on_changed();
+                }
+                Err(e) => set_message.set(Some(format!("恢复失败: {}", e))),
+            }
+            set_confirm_replace.set(false);
+            set_busy.set(false);
+        });
+    };
+
+    let dismiss = move |_| {
+        let Some(backup) = newer.get_untracked() else { return };
+        set_newer.set(None);
+        spawn_local(async move {
+            if let Err(e) = dismiss_folder_backup(&backup.file_name).await {
+                set_message.set(Some(format!("操作失败: {}", e)));
+            }
+        });
+    };
+
+    view! {
+        {move || newer.get().map(|backup| view! {
+            <div class="folder-backup-prompt">
+                <span>{format!("备份文件夹中有设备 {} 于 {} 写入的新备份", backup.device_id, backup.created_at)}</span>
+                <div class="data-actions">
+                    <button class="btn-primary" disabled=move || busy.get() on:click=move |_| apply(RestoreMode::Merge)>
+                        "合并"
+                    </button>
+                    {move || if confirm_replace.get() {
+                        view! {
+                            <span class="data-warning">"将清空现有数据，确定吗？"</span>
+                            <button class="btn-danger" disabled=move || busy.get() on:click=move |_| apply(RestoreMode::Replace)>
+                                "确认覆盖"
+                            </button>
+                            <button on:click=move |_| set_confirm_replace.set(false)>"取消"</button>
+                        }.into_any()
+                    } else {
+                        view! {
+                            <button class="btn-danger" disabled=move || busy.get() on:click=move |_| set_confirm_replace.set(true)>
+                                "覆盖恢复"
+                            </button>
+                            <button disabled=move || busy.get() on:click=dismiss>"忽略"</button>
+                        }.into_any()
+                    }}
+                </div>
+            </div>
+        })}
+        {move || message.get().map(|m| view! {
+            <div class="folder-backup-prompt" on:click=move |_| set_message.set(None)>{m}</div>
+        })}
+    }
+}
@@ -0,0 +1,284 @@
+//! Bill import: pick a bill file, review the preview, import the chosen rows.
+//!
+//! Other CSVs are sniffed first and previewed once the user has mapped the columns.
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+use crate::api::{picked_file, read_file_bytes};
+use super::csv_mapping::CsvMappingForm;
+use crate::types::{Category, CsvMapping, CsvSniff, ImportRow, ImportSource};
+use crate::shared::{commit_import, preview_csv_import, preview_import, sniff_csv, DEFAULT_ICON};
+
+#[component]
+pub fn BillImport(
+    categories: ReadSignal<Vec<Category>>,
+    /// Called after rows were imported so the caller can reload its data
+    on_imported: impl Fn() + 'static + Copy,
+) -> impl IntoView {
+    let (source, set_source) = create_signal(ImportSource::Alipay);
+    let (account, set_account) = create_signal(String::new());
+    let (rows, set_rows) = create_signal(Vec::<ImportRow>::new());
+    // Lines of the rows to import; skipped and likely duplicate rows start unselected
+    let (selected, set_selected) = create_signal(Vec::<u64>::new());
+    let (busy, set_busy) = create_signal(false);
+    let (message, set_message) = create_signal(None::<String>);
+    // Generic CSV: the picked file and its sniffed layout, kept for re-previewing
+    let (csv_content, set_csv_content) = create_signal(None::<Vec<u8>>);
+    let (sniff, set_sniff) = create_signal(None::<CsvSniff>);
+
+    let show_preview = move |result: Result<Vec<ImportRow>, String>| {
+        match result {
+            Ok(preview) if preview.is_empty() => set_message.set(Some("账单中没有交易记录".to_string())),
+            Ok(preview) => {
+                set_selected.set(
+                    preview
+                        .iter()
+                        .filter(|r| r.skip_reason.is_none() && r.duplicate_of.is_none())
+                        .map(|r| r.line)
+                        .collect(),
+                );
+                set_rows.set(preview);
+            }
+            Err(e) => set_message.set(Some(e)),
+        }
+    };
+
+    let on_file = move |ev: leptos::ev::Event| {
+        set_rows.set(Vec::new());
+        set_selected.set(Vec::new());
+        set_message.set(None);
+        set_csv_content.set(None);
+        set_sniff.set(None);
+        let Some(file) = picked_file(&ev) else { return };
+        set_busy.set(true);
+        spawn_local(async move {
+            let bytes = match read_file_bytes(&file).await {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    set_message.set(Some(format!("读取文件失败: {}", e)));
+                    set_busy.set(false);
+                    return;
+                }
+            };
+            if source.get_untracked() == ImportSource::Csv {
+                match sniff_csv(&bytes).await {
+                    Ok(result) if result.rows.is_empty() => set_message.set(Some("文件是空的".to_string())),
+                    Ok(result) => {
+                        set_csv_content.set(Some(bytes));
+                        set_sniff.set(Some(result));
+                    }
+                    Err(e) => set_message.set(Some(e)),
+                }
+            } else {
+                let account = Some(account.get_untracked()).filter(|a| !a.trim().is_empty());
+                show_preview(preview_import(source.get_untracked(), &bytes, account).await);
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let on_mapped = move |mapping: CsvMapping| {
+        let Some(bytes) = csv_content.get_untracked() else { return };
+        set_rows.set(Vec::new());
+        set_selected.set(Vec::new());
+        set_message.set(None);
+        set_busy.set(true);
+        spawn_local(async move {
+            let account = Some(account.get_untracked()).filter(|a| !a.trim().is_empty());
+            show_preview(preview_csv_import(&bytes, &mapping, account).await);
+            set_busy.set(false);
+        });
+    };
+
+    let toggle = move |line: u64| {
+        set_selected.update(|lines| {
+            if let Some(pos) = lines.iter().position(|l| *l == line) {
+                lines.remove(pos);
+            } else {
+                lines.push(line);
+            }
+        });
+    };
+
+    // With no category picked, a row's `category_name` is created on import
+    let set_category = move |line: u64, category_id: Option<i64>| {
+        set_rows.update(|rows| {
+            if let Some(row) = rows.iter_mut().find(|r| r.line == line) {
+                row.category_id = category_id;
+            }
+        });
+    };
+
+    // Map every row naming an unknown category at once
+    let map_category_name = move |name: &str, category_id: Option<i64>| {
+        set_rows.update(|rows| {
+            for row in rows.iter_mut().filter(|r| r.category_name.as_deref() == Some(name)) {
+                row.category_id = category_id;
+            }
+        });
+    };
+
+    // Unknown category names in the file, with the category each is mapped to
+    let unknown_categories = move || {
+        let mut names: Vec<(String, Option<i64>)> = Vec::new();
+        for row in rows.get() {
+            if let Some(name) = row.category_name {
+                if !names.iter().any(|(n, _)| *n == name) {
+                    names.push((name, row.category_id));
+                }
+            }
+        }
+        names
+    };
+
+    let import = move |_| {
+        let lines = selected.get_untracked();
+        let chosen: Vec<ImportRow> = rows
+            .get_untracked()
+            .into_iter()
+            .filter(|r| lines.contains(&r.line))
+            .collect();
+        if chosen.is_empty() {
+            set_message.set(Some("请至少选择一条记录".to_string()));
+            return;
+        }
+        set_busy.set(true);
+        set_message.set(None);
+        spawn_local(async move {
+            match commit_import(source.get_untracked(), &chosen).await {
+                Ok(result) => {
+                    set_message.set(Some(format!("已导入 {} 条记录", result.imported)));
+                    set_rows.set(Vec::new());
+                    set_selected.set(Vec::new());
+                    set_csv_content.set(None);
+                    set_sniff.set(None);
+                    on_imported();
+                }
+                Err(e) => set_message.set(Some(format!("导入失败: {}", e))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <div class="annual-card data-card bill-import">
+            <h3>"导入账单"</h3>
+            <div class="filter-row">
+                <select on:change=move |ev| {
+                    if let Some(s) = event_target_value(&ev).parse::<usize>().ok().and_then(|i| ImportSource::ALL.get(i)) {
+                        set_source.set(*s);
+                        set_csv_content.set(None);
+                        set_sniff.set(None);
+                    }
+                }>
+                    {ImportSource::ALL.into_iter().enumerate().map(|(i, s)| view! {
+                        <option value=i.to_string() selected=move || source.get() == s>{s.label()}</option>
+                    }).collect_view()}
+                </select>
+                <input
+                    type="text"
+                    placeholder="账户（可选，如 招行信用卡）"
+                    prop:value=move || account.get()
+                    on:input=move |ev| set_account.set(event_target_value(&ev))
+                />
+                <input type="file" accept=move || source.get().accept() on:change=on_file />
+            </div>
+            <div class="annual-note">"导入前会先预览；退款、交易关闭、不计收支、账户间转账、已导入和疑似重复的记录默认不勾选。OFX 未填账户时使用文件中的账号"</div>
+
+            <CsvMappingForm sniff=sniff busy=busy on_preview=on_mapped />
+
+            <Show when=move || !rows.get().is_empty()>
+                <div class="import-summary">
+                    {move || format!("共 {} 条，已选 {} 条", rows.get().len(), selected.get().len())}
+                </div>
+                <Show when=move || !unknown_categories().is_empty()>
+                    <div class="import-unknown">
+                        <span>"文件中的新分类："</span>
+                        {move || unknown_categories().into_iter().map(|(name, mapped)| {
+                            let label = name.clone();
+                            view! {
+                                <label class="csv-field">
+                                    <span>{label}</span>
+                                    <select on:change=move |ev| map_category_name(&name, event_target_value(&ev).parse().ok())>
+                                        <option value="" selected=mapped.is_none()>"新建"</option>
+                                        {categories.get().into_iter().map(|cat| view! {
+                                            <option value=cat.id.to_string() selected=mapped == Some(cat.id)>
+                                                {format!("{} {}", cat.icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string()), cat.name)}
+                                            </option>
+                                        }).collect_view()}
+                                    </select>
+                                </label>
+                            }
+                        }).collect_view()}
+                    </div>
+                </Show>
+                <div class="import-rows">
+                    <For each=move || rows.get() key=|row| (row.line, row.category_id) let:row>
+                        {
+                            let line = row.line;
+                            let category_id = row.category_id;
+                            let new_category = row.category_name.clone().map(|name| format!("新建「{}」", name));
+                            view! {
+                                <div class=move || {
+                                    if selected.get().contains(&line) { "import-row" } else { "import-row unselected" }
+                                }>
+                                    <input
+                                        type="checkbox"
+                                        prop:checked=move || selected.get().contains(&line)
+                                        on:change=move |_| toggle(line)
+                                    />
+                                    <span class="import-date">{row.transaction_date.get(..16).unwrap_or(&row.transaction_date).to_string()}</span>
+                                    <span class=if row.amount < 0.0 { "import-amount tx-amount-negative" } else { "import-amount tx-amount-positive" }>
+                                        {format!("{:+.2}", row.amount)}
+                                    </span>
+                                    <span class="import-note">{row.note.clone().unwrap_or_default()}</span>
+                                    {row.account.clone().map(|account| view! { <span class="import-account">{account}</span> })}
+                                    <select on:change=move |ev| set_category(line, event_target_value(&ev).parse().ok())>
+                                        <option value="" selected=category_id.is_none()>
+                                            {new_category.unwrap_or_else(|| "选择分类".to_string())}
+                                        </option>
+                                        {move || categories.get().into_iter().map(|cat| view! {
+                                            <option value=cat.id.to_string() selected=category_id == Some(cat.id)>
+                                                {format!("{} {}", cat.icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string()), cat.name)}
+                                            </option>
+                                        }).collect_view()}
+                                    </select>
+                                    {
+                                        // Other likely categories learned from the ledger, one click away
+                                        let cats = categories.get_untracked();
+                                        row.suggestions.iter().filter(|s| Some(s.category_id) != category_id).filter_map(|s| {
+                                            let cat = cats.iter().find(|c| c.id == s.category_id)?;
+                                            let id = cat.id;
+                                            Some(view! {
+                                                <button class="import-suggestion" title=format!("可能性 {:.0}%", s.probability * 100.0)
+                                                    on:click=move |_| set_category(line, Some(id))>
+                                                    {cat.icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string())}
+                                                </button>
+                                            })
+                                        }).collect_view()
+                                    }
+                                    {row.skip_reason.clone().map(|reason| view! { <span class="import-skip">{reason}</span> })}
+                                    {row.duplicate_of.clone().map(|existing| view! {
+                                        <span class="import-skip" title=format!("账本中已有：{}", existing)>"疑似重复"</span>
+                                    })}
+                                </div>
+                            }
+                        }
+                    </For>
+                </div>
+                <div class="data-actions">
+                    <button class="btn-primary" disabled=move || busy.get() on:click=import>
+                        {move || format!("导入所选（{}）", selected.get().len())}
+                    </button>
+                    <button on:click=move |_| {
+                        set_rows.set(Vec::new());
+                        set_selected.set(Vec::new());
+                    }>"取消"</button>
+                </div>
+            </Show>
+
+            {move || message.get().map(|m| view! { <div class="annual-message">{m}</div> })}
+        </div>
+    }
+}
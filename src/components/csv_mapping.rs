@@ -0,0 +1,285 @@
+//! Column mapping for the generic CSV import, with saved presets.
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+use crate::types::{CsvMapping, CsvPreset, CsvSniff, SignConvention};
+use crate::shared::{delete_csv_preset, fetch_csv_presets, save_csv_preset};
+
+/// Date formats offered in the mapping form, with an example
+const DATE_FORMATS: [(&str, &str); 10] = [
+    ("%Y-%m-%d", "2024-03-02"),
+    ("%Y/%m/%d", "2024/03/02"),
+    ("%Y-%m-%d %H:%M:%S", "2024-03-02 12:30:00"),
+    ("%Y-%m-%d %H:%M", "2024-03-02 12:30"),
+    ("%Y/%m/%d %H:%M:%S", "2024/03/02 12:30:00"),
+    ("%Y/%m/%d %H:%M", "2024/03/02 12:30"),
+    ("%Y年%m月%d日", "2024年03月02日"),
+    ("%Y%m%d", "20240302"),
+    ("%m/%d/%Y", "03/02/2024"),
+    ("%d/%m/%Y", "02/03/2024"),
+];
+
+/// Delimiters offered in the mapping form
+const DELIMITERS: [(char, &str); 4] = [(',', "逗号"), ('\t', "制表符"), (';', "分号"), ('|', "竖线")];
+
+/// Header names that usually mean each column
+const DATE_NAMES: [&str; 3] = ["日期", "时间", "date"];
+const AMOUNT_NAMES: [&str; 2] = ["金额", "amount"];
+const TYPE_NAMES: [&str; 3] = ["类型", "收支", "type"];
+const CATEGORY_NAMES: [&str; 2] = ["分类", "category"];
+const NOTE_NAMES: [&str; 4] = ["备注", "说明", "note", "memo"];
+
+/// First column whose header contains one of `names`
+fn find_column(header: &[String], names: &[&str]) -> Option<usize> {
+    header.iter().position(|cell| {
+        let cell = cell.to_lowercase();
+        names.iter().any(|name| cell.contains(name))
+    })
+}
+
+/// A starting mapping guessed from the first line of the file
+fn guess_mapping(sniff: &CsvSniff) -> CsvMapping {
+    let header = sniff.rows.first().cloned().unwrap_or_default();
+    let type_column = find_column(&header, &TYPE_NAMES);
+    CsvMapping {
+        delimiter: sniff.delimiter,
+        skip_rows: 0,
+        has_header: true,
+        date_column: find_column(&header, &DATE_NAMES).unwrap_or(0),
+        date_format: DATE_FORMATS[0].0.to_string(),
+        amount_column: find_column(&header, &AMOUNT_NAMES).unwrap_or(1),
+        sign: if type_column.is_some() { SignConvention::TypeColumn } else { SignConvention::Signed },
+        type_column,
+        category_column: find_column(&header, &CATEGORY_NAMES),
+        note_column: find_column(&header, &NOTE_NAMES),
+    }
+}
+
+#[component]
+pub fn CsvMappingForm(
+    sniff: ReadSignal<Option<CsvSniff>>,
+    busy: ReadSignal<bool>,
+    /// Called with the mapping when the user asks for a preview
+    on_preview: impl Fn(CsvMapping) + 'static + Copy,
+) -> impl IntoView {
+    let (mapping, set_mapping) = create_signal(None::<CsvMapping>);
+    let (presets, set_presets) = create_signal(Vec::<CsvPreset>::new());
+    let (preset_name, set_preset_name) = create_signal(String::new());
+    let (message, set_message) = create_signal(None::<String>);
+
+    let load_presets = move || {
+        spawn_local(async move {
+            match fetch_csv_presets().await {
+                Ok(list) => set_presets.set(list),
+                Err(e) => set_message.set(Some(format!("加载预设失败: {}", e))),
+            }
+        });
+    };
+    load_presets();
+
+    // Start over from a guess whenever another file is sniffed
+    create_effect(move |_| {
+        set_mapping.set(sniff.get().as_ref().map(guess_mapping));
+    });
+
+    let update = move |change: &dyn Fn(&mut CsvMapping)| {
+        set_mapping.update(|m| {
+            if let Some(m) = m.as_mut() {
+                change(m);
+            }
+        });
+    };
+
+    // "第 n 列：header" (or the first value when there is no header)
+    let columns = move || {
+        let (Some(sniff), Some(mapping)) = (sniff.get(), mapping.get()) else { return Vec::new() };
+        let first = sniff.rows.get(mapping.skip_rows).cloned().unwrap_or_default();
+        let width = sniff.rows.iter().map(|r| r.len()).max().unwrap_or(0);
+        (0..width)
+            .map(|i| {
+                let text = first.get(i).map(|c| c.trim()).unwrap_or_default();
+                (i, format!("第 {} 列：{}", i + 1, text.chars().take(12).collect::<String>()))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let column_select = move |label: &'static str, get: fn(&CsvMapping) -> Option<usize>, set: fn(&mut CsvMapping, Option<usize>), optional: bool| {
+        view! {
+            <label class="csv-field">
+                <span>{label}</span>
+                <select on:change=move |ev| {
+                    let value = event_target_value(&ev).parse::<usize>().ok();
+                    update(&|m| set(m, value));
+                }>
+                    {optional.then(|| view! {
+                        <option value="" selected=move || mapping.get().as_ref().and_then(get).is_none()>"（无）"</option>
+                    })}
+                    {move || columns().into_iter().map(|(i, text)| view! {
+                        <option value=i.to_string() selected=move || mapping.get().as_ref().and_then(get) == Some(i)>{text}</option>
+                    }).collect_view()}
+                </select>
+            </label>
+        }
+    };
+
+    let apply_preset = move |preset: CsvPreset| {
+        set_preset_name.set(preset.name.clone());
+        set_mapping.set(Some(preset.mapping));
+    };
+
+    let save_preset = move |_| {
+        let Some(current) = mapping.get_untracked() else { return };
+        let name = preset_name.get_untracked();
+        if name.trim().is_empty() {
+            set_message.set(Some("请输入预设名称".to_string()));
+            return;
+        }
+        spawn_local(async move {
+            match save_csv_preset(&name, &current).await {
+                Ok(preset) => {
+                    set_message.set(Some(format!("已保存预设「{}」", preset.name)));
+                    load_presets();
+                }
+                Err(e) => set_message.set(Some(format!("保存预设失败: {}", e))),
+            }
+        });
+    };
+
+    let remove_preset = move |id: i64| {
+        spawn_local(async move {
+            match delete_csv_preset(id).await {
+                Ok(()) => load_presets(),
+                Err(e) => set_message.set(Some(format!("删除预设失败: {}", e))),
+            }
+        });
+    };
+
+    view! {
+        <Show when=move || mapping.get().is_some()>
+            <div class="csv-mapping">
+                <Show when=move || !presets.get().is_empty()>
+                    <div class="csv-presets">
+                        <span>"预设："</span>
+                        <For each=move || presets.get() key=|p| (p.id, p.name.clone()) let:preset>
+                            {
+                                let id = preset.id;
+                                let name = preset.name.clone();
+                                view! {
+                                    <span class="csv-preset">
+                                        <button on:click=move |_| apply_preset(preset.clone())>{name}</button>
+                                        <button class="csv-preset-delete" title="删除预设" on:click=move |_| remove_preset(id)>"×"</button>
+                                    </span>
+                                }
+                            }
+                        </For>
+                    </div>
+                </Show>
+
+                <div class="annual-note">
+                    {move || sniff.get().map(|s| format!("检测到编码 {}，以下为文件前 {} 行", s.encoding, s.rows.len()))}
+                </div>
+                <div class="csv-sample">
+                    <table>
+                        {move || sniff.get().map(|s| s.rows).unwrap_or_default().into_iter().map(|row| view! {
+                            <tr>{row.into_iter().map(|cell| view! { <td>{cell}</td> }).collect_view()}</tr>
+                        }).collect_view()}
+                    </table>
+                </div>
+
+                <div class="csv-fields">
+                    <label class="csv-field">
+                        <span>"分隔符"</span>
+                        <select on:change=move |ev| {
+                            if let Some(c) = event_target_value(&ev).parse::<usize>().ok().and_then(|i| DELIMITERS.get(i)) {
+                                update(&|m| m.delimiter = c.0);
+                            }
+                        }>
+                            {DELIMITERS.into_iter().enumerate().map(|(i, (c, label))| view! {
+                                <option value=i.to_string() selected=move || mapping.get().is_some_and(|m| m.delimiter == c)>{label}</option>
+                            }).collect_view()}
+                        </select>
+                    </label>
+                    <label class="csv-field">
+                        <span>"跳过开头行数"</span>
+                        <input
+                            type="number"
+                            min="0"
+                            prop:value=move || mapping.get().map(|m| m.skip_rows.to_string()).unwrap_or_default()
+                            on:change=move |ev| {
+                                let rows = event_target_value(&ev).parse().unwrap_or(0);
+                                update(&|m| m.skip_rows = rows);
+                            }
+                        />
+                    </label>
+                    <label class="csv-field csv-checkbox">
+                        <input
+                            type="checkbox"
+                            prop:checked=move || mapping.get().is_some_and(|m| m.has_header)
+                            on:change=move |ev| {
+                                let checked = event_target_checked(&ev);
+                                update(&|m| m.has_header = checked);
+                            }
+                        />
+                        <span>"第一行是表头"</span>
+                    </label>
+                </div>
+
+                <div class="csv-fields">
+                    {column_select("日期", |m| Some(m.date_column), |m, v| m.date_column = v.unwrap_or(0), false)}
+                    <label class="csv-field">
+                        <span>"日期格式"</span>
+                        <select on:change=move |ev| {
+                            if let Some(f) = event_target_value(&ev).parse::<usize>().ok().and_then(|i| DATE_FORMATS.get(i)) {
+                                update(&|m| m.date_format = f.0.to_string());
+                            }
+                        }>
+                            {DATE_FORMATS.into_iter().enumerate().map(|(i, (format, example))| view! {
+                                <option value=i.to_string() selected=move || mapping.get().is_some_and(|m| m.date_format == format)>{example}</option>
+                            }).collect_view()}
+                        </select>
+                    </label>
+                    {column_select("金额", |m| Some(m.amount_column), |m, v| m.amount_column = v.unwrap_or(0), false)}
+                    <label class="csv-field">
+                        <span>"收支方向"</span>
+                        <select on:change=move |ev| {
+                            if let Some(s) = event_target_value(&ev).parse::<usize>().ok().and_then(|i| SignConvention::ALL.get(i)) {
+                                update(&|m| m.sign = *s);
+                            }
+                        }>
+                            {SignConvention::ALL.into_iter().enumerate().map(|(i, s)| view! {
+                                <option value=i.to_string() selected=move || mapping.get().is_some_and(|m| m.sign == s)>{s.label()}</option>
+                            }).collect_view()}
+                        </select>
+                    </label>
+                    <Show when=move || mapping.get().is_some_and(|m| m.sign == SignConvention::TypeColumn)>
+                        {column_select("收支类型", |m| m.type_column, |m, v| m.type_column = v, true)}
+                    </Show>
+                    {column_select("分类", |m| m.category_column, |m, v| m.category_column = v, true)}
+                    {column_select("备注", |m| m.note_column, |m, v| m.note_column = v, true)}
+                </div>
+
+                <div class="data-actions">
+                    <button
+                        class="btn-primary"
+                        disabled=move || busy.get()
+                        on:click=move |_| {
+                            if let Some(m) = mapping.get_untracked() {
+                                on_preview(m);
+                            }
+                        }
+                    >"预览"</button>
+                    <input
+                        type="text"
+                        placeholder="预设名称，如 随手记"
+                        prop:value=move || preset_name.get()
+                        on:input=move |ev| set_preset_name.set(event_target_value(&ev))
+                    />
+                    <button on:click=save_preset>"保存为预设"</button>
+                </div>
+                <div class="annual-note">"文件中写的分类若不存在，导入时会自动新建；也可以在预览中改为已有分类"</div>
+                {move || message.get().map(|m| view! { <div class="annual-message">{m}</div> })}
+            </div>
+        </Show>
+    }
+}
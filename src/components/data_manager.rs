@@ -0,0 +1,264 @@
+//! Data manager: JSON backup and restore, local snapshots, folder backups,
+//! bill import, duplicate review.
+//!
+//! Used by the desktop "数据" tab and the mobile data view.
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+use crate::api::{picked_file, read_file_text};
+use super::backup_folder::BackupFolder;
+use super::bill_import::BillImport;
+use super::duplicate_review::DuplicateReview;
+use super::snapshot_manager::SnapshotManager;
+use crate::types::{BackupCounts, BackupInfo, Category, RestoreMode, ENCRYPTED_BACKUP_FORMAT};
+use crate::shared::{export_file, inspect_backup, restore_backup, ExportFile};
+
+/// One line per table, skipping empty ones
+pub(super) fn describe_counts(counts: &BackupCounts) -> Vec<String> {
+    [
+        ("分类", counts.categories),
+        ("交易", counts.transactions),
+        ("分期", counts.installments),
+        ("分期明细", counts.installment_details),
+        ("设置", counts.settings),
+        ("智能视图", counts.smart_views),
+        ("分类规则", counts.rules),
+        ("CSV 预设", counts.csv_presets),
+        ("周期账单", counts.recurring_bills),
+    ]
+    .into_iter()
+    .filter(|(_, count)| *count > 0)
+    .map(|(label, count)| format!("{} {} 条", label, count))
+    .collect()
+}
+
+/// Whether a picked file is a password-protected backup
+fn is_encrypted(text: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(text)
+        .is_ok_and(|value| value.get("format").and_then(|f| f.as_str()) == Some(ENCRYPTED_BACKUP_FORMAT))
+}
+
+#[component]
+pub fn DataManager(
+    categories: ReadSignal<Vec<Category>>,
+    /// Called after a restore or import so the caller can reload its data
+    on_changed: impl Fn() + 'static + Copy,
+) -> impl IntoView {
+    let (content, set_content) = create_signal(None::<String>);
+    let (info, set_info) = create_signal(None::<BackupInfo>);
+    let (confirm_replace, set_confirm_replace) = create_signal(false);
+    let (encrypt, set_encrypt) = create_signal(false);
+    let (password, set_password) = create_signal(String::new());
+    let (password_again, set_password_again) = create_signal(String::new());
+    // Password of the picked backup; also set when it is not encrypted (empty)
+    let (unlock_password, set_unlock_password) = create_signal(String::new());
+    let (locked, set_locked) = create_signal(false);
+    let (busy, set_busy) = create_signal(false);
+    let (message, set_message) = create_signal(None::<String>);
+
+    let export_backup = move |_| {
+        let password = encrypt.get_untracked().then(|| password.get_untracked());
+        if let Some(password) = &password {
+            if password != &password_again.get_untracked() {
+                set_message.set(Some("两次输入的密码不一致".to_string()));
+                return;
+            }
+        }
+        set_busy.set(true);
+        set_message.set(None);
+        spawn_local(async move {
+            let suffix = if password.is_some() { "-加密" } else { "" };
+            let file_name = format!("记账备份-{}{}.json", chrono::Local::now().format("%Y%m%d-%H%M%S"), suffix);
+            let result = export_file(
+                "export_json_backup",
+                serde_json::json!({ "password": password }),
+                ExportFile {
+                    file_name: &file_name,
+                    filter_name: "JSON",
+                    extensions: &["json"],
+                    mime_type: "application/json",
+                },
+            ).await;
+            match result {
+                Ok(Some(path)) => set_message.set(Some(format!("已备份: {}", path))),
+                Ok(None) => {}
+                Err(e) => set_message.set(Some(format!("备份失败: {}", e))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let on_file = move |ev: leptos::ev::Event| {
+        set_content.set(None);
+        set_info.set(None);
+        set_confirm_replace.set(false);
+        set_locked.set(false);
+        set_unlock_password.set(String::new());
+        set_message.set(None);
+        let Some(file) = picked_file(&ev) else { return };
+        spawn_local(async move {
+            let text = match read_file_text(&file).await {
+                Ok(text) => text,
+                Err(e) => {
+                    set_message.set(Some(format!("读取文件失败: {}", e)));
+                    return;
+                }
+            };
+            if is_encrypted(&text) {
+                set_locked.set(true);
+                set_content.set(Some(text));
+                return;
+            }
+            match inspect_backup(&text, None).await {
+                Ok(backup) => {
+                    set_info.set(Some(backup));
+                    set_content.set(Some(text));
+                }
+                Err(e) => set_message.set(Some(e)),
+            }
+        });
+    };
+
+    let unlock = move |_| {
+        let Some(text) = content.get_untracked() else { return };
+        let password = unlock_password.get_untracked();
+        set_busy.set(true);
+        set_message.set(None);
+        spawn_local(async move {
+            match inspect_backup(&text, Some(&password)).await {
+                Ok(backup) => {
+                    set_info.set(Some(backup));
+                    set_locked.set(false);
+                }
+                Err(e) => set_message.set(Some(e)),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let restore = move |mode: RestoreMode| {
+        let Some(text) = content.get_untracked() else { return };
+        let password = Some(unlock_password.get_untracked()).filter(|p| !p.is_empty());
+        set_busy.set(true);
+        set_message.set(None);
+        spawn_local(async move {
+            match restore_backup(&text, mode, password.as_deref()).await {
+                Ok(summary) => {
+                    let imported = describe_counts(&summary.imported);
+                    let skipped = describe_counts(&summary.skipped);
+                    let mut text = if imported.is_empty() {
+                        "没有需要导入的新数据".to_string()
+                    } else {
+                        format!("已导入: {}", imported.join("，"))
+                    };
+                    if !skipped.is_empty() {
+                        text.push_str(&format!("；已存在而跳过: {}", skipped.join("，")));
+                    }
+                    set_message.set(Some(text));
+                    set_content.set(None);
+                    set_info.set(None);
+                    set_unlock_password.set(String::new());
+                    on_changed();
+                }
+                Err(e) => set_message.set(Some(format!("恢复失败: {}", e))),
+            }
+            set_confirm_replace.set(false);
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <div class="data-manager">
+            <div class="section-header">
+                <h2>"数据备份"</h2>
+            </div>
+
+            <div class="annual-card data-card">
+                <h3>"导出备份"</h3>
+                <div class="annual-note">"包含全部分类、交易、分期、设置、智能视图、分类规则、CSV 导入预设和周期账单，可在其他设备上恢复"</div>
+                <label class="rule-option">
+                    <input type="checkbox" prop:checked=move || encrypt.get()
+                        on:change=move |ev| set_encrypt.set(event_target_checked(&ev)) />
+                    "设置密码加密"
+                </label>
+                <Show when=move || encrypt.get()>
+                    <div class="data-actions">
+                        <input type="password" placeholder="密码（至少 6 位）" prop:value=password
+                            on:input=move |ev| set_password.set(event_target_value(&ev)) />
+                        <input type="password" placeholder="再次输入密码" prop:value=password_again
+                            on:input=move |ev| set_password_again.set(event_target_value(&ev)) />
+                    </div>
+                    <div class="annual-note">"恢复时需要输入此密码，忘记密码将无法恢复备份"</div>
+                </Show>
+                <button class="btn-primary" disabled=move || busy.get() on:click=export_backup>
+                    "导出 JSON 备份"
+                </button>
+            </div>
+
+            <div class="annual-card data-card">
+                <h3>"从备份恢复"</h3>
+                <input type="file" accept=".json,application/json" on:change=on_file />
+
+                <Show when=move || locked.get()>
+                    <div class="annual-note">"此备份已加密"</div>
+                    <div class="data-actions">
+                        <input type="password" placeholder="备份密码" prop:value=unlock_password
+                            on:input=move |ev| set_unlock_password.set(event_target_value(&ev)) />
+                        <button class="btn-primary" disabled=move || busy.get() on:click=unlock>"解锁"</button>
+                    </div>
+                </Show>
+
+                {move || info.get().map(|backup| view! {
+                    <div class="data-backup-info">
+                        <div>{format!("备份时间: {}（格式版本 {}）", backup.exported_at, backup.version)}</div>
+                        <div>{describe_counts(&backup.counts).join("，")}</div>
+                    </div>
+                    <div class="data-actions">
+                        <button
+                            class="btn-primary"
+                            disabled=move || busy.get()
+                            on:click=move |_| restore(RestoreMode::Merge)
+                        >
+                            "合并导入"
+                        </button>
+                        {move || if confirm_replace.get() {
+                            view! {
+                                <span class="data-warning">"将清空现有数据，确定吗？"</span>
+                                <button
+                                    class="btn-danger"
+                                    disabled=move || busy.get()
+                                    on:click=move |_| restore(RestoreMode::Replace)
+                                >
+                                    "确认覆盖"
+                                </button>
+                                <button on:click=move |_| set_confirm_replace.set(false)>"取消"</button>
+                            }.into_any()
+                        } else {
+                            view! {
+                                <button
+                                    class="btn-danger"
+                                    disabled=move || busy.get()
+                                    on:click=move |_| set_confirm_replace.set(true)
+                                >
+                                    "覆盖恢复"
+                                </button>
+                            }.into_any()
+                        }}
+                    </div>
+                    <div class="annual-note">"合并导入只添加当前没有的数据；覆盖恢复会先清空当前账本"</div>
+                })}
+            </div>
+
+            {move || message.get().map(|m| view! { <div class="annual-message">{m}</div> })}
+
+            <SnapshotManager on_changed=on_changed />
+
+            <BackupFolder />
+
+            <BillImport categories=categories on_imported=on_changed />
+
+            <DuplicateReview on_changed=on_changed />
+        </div>
+    }
+}
@@ -4,8 +4,8 @@ use leptos::prelude::*;
 use leptos::task::spawn_local;
 use chrono::Datelike;
 
-use crate::types::{Category, TransactionWithCategory, MonthlySummary, InstallmentDetail};
-use crate::shared::{fetch_transactions, fetch_monthly_summary, fetch_due_installments, create_transaction, delete_transaction, validate_amount, validate_category_id, DEFAULT_ICON};
+use crate::types::{Category, MonthlySummary, InstallmentDetail, StatementFormat, TransactionKind, TransactionWithCategory};
+use crate::shared::{fetch_monthly_summary, fetch_due_installments, export_monthly_statement, create_transaction, delete_transaction, validate_amount, validate_category_id, SearchForm, SearchResults, SmartViews, TransactionFeed, DEFAULT_ICON};
 
 #[component]
 pub fn DesktopTransactionView(
@@ -15,9 +15,12 @@ pub fn DesktopTransactionView(
     set_selected_year: WriteSignal<i32>,
     set_selected_month: WriteSignal<i32>,
 ) -> impl IntoView {
-    let (transactions, set_transactions) = create_signal(Vec::<TransactionWithCategory>::new());
+    // Month's transactions, paged in as the window scrolls
+    let feed = TransactionFeed::new();
+    feed.load_on_window_scroll();
     let (monthly_summary, set_monthly_summary) = create_signal(None::<MonthlySummary>);
     let (installment_details, set_installment_details) = create_signal(Vec::<InstallmentDetail>::new());
+    let (statement_message, set_statement_message) = create_signal(None::<String>);
 
     // Form state
     let (show_add_form, set_show_add_form) = create_signal(false);
@@ -25,23 +28,69 @@ pub fn DesktopTransactionView(
     let (amount, set_amount) = create_signal(String::new());
     let (note, set_note) = create_signal(String::new());
     let (form_error, set_form_error) = create_signal(String::new());
+    // Similar transactions found on save; the user confirms or cancels
+    let (duplicates, set_duplicates) = create_signal(Vec::<TransactionWithCategory>::new());
     let (transaction_date, set_transaction_date) = create_signal({
         let now = chrono::Local::now();
         now.format("%Y-%m-%d").to_string()
     });
 
+    // Filter bar state; while a search is active the list shows search results
+    let search_form = SearchForm::new();
+    let search_results = SearchResults::new();
+    let (search_active, set_search_active) = create_signal(false);
+    let (show_more_filters, set_show_more_filters) = create_signal(false);
+    let (search_error, set_search_error) = create_signal(String::new());
+    // Advanced query row: query language input plus saved smart views
+    let (show_query, set_show_query) = create_signal(false);
+    let smart_views = SmartViews::new();
+
+    let run_search = move || {
+        match search_form.to_filter() {
+            Ok(filter) => {
+                set_search_error.set(String::new());
+                set_search_active.set(true);
+                search_results.search(filter);
+            }
+            Err(e) => set_search_error.set(e.to_string()),
+        }
+    };
+
+    let run_query = move || {
+        set_search_error.set(String::new());
+        set_search_active.set(true);
+        search_results.search_query(smart_views.query.get_untracked());
+    };
+
+    let clear_search = move |_| {
+        search_form.clear();
+        set_search_error.set(String::new());
+        set_search_active.set(false);
+    };
+
+    // Printable statement of the selected month
+    let export_statement = move |format: StatementFormat| {
+        let year = selected_year.get_untracked();
+        let month = selected_month.get_untracked();
+        set_statement_message.set(None);
+        spawn_local(async move {
+            match export_monthly_statement(year, month, format).await {
+                Ok(Some(path)) => set_statement_message.set(Some(format!("已导出: {}", path))),
+                Ok(None) => {}
+                Err(e) => set_statement_message.set(Some(format!("导出失败: {}", e))),
+            }
+        });
+    };
+
     // Load transactions for selected month
     let load_transactions = move || {
         let year = selected_year.get_untracked();
         let month = selected_month.get_untracked();
-        let set_transactions = set_transactions.clone();
         let set_monthly_summary = set_monthly_summary.clone();
         let set_installment_details = set_installment_details.clone();
 
+        feed.reset(Some((year, month)));
         spawn_local(async move {
-            if let Ok(txs) = fetch_transactions(year, month).await {
-                set_transactions.set(txs);
-            }
             if let Ok(summary) = fetch_monthly_summary(year, month).await {
                 set_monthly_summary.set(Some(summary));
             }
@@ -58,8 +107,8 @@ pub fn DesktopTransactionView(
         load_transactions();
     });
 
-    // Add transaction
-    let add_transaction = move |_| {
+    // Add transaction; `allow_duplicate` saves even if a similar one exists
+    let save_transaction = move |allow_duplicate: bool| {
         let cat_id = selected_category_id.get();
         let amt_str = amount.get();
         let dt = transaction_date.get();
@@ -90,18 +139,23 @@ pub fn DesktopTransactionView(
 
         spawn_local(async move {
             // Use shared create_transaction
-            if let Err(e) = create_transaction(cat_id, amt, &dt, note_val).await {
-                set_form_error.set(format!("保存失败: {}", e));
-                return;
+            match create_transaction(cat_id, amt, &dt, note_val, allow_duplicate).await {
+                Ok(similar) if !similar.is_empty() => {
+                    set_duplicates.set(similar);
+                    return;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    set_form_error.set(format!("保存失败: {}", e));
+                    return;
+                }
             }
 
             // Reload data using shared helpers
             let year = selected_year.get_untracked();
             let month = selected_month.get_untracked();
             
-            if let Ok(txs) = fetch_transactions(year, month).await {
-                set_transactions.set(txs);
-            }
+            feed.reset(Some((year, month)));
             if let Ok(summary) = fetch_monthly_summary(year, month).await {
                 set_monthly_summary.set(Some(summary));
             }
@@ -110,9 +164,11 @@ pub fn DesktopTransactionView(
             set_amount.set(String::new());
             set_note.set(String::new());
             set_form_error.set(String::new());
+            set_duplicates.set(Vec::new());
             set_show_add_form.set(false);
         });
     };
+    let add_transaction = move |_| save_transaction(false);
 
     view! {
         <div class="transaction-view">
@@ -149,7 +205,10 @@ pub fn DesktopTransactionView(
                 >
                     "▶"
                 </button>
+                <button on:click=move |_| export_statement(StatementFormat::Html)>"对账单"</button>
+                <button on:click=move |_| export_statement(StatementFormat::Pdf)>"PDF"</button>
             </div>
+            {move || statement_message.get().map(|m| view! { <div class="annual-message">{m}</div> })}
 
             // Monthly summary
             {move || {
@@ -177,6 +236,171 @@ pub fn DesktopTransactionView(
                 })
             }}
 
+            // Filter bar
+            <div class="filter-bar">
+                <div class="filter-row">
+                    <input
+                        type="search"
+                        placeholder="搜索备注"
+                        prop:value=move || search_form.text.get()
+                        on:input=move |ev| search_form.text.set(event_target_value(&ev))
+                        on:keydown=move |ev| if ev.key() == "Enter" { run_search() }
+                    />
+                    <select on:change=move |ev| {
+                        search_form.kind.set(match event_target_value(&ev).as_str() {
+                            "expense" => Some(TransactionKind::Expense),
+                            "income" => Some(TransactionKind::Income),
+                            _ => None,
+                        });
+                    }>
+                        <option value="" selected=move || search_form.kind.get().is_none()>"全部"</option>
+                        <option value="expense" selected=move || search_form.kind.get() == Some(TransactionKind::Expense)>"支出"</option>
+                        <option value="income" selected=move || search_form.kind.get() == Some(TransactionKind::Income)>"收入"</option>
+                    </select>
+                    <button on:click=move |_| set_show_more_filters.set(!show_more_filters.get())>
+                        {move || if show_more_filters.get() { "收起" } else { "更多筛选" }}
+                    </button>
+                    <button on:click=move |_| set_show_query.set(!show_query.get())>
+                        {move || if show_query.get() { "收起查询" } else { "高级查询" }}
+                    </button>
+                    <button class="btn-primary" on:click=move |_| run_search()>"搜索"</button>
+                    <Show when=move || search_active.get()>
+                        <button on:click=clear_search>"清除"</button>
+                    </Show>
+                </div>
+                <Show when=move || show_more_filters.get()>
+                    <div class="filter-row filter-categories">
+                        {move || categories.get().into_iter().map(|cat| {
+                            let cat_id = cat.id;
+                            view! {
+                                <button
+                                    class=move || {
+                                        if search_form.category_ids.get().contains(&cat_id) { "filter-chip selected" } else { "filter-chip" }
+                                    }
+                                    on:click=move |_| search_form.toggle_category(cat_id)
+                                >
+                                    {format!("{} {}", cat.icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string()), cat.name)}
+                                </button>
+                            }
+                        }).collect_view()}
+                    </div>
+                    <div class="filter-row">
+                        <span class="filter-label">"金额"</span>
+                        <input
+                            type="number"
+                            placeholder="最小"
+                            prop:value=move || search_form.min_amount.get()
+                            on:input=move |ev| search_form.min_amount.set(event_target_value(&ev))
+                        />
+                        <span>"-"</span>
+                        <input
+                            type="number"
+                            placeholder="最大"
+                            prop:value=move || search_form.max_amount.get()
+                            on:input=move |ev| search_form.max_amount.set(event_target_value(&ev))
+                        />
+                        <span class="filter-label">"日期"</span>
+                        <input
+                            type="date"
+                            prop:value=move || search_form.start_date.get()
+                            on:input=move |ev| search_form.start_date.set(event_target_value(&ev))
+                        />
+                        <span>"-"</span>
+                        <input
+                            type="date"
+                            prop:value=move || search_form.end_date.get()
+                            on:input=move |ev| search_form.end_date.set(event_target_value(&ev))
+                        />
+                    </div>
+                </Show>
+                <Show when=move || show_query.get()>
+                    <div class="filter-row">
+                        <input
+                            type="search"
+                            class="filter-query"
+                            placeholder="cat:食物 amt<-50 date:2024-03..2024-05 #出差"
+                            prop:value=move || smart_views.query.get()
+                            on:input=move |ev| smart_views.query.set(event_target_value(&ev))
+                            on:keydown=move |ev| if ev.key() == "Enter" { run_query() }
+                        />
+                        <button class="btn-primary" on:click=move |_| run_query()>"查询"</button>
+                        <input
+                            type="text"
+                            placeholder="视图名称"
+                            prop:value=move || smart_views.new_name.get()
+                            on:input=move |ev| smart_views.new_name.set(event_target_value(&ev))
+                        />
+                        <button on:click=move |_| smart_views.save_current()>"保存为视图"</button>
+                    </div>
+                    <div class="filter-row filter-categories">
+                        <span class="filter-label">"智能视图"</span>
+                        <For each=move || smart_views.views.get() key=|v| v.id let:view_item>
+                            <span class="filter-chip smart-view-chip">
+                                <span
+                                    class="smart-view-name"
+                                    title=view_item.query.clone()
+                                    on:click={
+                                        let query = view_item.query.clone();
+                                        move |_| {
+                                            smart_views.query.set(query.clone());
+                                            run_query();
+                                        }
+                                    }
+                                >
+                                    {view_item.name.clone()}
+                                </span>
+                                <span class="smart-view-delete" on:click=move |_| smart_views.delete(view_item.id)>"×"</span>
+                            </span>
+                        </For>
+                    </div>
+                    {move || smart_views.error.get().map(|e| view! { <div class="filter-error">{e}</div> })}
+                </Show>
+                {move || {
+                    let error = search_error.get();
+                    let error = if error.is_empty() { search_results.error.get().unwrap_or_default() } else { error };
+                    (!error.is_empty()).then(|| view! { <div class="filter-error">{error}</div> })
+                }}
+            </div>
+
+            // Search results
+            <Show when=move || search_active.get()>
+                <div class="section-header">
+                    <h2>{move || format!("搜索结果（{}条）", search_results.total_count.get())}</h2>
+                </div>
+                <div class="transaction-list">
+                    <For
+                        each=move || search_results.items.get()
+                        key=|tx| tx.id
+                        let:tx
+                    >
+                        <div class="transaction-item">
+                            <span class="tx-icon">
+                                {tx.category_icon.clone().unwrap_or_else(|| "📦".to_string())}
+                            </span>
+                            <div class="tx-details">
+                                <span class="tx-category">{tx.category_name}</span>
+                                {tx.note.map(|n| view! { <span class="tx-note">{n}</span> })}
+                            </div>
+                            <span class=move || {
+                                if tx.amount >= 0.0 { "tx-amount-positive" } else { "tx-amount-negative" }
+                            }>
+                                {format!("{:+.2}", tx.amount)}
+                            </span>
+                            <span class="tx-date">{tx.transaction_date}</span>
+                        </div>
+                    </For>
+                    <Show when=move || !search_results.loading.get() && search_results.items.get().is_empty()>
+                        <div class="chart-empty">"没有找到匹配的记录"</div>
+                    </Show>
+                    <Show when=move || search_results.has_more.get()>
+                        <button class="load-more" on:click=move |_| search_results.load_more()>
+                            {move || if search_results.loading.get() { "加载中..." } else { "加载更多" }}
+                        </button>
+                    </Show>
+                </div>
+            </Show>
+
+            <div style:display=move || if search_active.get() { "none" } else { "block" }>
             // Add transaction button
             <div class="section-header">
                 <h2>"交易记录"</h2>
@@ -188,6 +412,7 @@ pub fn DesktopTransactionView(
                         set_transaction_date.set(today);
                         // Clear any previous error
                         set_form_error.set(String::new());
+                        set_duplicates.set(Vec::new());
                     }
                     set_show_add_form.set(!is_showing);
                 }>
@@ -244,12 +469,34 @@ pub fn DesktopTransactionView(
                             value=note
                             on:input=move |ev| set_note.set(event_target_value(&ev))
                         />
-                        <button
-                            class="btn-primary"
-                            on:click=add_transaction
-                        >
-                            "保存"
-                        </button>
+                        <Show when=move || !duplicates.get().is_empty()>
+                            <div class="duplicate-warning">
+                                <div>"可能重复，已有相似的记录："</div>
+                                {move || duplicates.get().into_iter().map(|tx| view! {
+                                    <div class="duplicate-item">
+                                        {format!(
+                                            "{} {} {:+.2} {}",
+                                            tx.transaction_date.get(..10).unwrap_or(&tx.transaction_date),
+                                            tx.category_name,
+                                            tx.amount,
+                                            tx.note.clone().unwrap_or_default()
+                                        )}
+                                    </div>
+                                }).collect_view()}
+                                <div class="duplicate-actions">
+                                    <button class="btn-primary" on:click=move |_| save_transaction(true)>"仍然保存"</button>
+                                    <button on:click=move |_| set_duplicates.set(Vec::new())>"返回修改"</button>
+                                </div>
+                            </div>
+                        </Show>
+                        <Show when=move || duplicates.get().is_empty()>
+                            <button
+                                class="btn-primary"
+                                on:click=add_transaction
+                            >
+                                "保存"
+                            </button>
+                        </Show>
                     </div>
                 })
             } else {
@@ -259,7 +506,7 @@ pub fn DesktopTransactionView(
             // Transaction list
             <div class="transaction-list">
                 <For
-                    each=move || transactions.get()
+                    each=move || feed.items.get()
                     key=|tx| tx.id
                     let:tx
                 >
@@ -281,21 +528,20 @@ pub fn DesktopTransactionView(
                             class="btn-delete"
                             on:click=move |_| {
                                 let tx_id = tx.id;
-                                let set_transactions = set_transactions.clone();
                                 let selected_year = selected_year.clone();
                                 let selected_month = selected_month.clone();
                                 let set_monthly_summary = set_monthly_summary.clone();
 
                                 spawn_local(async move {
-                                    let _ = delete_transaction(tx_id).await;
+                                    if delete_transaction(tx_id).await.is_err() {
+                                        return;
+                                    }
+                                    feed.remove(tx_id);
 
                                     // Reload data using shared helpers
                                     let year = selected_year.get_untracked();
                                     let month = selected_month.get_untracked();
                                     
-                                    if let Ok(txs) = fetch_transactions(year, month).await {
-                                        set_transactions.set(txs);
-                                    }
                                     if let Ok(summary) = fetch_monthly_summary(year, month).await {
                                         set_monthly_summary.set(Some(summary));
                                     }
@@ -306,6 +552,9 @@ pub fn DesktopTransactionView(
                         </button>
                     </div>
                 </For>
+                <Show when=move || feed.loading.get()>
+                    <div class="load-more-status">"加载中..."</div>
+                </Show>
 
                 // Installment details for this month
                 <For
@@ -333,6 +582,7 @@ pub fn DesktopTransactionView(
                     </div>
                 </For>
             </div>
+            </div>
         </div>
     }
 }
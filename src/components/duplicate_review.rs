@@ -0,0 +1,113 @@
+//! Duplicate review: list likely duplicate pairs, merge or dismiss each.
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+use crate::types::{DuplicatePair, TransactionWithCategory};
+use crate::shared::{dismiss_duplicate, fetch_duplicate_pairs, merge_duplicates, DEFAULT_ICON};
+
+/// One side of a pair
+fn describe(tx: &TransactionWithCategory) -> String {
+    format!(
+        "{} {} {} {:+.2} {}",
+        tx.transaction_date.get(..16).unwrap_or(&tx.transaction_date),
+        tx.category_icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string()),
+        tx.category_name,
+        tx.amount,
+        tx.note.clone().unwrap_or_default()
+    )
+}
+
+#[component]
+pub fn DuplicateReview(
+    /// Called after a merge so the caller can reload its data
+    on_changed: impl Fn() + 'static + Copy,
+) -> impl IntoView {
+    // None until the first scan
+    let (pairs, set_pairs) = create_signal(None::<Vec<DuplicatePair>>);
+    let (busy, set_busy) = create_signal(false);
+    let (message, set_message) = create_signal(None::<String>);
+
+    let scan = move |_| {
+        set_busy.set(true);
+        set_message.set(None);
+        spawn_local(async move {
+            match fetch_duplicate_pairs().await {
+                Ok(list) => set_pairs.set(Some(list)),
+                Err(e) => set_message.set(Some(format!("查找失败: {}", e))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let merge = move |keep_id: i64, remove_id: i64| {
+        set_busy.set(true);
+        spawn_local(async move {
+            match merge_duplicates(keep_id, remove_id).await {
+                Ok(()) => {
+                    // Pairs with the deleted transaction are gone too
+                    set_pairs.update(|list| {
+                        if let Some(list) = list {
+                            list.retain(|p| p.first.id != remove_id && p.second.id != remove_id);
+                        }
+                    });
+                    on_changed();
+                }
+                Err(e) => set_message.set(Some(format!("合并失败: {}", e))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let dismiss = move |first_id: i64, second_id: i64| {
+        spawn_local(async move {
+            match dismiss_duplicate(first_id, second_id).await {
+                Ok(()) => set_pairs.update(|list| {
+                    if let Some(list) = list {
+                        list.retain(|p| !(p.first.id == first_id && p.second.id == second_id));
+                    }
+                }),
+                Err(e) => set_message.set(Some(format!("操作失败: {}", e))),
+            }
+        });
+    };
+
+    view! {
+        <div class="annual-card data-card">
+            <h3>"重复记录"</h3>
+            <div class="annual-note">"金额相同、日期相差不超过 3 天且分类或备注相近的记录。合并时保留选中的一条，另一条的备注在保留的一条没有备注时会被沿用"</div>
+            <button class="btn-primary" disabled=move || busy.get() on:click=scan>"查找重复"</button>
+
+            {move || pairs.get().map(|list| if list.is_empty() {
+                view! { <div class="annual-message">"没有发现疑似重复的记录"</div> }.into_any()
+            } else {
+                view! {
+                    <div class="import-summary">{format!("共 {} 组疑似重复", list.len())}</div>
+                    <div class="duplicate-pairs">
+                        {list.into_iter().map(|pair| {
+                            let (first_id, second_id) = (pair.first.id, pair.second.id);
+                            view! {
+                                <div class="duplicate-pair">
+                                    <div class="duplicate-side">
+                                        <span class="import-note">{describe(&pair.first)}</span>
+                                        <button disabled=move || busy.get() on:click=move |_| merge(first_id, second_id)>"保留这条"</button>
+                                    </div>
+                                    <div class="duplicate-side">
+                                        <span class="import-note">{describe(&pair.second)}</span>
+                                        <button disabled=move || busy.get() on:click=move |_| merge(second_id, first_id)>"保留这条"</button>
+                                    </div>
+                                    <div class="duplicate-actions">
+                                        <span class="import-summary">{format!("相似度 {:.0}%", pair.score * 100.0)}</span>
+                                        <button on:click=move |_| dismiss(first_id, second_id)>"不是重复"</button>
+                                    </div>
+                                </div>
+                            }
+                        }).collect_view()}
+                    </div>
+                }.into_any()
+            })}
+
+            {move || message.get().map(|m| view! { <div class="annual-message">{m}</div> })}
+        </div>
+    }
+}
@@ -1,9 +1,21 @@
 //! Desktop components module.
 
+mod backup_folder;
+mod bill_import;
 mod category_manager;
+mod csv_mapping;
+mod data_manager;
 mod desktop_transaction;
+mod duplicate_review;
 mod installment_manager;
+mod recurring_bill_manager;
+mod rule_manager;
+mod snapshot_manager;
 
+pub use backup_folder::FolderBackupPrompt;
 pub use category_manager::CategoryManager;
+pub use data_manager::DataManager;
 pub use desktop_transaction::DesktopTransactionView;
 pub use installment_manager::InstallmentManager;
+pub use recurring_bill_manager::RecurringBillManager;
+pub use rule_manager::RuleManager;
@@ -0,0 +1,159 @@
+//! Recurring bill manager: bills that come back every few months (房租,
+//! 订阅). They are not booked automatically; the calendar export reminds
+//! of them.
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+use crate::types::{NewRecurringBill, RecurringBill};
+use crate::shared::{create_recurring_bill, delete_recurring_bill, fetch_recurring_bills, validate_amount};
+
+/// "每月" / "每年" / "每 3 个月"
+fn describe_interval(months: i32) -> String {
+    match months {
+        1 => "每月".to_string(),
+        12 => "每年".to_string(),
+        n => format!("每 {} 个月", n),
+    }
+}
+
+#[component]
+pub fn RecurringBillManager() -> impl IntoView {
+    let (bills, set_bills) = create_signal(Vec::<RecurringBill>::new());
+    let (message, set_message) = create_signal(None::<String>);
+
+    // Form state
+    let (show_add_form, set_show_add_form) = create_signal(false);
+    let (name, set_name) = create_signal(String::new());
+    let (amount, set_amount) = create_signal(String::new());
+    let (interval_months, set_interval_months) = create_signal(1i32);
+    let (start_date, set_start_date) = create_signal(chrono::Local::now().format("%Y-%m-%d").to_string());
+    let (end_date, set_end_date) = create_signal(String::new());
+
+    let reload = move || {
+        spawn_local(async move {
+            match fetch_recurring_bills().await {
+                Ok(list) => set_bills.set(list),
+                Err(e) => set_message.set(Some(format!("加载周期账单失败: {}", e))),
+            }
+        });
+    };
+
+    reload();
+
+    let add_bill = move |_| {
+        let amount = match validate_amount(&amount.get()) {
+            Ok(a) => a,
+            Err(e) => {
+                set_message.set(Some(e.to_string()));
+                return;
+            }
+        };
+        let end_date = Some(end_date.get()).filter(|d| !d.is_empty());
+        let bill = NewRecurringBill {
+            name: name.get().trim().to_string(),
+            amount,
+            interval_months: interval_months.get(),
+            start_date: start_date.get(),
+            end_date,
+        };
+        set_message.set(None);
+        spawn_local(async move {
+            match create_recurring_bill(&bill).await {
+                Ok(_) => {
+                    set_name.set(String::new());
+                    set_amount.set(String::new());
+                    set_end_date.set(String::new());
+                    set_show_add_form.set(false);
+                    reload();
+                }
+                Err(e) => set_message.set(Some(format!("保存失败: {}", e))),
+            }
+        });
+    };
+
+    let on_delete = move |id: i64| {
+        spawn_local(async move {
+            match delete_recurring_bill(id).await {
+                Ok(()) => reload(),
+                Err(e) => set_message.set(Some(format!("删除失败: {}", e))),
+            }
+        });
+    };
+
+    view! {
+        <div class="installment-manager recurring-bill-manager">
+            <div class="section-header">
+                <h2>"周期账单"</h2>
+                <button class="btn-primary" on:click=move |_| {
+                    let is_showing = show_add_form.get();
+                    if !is_showing {
+                        set_start_date.set(chrono::Local::now().format("%Y-%m-%d").to_string());
+                        set_interval_months.set(1);
+                    }
+                    set_show_add_form.set(!is_showing);
+                }>
+                    {move || if show_add_form.get() { "取消" } else { "+ 新建周期账单" }}
+                </button>
+            </div>
+
+            {move || if show_add_form.get() {
+                Some(view! {
+                    <div class="add-form">
+                        <input type="text" placeholder="名称（如 房租）" value=name
+                            on:input=move |ev| set_name.set(event_target_value(&ev)) />
+                        <input type="number" placeholder="每次金额" value=amount
+                            on:input=move |ev| set_amount.set(event_target_value(&ev)) />
+                        <div class="input-group">
+                            <label>"重复:"</label>
+                            <select on:change=move |ev| {
+                                set_interval_months.set(event_target_value(&ev).parse().unwrap_or(1));
+                            }>
+                                <option value="1">"每月"</option>
+                                <option value="3">"每 3 个月"</option>
+                                <option value="6">"每 6 个月"</option>
+                                <option value="12">"每年"</option>
+                            </select>
+                        </div>
+                        <div class="input-group">
+                            <label>"首次日期:"</label>
+                            <input type="date" value=start_date on:input=move |ev| set_start_date.set(event_target_value(&ev)) />
+                        </div>
+                        <div class="input-group">
+                            <label>"结束日期（可选）:"</label>
+                            <input type="date" value=end_date on:input=move |ev| set_end_date.set(event_target_value(&ev)) />
+                        </div>
+                        <button class="btn-primary" on:click=add_bill>"保存"</button>
+                    </div>
+                })
+            } else { None }}
+
+            <div class="installment-list">
+                <For each=move || bills.get() key=|bill| bill.id let:bill>
+                    {
+                        let id = bill.id;
+                        let schedule = match &bill.end_date {
+                            Some(end) => format!("{}起 · {} · 至 {}", bill.start_date, describe_interval(bill.interval_months), end),
+                            None => format!("{}起 · {}", bill.start_date, describe_interval(bill.interval_months)),
+                        };
+                        view! {
+                            <div class="installment-item">
+                                <span class="installment-icon">"🔁"</span>
+                                <div class="installment-info">
+                                    <span class="installment-category">{bill.name}</span>
+                                    <span class="installment-dates">{schedule}</span>
+                                </div>
+                                <div class="installment-amount">
+                                    <span class="total-amount">{format!("{:.2}", bill.amount)}</span>
+                                </div>
+                                <button class="btn-delete" on:click=move |_| on_delete(id)>"删除"</button>
+                            </div>
+                        }
+                    }
+                </For>
+            </div>
+
+            {move || message.get().map(|m| view! { <div class="annual-message">{m}</div> })}
+        </div>
+    }
+}
@@ -0,0 +1,408 @@
+//! Rule manager: auto-categorization rules, their order, and re-running them
+//! on existing transactions after a dry run.
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+use crate::types::{Category, CategoryRule, NewCategoryRule, RuleChange};
+use crate::shared::{
+    apply_rule_changes, create_category_rule, delete_category_rule, fetch_category_rules, fetch_rules_on_entry,
+    parse_optional_amount, preview_rule_changes, reorder_category_rules, set_rules_on_entry, update_category_rule,
+    DEFAULT_ICON,
+};
+
+/// The rule form; amounts and tags are kept as typed
+#[derive(Clone, Default)]
+struct RuleForm {
+    /// None for a new rule
+    id: Option<i64>,
+    name: String,
+    enabled: bool,
+    pattern: String,
+    is_regex: bool,
+    min_amount: String,
+    max_amount: String,
+    account: String,
+    category_id: Option<i64>,
+    tags: String,
+    note_rewrite: String,
+}
+
+impl RuleForm {
+    fn new() -> Self {
+        RuleForm {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    fn edit(rule: &CategoryRule) -> Self {
+        RuleForm {
+            id: Some(rule.id),
+            name: rule.name.clone(),
+            enabled: rule.enabled,
+            pattern: rule.pattern.clone().unwrap_or_default(),
+            is_regex: rule.is_regex,
+            min_amount: rule.min_amount.map(|a| a.to_string()).unwrap_or_default(),
+            max_amount: rule.max_amount.map(|a| a.to_string()).unwrap_or_default(),
+            account: rule.account.clone().unwrap_or_default(),
+            category_id: rule.category_id,
+            tags: rule.tags.join(" "),
+            note_rewrite: rule.note_rewrite.clone().unwrap_or_default(),
+        }
+    }
+
+    fn to_rule(&self) -> Result<NewCategoryRule, String> {
+        let text = |value: &str| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        Ok(NewCategoryRule {
+            name: self.name.trim().to_string(),
+            enabled: self.enabled,
+            pattern: text(&self.pattern),
+            is_regex: self.is_regex,
+            min_amount: parse_optional_amount(&self.min_amount).map_err(|e| format!("金额下限: {}", e))?,
+            max_amount: parse_optional_amount(&self.max_amount).map_err(|e| format!("金额上限: {}", e))?,
+            account: text(&self.account),
+            category_id: self.category_id,
+            tags: self
+                .tags
+                .split_whitespace()
+                .map(|t| t.trim_start_matches('#').to_string())
+                .filter(|t| !t.is_empty())
+                .collect(),
+            note_rewrite: text(&self.note_rewrite),
+        })
+    }
+}
+
+/// "备注含 滴滴，金额 ≥ 100" → "交通 #出行"
+fn describe(rule: &CategoryRule, categories: &[Category]) -> (String, String) {
+    let mut conditions = Vec::new();
+    if let Some(pattern) = &rule.pattern {
+        conditions.push(if rule.is_regex { format!("备注匹配 /{}/", pattern) } else { format!("备注含「{}」", pattern) });
+    }
+    match (rule.min_amount, rule.max_amount) {
+        (Some(min), Some(max)) => conditions.push(format!("金额 {}–{}", min, max)),
+        (Some(min), None) => conditions.push(format!("金额 ≥ {}", min)),
+        (None, Some(max)) => conditions.push(format!("金额 ≤ {}", max)),
+        (None, None) => {}
+    }
+    if let Some(account) = &rule.account {
+        conditions.push(format!("账户 {}", account));
+    }
+
+    let mut actions = Vec::new();
+    if let Some(category) = rule.category_id.and_then(|id| categories.iter().find(|c| c.id == id)) {
+        actions.push(format!("{} {}", category.icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string()), category.name));
+    }
+    if let Some(note) = &rule.note_rewrite {
+        actions.push(format!("备注改为「{}」", note));
+    }
+    actions.extend(rule.tags.iter().map(|t| format!("#{}", t)));
+
+    let conditions = if conditions.is_empty() { "全部记录".to_string() } else { conditions.join("，") };
+    (conditions, actions.join(" "))
+}
+
+fn category_label(categories: &[Category], id: i64) -> String {
+    categories
+        .iter()
+        .find(|c| c.id == id)
+        .map(|c| format!("{} {}", c.icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string()), c.name))
+        .unwrap_or_else(|| "?".to_string())
+}
+
+#[component]
+pub fn RuleManager(categories: ReadSignal<Vec<Category>>) -> impl IntoView {
+    let (rules, set_rules) = create_signal(Vec::<CategoryRule>::new());
+    let (form, set_form) = create_signal(None::<RuleForm>);
+    let (on_entry, set_on_entry) = create_signal(false);
+    // Dry run of re-running the rules, and the transactions kept from it
+    let (changes, set_changes) = create_signal(None::<Vec<RuleChange>>);
+    let (kept, set_kept) = create_signal(Vec::<i64>::new());
+    let (busy, set_busy) = create_signal(false);
+    let (message, set_message) = create_signal(None::<String>);
+
+    let load_rules = move || {
+        spawn_local(async move {
+            match fetch_category_rules().await {
+                Ok(list) => set_rules.set(list),
+                Err(e) => set_message.set(Some(format!("加载规则失败: {}", e))),
+            }
+        });
+    };
+    load_rules();
+    spawn_local(async move {
+        if let Ok(enabled) = fetch_rules_on_entry().await {
+            set_on_entry.set(enabled);
+        }
+    });
+
+    let edit = move |change: &dyn Fn(&mut RuleForm)| {
+        set_form.update(|f| {
+            if let Some(f) = f.as_mut() {
+                change(f);
+            }
+        });
+    };
+
+    // Current value of a text field of the form
+    let field = move |get: fn(&RuleForm) -> String| move || form.get().map(|f| get(&f)).unwrap_or_default();
+
+    let save = move |_| {
+        let Some(current) = form.get_untracked() else { return };
+        let rule = match current.to_rule() {
+            Ok(rule) => rule,
+            Err(e) => {
+                set_message.set(Some(e));
+                return;
+            }
+        };
+        set_message.set(None);
+        spawn_local(async move {
+            let result = match current.id {
+                Some(id) => update_category_rule(id, &rule).await,
+                None => create_category_rule(&rule).await.map(|_| ()),
+            };
+            match result {
+                Ok(()) => {
+                    set_form.set(None);
+                    load_rules();
+                }
+                Err(e) => set_message.set(Some(format!("保存失败: {}", e))),
+            }
+        });
+    };
+
+    let toggle_enabled = move |rule: CategoryRule| {
+        let mut updated = NewCategoryRule::from(&rule);
+        updated.enabled = !rule.enabled;
+        spawn_local(async move {
+            match update_category_rule(rule.id, &updated).await {
+                Ok(()) => load_rules(),
+                Err(e) => set_message.set(Some(format!("保存失败: {}", e))),
+            }
+        });
+    };
+
+    let remove = move |id: i64| {
+        spawn_local(async move {
+            match delete_category_rule(id).await {
+                Ok(()) => load_rules(),
+                Err(e) => set_message.set(Some(format!("删除失败: {}", e))),
+            }
+        });
+    };
+
+    // Swap a rule with its neighbour; `offset` is -1 (up) or 1 (down)
+    let move_rule = move |index: usize, offset: isize| {
+        let mut ids: Vec<i64> = rules.get_untracked().iter().map(|r| r.id).collect();
+        let Some(other) = index.checked_add_signed(offset).filter(|i| *i < ids.len()) else { return };
+        ids.swap(index, other);
+        spawn_local(async move {
+            match reorder_category_rules(&ids).await {
+                Ok(()) => load_rules(),
+                Err(e) => set_message.set(Some(format!("排序失败: {}", e))),
+            }
+        });
+    };
+
+    let toggle_on_entry = move |ev: leptos::ev::Event| {
+        let enabled = event_target_checked(&ev);
+        spawn_local(async move {
+            match set_rules_on_entry(enabled).await {
+                Ok(()) => set_on_entry.set(enabled),
+                Err(e) => set_message.set(Some(format!("保存失败: {}", e))),
+            }
+        });
+    };
+
+    let dry_run = move |_| {
+        set_busy.set(true);
+        set_message.set(None);
+        spawn_local(async move {
+            match preview_rule_changes().await {
+                Ok(list) if list.is_empty() => {
+                    set_changes.set(None);
+                    set_message.set(Some("已有记录都符合当前规则，没有需要修改的".to_string()));
+                }
+                Ok(list) => {
+                    set_kept.set(list.iter().map(|c| c.transaction_id).collect());
+                    set_changes.set(Some(list));
+                }
+                Err(e) => set_message.set(Some(format!("预览失败: {}", e))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let toggle_kept = move |id: i64| {
+        set_kept.update(|ids| {
+            if let Some(pos) = ids.iter().position(|i| *i == id) {
+                ids.remove(pos);
+            } else {
+                ids.push(id);
+            }
+        });
+    };
+
+    let apply = move |_| {
+        let ids = kept.get_untracked();
+        let chosen: Vec<RuleChange> = changes
+            .get_untracked()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|c| ids.contains(&c.transaction_id))
+            .collect();
+        if chosen.is_empty() {
+            set_message.set(Some("请至少选择一条记录".to_string()));
+            return;
+        }
+        set_busy.set(true);
+        spawn_local(async move {
+            match apply_rule_changes(&chosen).await {
+                Ok(count) => {
+                    set_changes.set(None);
+                    set_message.set(Some(format!("已修改 {} 条记录", count)));
+                }
+                Err(e) => set_message.set(Some(format!("修改失败: {}", e))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <div class="rule-manager">
+            <div class="section-header">
+                <h2>"自动分类规则"</h2>
+                <button class="btn-primary" on:click=move |_| {
+                    set_form.set(if form.get_untracked().is_some() { None } else { Some(RuleForm::new()) });
+                }>
+                    {move || if form.get().is_some() { "取消" } else { "+ 新建规则" }}
+                </button>
+            </div>
+            <div class="annual-note">"导入账单时按顺序匹配规则，第一条符合条件的规则生效；文件中写明的分类优先于规则"</div>
+            <label class="rule-option">
+                <input type="checkbox" prop:checked=move || on_entry.get() on:change=toggle_on_entry />
+                "手动记账时也应用规则（会覆盖所选分类）"
+            </label>
+
+            <Show when=move || form.get().is_some()>
+                <div class="add-form rule-form">
+                    <input type="text" placeholder="规则名称" prop:value=field(|f| f.name.clone())
+                        on:input=move |ev| { let v = event_target_value(&ev); edit(&|f| f.name = v.clone()) } />
+                    <div class="filter-row">
+                        <input type="text" placeholder="备注/商户包含（留空表示不限）" prop:value=field(|f| f.pattern.clone())
+                            on:input=move |ev| { let v = event_target_value(&ev); edit(&|f| f.pattern = v.clone()) } />
+                        <label class="rule-option">
+                            <input type="checkbox" prop:checked=move || form.get().is_some_and(|f| f.is_regex)
+                                on:change=move |ev| { let v = event_target_checked(&ev); edit(&|f| f.is_regex = v) } />
+                            "正则表达式"
+                        </label>
+                    </div>
+                    <div class="filter-row">
+                        <span class="filter-label">"金额"</span>
+                        <input type="number" placeholder="最小" prop:value=field(|f| f.min_amount.clone())
+                            on:input=move |ev| { let v = event_target_value(&ev); edit(&|f| f.min_amount = v.clone()) } />
+                        <span>"–"</span>
+                        <input type="number" placeholder="最大" prop:value=field(|f| f.max_amount.clone())
+                            on:input=move |ev| { let v = event_target_value(&ev); edit(&|f| f.max_amount = v.clone()) } />
+                        <input type="text" placeholder="账户（仅导入，可选）" prop:value=field(|f| f.account.clone())
+                            on:input=move |ev| { let v = event_target_value(&ev); edit(&|f| f.account = v.clone()) } />
+                    </div>
+                    <div class="filter-row">
+                        <select on:change=move |ev| { let v = event_target_value(&ev).parse().ok(); edit(&|f| f.category_id = v) }>
+                            <option value="" selected=move || form.get().is_some_and(|f| f.category_id.is_none())>"不改分类"</option>
+                            {move || categories.get().into_iter().map(|cat| {
+                                let id = cat.id;
+                                view! {
+                                    <option value=id.to_string() selected=move || form.get().is_some_and(|f| f.category_id == Some(id))>
+                                        {format!("{} {}", cat.icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string()), cat.name)}
+                                    </option>
+                                }
+                            }).collect_view()}
+                        </select>
+                        <input type="text" placeholder="标签，空格分隔" prop:value=field(|f| f.tags.clone())
+                            on:input=move |ev| { let v = event_target_value(&ev); edit(&|f| f.tags = v.clone()) } />
+                        <input type="text" placeholder="备注改为（可选）" prop:value=field(|f| f.note_rewrite.clone())
+                            on:input=move |ev| { let v = event_target_value(&ev); edit(&|f| f.note_rewrite = v.clone()) } />
+                    </div>
+                    <button class="btn-primary" on:click=save>"保存"</button>
+                </div>
+            </Show>
+
+            <div class="rule-list">
+                {move || {
+                    let cats = categories.get();
+                    let list = rules.get();
+                    let count = list.len();
+                    list.into_iter().enumerate().map(|(index, rule)| {
+                        let (conditions, actions) = describe(&rule, &cats);
+                        let id = rule.id;
+                        let for_toggle = rule.clone();
+                        let for_edit = rule.clone();
+                        view! {
+                            <div class=if rule.enabled { "rule-item" } else { "rule-item disabled" }>
+                                <input type="checkbox" title="启用" prop:checked=rule.enabled
+                                    on:change=move |_| toggle_enabled(for_toggle.clone()) />
+                                <div class="rule-text">
+                                    <div class="rule-name">{rule.name.clone()}</div>
+                                    <div class="rule-detail">{format!("{} → {}", conditions, actions)}</div>
+                                </div>
+                                <button disabled=index == 0 on:click=move |_| move_rule(index, -1)>"↑"</button>
+                                <button disabled=index + 1 == count on:click=move |_| move_rule(index, 1)>"↓"</button>
+                                <button on:click=move |_| set_form.set(Some(RuleForm::edit(&for_edit)))>"编辑"</button>
+                                <button class="btn-danger" on:click=move |_| remove(id)>"删除"</button>
+                            </div>
+                        }
+                    }).collect_view()
+                }}
+            </div>
+
+            <div class="data-actions">
+                <button disabled=move || busy.get() on:click=dry_run>"重新应用到已有记录…"</button>
+            </div>
+
+            {move || changes.get().map(|list| {
+                let cats = categories.get_untracked();
+                let total = list.len();
+                view! {
+                    <div class="import-summary">
+                        {move || format!("将修改 {} 条记录，已选 {} 条（尚未保存）", total, kept.get().len())}
+                    </div>
+                    <div class="import-rows">
+                        {list.iter().map(|change| {
+                            let id = change.transaction_id;
+                            let category = if change.old_category_id == change.new_category_id {
+                                category_label(&cats, change.new_category_id)
+                            } else {
+                                format!("{} → {}", category_label(&cats, change.old_category_id), category_label(&cats, change.new_category_id))
+                            };
+                            let note = if change.old_note == change.new_note {
+                                change.new_note.clone().unwrap_or_default()
+                            } else {
+                                format!("{} → {}", change.old_note.clone().unwrap_or_default(), change.new_note.clone().unwrap_or_default())
+                            };
+                            view! {
+                                <div class=move || if kept.get().contains(&id) { "import-row" } else { "import-row unselected" }>
+                                    <input type="checkbox" prop:checked=move || kept.get().contains(&id) on:change=move |_| toggle_kept(id) />
+                                    <span class="import-date">{change.transaction_date.get(..10).unwrap_or(&change.transaction_date).to_string()}</span>
+                                    <span class="import-amount">{format!("{:+.2}", change.amount)}</span>
+                                    <span class="import-note">{format!("{}　{}", category, note)}</span>
+                                    <span class="import-skip">{change.rule_name.clone()}</span>
+                                </div>
+                            }
+                        }).collect_view()}
+                    </div>
+                    <div class="data-actions">
+                        <button class="btn-primary" disabled=move || busy.get() on:click=apply>
+                            {move || format!("应用所选（{}）", kept.get().len())}
+                        </button>
+                        <button on:click=move |_| set_changes.set(None)>"取消"</button>
+                    </div>
+                }
+            })}
+
+            {move || message.get().map(|m| view! { <div class="annual-message">{m}</div> })}
+        </div>
+    }
+}
@@ -0,0 +1,175 @@
+//! Local snapshots: list, take one now, roll back, retention policy.
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+use crate::types::{SnapshotInfo, SnapshotPolicy};
+use crate::shared::{create_snapshot, fetch_snapshot_policy, fetch_snapshots, restore_snapshot, save_snapshot_policy};
+
+/// File size for display
+fn format_size(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{} KB", bytes.div_ceil(1024))
+    }
+}
+
+#[component]
+pub fn SnapshotManager(
+    /// Called after a restore so the caller can reload its data
+    on_changed: impl Fn() + 'static + Copy,
+) -> impl IntoView {
+    let (snapshots, set_snapshots) = create_signal(Vec::<SnapshotInfo>::new());
+    // File name of the snapshot waiting for confirmation
+    let (confirm, set_confirm) = create_signal(None::<String>);
+    let (keep_last, set_keep_last) = create_signal(String::new());
+    let (keep_daily, set_keep_daily) = create_signal(String::new());
+    let (keep_weekly, set_keep_weekly) = create_signal(String::new());
+    let (busy, set_busy) = create_signal(false);
+    let (message, set_message) = create_signal(None::<String>);
+
+    let reload = move || {
+        spawn_local(async move {
+            match fetch_snapshots().await {
+                Ok(list) => set_snapshots.set(list),
+                Err(e) => set_message.set(Some(format!("加载快照失败: {}", e))),
+            }
+        });
+    };
+
+    reload();
+    spawn_local(async move {
+        if let Ok(policy) = fetch_snapshot_policy().await {
+            set_keep_last.set(policy.keep_last.to_string());
+            set_keep_daily.set(policy.keep_daily.to_string());
+            set_keep_weekly.set(policy.keep_weekly.to_string());
+        }
+    });
+
+    let take_snapshot = move |_| {
+        set_busy.set(true);
+        set_message.set(None);
+        spawn_local(async move {
+            match create_snapshot().await {
+                Ok(snapshot) => set_message.set(Some(format!("已创建快照: {}", snapshot.file_name))),
+                Err(e) => set_message.set(Some(format!("创建快照失败: {}", e))),
+            }
+            reload();
+            set_busy.set(false);
+        });
+    };
+
+    let restore = move |file_name: String| {
+        set_busy.set(true);
+        set_message.set(None);
+        spawn_local(async move {
+            match restore_snapshot(&file_name).await {
+                Ok(()) => {
+                    set_message.set(Some(format!("已恢复到快照 {}，恢复前的数据已另存为快照", file_name)));
+                    on_changed();
+                }
+                Err(e) => set_message.set(Some(format!("恢复失败: {}", e))),
+            }
+            set_confirm.set(None);
+            reload();
+            set_busy.set(false);
+        });
+    };
+
+    let save_policy = move |_| {
+        let parse = |value: String| value.trim().parse::<u32>().ok();
+        let (Some(keep_last), Some(keep_daily), Some(keep_weekly)) = (
+            parse(keep_last.get_untracked()),
+            parse(keep_daily.get_untracked()),
+            parse(keep_weekly.get_untracked()),
+        ) else {
+            set_message.set(Some("保留数量必须是非负整数".to_string()));
+            return;
+        };
+        set_busy.set(true);
+        set_message.set(None);
+        spawn_local(async move {
+            match save_snapshot_policy(SnapshotPolicy { keep_last, keep_daily, keep_weekly }).await {
+                Ok(()) => set_message.set(Some("保留策略已保存".to_string())),
+                Err(e) => set_message.set(Some(format!("保存失败: {}", e))),
+            }
+            reload();
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <div class="annual-card data-card">
+            <h3>"本地快照"</h3>
+            <div class="annual-note">"启动时、每天以及导入、恢复、批量应用规则、删除分类、合并重复交易之前会自动保存数据库副本，可随时回滚"</div>
+            <button class="btn-primary" disabled=move || busy.get() on:click=take_snapshot>"立即创建快照"</button>
+
+            {move || {
+                let list = snapshots.get();
+                if list.is_empty() {
+                    view! { <div class="import-summary">"还没有快照"</div> }.into_any()
+                } else {
+                    view! {
+                        <div class="snapshot-list">
+                            {list.into_iter().map(|snapshot| {
+                                let file_name = snapshot.file_name.clone();
+                                let is_confirming = {
+                                    let file_name = file_name.clone();
+                                    move || confirm.get().as_deref() == Some(file_name.as_str())
+                                };
+                                view! {
+                                    <div class="snapshot-item">
+                                        <span>{snapshot.created_at.clone()}</span>
+                                        <span class="import-summary">{snapshot.reason.label()}</span>
+                                        <span class="import-summary">{format_size(snapshot.size)}</span>
+                                        {move || if is_confirming() {
+                                            let file_name = file_name.clone();
+                                            view! {
+                                                <span class="data-warning">"当前数据将被替换，确定吗？"</span>
+                                                <button
+                                                    class="btn-danger"
+                                                    disabled=move || busy.get()
+                                                    on:click=move |_| restore(file_name.clone())
+                                                >
+                                                    "确认恢复"
+                                                </button>
+                                                <button on:click=move |_| set_confirm.set(None)>"取消"</button>
+                                            }.into_any()
+                                        } else {
+                                            let file_name = file_name.clone();
+                                            view! {
+                                                <button
+                                                    disabled=move || busy.get()
+                                                    on:click=move |_| set_confirm.set(Some(file_name.clone()))
+                                                >
+                                                    "恢复"
+                                                </button>
+                                            }.into_any()
+                                        }}
+                                    </div>
+                                }
+                            }).collect_view()}
+                        </div>
+                    }.into_any()
+                }
+            }}
+
+            <div class="data-actions">
+                <span class="import-summary">"保留最近"</span>
+                <input type="number" min="1" class="snapshot-keep" prop:value=keep_last
+                    on:input=move |ev| set_keep_last.set(event_target_value(&ev)) />
+                <span class="import-summary">"个，最近"</span>
+                <input type="number" min="0" class="snapshot-keep" prop:value=keep_daily
+                    on:input=move |ev| set_keep_daily.set(event_target_value(&ev)) />
+                <span class="import-summary">"天每天一个，最近"</span>
+                <input type="number" min="0" class="snapshot-keep" prop:value=keep_weekly
+                    on:input=move |ev| set_keep_weekly.set(event_target_value(&ev)) />
+                <span class="import-summary">"周每周一个"</span>
+                <button disabled=move || busy.get() on:click=save_policy>"保存"</button>
+            </div>
+
+            {move || message.get().map(|m| view! { <div class="annual-message">{m}</div> })}
+        </div>
+    }
+}
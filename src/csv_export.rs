@@ -0,0 +1,329 @@
+//! Export (数据导出) form: CSV, Excel, Beancount, ledger-cli and calendar.
+//!
+//! Picks the format, date range and categories (plus columns and encoding for
+//! CSV, accounts for the plain-text formats, reminders for the calendar), then
+//! hands the file(s) to the
+//! save dialog (desktop) or the share sheet (Android).
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+use crate::types::{Category, CsvColumn, CsvEncoding, CsvExportOptions, IcsExportOptions, PlainTextExportOptions, PlainTextFormat, XlsxExportOptions};
+use crate::shared::{export_file, ExportFile, DEFAULT_ICON};
+
+#[derive(Clone, Copy, PartialEq)]
+enum ExportFormat {
+    Csv,
+    Xlsx,
+    /// Beancount or ledger-cli; always the whole ledger
+    PlainText(PlainTextFormat),
+    /// Unpaid installment periods as calendar events
+    Ics,
+}
+
+impl ExportFormat {
+    const ALL: [ExportFormat; 5] = [
+        ExportFormat::Csv,
+        ExportFormat::Xlsx,
+        ExportFormat::PlainText(PlainTextFormat::Beancount),
+        ExportFormat::PlainText(PlainTextFormat::Ledger),
+        ExportFormat::Ics,
+    ];
+
+    fn key(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::PlainText(PlainTextFormat::Beancount) => "beancount",
+            ExportFormat::PlainText(PlainTextFormat::Ledger) => "ledger",
+            ExportFormat::Ics => "ics",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Xlsx => "Excel（xlsx）",
+            ExportFormat::PlainText(PlainTextFormat::Beancount) => "Beancount",
+            ExportFormat::PlainText(PlainTextFormat::Ledger) => "ledger-cli",
+            ExportFormat::Ics => "日历（ICS）",
+        }
+    }
+
+    /// Command, save dialog filter name and MIME type
+    fn command(self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            ExportFormat::Csv => ("export_csv", "CSV", "text/csv"),
+            ExportFormat::Xlsx => ("export_xlsx", "Excel", "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+            ExportFormat::PlainText(_) => ("export_plaintext", self.label(), "text/plain"),
+            ExportFormat::Ics => ("export_ics", "iCalendar", "text/calendar"),
+        }
+    }
+
+    /// Whether the date range and categories apply
+    fn is_filtered(self) -> bool {
+        matches!(self, ExportFormat::Csv | ExportFormat::Xlsx)
+    }
+}
+
+#[component]
+pub fn CsvExportView(
+    categories: ReadSignal<Vec<Category>>,
+    /// Set to false to leave the export form
+    show_export: WriteSignal<bool>,
+) -> impl IntoView {
+    let (format, set_format) = create_signal(ExportFormat::Csv);
+    let (start_date, set_start_date) = create_signal(String::new());
+    let (end_date, set_end_date) = create_signal(String::new());
+    let (category_ids, set_category_ids) = create_signal(Vec::<i64>::new());
+    let (columns, set_columns) = create_signal(CsvColumn::ALL.to_vec());
+    let (encoding, set_encoding) = create_signal(CsvEncoding::Utf8Bom);
+    let (include_installments, set_include_installments) = create_signal(true);
+    let (asset_account, set_asset_account) = create_signal(String::new());
+    let (currency, set_currency) = create_signal(String::new());
+    let (remind, set_remind) = create_signal(true);
+    let (reminder_days, set_reminder_days) = create_signal(1u32);
+    let (exporting, set_exporting) = create_signal(false);
+    let (message, set_message) = create_signal(None::<String>);
+
+    let toggle_category = move |id: i64| {
+        set_category_ids.update(|ids| {
+            if let Some(pos) = ids.iter().position(|c| *c == id) {
+                ids.remove(pos);
+            } else {
+                ids.push(id);
+            }
+        });
+    };
+
+    // Keep the selected columns in their canonical order
+    let toggle_column = move |column: CsvColumn| {
+        set_columns.update(|selected| {
+            if selected.contains(&column) {
+                selected.retain(|c| *c != column);
+            } else {
+                selected.push(column);
+                selected.sort_by_key(|c| CsvColumn::ALL.iter().position(|a| a == c));
+            }
+        });
+    };
+
+    let export = move |_| {
+        let non_empty = |value: String| if value.trim().is_empty() { None } else { Some(value.trim().to_string()) };
+        let format = format.get_untracked();
+        let options = match format {
+            ExportFormat::Csv => {
+                if columns.get_untracked().is_empty() {
+                    set_message.set(Some("请至少选择一列".to_string()));
+                    return;
+                }
+                serde_json::to_value(CsvExportOptions {
+                    start_date: non_empty(start_date.get_untracked()),
+                    end_date: non_empty(end_date.get_untracked()),
+                    category_ids: category_ids.get_untracked(),
+                    columns: columns.get_untracked(),
+                    encoding: encoding.get_untracked(),
+                    include_installments: include_installments.get_untracked(),
+                })
+            }
+            ExportFormat::Xlsx => serde_json::to_value(XlsxExportOptions {
+                start_date: non_empty(start_date.get_untracked()),
+                end_date: non_empty(end_date.get_untracked()),
+                category_ids: category_ids.get_untracked(),
+            }),
+            ExportFormat::PlainText(plain_text_format) => serde_json::to_value(PlainTextExportOptions {
+                format: plain_text_format,
+                asset_account: non_empty(asset_account.get_untracked()),
+                currency: non_empty(currency.get_untracked()),
+            }),
+            ExportFormat::Ics => serde_json::to_value(IcsExportOptions {
+                reminder_days: remind.get_untracked().then(|| reminder_days.get_untracked()),
+            }),
+        };
+        let options = match options {
+            Ok(options) => options,
+            Err(e) => {
+                set_message.set(Some(format!("导出失败: {}", e)));
+                return;
+            }
+        };
+
+        set_exporting.set(true);
+        set_message.set(None);
+        spawn_local(async move {
+            let (command, filter_name, mime_type) = format.command();
+            let prefix = match format {
+                ExportFormat::PlainText(_) => "记账",
+                ExportFormat::Ics => "记账-还款提醒",
+                _ => "记账明细",
+            };
+            let file_name = format!("{}-{}.{}", prefix, chrono::Local::now().format("%Y%m%d"), format.key());
+            let result = export_file(
+                command,
+                serde_json::json!({ "options": options }),
+                ExportFile {
+                    file_name: &file_name,
+                    filter_name,
+                    extensions: &[format.key()],
+                    mime_type,
+                },
+            ).await;
+            match result {
+                Ok(Some(paths)) => set_message.set(Some(format!("已导出: {}", paths))),
+                Ok(None) => {}
+                Err(e) => set_message.set(Some(format!("导出失败: {}", e))),
+            }
+            set_exporting.set(false);
+        });
+    };
+
+    view! {
+        <div class="csv-export">
+            <div class="month-selector">
+                <button on:click=move |_| show_export.set(false)>"← 返回"</button>
+                <span class="month-display">"导出"</span>
+            </div>
+
+            <div class="annual-card csv-export-form">
+                <div class="filter-row">
+                    <span class="filter-label">"格式"</span>
+                    <select on:change=move |ev| {
+                        let key = event_target_value(&ev);
+                        if let Some(chosen) = ExportFormat::ALL.into_iter().find(|f| f.key() == key) {
+                            set_format.set(chosen);
+                        }
+                    }>
+                        {ExportFormat::ALL.into_iter().map(|option| view! {
+                            <option value=option.key() selected=move || format.get() == option>{option.label()}</option>
+                        }).collect_view()}
+                    </select>
+                </div>
+
+                <Show when=move || matches!(format.get(), ExportFormat::PlainText(_))>
+                    <div class="filter-row">
+                        <span class="filter-label">"账户"</span>
+                        <input
+                            type="text"
+                            placeholder="Assets:Cash"
+                            prop:value=move || asset_account.get()
+                            on:input=move |ev| set_asset_account.set(event_target_value(&ev))
+                        />
+                        <input
+                            type="text"
+                            placeholder="CNY"
+                            prop:value=move || currency.get()
+                            on:input=move |ev| set_currency.set(event_target_value(&ev))
+                        />
+                    </div>
+                    <div class="annual-note">"导出整个账本：分类对应 Expenses: / Income: 账户，每笔记录都记到上面的资产账户；分期在开始日记入 Liabilities:Installments，已还的各期从资产账户还款"</div>
+                </Show>
+
+                <Show when=move || format.get() == ExportFormat::Ics>
+                    <label class="filter-row">
+                        <input
+                            type="checkbox"
+                            prop:checked=move || remind.get()
+                            on:change=move |ev| set_remind.set(event_target_checked(&ev))
+                        />
+                        "提前"
+                        <input
+                            type="number"
+                            min="0"
+                            max="60"
+                            prop:value=move || reminder_days.get().to_string()
+                            on:input=move |ev| {
+                                if let Ok(days) = event_target_value(&ev).parse::<u32>() {
+                                    set_reminder_days.set(days.min(60));
+                                }
+                            }
+                        />
+                        "天提醒"
+                    </label>
+                    <div class="annual-note">"每个未还的分期还款生成一个全天日程，每个周期账单生成一个重复日程；重新导入同一日历时会更新已有日程，不会重复"</div>
+                </Show>
+
+                <Show when=move || format.get().is_filtered()>
+                    <div class="filter-row">
+                        <span class="filter-label">"日期"</span>
+                        <input
+                            type="date"
+                            prop:value=move || start_date.get()
+                            on:input=move |ev| set_start_date.set(event_target_value(&ev))
+                        />
+                        <span>"-"</span>
+                        <input
+                            type="date"
+                            prop:value=move || end_date.get()
+                            on:input=move |ev| set_end_date.set(event_target_value(&ev))
+                        />
+                    </div>
+
+                    <div class="filter-row">
+                        <span class="filter-label">"分类"</span>
+                        {move || categories.get().into_iter().map(|cat| {
+                            let cat_id = cat.id;
+                            view! {
+                                <button
+                                    class=move || {
+                                        if category_ids.get().contains(&cat_id) { "filter-chip selected" } else { "filter-chip" }
+                                    }
+                                    on:click=move |_| toggle_category(cat_id)
+                                >
+                                    {format!("{} {}", cat.icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string()), cat.name)}
+                                </button>
+                            }
+                        }).collect_view()}
+                    </div>
+                    <div class="annual-note">"不选分类表示导出全部分类"</div>
+                </Show>
+
+                <Show when=move || format.get() == ExportFormat::Xlsx>
+                    <div class="annual-note">"Excel 文件包含按分类和月份的汇总表、每月一张明细表以及分期还款计划"</div>
+                </Show>
+
+                <Show when=move || format.get() == ExportFormat::Csv>
+                    <div class="filter-row">
+                        <span class="filter-label">"列"</span>
+                        {CsvColumn::ALL.into_iter().map(|column| view! {
+                            <button
+                                class=move || {
+                                    if columns.get().contains(&column) { "filter-chip selected" } else { "filter-chip" }
+                                }
+                                on:click=move |_| toggle_column(column)
+                            >
+                                {column.label()}
+                            </button>
+                        }).collect_view()}
+                    </div>
+
+                    <div class="filter-row">
+                        <span class="filter-label">"编码"</span>
+                        <select on:change=move |ev| {
+                            set_encoding.set(match event_target_value(&ev).as_str() {
+                                "gb18030" => CsvEncoding::Gb18030,
+                                _ => CsvEncoding::Utf8Bom,
+                            });
+                        }>
+                            <option value="utf8_bom" selected=move || encoding.get() == CsvEncoding::Utf8Bom>"UTF-8（带 BOM，推荐 Excel 使用）"</option>
+                            <option value="gb18030" selected=move || encoding.get() == CsvEncoding::Gb18030>"GB18030"</option>
+                        </select>
+                    </div>
+
+                    <label class="filter-row">
+                        <input
+                            type="checkbox"
+                            prop:checked=move || include_installments.get()
+                            on:change=move |ev| set_include_installments.set(event_target_checked(&ev))
+                        />
+                        "同时导出分期还款计划（单独的文件）"
+                    </label>
+                </Show>
+
+                <button class="btn-primary" disabled=move || exporting.get() on:click=export>
+                    {move || if exporting.get() { "导出中..." } else { "导出" }}
+                </button>
+            </div>
+            {move || message.get().map(|m| view! { <div class="annual-message">{m}</div> })}
+        </div>
+    }
+}
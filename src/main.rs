@@ -7,6 +7,9 @@ mod shared;
 mod components;
 mod mobile;
 mod summary;
+mod charts;
+mod annual_report;
+mod csv_export;
 
 // Main app
 mod app;
@@ -0,0 +1,227 @@
+//! Mobile calendar heatmap component.
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use chrono::{Datelike, NaiveDate};
+
+use crate::types::{DailyTotal, TransactionWithCategory};
+use crate::shared::{fetch_daily_totals, fetch_transactions_by_date, month_range, DEFAULT_ICON};
+
+const WEEKDAY_LABELS: [&str; 7] = ["一", "二", "三", "四", "五", "六", "日"];
+
+/// 移动端日历视图：每天显示支出总额，并按支出强度着色
+#[component]
+pub fn MobileCalendarView(
+    selected_year: ReadSignal<i32>,
+    selected_month: ReadSignal<i32>,
+    set_selected_year: WriteSignal<i32>,
+    set_selected_month: WriteSignal<i32>,
+    show_calendar: RwSignal<bool>,
+) -> impl IntoView {
+    // 当月每日汇总
+    let daily_totals = RwSignal::new(Vec::<DailyTotal>::new());
+    // 选中的日期及当天交易
+    let selected_day = RwSignal::new(None::<String>);
+    let day_transactions = RwSignal::new(Vec::<TransactionWithCategory>::new());
+
+    // 月份变化时重新加载
+    create_effect(move |_| {
+        let year = selected_year.get();
+        let month = selected_month.get();
+        selected_day.set(None);
+        day_transactions.set(Vec::new());
+
+        spawn_local(async move {
+            let (start, end) = month_range(year, month);
+            if let Ok(days) = fetch_daily_totals(&start, &end).await {
+                daily_totals.set(days);
+            }
+        });
+    });
+
+    // 点击某天：加载当天交易
+    let select_day = move |date: String| {
+        selected_day.set(Some(date.clone()));
+        spawn_local(async move {
+            if let Ok(txs) = fetch_transactions_by_date(&date).await {
+                day_transactions.set(txs);
+            }
+        });
+    };
+
+    // 上一个月
+    let prev_month = move |_| {
+        let year = selected_year.get_untracked();
+        let month = selected_month.get_untracked();
+        if month == 1 {
+            set_selected_year.set(year - 1);
+            set_selected_month.set(12);
+        } else {
+            set_selected_month.set(month - 1);
+        }
+    };
+
+    // 下一个月
+    let next_month = move |_| {
+        let year = selected_year.get_untracked();
+        let month = selected_month.get_untracked();
+        if month == 12 {
+            set_selected_year.set(year + 1);
+            set_selected_month.set(1);
+        } else {
+            set_selected_month.set(month + 1);
+        }
+    };
+
+    view! {
+        <div style="height: 100vh; overflow-y: auto; -webkit-overflow-scrolling: touch; background: #f5f5f5;">
+            <div style="display: flex; align-items: center; justify-content: space-between; padding: 8px 16px; background: white; box-shadow: 0 2px 4px rgba(0,0,0,0.1); position: sticky; top: 0; z-index: 10;">
+                <button
+                    on:click=prev_month
+                    style="background: none; border: none; font-size: 20px; cursor: pointer; padding: 8px;"
+                >
+                    "◀"
+                </button>
+                <h2 style="margin: 0; flex: 1; text-align: center;">
+                    {move || format!("{}年{:02}月", selected_year.get(), selected_month.get())}
+                </h2>
+                <button
+                    on:click=next_month
+                    style="background: none; border: none; font-size: 20px; cursor: pointer; padding: 8px;"
+                >
+                    "▶"
+                </button>
+                <button
+                    on:click=move |_| show_calendar.set(false)
+                    title="列表"
+                    style="background: none; border: none; font-size: 20px; cursor: pointer; padding: 8px;"
+                >
+                    "📝"
+                </button>
+            </div>
+
+            // 星期标题
+            <div style="display: grid; grid-template-columns: repeat(7, 1fr); gap: 4px; padding: 8px 8px 0 8px;">
+                {WEEKDAY_LABELS.iter().map(|label| view! {
+                    <div style="text-align: center; font-size: 12px; color: #7f8c8d;">{*label}</div>
+                }).collect_view()}
+            </div>
+
+            // 日期格子
+            {move || {
+                let year = selected_year.get();
+                let month = selected_month.get() as u32;
+                let totals = daily_totals.get();
+                let selected = selected_day.get();
+                let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+                let Some(first) = NaiveDate::from_ymd_opt(year, month, 1) else {
+                    return view! { <div></div> }.into_any();
+                };
+                let next_first = if month == 12 {
+                    NaiveDate::from_ymd_opt(year + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(year, month + 1, 1)
+                };
+                let days_in_month = next_first.map(|d| (d - first).num_days()).unwrap_or(30);
+                let leading = first.weekday().num_days_from_monday() as usize;
+                let max_expense = totals.iter().map(|d| d.total_expense).fold(0.0, f64::max);
+
+                let blanks = (0..leading).map(|_| view! { <div></div> }.into_any());
+                let cells = (1..=days_in_month).map(|day| {
+                    let date = format!("{:04}-{:02}-{:02}", year, month, day);
+                    let total = totals.iter().find(|d| d.date == date).cloned();
+                    let expense = total.as_ref().map(|d| d.total_expense).unwrap_or(0.0);
+                    let has_installment = total.as_ref().map(|d| d.installment_count > 0).unwrap_or(false);
+
+                    // 支出越多颜色越深
+                    let intensity = if max_expense > 0.0 { expense / max_expense } else { 0.0 };
+                    let background = if expense > 0.0 {
+                        format!("rgba(231, 76, 60, {:.2})", 0.12 + 0.68 * intensity)
+                    } else {
+                        "white".to_string()
+                    };
+                    let color = if intensity > 0.5 { "white" } else { "#2c3e50" };
+                    let border = if selected.as_deref() == Some(date.as_str()) {
+                        "2px solid #3b82f6"
+                    } else if date == today {
+                        "2px solid #f39c12"
+                    } else {
+                        "1px solid #e0e0e0"
+                    };
+
+                    let click_date = date.clone();
+                    view! {
+                        <button
+                            on:click=move |_| select_day(click_date.clone())
+                            style=format!(
+                                "position: relative; aspect-ratio: 1; padding: 2px; border-radius: 6px; border: {}; background: {}; color: {}; display: flex; flex-direction: column; align-items: center; justify-content: center; gap: 2px; min-height: 0;",
+                                border, background, color
+                            )
+                        >
+                            <span style="font-size: 13px; font-weight: 500;">{day}</span>
+                            <span style="font-size: 9px; white-space: nowrap;">
+                                {if expense > 0.0 { format!("{:.0}", expense) } else { String::new() }}
+                            </span>
+                            {has_installment.then(|| view! {
+                                <span style="position: absolute; top: 2px; right: 3px; width: 6px; height: 6px; border-radius: 3px; background: #ffc107;"></span>
+                            })}
+                        </button>
+                    }.into_any()
+                });
+
+                view! {
+                    <div style="display: grid; grid-template-columns: repeat(7, 1fr); gap: 4px; padding: 8px;">
+                        {blanks.chain(cells).collect_view()}
+                    </div>
+                }.into_any()
+            }}
+
+            <div style="display: flex; gap: 16px; justify-content: center; font-size: 11px; color: #7f8c8d; padding-bottom: 8px;">
+                <span>"颜色越深支出越多"</span>
+                <span>
+                    <span style="display: inline-block; width: 6px; height: 6px; border-radius: 3px; background: #ffc107; margin-right: 4px;"></span>
+                    "分期到期"
+                </span>
+            </div>
+
+            // 选中日期的交易
+            <div class="mobile-list-content" style="padding-bottom: 100px;">
+                {move || selected_day.get().map(|date| {
+                    let total = daily_totals.get().into_iter().find(|d| d.date == date);
+                    view! {
+                        <div style="margin: 8px 16px; font-weight: 500; color: #2c3e50;">
+                            {format!("{} 支出 ¥{:.2}", date, total.as_ref().map(|d| d.total_expense).unwrap_or(0.0))}
+                        </div>
+                        {total.filter(|d| d.installment_count > 0).map(|d| view! {
+                            <div style="margin: 8px 16px; padding: 12px; background: #fff3cd; border-radius: 8px; border-left: 4px solid #ffc107; font-size: 14px; color: #856404;">
+                                {format!("分期到期: {}笔 共 ¥{:.2}", d.installment_count, d.installment_amount)}
+                            </div>
+                        })}
+                    }
+                })}
+                <Show when=move || selected_day.get().is_some() && day_transactions.get().is_empty()>
+                    <div class="mobile-empty-state">
+                        <div class="mobile-empty-text">"当天没有记账记录"</div>
+                    </div>
+                </Show>
+                <For each=move || day_transactions.get() key=|tx| tx.id let:tx>
+                    <div class="mobile-transaction-item">
+                        <div class="mobile-tx-icon">
+                            {tx.category_icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string())}
+                        </div>
+                        <div class="mobile-tx-info">
+                            <div class="mobile-tx-category">{tx.category_name.clone()}</div>
+                            {tx.note.clone().map(|n| view! { <div class="mobile-tx-note">{n}</div> })}
+                        </div>
+                        <div class=move || {
+                            if tx.amount >= 0.0 { "mobile-tx-amount positive" } else { "mobile-tx-amount negative" }
+                        }>
+                            {format!("{:+.2}", tx.amount)}
+                        </div>
+                    </div>
+                </For>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,130 @@
+//! Mobile month comparison card component.
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+use crate::types::MonthComparison;
+use crate::shared::{fetch_month_comparison, DEFAULT_ICON};
+
+/// 格式化变化百分比，无基数时显示 "新增"
+fn format_percent(percent: Option<f64>, change: f64) -> String {
+    match percent {
+        Some(p) => format!("{:+.1}%", p),
+        None if change.abs() < 0.005 => "—".to_string(),
+        None => "新增".to_string(),
+    }
+}
+
+/// 支出增加显示红色，减少显示绿色
+fn change_color(change: f64) -> &'static str {
+    if change > 0.0 { "#e53e3e" } else if change < 0.0 { "#38a169" } else { "#7f8c8d" }
+}
+
+/// 移动端月度对比卡片：本月 vs 上月 vs 去年同月
+#[component]
+pub fn MobileComparisonCard(
+    selected_year: ReadSignal<i32>,
+    selected_month: ReadSignal<i32>,
+) -> impl IntoView {
+    let comparison = RwSignal::new(None::<MonthComparison>);
+    let expanded = RwSignal::new(false);
+
+    // 月份变化时重新加载
+    create_effect(move |_| {
+        let year = selected_year.get();
+        let month = selected_month.get();
+        spawn_local(async move {
+            if let Ok(result) = fetch_month_comparison(year, month).await {
+                comparison.set(Some(result));
+            }
+        });
+    });
+
+    view! {
+        {move || comparison.get().map(|cmp| {
+            let current = cmp.current.total_expense;
+            let mom_change = current - cmp.previous.total_expense;
+            let yoy_change = current - cmp.last_year.total_expense;
+            let mom_percent = if cmp.previous.total_expense > 0.0 {
+                Some(mom_change / cmp.previous.total_expense * 100.0)
+            } else {
+                None
+            };
+            let yoy_percent = if cmp.last_year.total_expense > 0.0 {
+                Some(yoy_change / cmp.last_year.total_expense * 100.0)
+            } else {
+                None
+            };
+            let drivers: Vec<_> = cmp.categories.iter().filter(|c| c.is_driver).cloned().collect();
+            let categories = cmp.categories.clone();
+
+            view! {
+                <div
+                    on:click=move |_| expanded.set(!expanded.get_untracked())
+                    style="margin: 8px 16px; padding: 12px; background: white; border-radius: 8px; box-shadow: 0 1px 3px rgba(0,0,0,0.08); cursor: pointer;"
+                >
+                    <div style="display: flex; justify-content: space-between; align-items: baseline;">
+                        <span style="font-size: 13px; color: #7f8c8d;">"本月支出"</span>
+                        <span style="font-size: 18px; font-weight: bold; color: #2c3e50;">{format!("¥{:.2}", current)}</span>
+                    </div>
+                    <div style="display: flex; gap: 12px; margin-top: 6px; font-size: 12px;">
+                        <div style="flex: 1;">
+                            <span style="color: #7f8c8d;">"环比 "</span>
+                            <span style=format!("color: {}; font-weight: 500;", change_color(mom_change))>
+                                {format!("{:+.2} ({})", mom_change, format_percent(mom_percent, mom_change))}
+                            </span>
+                        </div>
+                        <div style="flex: 1;">
+                            <span style="color: #7f8c8d;">"同比 "</span>
+                            <span style=format!("color: {}; font-weight: 500;", change_color(yoy_change))>
+                                {format!("{:+.2} ({})", yoy_change, format_percent(yoy_percent, yoy_change))}
+                            </span>
+                        </div>
+                    </div>
+                    {(!drivers.is_empty()).then(|| view! {
+                        <div style="margin-top: 6px; font-size: 12px; color: #555;">
+                            "主要变化: "
+                            {drivers.iter().map(|c| view! {
+                                <span style=format!("margin-right: 8px; color: {};", change_color(c.mom_change))>
+                                    {format!(
+                                        "{}{} {:+.0}",
+                                        c.category_icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string()),
+                                        c.category_name,
+                                        c.mom_change
+                                    )}
+                                </span>
+                            }).collect_view()}
+                        </div>
+                    })}
+                    <Show when=move || expanded.get()>
+                        <div style="margin-top: 8px; border-top: 1px solid #eee; padding-top: 6px;">
+                            <div style="display: grid; grid-template-columns: 2fr 1fr 1fr 1fr; gap: 4px; font-size: 11px; color: #7f8c8d;">
+                                <span>"分类"</span>
+                                <span style="text-align: right;">"本月"</span>
+                                <span style="text-align: right;">"环比"</span>
+                                <span style="text-align: right;">"同比"</span>
+                            </div>
+                            {categories.iter().map(|c| view! {
+                                <div style=format!(
+                                    "display: grid; grid-template-columns: 2fr 1fr 1fr 1fr; gap: 4px; font-size: 12px; padding: 3px 0; {}",
+                                    if c.is_driver { "font-weight: 600; background: #fff8e1;" } else { "" }
+                                )>
+                                    <span>
+                                        {format!("{} {}", c.category_icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string()), c.category_name)}
+                                    </span>
+                                    <span style="text-align: right;">{format!("{:.0}", c.current)}</span>
+                                    <span style=format!("text-align: right; color: {};", change_color(c.mom_change))>
+                                        {format_percent(c.mom_percent, c.mom_change)}
+                                    </span>
+                                    <span style=format!("text-align: right; color: {};", change_color(c.yoy_change))>
+                                        {format_percent(c.yoy_percent, c.yoy_change)}
+                                    </span>
+                                </div>
+                            }).collect_view()}
+                        </div>
+                    </Show>
+                </div>
+            }
+        })}
+    }
+}
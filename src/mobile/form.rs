@@ -3,8 +3,8 @@
 use leptos::prelude::*;
 use leptos::task::spawn_local;
 
-use crate::types::Category;
-use crate::shared::{create_transaction, validate_category_id, DEFAULT_ICON};
+use crate::types::{Category, CategorySuggestion, TransactionWithCategory};
+use crate::shared::{create_transaction, suggest_categories, validate_category_id, DEFAULT_ICON};
 /// 移动端记账表单
 #[component]
 pub fn MobileTransactionForm(
@@ -32,6 +32,34 @@ pub fn MobileTransactionForm(
     // 成功信息
     let success_message = RwSignal::new(String::new());
 
+    // 保存时发现的疑似重复记录，由用户确认是否仍然保存
+    let duplicates = RwSignal::new(Vec::<TransactionWithCategory>::new());
+
+    // 根据备注推荐的分类（从历史记录学习）
+    let suggestions = RwSignal::new(Vec::<CategorySuggestion>::new());
+    // 最近一次推荐请求的编号，丢弃过期的结果
+    let suggestion_request = RwSignal::new(0u32);
+
+    // 输入备注时更新推荐
+    create_effect(move |_| {
+        let note_val = note.get();
+        let amount = amount_display.get().parse::<f64>().unwrap_or(0.0);
+        let amount = if is_expense.get() { -amount } else { amount };
+        let request = suggestion_request.get_untracked() + 1;
+        suggestion_request.set(request);
+        if note_val.trim().is_empty() {
+            suggestions.set(Vec::new());
+            return;
+        }
+        spawn_local(async move {
+            if let Ok(list) = suggest_categories(&note_val, amount).await {
+                if suggestion_request.get_untracked() == request {
+                    suggestions.set(list);
+                }
+            }
+        });
+    });
+
     // 处理数字键盘输入
     let handle_number_input = move |num: &str| {
         let current = amount_display.get();
@@ -69,8 +97,8 @@ pub fn MobileTransactionForm(
         }
     };
 
-    // 提交记账
-    let submit_transaction = move |_| {
+    // 提交记账；allow_duplicate 为 true 时即使有相似记录也保存
+    let save_transaction = move |allow_duplicate: bool| {
         error_message.set(String::new());
         success_message.set(String::new());
         
@@ -97,14 +125,23 @@ pub fn MobileTransactionForm(
         let note_value = if note_val.is_empty() { None } else { Some(note_val) };
         
         spawn_local(async move {
-            if let Err(e) = create_transaction(cat_id, amount, &today, note_value).await {
-                error_message.set(format!("保存失败: {}", e));
-                return;
+            match create_transaction(cat_id, amount, &today, note_value, allow_duplicate).await {
+                Ok(similar) if !similar.is_empty() => {
+                    duplicates.set(similar);
+                    return;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    error_message.set(format!("保存失败: {}", e));
+                    return;
+                }
             }
             
+            duplicates.set(Vec::new());
             on_success();
         });
     };
+    let submit_transaction = move |_| save_transaction(false);
 
     view! {
         <div class="mobile-form-view">
@@ -142,9 +179,65 @@ pub fn MobileTransactionForm(
                 }
             }}
             
+            // 疑似重复提示
+            <Show when=move || !duplicates.get().is_empty()>
+                <div style="margin: 8px; padding: 10px; background: #fffbeb; border: 1px solid #fcd34d; border-radius: 8px; font-size: 13px;">
+                    <div style="font-weight: bold; margin-bottom: 4px;">"可能重复，已有相似的记录："</div>
+                    {move || duplicates.get().into_iter().map(|tx| view! {
+                        <div style="color: #6b7280;">
+                            {format!(
+                                "{} {} {:+.2} {}",
+                                tx.transaction_date.get(..10).unwrap_or(&tx.transaction_date),
+                                tx.category_name,
+                                tx.amount,
+                                tx.note.clone().unwrap_or_default()
+                            )}
+                        </div>
+                    }).collect_view()}
+                    <div style="display: flex; gap: 8px; margin-top: 8px;">
+                        <button
+                            style="flex: 1; padding: 8px; background: #3b82f6; color: white; border: none; border-radius: 6px;"
+                            on:click=move |_| save_transaction(true)
+                        >
+                            "仍然保存"
+                        </button>
+                        <button
+                            style="flex: 1; padding: 8px; background: white; border: 1px solid #ddd; border-radius: 6px;"
+                            on:click=move |_| duplicates.set(Vec::new())
+                        >
+                            "返回修改"
+                        </button>
+                    </div>
+                </div>
+            </Show>
+            
             // 分类图标选择区（顶部）
             <div class="mobile-section mobile-icon-section" style="padding: 8px; flex: 1; overflow-y: auto;">
                 <div class="mobile-section-title" style="font-size: 14px; margin-bottom: 6px;">"选择类型"</div>
+                // 推荐分类
+                <Show when=move || !suggestions.get().is_empty()>
+                    <div style="display: flex; gap: 6px; align-items: center; margin-bottom: 6px; font-size: 12px; color: #6b7280;">
+                        "推荐："
+                        {move || {
+                            let cats = categories.get();
+                            suggestions.get().into_iter().filter_map(|s| {
+                                let category = cats.iter().find(|c| c.id == s.category_id)?.clone();
+                                let id = category.id;
+                                Some(view! {
+                                    <button
+                                        style=move || format!(
+                                            "padding: 4px 8px; border-radius: 12px; font-size: 12px; border: 1px solid {};",
+                                            if selected_category_id.get() == id { "#3b82f6; background: #eff6ff" } else { "#ddd; background: white" }
+                                        )
+                                        on:click=move |_| selected_category_id.set(id)
+                                    >
+                                        {format!("{} {}", category.icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string()), category.name)}
+                                    </button>
+                                })
+                            }).collect_view()
+                        }}
+                    </div>
+                </Show>
                 <div class="mobile-icon-grid" style="display: grid; grid-template-columns: repeat(5, 1fr); gap: 6px;">
                     <For
                         each=move || categories.get()
@@ -3,24 +3,38 @@
 use leptos::prelude::*;
 use leptos::task::spawn_local;
 
-use crate::types::{TransactionWithCategory, InstallmentDetail};
-use crate::shared::{delete_transaction, fetch_transactions, DEFAULT_ICON};
+use crate::types::{InstallmentDetail, StatementFormat};
+use crate::shared::{delete_transaction, export_monthly_statement, fetch_monthly_summary, TransactionFeed, DEFAULT_ICON};
 use crate::api::JsValue;
-use crate::mobile::LiquidContainer;
+use crate::mobile::{LiquidContainer, MobileComparisonCard};
 
 /// 移动端交易列表
 #[component]
 pub fn MobileTransactionList(
-    transactions: RwSignal<Vec<TransactionWithCategory>>,
+    feed: TransactionFeed,
     selected_year: ReadSignal<i32>,
     selected_month: ReadSignal<i32>,
     set_selected_year: WriteSignal<i32>,
     set_selected_month: WriteSignal<i32>,
+    show_calendar: RwSignal<bool>,
+    on_search: impl Fn() + 'static + Copy,
 ) -> impl IntoView {
     // Current month's total expense for liquid container
     let current_month_expense = RwSignal::new(0.0);
     // 当月到期分期
     let due_installments = RwSignal::new(Vec::<InstallmentDetail>::new());
+    // 当月交易支出合计（列表分页加载，合计由后端统计）
+    let month_tx_expense = RwSignal::new(0.0);
+    
+    let load_month_expense = move || {
+        let year = selected_year.get_untracked();
+        let month = selected_month.get_untracked();
+        spawn_local(async move {
+            if let Ok(summary) = fetch_monthly_summary(year, month).await {
+                month_tx_expense.set(summary.total_expense);
+            }
+        });
+    };
     
     // 加载当月到期分期
     let load_due_installments = move || {
@@ -46,19 +60,14 @@ pub fn MobileTransactionList(
         let _year = selected_year.get();
         let _month = selected_month.get();
         load_due_installments();
+        load_month_expense();
     });
     
     // Calculate current month's total expense (transactions + installments)
     create_effect(move |_| {
-        let txs = transactions.get();
+        let tx_expense = month_tx_expense.get();
         let installments = due_installments.get();
         
-        // Sum of transaction expenses (negative amounts)
-        let tx_expense: f64 = txs.iter()
-            .filter(|tx| tx.amount < 0.0)
-            .map(|tx| tx.amount.abs())
-            .sum();
-        
         // Sum of installment amounts
         let installment_expense: f64 = installments.iter()
             .map(|i| i.amount)
@@ -68,13 +77,10 @@ pub fn MobileTransactionList(
     });
     
     let on_delete = move |tx_id: i64| {
-        let year = selected_year.get_untracked();
-        let month = selected_month.get_untracked();
-        
         spawn_local(async move {
-            let _ = delete_transaction(tx_id).await;
-            if let Ok(txs) = fetch_transactions(year, month).await {
-                transactions.set(txs);
+            if delete_transaction(tx_id).await.is_ok() {
+                feed.remove(tx_id);
+                load_month_expense();
             }
         });
     };
@@ -91,6 +97,19 @@ pub fn MobileTransactionList(
         }
     };
     
+    // 导出当月对账单（HTML，通过分享面板发送）
+    let statement_error = RwSignal::new(String::new());
+    let export_statement = move |_| {
+        let year = selected_year.get_untracked();
+        let month = selected_month.get_untracked();
+        statement_error.set(String::new());
+        spawn_local(async move {
+            if let Err(e) = export_monthly_statement(year, month, StatementFormat::Html).await {
+                statement_error.set(format!("导出失败: {}", e));
+            }
+        });
+    };
+
     // 下一个月
     let next_month = move |_| {
         let year = selected_year.get_untracked();
@@ -104,7 +123,10 @@ pub fn MobileTransactionList(
     };
     
     view! {
-        <div style="height: 100vh; overflow-y: auto; -webkit-overflow-scrolling: touch; scroll-snap-type: y proximity;">
+        <div
+            on:scroll=move |ev| feed.on_scroll(&ev)
+            style="height: 100vh; overflow-y: auto; -webkit-overflow-scrolling: touch; scroll-snap-type: y proximity;"
+        >
             // Liquid Container (占据第一屏)
             <div style="height: 100vh; scroll-snap-align: start;">
                 <LiquidContainer current_expense=current_month_expense.read_only() />
@@ -128,7 +150,37 @@ pub fn MobileTransactionList(
                     >
                         "▶"
                     </button>
+                    <button
+                        on:click=move |_| on_search()
+                        title="搜索"
+                        style="background: none; border: none; font-size: 20px; cursor: pointer; padding: 8px;"
+                    >
+                        "🔍"
+                    </button>
+                    <button
+                        on:click=move |_| show_calendar.set(true)
+                        title="日历"
+                        style="background: none; border: none; font-size: 20px; cursor: pointer; padding: 8px;"
+                    >
+                        "📅"
+                    </button>
+                    <button
+                        on:click=export_statement
+                        title="对账单"
+                        style="background: none; border: none; font-size: 20px; cursor: pointer; padding: 8px;"
+                    >
+                        "🧾"
+                    </button>
                 </div>
+                {move || {
+                    let error = statement_error.get();
+                    (!error.is_empty()).then(|| view! {
+                        <div style="margin: 8px 16px; color: #e74c3c; font-size: 14px;">{error}</div>
+                    })
+                }}
+                
+                // 本月 vs 上月 vs 去年同月
+                <MobileComparisonCard selected_year=selected_year selected_month=selected_month />
                 
                 // 当月分期到期提醒
                 {move || {
@@ -148,7 +200,7 @@ pub fn MobileTransactionList(
                 }}
                 
                 <div class="mobile-list-content" style="padding-bottom: 100px;">
-                    <Show when=move || !transactions.get().is_empty()
+                    <Show when=move || !feed.items.get().is_empty() || feed.loading.get()
                         fallback=|| view! {
                             <div class="mobile-empty-state">
                                 <div class="mobile-empty-icon">"📝"</div>
@@ -156,7 +208,7 @@ pub fn MobileTransactionList(
                                 <div class="mobile-empty-hint">"点击右下角 + 按钮开始记账"</div>
                             </div>
                         }>
-                        <For each=move || transactions.get() key=|tx| tx.id let:tx>
+                        <For each=move || feed.items.get() key=|tx| tx.id let:tx>
                             <div class="mobile-transaction-item">
                                 <div class="mobile-tx-icon">
                                     {tx.category_icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string())}
@@ -174,6 +226,9 @@ pub fn MobileTransactionList(
                                 <button class="mobile-tx-delete" on:click=move |_| on_delete(tx.id)>"×"</button>
                             </div>
                         </For>
+                        <Show when=move || feed.loading.get()>
+                            <div style="padding: 12px; text-align: center; color: #7f8c8d; font-size: 13px;">"加载中..."</div>
+                        </Show>
                     </Show>
                 </div>
             </div>
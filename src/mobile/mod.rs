@@ -7,6 +7,9 @@ mod category_form;
 mod installment_form;
 mod view;
 mod liquid_container;
+mod calendar;
+mod comparison_card;
+mod search;
 
 pub use nav::{MobileView, MobileBottomNav};
 pub use list::MobileTransactionList;
@@ -15,6 +18,9 @@ pub use category_form::MobileCategoryForm;
 pub use installment_form::MobileInstallmentForm;
 pub use view::MobileTransactionView;
 pub use liquid_container::LiquidContainer;
+pub use calendar::MobileCalendarView;
+pub use comparison_card::MobileComparisonCard;
+pub use search::MobileSearchView;
 
 // Import shared sync settings form from frontend crate
 pub use tauri_sync_db_frontend::mobile::SyncSettingsForm;
@@ -5,7 +5,7 @@ use tauri_sync_db_frontend::GenericBottomNav;
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum MobileView {
-    List, Form, Categories, CategoryForm, Installments, InstallmentForm, Summary, Settings,
+    List, Form, Categories, CategoryForm, Installments, InstallmentForm, Summary, Settings, Search, Data,
 }
 
 /// 底部导航栏
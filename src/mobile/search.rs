@@ -0,0 +1,266 @@
+//! Mobile transaction search screen.
+
+use leptos::prelude::*;
+
+use crate::types::{Category, TransactionKind};
+use crate::shared::{SearchForm, SearchResults, SmartViews, DEFAULT_ICON};
+
+/// 查询语法提示
+const QUERY_HELP: &str = "cat:食物 amt<-50 date:2024-03..2024-05 note:外卖 #出差 · 支持 OR、-排除、括号";
+
+/// 移动端搜索页：备注关键字 + 分类/金额/日期/收支筛选，或直接输入查询语法
+#[component]
+pub fn MobileSearchView(
+    categories: ReadSignal<Vec<Category>>,
+    on_back: impl Fn() + 'static + Copy,
+) -> impl IntoView {
+    let form = SearchForm::new();
+    let results = SearchResults::new();
+    // 是否展开更多筛选条件
+    let show_filters = RwSignal::new(false);
+    let form_error = RwSignal::new(None::<String>);
+    let searched = RwSignal::new(false);
+    // 查询语法模式与保存的智能视图
+    let query_mode = RwSignal::new(false);
+    let smart_views = SmartViews::new();
+
+    let run_search = move || {
+        if query_mode.get_untracked() {
+            form_error.set(None);
+            searched.set(true);
+            results.search_query(smart_views.query.get_untracked());
+            return;
+        }
+        match form.to_filter() {
+            Ok(filter) => {
+                form_error.set(None);
+                searched.set(true);
+                results.search(filter);
+            }
+            Err(e) => form_error.set(Some(e.to_string())),
+        }
+    };
+
+    let kind_button = move |label: &'static str, kind: Option<TransactionKind>| {
+        view! {
+            <button
+                on:click=move |_| form.kind.set(kind)
+                style=move || format!(
+                    "flex: 1; padding: 6px; border-radius: 6px; border: 1px solid #3b82f6; font-size: 13px; background: {}; color: {};",
+                    if form.kind.get() == kind { "#3b82f6" } else { "white" },
+                    if form.kind.get() == kind { "white" } else { "#3b82f6" }
+                )
+            >
+                {label}
+            </button>
+        }
+    };
+
+    view! {
+        <div style="height: 100vh; overflow-y: auto; -webkit-overflow-scrolling: touch; background: #f5f5f5;">
+            <div style="display: flex; align-items: center; gap: 8px; padding: 8px 12px; background: white; box-shadow: 0 2px 4px rgba(0,0,0,0.1); position: sticky; top: 0; z-index: 10;">
+                <button
+                    on:click=move |_| on_back()
+                    style="background: none; border: none; font-size: 20px; cursor: pointer; padding: 4px;"
+                >
+                    "←"
+                </button>
+                <input
+                    type="search"
+                    placeholder=move || if query_mode.get() { "cat:食物 amt<-50 #出差" } else { "搜索备注，如 京东" }
+                    prop:value=move || if query_mode.get() { smart_views.query.get() } else { form.text.get() }
+                    on:input=move |ev| {
+                        if query_mode.get_untracked() {
+                            smart_views.query.set(event_target_value(&ev));
+                        } else {
+                            form.text.set(event_target_value(&ev));
+                        }
+                    }
+                    on:keydown=move |ev| if ev.key() == "Enter" { run_search() }
+                    style="flex: 1; min-width: 0; padding: 8px 10px; border: 1px solid #ddd; border-radius: 6px; font-size: 15px;"
+                />
+                <button
+                    on:click=move |_| query_mode.set(!query_mode.get_untracked())
+                    title="查询语法"
+                    style=move || format!(
+                        "background: none; border: none; font-size: 14px; font-family: monospace; cursor: pointer; padding: 4px; color: {};",
+                        if query_mode.get() { "#3b82f6" } else { "#7f8c8d" }
+                    )
+                >
+                    "</>"
+                </button>
+                <Show when=move || !query_mode.get()>
+                    <button
+                        on:click=move |_| show_filters.set(!show_filters.get_untracked())
+                        style="background: none; border: none; font-size: 18px; cursor: pointer; padding: 4px;"
+                    >
+                        "⚙️"
+                    </button>
+                </Show>
+                <button
+                    on:click=move |_| run_search()
+                    style="padding: 8px 12px; background: #3b82f6; color: white; border: none; border-radius: 6px; font-size: 14px;"
+                >
+                    "搜索"
+                </button>
+            </div>
+
+            <Show when=move || query_mode.get()>
+                <div style="margin: 8px 12px; padding: 12px; background: white; border-radius: 8px; display: flex; flex-direction: column; gap: 8px;">
+                    <div style="font-size: 12px; color: #7f8c8d;">{QUERY_HELP}</div>
+                    <div style="display: flex; flex-wrap: wrap; gap: 6px;">
+                        <For each=move || smart_views.views.get() key=|v| v.id let:view_item>
+                            <span style="display: inline-flex; align-items: center; border-radius: 14px; border: 1px solid #3b82f6; font-size: 13px; overflow: hidden;">
+                                <button
+                                    on:click={
+                                        let query = view_item.query.clone();
+                                        move |_| {
+                                            smart_views.query.set(query.clone());
+                                            run_search();
+                                        }
+                                    }
+                                    style="padding: 4px 8px; background: #dbeafe; border: none; color: #1e40af;"
+                                >
+                                    {view_item.name.clone()}
+                                </button>
+                                <button
+                                    on:click=move |_| smart_views.delete(view_item.id)
+                                    style="padding: 4px 6px; background: white; border: none; color: #e74c3c;"
+                                >
+                                    "×"
+                                </button>
+                            </span>
+                        </For>
+                    </div>
+                    <div style="display: flex; gap: 6px;">
+                        <input
+                            type="text"
+                            placeholder="视图名称"
+                            prop:value=move || smart_views.new_name.get()
+                            on:input=move |ev| smart_views.new_name.set(event_target_value(&ev))
+                            style="flex: 1; min-width: 0; padding: 6px; border: 1px solid #ddd; border-radius: 6px; font-size: 13px;"
+                        />
+                        <button
+                            on:click=move |_| smart_views.save_current()
+                            style="padding: 6px 12px; background: white; border: 1px solid #3b82f6; color: #3b82f6; border-radius: 6px; font-size: 13px;"
+                        >
+                            "保存为视图"
+                        </button>
+                    </div>
+                    {move || smart_views.error.get().map(|e| view! {
+                        <div style="font-size: 12px; color: #c33;">{e}</div>
+                    })}
+                </div>
+            </Show>
+
+            <Show when=move || show_filters.get() && !query_mode.get()>
+                <div style="margin: 8px 12px; padding: 12px; background: white; border-radius: 8px; display: flex; flex-direction: column; gap: 10px;">
+                    <div style="display: flex; gap: 6px;">
+                        {kind_button("全部", None)}
+                        {kind_button("支出", Some(TransactionKind::Expense))}
+                        {kind_button("收入", Some(TransactionKind::Income))}
+                    </div>
+                    <div style="display: flex; flex-wrap: wrap; gap: 6px;">
+                        {move || categories.get().into_iter().map(|cat| {
+                            let cat_id = cat.id;
+                            view! {
+                                <button
+                                    on:click=move |_| form.toggle_category(cat_id)
+                                    style=move || format!(
+                                        "padding: 4px 10px; border-radius: 14px; font-size: 13px; border: 1px solid #ddd; background: {};",
+                                        if form.category_ids.get().contains(&cat_id) { "#dbeafe" } else { "white" }
+                                    )
+                                >
+                                    {format!("{} {}", cat.icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string()), cat.name)}
+                                </button>
+                            }
+                        }).collect_view()}
+                    </div>
+                    <div style="display: flex; align-items: center; gap: 6px; font-size: 13px;">
+                        <span style="width: 36px; color: #7f8c8d;">"金额"</span>
+                        <input
+                            type="number"
+                            placeholder="最小"
+                            prop:value=move || form.min_amount.get()
+                            on:input=move |ev| form.min_amount.set(event_target_value(&ev))
+                            style="flex: 1; min-width: 0; padding: 6px; border: 1px solid #ddd; border-radius: 6px;"
+                        />
+                        "-"
+                        <input
+                            type="number"
+                            placeholder="最大"
+                            prop:value=move || form.max_amount.get()
+                            on:input=move |ev| form.max_amount.set(event_target_value(&ev))
+                            style="flex: 1; min-width: 0; padding: 6px; border: 1px solid #ddd; border-radius: 6px;"
+                        />
+                    </div>
+                    <div style="display: flex; align-items: center; gap: 6px; font-size: 13px;">
+                        <span style="width: 36px; color: #7f8c8d;">"日期"</span>
+                        <input
+                            type="date"
+                            prop:value=move || form.start_date.get()
+                            on:input=move |ev| form.start_date.set(event_target_value(&ev))
+                            style="flex: 1; min-width: 0; padding: 6px; border: 1px solid #ddd; border-radius: 6px;"
+                        />
+                        "-"
+                        <input
+                            type="date"
+                            prop:value=move || form.end_date.get()
+                            on:input=move |ev| form.end_date.set(event_target_value(&ev))
+                            style="flex: 1; min-width: 0; padding: 6px; border: 1px solid #ddd; border-radius: 6px;"
+                        />
+                    </div>
+                    <button
+                        on:click=move |_| form.clear()
+                        style="align-self: flex-end; background: none; border: none; color: #7f8c8d; font-size: 13px;"
+                    >
+                        "清空条件"
+                    </button>
+                </div>
+            </Show>
+
+            {move || form_error.get().or_else(|| results.error.get()).map(|e| view! {
+                <div style="margin: 8px 12px; padding: 10px; background: #fee; color: #c33; border-radius: 6px; font-size: 13px;">{e}</div>
+            })}
+
+            <Show when=move || searched.get()>
+                <div style="margin: 8px 16px; font-size: 13px; color: #7f8c8d;">
+                    {move || format!("共 {} 条", results.total_count.get())}
+                </div>
+            </Show>
+
+            <div class="mobile-list-content" style="padding-bottom: 40px;">
+                <For each=move || results.items.get() key=|tx| tx.id let:tx>
+                    <div class="mobile-transaction-item">
+                        <div class="mobile-tx-icon">
+                            {tx.category_icon.clone().unwrap_or_else(|| DEFAULT_ICON.to_string())}
+                        </div>
+                        <div class="mobile-tx-info">
+                            <div class="mobile-tx-category">{tx.category_name.clone()}</div>
+                            {tx.note.clone().map(|n| view! { <div class="mobile-tx-note">{n}</div> })}
+                            <div class="mobile-tx-date">{tx.transaction_date.clone()}</div>
+                        </div>
+                        <div class=move || {
+                            if tx.amount >= 0.0 { "mobile-tx-amount positive" } else { "mobile-tx-amount negative" }
+                        }>
+                            {format!("{:+.2}", tx.amount)}
+                        </div>
+                    </div>
+                </For>
+                <Show when=move || results.has_more.get()>
+                    <button
+                        on:click=move |_| results.load_more()
+                        style="display: block; margin: 12px auto; padding: 8px 24px; background: white; border: 1px solid #ddd; border-radius: 16px; color: #3b82f6;"
+                    >
+                        {move || if results.loading.get() { "加载中..." } else { "加载更多" }}
+                    </button>
+                </Show>
+                <Show when=move || searched.get() && !results.loading.get() && results.items.get().is_empty()>
+                    <div class="mobile-empty-state">
+                        <div class="mobile-empty-text">"没有找到匹配的记录"</div>
+                    </div>
+                </Show>
+            </div>
+        </div>
+    }
+}
@@ -4,7 +4,8 @@ use leptos::prelude::*;
 use leptos::task::spawn_local;
 use wasm_bindgen::JsCast;
 
-use crate::types::{Category, TransactionWithCategory, InstallmentWithCategory};
+use crate::types::{Category, InstallmentWithCategory};
+use crate::shared::TransactionFeed;
 use crate::api::JsValue;
 
 pub use super::nav::{MobileView, MobileBottomNav};
@@ -13,6 +14,8 @@ pub use super::form::MobileTransactionForm;
 pub use super::category_form::MobileCategoryForm;
 pub use super::installment_form::MobileInstallmentForm;
 pub use super::SyncSettingsForm;
+pub use super::calendar::MobileCalendarView;
+pub use super::search::MobileSearchView;
 /// 移动端记账组件
 #[component]
 pub fn MobileTransactionView(
@@ -26,12 +29,15 @@ pub fn MobileTransactionView(
     // 当前视图：列表或表单
     let current_view = RwSignal::new(MobileView::List);
     
-    // 交易列表
-    let transactions = RwSignal::new(Vec::<TransactionWithCategory>::new());
+    // 交易列表（按游标分页，滚动时加载更多）
+    let feed = TransactionFeed::new();
     
     // 分期列表
     let installments = RwSignal::new(Vec::<InstallmentWithCategory>::new());
     
+    // 记账页显示日历还是列表
+    let show_calendar = RwSignal::new(false);
+    
     // 加载分类列表
     let load_categories = move || {
         spawn_local(async move {
@@ -50,19 +56,7 @@ pub fn MobileTransactionView(
         
         spawn_local(async move {
 
-            let load_fn = move || {
-                let args = serde_wasm_bindgen::to_value(&serde_json::json!({
-                    "year": year,
-                    "month": month,
-                })).unwrap();
-                spawn_local(async move {
-                    if let Ok(result) = crate::api::invoke_safe("get_transactions_by_month", args).await {
-                        if let Ok(txs) = serde_wasm_bindgen::from_value::<Vec<TransactionWithCategory>>(result) {
-                            transactions.set(txs);
-                        }
-                    }
-                });
-            };
+            let load_fn = move || feed.reset(Some((year, month)));
             
             // Initial load
             load_fn();
@@ -109,8 +103,8 @@ pub fn MobileTransactionView(
         
         // 如果从非表单视图切换到表单视图，推入历史状态
         if let Some(prev) = prev_view {
-            let is_entering_form = matches!(view, MobileView::Form | MobileView::CategoryForm | MobileView::InstallmentForm)
-                && !matches!(prev, MobileView::Form | MobileView::CategoryForm | MobileView::InstallmentForm);
+            let is_entering_form = matches!(view, MobileView::Form | MobileView::CategoryForm | MobileView::InstallmentForm | MobileView::Search | MobileView::Data)
+                && !matches!(prev, MobileView::Form | MobileView::CategoryForm | MobileView::InstallmentForm | MobileView::Search | MobileView::Data);
             
             if is_entering_form {
                 if let Some(window) = web_sys::window() {
@@ -135,9 +129,10 @@ pub fn MobileTransactionView(
             
             // 根据当前视图决定返回到哪里
             match view {
-                MobileView::Form => current_view.set(MobileView::List),
+                MobileView::Form | MobileView::Search => current_view.set(MobileView::List),
                 MobileView::CategoryForm => current_view.set(MobileView::Categories),
                 MobileView::InstallmentForm => current_view.set(MobileView::Installments),
+                MobileView::Data => current_view.set(MobileView::Settings),
                 _ => {
                     // 主视图时允许默认行为（退出应用）
                     // 但需要补回历史状态以保持一致性
@@ -176,13 +171,28 @@ pub fn MobileTransactionView(
                     <Show when=move || view_type == MobileView::List fallback=|| ()>
                         <div style="display: flex; flex-direction: column; height: 100vh; position: relative;">
                             <div style="flex: 1; overflow: hidden;">
-                                <MobileTransactionList
-                                    transactions=transactions
-                                    selected_year=selected_year
-                                    selected_month=selected_month
-                                    set_selected_year=set_selected_year
-                                    set_selected_month=set_selected_month
-                                />
+                                <Show
+                                    when=move || !show_calendar.get()
+                                    fallback=move || view! {
+                                        <MobileCalendarView
+                                            selected_year=selected_year
+                                            selected_month=selected_month
+                                            set_selected_year=set_selected_year
+                                            set_selected_month=set_selected_month
+                                            show_calendar=show_calendar
+                                        />
+                                    }
+                                >
+                                    <MobileTransactionList
+                                        feed=feed
+                                        selected_year=selected_year
+                                        selected_month=selected_month
+                                        set_selected_year=set_selected_year
+                                        set_selected_month=set_selected_month
+                                        show_calendar=show_calendar
+                                        on_search=move || current_view.set(MobileView::Search)
+                                    />
+                                </Show>
                             </div>
                             <MobileBottomNav current_view=current_view />
                             <button
@@ -194,6 +204,14 @@ pub fn MobileTransactionView(
                             </button>
                         </div>
                     </Show>
+                    <Show when=move || view_type == MobileView::Search fallback=|| ()>
+                        <div style="height: 100vh;">
+                            <MobileSearchView
+                                categories=categories
+                                on_back=move || current_view.set(MobileView::List)
+                            />
+                        </div>
+                    </Show>
                     <Show when=move || view_type == MobileView::Categories fallback=|| ()>
                         <div style="display: flex; flex-direction: column; height: 100vh; position: relative;">
                             <h2 style="margin: 0; font-size: 18px; padding: 12px 16px; background: white; border-bottom: 1px solid #e0e0e0; flex-shrink: 0;">"消费类型"</h2>
@@ -366,6 +384,12 @@ pub fn MobileTransactionView(
 
                     <Show when=move || view_type == MobileView::Settings fallback=|| ()>
                         <div style="display: flex; flex-direction: column; height: 100vh;">
+                            <button
+                                on:click=move |_| current_view.set(MobileView::Data)
+                                style="margin: 12px 16px 0; padding: 12px; border: 1px solid #e0e0e0; border-radius: 8px; background: white; font-size: 15px; text-align: left; cursor: pointer;"
+                            >
+                                "💾 数据备份与恢复"
+                            </button>
                             <div style="flex: 1; overflow: hidden;">
                                 <SyncSettingsForm
                                     on_back=move || current_view.set(MobileView::List)
@@ -374,6 +398,32 @@ pub fn MobileTransactionView(
                             <MobileBottomNav current_view=current_view />
                         </div>
                     </Show>
+
+                    <Show when=move || view_type == MobileView::Data fallback=|| ()>
+                        <div style="display: flex; flex-direction: column; height: 100vh;">
+                            <div style="display: flex; align-items: center; padding: 12px 16px; border-bottom: 1px solid #eee;">
+                                <button
+                                    on:click=move |_| current_view.set(MobileView::Settings)
+                                    style="background: none; border: none; font-size: 20px; cursor: pointer; padding: 4px;"
+                                >
+                                    "←"
+                                </button>
+                                <span style="font-size: 17px; font-weight: 600; margin-left: 8px;">"数据备份与恢复"</span>
+                            </div>
+                            <div style="flex: 1; overflow-y: auto; padding: 0 12px;">
+                                // 恢复或导入后重新加载分类、交易和分期
+                                <crate::components::DataManager categories=categories on_changed=move || {
+                                    load_categories();
+                                    feed.reload();
+                                    spawn_local(async move {
+                                        if let Ok(items) = crate::shared::fetch_installments().await {
+                                            installments.set(items);
+                                        }
+                                    });
+                                } />
+                            </div>
+                        </div>
+                    </Show>
                     </>
                 }
             }}
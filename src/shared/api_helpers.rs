@@ -1,8 +1,8 @@
 //! API helper functions to reduce boilerplate in components.
 
 use leptos::task::spawn_local;
-use crate::api::{invoke_safe, JsValue};
-use crate::types::{Category, TransactionWithCategory, MonthlySummary, InstallmentWithCategory, InstallmentDetail};
+use crate::api::{can_share_files, invoke_safe, pick_save_path, share_file, share_files, JsValue};
+use crate::types::{Category, TransactionWithCategory, MonthlySummary, InstallmentWithCategory, InstallmentDetail, CategoryTotal, MonthlyTrendPoint, DailyTotal, MonthComparison, AnnualReport, TransactionFilter, TransactionPage, SmartView, TransactionCursor, TransactionCursorPage, MonthTotals, BackupInfo, RestoreMode, RestoreSummary, SnapshotInfo, SnapshotPolicy, BackupFolderSettings, FolderBackup, ImportSource, ImportRow, ImportResult, CsvMapping, CsvSniff, CsvPreset, CreateTransactionResult, DuplicatePair, CategoryRule, NewCategoryRule, RuleChange, CategorySuggestion, StatementFormat, RecurringBill, NewRecurringBill};
 
 /// Load categories from backend
 pub async fn fetch_categories() -> Result<Vec<Category>, String> {
@@ -23,6 +23,100 @@ pub async fn fetch_transactions(year: i32, month: i32) -> Result<Vec<Transaction
         .map_err(|e| format!("Failed to parse transactions: {:?}", e))
 }
 
+/// Load transactions of a single day (YYYY-MM-DD)
+pub async fn fetch_transactions_by_date(date: &str) -> Result<Vec<TransactionWithCategory>, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "date": date }))
+        .map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("get_transactions_by_date", args).await?;
+    serde_wasm_bindgen::from_value::<Vec<TransactionWithCategory>>(result)
+        .map_err(|e| format!("Failed to parse transactions: {:?}", e))
+}
+
+/// Number of transactions loaded per page by scrolling lists
+pub const TRANSACTION_PAGE_SIZE: i64 = 100;
+
+/// Load the page of transactions after `cursor` (newest first), optionally within one month
+pub async fn fetch_transactions_page(
+    month: Option<(i32, i32)>,
+    cursor: Option<&TransactionCursor>,
+    limit: i64,
+) -> Result<TransactionCursorPage, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "year": month.map(|(year, _)| year),
+        "month": month.map(|(_, month)| month),
+        "cursor": cursor,
+        "limit": limit,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("get_transactions_page", args).await?;
+    serde_wasm_bindgen::from_value::<TransactionCursorPage>(result)
+        .map_err(|e| format!("Failed to parse transaction page: {:?}", e))
+}
+
+/// Load income/expense totals of every month with data, newest first
+pub async fn fetch_month_totals() -> Result<Vec<MonthTotals>, String> {
+    let result = invoke_safe("get_month_totals", JsValue::NULL).await?;
+    serde_wasm_bindgen::from_value::<Vec<MonthTotals>>(result)
+        .map_err(|e| format!("Failed to parse month totals: {:?}", e))
+}
+
+/// Number of transactions loaded per search page
+pub const SEARCH_PAGE_SIZE: i64 = 50;
+
+/// Search transactions, returning one page of results (newest first)
+pub async fn search_transactions(filter: &TransactionFilter, limit: i64, offset: i64) -> Result<TransactionPage, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "filter": filter,
+        "limit": limit,
+        "offset": offset,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("search_transactions", args).await?;
+    serde_wasm_bindgen::from_value::<TransactionPage>(result)
+        .map_err(|e| format!("Failed to parse transaction page: {:?}", e))
+}
+
+/// Search transactions with a filter query string, e.g. `cat:食物 amt<-50 #出差`
+pub async fn query_transactions(query: &str, limit: i64, offset: i64) -> Result<TransactionPage, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "query": query,
+        "limit": limit,
+        "offset": offset,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("query_transactions", args).await?;
+    serde_wasm_bindgen::from_value::<TransactionPage>(result)
+        .map_err(|e| format!("Failed to parse transaction page: {:?}", e))
+}
+
+/// Load saved smart views
+pub async fn fetch_smart_views() -> Result<Vec<SmartView>, String> {
+    let result = invoke_safe("get_smart_views", JsValue::NULL).await?;
+    serde_wasm_bindgen::from_value::<Vec<SmartView>>(result)
+        .map_err(|e| format!("Failed to parse smart views: {:?}", e))
+}
+
+/// Save a query as a named smart view (same name replaces the query)
+pub async fn save_smart_view(name: &str, query: &str) -> Result<SmartView, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "name": name,
+        "query": query,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("save_smart_view", args).await?;
+    serde_wasm_bindgen::from_value::<SmartView>(result)
+        .map_err(|e| format!("Failed to parse smart view: {:?}", e))
+}
+
+/// Delete a smart view
+pub async fn delete_smart_view(id: i64) -> Result<(), String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "id": id }))
+        .map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+    invoke_safe("delete_smart_view", args).await?;
+    Ok(())
+}
+
 /// Load monthly summary
 pub async fn fetch_monthly_summary(year: i32, month: i32) -> Result<MonthlySummary, String> {
     let args = serde_wasm_bindgen::to_value(&serde_json::json!({
@@ -36,21 +130,28 @@ pub async fn fetch_monthly_summary(year: i32, month: i32) -> Result<MonthlySumma
 }
 
 /// Create a new transaction
+///
+/// Unless `allow_duplicate` is set, a transaction that looks like one already
+/// in the ledger is not saved; the similar transactions are returned instead.
 pub async fn create_transaction(
     category_id: i64,
     amount: f64,
     transaction_date: &str,
     note: Option<String>,
-) -> Result<(), String> {
+    allow_duplicate: bool,
+) -> Result<Vec<TransactionWithCategory>, String> {
     let args = serde_wasm_bindgen::to_value(&serde_json::json!({
         "categoryId": category_id,
         "amount": amount,
         "transactionDate": transaction_date,
         "note": note,
+        "allowDuplicate": allow_duplicate,
     })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
     
-    let _ = invoke_safe("create_transaction", args).await?;
-    Ok(())
+    let result = invoke_safe("create_transaction", args).await?;
+    serde_wasm_bindgen::from_value::<CreateTransactionResult>(result)
+        .map(|r| r.duplicates)
+        .map_err(|e| format!("Failed to parse transaction: {:?}", e))
 }
 
 /// Create a new category
@@ -140,3 +241,482 @@ pub async fn fetch_installment_details(installment_id: i64) -> Result<Vec<Instal
     serde_wasm_bindgen::from_value::<Vec<InstallmentDetail>>(result)
         .map_err(|e| format!("Failed to parse installment details: {:?}", e))
 }
+
+/// Load all recurring bills
+pub async fn fetch_recurring_bills() -> Result<Vec<RecurringBill>, String> {
+    let result = invoke_safe("get_recurring_bills", JsValue::NULL).await?;
+    serde_wasm_bindgen::from_value::<Vec<RecurringBill>>(result)
+        .map_err(|e| format!("Failed to parse recurring bills: {:?}", e))
+}
+
+/// Create a recurring bill
+pub async fn create_recurring_bill(bill: &NewRecurringBill) -> Result<RecurringBill, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "bill": bill }))
+        .map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+    let result = invoke_safe("create_recurring_bill", args).await?;
+    serde_wasm_bindgen::from_value::<RecurringBill>(result)
+        .map_err(|e| format!("Failed to parse recurring bill: {:?}", e))
+}
+
+/// Delete a recurring bill
+pub async fn delete_recurring_bill(id: i64) -> Result<(), String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "id": id }))
+        .map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+    invoke_safe("delete_recurring_bill", args).await?;
+    Ok(())
+}
+
+/// Load expense totals per category for a date range `[start_date, end_date)`
+pub async fn fetch_category_breakdown(start_date: &str, end_date: &str) -> Result<Vec<CategoryTotal>, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "startDate": start_date,
+        "endDate": end_date,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("get_category_breakdown", args).await?;
+    serde_wasm_bindgen::from_value::<Vec<CategoryTotal>>(result)
+        .map_err(|e| format!("Failed to parse category breakdown: {:?}", e))
+}
+
+/// Load income/expense totals for the `months` months ending at year/month
+pub async fn fetch_monthly_trend(year: i32, month: i32, months: i32) -> Result<Vec<MonthlyTrendPoint>, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "year": year,
+        "month": month,
+        "months": months,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("get_monthly_trend", args).await?;
+    serde_wasm_bindgen::from_value::<Vec<MonthlyTrendPoint>>(result)
+        .map_err(|e| format!("Failed to parse monthly trend: {:?}", e))
+}
+
+/// Load per-day totals for a date range `[start_date, end_date)`
+pub async fn fetch_daily_totals(start_date: &str, end_date: &str) -> Result<Vec<DailyTotal>, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "startDate": start_date,
+        "endDate": end_date,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("get_daily_totals", args).await?;
+    serde_wasm_bindgen::from_value::<Vec<DailyTotal>>(result)
+        .map_err(|e| format!("Failed to parse daily totals: {:?}", e))
+}
+
+/// Load the comparison of a month against last month and last year
+pub async fn fetch_month_comparison(year: i32, month: i32) -> Result<MonthComparison, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "year": year,
+        "month": month,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("get_month_comparison", args).await?;
+    serde_wasm_bindgen::from_value::<MonthComparison>(result)
+        .map_err(|e| format!("Failed to parse month comparison: {:?}", e))
+}
+
+/// Get the `[start, end)` date range of a month as `YYYY-MM-DD` strings
+pub fn month_range(year: i32, month: i32) -> (String, String) {
+    let start = format!("{:04}-{:02}-01", year, month);
+    let end = if month == 12 {
+        format!("{:04}-01-01", year + 1)
+    } else {
+        format!("{:04}-{:02}-01", year, month + 1)
+    };
+    (start, end)
+}
+
+/// Load the annual report of a year
+pub async fn fetch_annual_report(year: i32) -> Result<AnnualReport, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "year": year,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("get_annual_report", args).await?;
+    serde_wasm_bindgen::from_value::<AnnualReport>(result)
+        .map_err(|e| format!("Failed to parse annual report: {:?}", e))
+}
+
+/// Validate a backup file and describe its contents; `password` opens an encrypted one
+pub async fn inspect_backup(content: &str, password: Option<&str>) -> Result<BackupInfo, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "content": content,
+        "password": password,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("inspect_json_backup", args).await?;
+    serde_wasm_bindgen::from_value::<BackupInfo>(result)
+        .map_err(|e| format!("Failed to parse backup info: {:?}", e))
+}
+
+/// Restore a backup file
+pub async fn restore_backup(content: &str, mode: RestoreMode, password: Option<&str>) -> Result<RestoreSummary, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "content": content,
+        "mode": mode,
+        "password": password,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("restore_json_backup", args).await?;
+    serde_wasm_bindgen::from_value::<RestoreSummary>(result)
+        .map_err(|e| format!("Failed to parse restore summary: {:?}", e))
+}
+
+/// Local snapshots, newest first
+pub async fn fetch_snapshots() -> Result<Vec<SnapshotInfo>, String> {
+    let result = invoke_safe("list_snapshots", JsValue::NULL).await?;
+    serde_wasm_bindgen::from_value::<Vec<SnapshotInfo>>(result)
+        .map_err(|e| format!("Failed to parse snapshots: {:?}", e))
+}
+
+/// Take a snapshot now
+pub async fn create_snapshot() -> Result<SnapshotInfo, String> {
+    let result = invoke_safe("create_snapshot", JsValue::NULL).await?;
+    serde_wasm_bindgen::from_value::<SnapshotInfo>(result)
+        .map_err(|e| format!("Failed to parse snapshot: {:?}", e))
+}
+
+/// Replace the ledger with a snapshot
+pub async fn restore_snapshot(file_name: &str) -> Result<(), String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "fileName": file_name,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    invoke_safe("restore_snapshot", args).await?;
+    Ok(())
+}
+
+/// Load the snapshot retention policy
+pub async fn fetch_snapshot_policy() -> Result<SnapshotPolicy, String> {
+    let result = invoke_safe("get_snapshot_policy", JsValue::NULL).await?;
+    serde_wasm_bindgen::from_value::<SnapshotPolicy>(result)
+        .map_err(|e| format!("Failed to parse snapshot policy: {:?}", e))
+}
+
+/// Load this device's backup folder settings
+pub async fn fetch_backup_folder() -> Result<BackupFolderSettings, String> {
+    let result = invoke_safe("get_backup_folder", JsValue::NULL).await?;
+    serde_wasm_bindgen::from_value::<BackupFolderSettings>(result)
+        .map_err(|e| format!("Failed to parse backup folder settings: {:?}", e))
+}
+
+/// Save the backup folder settings and write a first backup; `password`
+/// None keeps the current one, empty turns encryption off
+pub async fn save_backup_folder(path: Option<&str>, keep: u32, password: Option<&str>) -> Result<Option<FolderBackup>, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "path": path,
+        "keep": keep,
+        "password": password,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("set_backup_folder", args).await?;
+    serde_wasm_bindgen::from_value::<Option<FolderBackup>>(result)
+        .map_err(|e| format!("Failed to parse folder backup: {:?}", e))
+}
+
+/// Write a backup into the backup folder now
+pub async fn backup_to_folder() -> Result<Option<FolderBackup>, String> {
+    let result = invoke_safe("backup_to_folder", JsValue::NULL).await?;
+    serde_wasm_bindgen::from_value::<Option<FolderBackup>>(result)
+        .map_err(|e| format!("Failed to parse folder backup: {:?}", e))
+}
+
+/// Backups in the backup folder, newest first
+pub async fn fetch_folder_backups() -> Result<Vec<FolderBackup>, String> {
+    let result = invoke_safe("list_folder_backups", JsValue::NULL).await?;
+    serde_wasm_bindgen::from_value::<Vec<FolderBackup>>(result)
+        .map_err(|e| format!("Failed to parse folder backups: {:?}", e))
+}
+
+/// A newer backup left in the folder by another device
+pub async fn check_folder_backup() -> Result<Option<FolderBackup>, String> {
+    let result = invoke_safe("check_folder_backup", JsValue::NULL).await?;
+    serde_wasm_bindgen::from_value::<Option<FolderBackup>>(result)
+        .map_err(|e| format!("Failed to parse folder backup: {:?}", e))
+}
+
+/// Restore or merge a backup from the backup folder
+pub async fn restore_folder_backup(file_name: &str, mode: RestoreMode) -> Result<RestoreSummary, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "fileName": file_name,
+        "mode": mode,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("restore_folder_backup", args).await?;
+    serde_wasm_bindgen::from_value::<RestoreSummary>(result)
+        .map_err(|e| format!("Failed to parse restore summary: {:?}", e))
+}
+
+/// Stop offering a backup of another device
+pub async fn dismiss_folder_backup(file_name: &str) -> Result<(), String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "fileName": file_name,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    invoke_safe("dismiss_folder_backup", args).await?;
+    Ok(())
+}
+
+/// Save the snapshot retention policy (rotates right away)
+pub async fn save_snapshot_policy(policy: SnapshotPolicy) -> Result<(), String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "policy": policy,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    invoke_safe("set_snapshot_policy", args).await?;
+    Ok(())
+}
+
+/// Parse a bill file into an import preview
+pub async fn preview_import(source: ImportSource, content: &[u8], account: Option<String>) -> Result<Vec<ImportRow>, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "source": source,
+        "content": content,
+        "account": account,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("preview_import", args).await?;
+    serde_wasm_bindgen::from_value::<Vec<ImportRow>>(result)
+        .map_err(|e| format!("Failed to parse import preview: {:?}", e))
+}
+
+/// Import the confirmed preview rows
+pub async fn commit_import(source: ImportSource, rows: &[ImportRow]) -> Result<ImportResult, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "source": source,
+        "rows": rows,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("commit_import", args).await?;
+    serde_wasm_bindgen::from_value::<ImportResult>(result)
+        .map_err(|e| format!("Failed to parse import result: {:?}", e))
+}
+
+/// Guess the delimiter and encoding of a CSV file
+pub async fn sniff_csv(content: &[u8]) -> Result<CsvSniff, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "content": content,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("sniff_csv", args).await?;
+    serde_wasm_bindgen::from_value::<CsvSniff>(result)
+        .map_err(|e| format!("Failed to parse CSV sniff: {:?}", e))
+}
+
+/// Parse a CSV file with a column mapping into an import preview
+pub async fn preview_csv_import(content: &[u8], mapping: &CsvMapping, account: Option<String>) -> Result<Vec<ImportRow>, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "content": content,
+        "mapping": mapping,
+        "account": account,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("preview_csv_import", args).await?;
+    serde_wasm_bindgen::from_value::<Vec<ImportRow>>(result)
+        .map_err(|e| format!("Failed to parse import preview: {:?}", e))
+}
+
+/// Fetch the saved CSV mappings
+pub async fn fetch_csv_presets() -> Result<Vec<CsvPreset>, String> {
+    let result = invoke_safe("get_csv_presets", JsValue::NULL).await?;
+    serde_wasm_bindgen::from_value::<Vec<CsvPreset>>(result)
+        .map_err(|e| format!("Failed to parse CSV presets: {:?}", e))
+}
+
+/// Save a CSV mapping under a name, replacing a preset with the same name
+pub async fn save_csv_preset(name: &str, mapping: &CsvMapping) -> Result<CsvPreset, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "name": name,
+        "mapping": mapping,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+
+    let result = invoke_safe("save_csv_preset", args).await?;
+    serde_wasm_bindgen::from_value::<CsvPreset>(result)
+        .map_err(|e| format!("Failed to parse CSV preset: {:?}", e))
+}
+
+/// Delete a saved CSV mapping
+pub async fn delete_csv_preset(id: i64) -> Result<(), String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "id": id }))
+        .map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+    invoke_safe("delete_csv_preset", args).await?;
+    Ok(())
+}
+
+/// Fetch the category rules in matching order
+pub async fn fetch_category_rules() -> Result<Vec<CategoryRule>, String> {
+    let result = invoke_safe("get_category_rules", JsValue::NULL).await?;
+    serde_wasm_bindgen::from_value::<Vec<CategoryRule>>(result)
+        .map_err(|e| format!("Failed to parse rules: {:?}", e))
+}
+
+/// Create a category rule, matched after the existing ones
+pub async fn create_category_rule(rule: &NewCategoryRule) -> Result<CategoryRule, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "rule": rule }))
+        .map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+    let result = invoke_safe("create_category_rule", args).await?;
+    serde_wasm_bindgen::from_value::<CategoryRule>(result)
+        .map_err(|e| format!("Failed to parse rule: {:?}", e))
+}
+
+/// Update a category rule
+pub async fn update_category_rule(id: i64, rule: &NewCategoryRule) -> Result<(), String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "id": id,
+        "rule": rule,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+    invoke_safe("update_category_rule", args).await?;
+    Ok(())
+}
+
+/// Delete a category rule
+pub async fn delete_category_rule(id: i64) -> Result<(), String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "id": id }))
+        .map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+    invoke_safe("delete_category_rule", args).await?;
+    Ok(())
+}
+
+/// Set the matching order of the rules
+pub async fn reorder_category_rules(ids: &[i64]) -> Result<(), String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "ids": ids }))
+        .map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+    invoke_safe("reorder_category_rules", args).await?;
+    Ok(())
+}
+
+/// Dry run of re-applying the rules to existing transactions
+pub async fn preview_rule_changes() -> Result<Vec<RuleChange>, String> {
+    let result = invoke_safe("preview_rule_changes", JsValue::NULL).await?;
+    serde_wasm_bindgen::from_value::<Vec<RuleChange>>(result)
+        .map_err(|e| format!("Failed to parse rule changes: {:?}", e))
+}
+
+/// Apply the kept changes of a dry run; returns how many transactions changed
+pub async fn apply_rule_changes(changes: &[RuleChange]) -> Result<i64, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "changes": changes }))
+        .map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+    let result = invoke_safe("apply_rule_changes", args).await?;
+    serde_wasm_bindgen::from_value::<i64>(result)
+        .map_err(|e| format!("Failed to parse rule result: {:?}", e))
+}
+
+/// Whether the rules also apply to manually entered transactions
+pub async fn fetch_rules_on_entry() -> Result<bool, String> {
+    let result = invoke_safe("get_rules_on_entry", JsValue::NULL).await?;
+    serde_wasm_bindgen::from_value::<bool>(result)
+        .map_err(|e| format!("Failed to parse setting: {:?}", e))
+}
+
+/// Turn applying the rules to manually entered transactions on or off
+pub async fn set_rules_on_entry(enabled: bool) -> Result<(), String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "enabled": enabled }))
+        .map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+    invoke_safe("set_rules_on_entry", args).await?;
+    Ok(())
+}
+
+/// The most likely categories for a transaction being entered
+pub async fn suggest_categories(note: &str, amount: f64) -> Result<Vec<CategorySuggestion>, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "note": note,
+        "amount": amount,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+    let result = invoke_safe("suggest_categories", args).await?;
+    serde_wasm_bindgen::from_value::<Vec<CategorySuggestion>>(result)
+        .map_err(|e| format!("Failed to parse suggestions: {:?}", e))
+}
+
+/// Fetch the likely duplicate pairs in the ledger
+pub async fn fetch_duplicate_pairs() -> Result<Vec<DuplicatePair>, String> {
+    let result = invoke_safe("get_duplicate_pairs", JsValue::NULL).await?;
+    serde_wasm_bindgen::from_value::<Vec<DuplicatePair>>(result)
+        .map_err(|e| format!("Failed to parse duplicates: {:?}", e))
+}
+
+/// Keep one transaction of a duplicate pair and delete the other
+pub async fn merge_duplicates(keep_id: i64, remove_id: i64) -> Result<(), String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "keepId": keep_id,
+        "removeId": remove_id,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+    invoke_safe("merge_duplicates", args).await?;
+    Ok(())
+}
+
+/// Mark a pair as not duplicates
+pub async fn dismiss_duplicate(first_id: i64, second_id: i64) -> Result<(), String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "firstId": first_id,
+        "secondId": second_id,
+    })).map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+    invoke_safe("dismiss_duplicate", args).await?;
+    Ok(())
+}
+
+/// Describes a file produced by a backend export command
+pub struct ExportFile<'a> {
+    /// Suggested file name, e.g. `年度账单-2025.html`
+    pub file_name: &'a str,
+    /// Save dialog filter name
+    pub filter_name: &'a str,
+    pub extensions: &'a [&'a str],
+    pub mime_type: &'a str,
+}
+
+/// Run an export command and deliver the file
+///
+/// On desktop the user picks a path with the save dialog; on Android the
+/// backend writes into the cache dir and the file goes to the share sheet.
+/// `cmd` receives the chosen path (or `null`) as its `path` argument along with `args`,
+/// and returns the written path or, for multi-file exports, a list of paths.
+/// Returns the written path(s), or `None` if the save dialog was cancelled.
+pub async fn export_file(cmd: &str, mut args: serde_json::Value, file: ExportFile<'_>) -> Result<Option<String>, String> {
+    let share = can_share_files();
+    let path = if share {
+        None
+    } else {
+        match pick_save_path(file.file_name, file.filter_name, file.extensions).await? {
+            Some(path) => Some(path),
+            None => return Ok(None),
+        }
+    };
+    args["path"] = serde_json::json!(path);
+
+    let args = serde_wasm_bindgen::to_value(&args)
+        .map_err(|e| format!("Failed to serialize args: {:?}", e))?;
+    let result = invoke_safe(cmd, args).await?;
+    let written: Vec<String> = match result.as_string() {
+        Some(path) => vec![path],
+        None => serde_wasm_bindgen::from_value(result)
+            .map_err(|_| "Export did not return a path".to_string())?,
+    };
+
+    if share {
+        match written.as_slice() {
+            [path] => share_file(path, file.mime_type)?,
+            paths => share_files(paths, file.mime_type)?,
+        }
+    }
+    Ok(Some(written.join(", ")))
+}
+
+/// Export a month's statement as HTML or PDF; see `export_file`
+pub async fn export_monthly_statement(year: i32, month: i32, format: StatementFormat) -> Result<Option<String>, String> {
+    let (extension, filter_name, mime_type) = match format {
+        StatementFormat::Html => ("html", "HTML", "text/html"),
+        StatementFormat::Pdf => ("pdf", "PDF", "application/pdf"),
+    };
+    let file_name = format!("对账单-{}-{:02}.{}", year, month, extension);
+    export_file(
+        "export_monthly_statement",
+        serde_json::json!({ "year": year, "month": month, "format": format }),
+        ExportFile {
+            file_name: &file_name,
+            filter_name,
+            extensions: &[extension],
+            mime_type,
+        },
+    )
+    .await
+}
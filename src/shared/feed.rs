@@ -0,0 +1,122 @@
+//! Cursor-paged transaction list shared by the scrolling views (mobile list,
+//! desktop month list and summary), loading more rows as the user scrolls.
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+use crate::types::{TransactionCursor, TransactionWithCategory};
+use super::api_helpers::{fetch_transactions_page, TRANSACTION_PAGE_SIZE};
+
+/// Distance from the bottom (px) at which the next page is requested
+const LOAD_MORE_THRESHOLD: i32 = 300;
+
+/// Transactions loaded so far plus the cursor of the next page
+#[derive(Clone, Copy)]
+pub struct TransactionFeed {
+    pub items: RwSignal<Vec<TransactionWithCategory>>,
+    pub has_more: RwSignal<bool>,
+    pub loading: RwSignal<bool>,
+    /// Month the feed is limited to, None for all transactions
+    month: RwSignal<Option<(i32, i32)>>,
+    cursor: RwSignal<Option<TransactionCursor>>,
+    /// Bumped on every reset so pages of an older feed are dropped
+    generation: RwSignal<u32>,
+}
+
+impl TransactionFeed {
+    pub fn new() -> Self {
+        Self {
+            items: RwSignal::new(Vec::new()),
+            has_more: RwSignal::new(false),
+            loading: RwSignal::new(false),
+            month: RwSignal::new(None),
+            cursor: RwSignal::new(None),
+            generation: RwSignal::new(0),
+        }
+    }
+
+    /// Start over from the newest transaction, optionally within one month
+    pub fn reset(&self, month: Option<(i32, i32)>) {
+        self.month.set(month);
+        self.cursor.set(None);
+        self.generation.update(|g| *g += 1);
+        self.load_page(true);
+    }
+
+    /// Reload the current feed from the first page
+    pub fn reload(&self) {
+        self.reset(self.month.get_untracked());
+    }
+
+    /// Append the next page if there is one and none is loading
+    pub fn load_more(&self) {
+        if self.has_more.get_untracked() && !self.loading.get_untracked() {
+            self.load_page(false);
+        }
+    }
+
+    /// Drop a transaction that was deleted, keeping the loaded pages
+    pub fn remove(&self, id: i64) {
+        self.items.update(|items| items.retain(|tx| tx.id != id));
+    }
+
+    /// Scroll handler for the list's scroll container: loads more near the bottom
+    pub fn on_scroll(&self, ev: &web_sys::Event) {
+        let element = event_target::<web_sys::Element>(ev);
+        let remaining = element.scroll_height() - element.scroll_top() - element.client_height();
+        if remaining < LOAD_MORE_THRESHOLD {
+            self.load_more();
+        }
+    }
+
+    /// Load more when the page itself is scrolled near the bottom, for views
+    /// without their own scroll container (desktop)
+    pub fn load_on_window_scroll(&self) {
+        let feed = *self;
+        let handle = window_event_listener(leptos::ev::scroll, move |_| {
+            let Some(root) = document().document_element() else {
+                return;
+            };
+            let viewport = window().inner_height().ok().and_then(|h| h.as_f64()).unwrap_or(0.0);
+            let scrolled = window().scroll_y().unwrap_or(0.0);
+            if root.scroll_height() as f64 - scrolled - viewport < LOAD_MORE_THRESHOLD as f64 {
+                feed.load_more();
+            }
+        });
+        on_cleanup(move || handle.remove());
+    }
+
+    fn load_page(&self, replace: bool) {
+        let feed = *self;
+        let generation = self.generation.get_untracked();
+        let month = self.month.get_untracked();
+        let cursor = self.cursor.get_untracked();
+        feed.loading.set(true);
+
+        spawn_local(async move {
+            let page = fetch_transactions_page(month, cursor.as_ref(), TRANSACTION_PAGE_SIZE).await;
+            if feed.generation.get_untracked() != generation {
+                return;
+            }
+            match page {
+                Ok(page) => {
+                    if replace {
+                        feed.items.set(page.transactions);
+                    } else {
+                        feed.items.update(|items| items.extend(page.transactions));
+                    }
+                    feed.has_more.set(page.next_cursor.is_some());
+                    feed.cursor.set(page.next_cursor);
+                }
+                Err(_) => feed.has_more.set(false),
+            }
+            feed.loading.set(false);
+        });
+    }
+}
+
+impl Default for TransactionFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
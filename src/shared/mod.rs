@@ -5,8 +5,12 @@
 pub mod icons;
 pub mod validators;
 pub mod api_helpers;
+pub mod search;
+pub mod feed;
 
 // Re-exports for convenience
 pub use icons::{COMMON_ICONS, DEFAULT_ICON};
-pub use validators::{validate_amount, validate_category_id, validate_not_empty, parse_positive_int};
+pub use validators::{validate_amount, validate_category_id, validate_not_empty, parse_positive_int, parse_optional_amount};
 pub use api_helpers::*;
+pub use search::{SearchForm, SearchResults, SmartViews};
+pub use feed::TransactionFeed;
@@ -0,0 +1,241 @@
+//! Transaction search state shared by the mobile search screen and the
+//! desktop filter bar, covering both the form filter and the query syntax.
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+use crate::types::{SmartView, TransactionFilter, TransactionKind, TransactionWithCategory};
+use super::api_helpers::{
+    delete_smart_view, fetch_smart_views, query_transactions, save_smart_view, search_transactions, SEARCH_PAGE_SIZE,
+};
+use super::validators::parse_optional_amount;
+
+/// Input state of the search form
+#[derive(Clone, Copy)]
+pub struct SearchForm {
+    pub text: RwSignal<String>,
+    pub kind: RwSignal<Option<TransactionKind>>,
+    pub category_ids: RwSignal<Vec<i64>>,
+    pub min_amount: RwSignal<String>,
+    pub max_amount: RwSignal<String>,
+    pub start_date: RwSignal<String>,
+    pub end_date: RwSignal<String>,
+}
+
+impl SearchForm {
+    pub fn new() -> Self {
+        Self {
+            text: RwSignal::new(String::new()),
+            kind: RwSignal::new(None),
+            category_ids: RwSignal::new(Vec::new()),
+            min_amount: RwSignal::new(String::new()),
+            max_amount: RwSignal::new(String::new()),
+            start_date: RwSignal::new(String::new()),
+            end_date: RwSignal::new(String::new()),
+        }
+    }
+
+    /// Select or unselect a category
+    pub fn toggle_category(&self, id: i64) {
+        self.category_ids.update(|ids| {
+            if let Some(pos) = ids.iter().position(|c| *c == id) {
+                ids.remove(pos);
+            } else {
+                ids.push(id);
+            }
+        });
+    }
+
+    /// Reset every field
+    pub fn clear(&self) {
+        self.text.set(String::new());
+        self.kind.set(None);
+        self.category_ids.set(Vec::new());
+        self.min_amount.set(String::new());
+        self.max_amount.set(String::new());
+        self.start_date.set(String::new());
+        self.end_date.set(String::new());
+    }
+
+    /// Build the backend filter from the current input
+    pub fn to_filter(&self) -> Result<TransactionFilter, &'static str> {
+        let non_empty = |value: String| {
+            let value = value.trim().to_string();
+            if value.is_empty() { None } else { Some(value) }
+        };
+
+        Ok(TransactionFilter {
+            text: non_empty(self.text.get_untracked()),
+            category_ids: self.category_ids.get_untracked(),
+            min_amount: parse_optional_amount(&self.min_amount.get_untracked())?,
+            max_amount: parse_optional_amount(&self.max_amount.get_untracked())?,
+            start_date: non_empty(self.start_date.get_untracked()),
+            end_date: non_empty(self.end_date.get_untracked()),
+            kind: self.kind.get_untracked(),
+        })
+    }
+}
+
+impl Default for SearchForm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What the current results were searched with
+#[derive(Clone, Debug, PartialEq)]
+enum SearchRequest {
+    Filter(TransactionFilter),
+    Query(String),
+}
+
+/// Paged search results
+#[derive(Clone, Copy)]
+pub struct SearchResults {
+    pub items: RwSignal<Vec<TransactionWithCategory>>,
+    pub total_count: RwSignal<i64>,
+    pub has_more: RwSignal<bool>,
+    pub loading: RwSignal<bool>,
+    pub error: RwSignal<Option<String>>,
+    request: RwSignal<SearchRequest>,
+}
+
+impl SearchResults {
+    pub fn new() -> Self {
+        Self {
+            items: RwSignal::new(Vec::new()),
+            total_count: RwSignal::new(0),
+            has_more: RwSignal::new(false),
+            loading: RwSignal::new(false),
+            error: RwSignal::new(None),
+            request: RwSignal::new(SearchRequest::Filter(TransactionFilter::default())),
+        }
+    }
+
+    /// Start a new search, replacing the current results
+    pub fn search(&self, filter: TransactionFilter) {
+        self.request.set(SearchRequest::Filter(filter));
+        self.items.set(Vec::new());
+        self.load_page(0);
+    }
+
+    /// Start a new search with the query syntax
+    pub fn search_query(&self, query: String) {
+        self.request.set(SearchRequest::Query(query));
+        self.items.set(Vec::new());
+        self.load_page(0);
+    }
+
+    /// Append the next page of the current search
+    pub fn load_more(&self) {
+        if self.has_more.get_untracked() && !self.loading.get_untracked() {
+            self.load_page(self.items.with_untracked(|items| items.len()) as i64);
+        }
+    }
+
+    fn load_page(&self, offset: i64) {
+        let results = *self;
+        let request = self.request.get_untracked();
+        results.loading.set(true);
+        results.error.set(None);
+
+        spawn_local(async move {
+            let page = match &request {
+                SearchRequest::Filter(filter) => search_transactions(filter, SEARCH_PAGE_SIZE, offset).await,
+                SearchRequest::Query(query) => query_transactions(query, SEARCH_PAGE_SIZE, offset).await,
+            };
+            // Ignore pages of a search that has been replaced meanwhile
+            if results.request.get_untracked() == request {
+                match page {
+                    Ok(page) => {
+                        results.items.update(|items| items.extend(page.transactions));
+                        results.total_count.set(page.total_count);
+                        results.has_more.set(page.has_more);
+                    }
+                    Err(e) => {
+                        results.total_count.set(0);
+                        results.has_more.set(false);
+                        results.error.set(Some(e));
+                    }
+                }
+            }
+            results.loading.set(false);
+        });
+    }
+}
+
+impl Default for SearchResults {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Saved smart views and the query box they fill
+#[derive(Clone, Copy)]
+pub struct SmartViews {
+    pub views: RwSignal<Vec<SmartView>>,
+    /// Query text typed by the user
+    pub query: RwSignal<String>,
+    /// Name entered when saving the query as a view
+    pub new_name: RwSignal<String>,
+    pub error: RwSignal<Option<String>>,
+}
+
+impl SmartViews {
+    /// Create the state and load saved views
+    pub fn new() -> Self {
+        let smart_views = Self {
+            views: RwSignal::new(Vec::new()),
+            query: RwSignal::new(String::new()),
+            new_name: RwSignal::new(String::new()),
+            error: RwSignal::new(None),
+        };
+        smart_views.reload();
+        smart_views
+    }
+
+    pub fn reload(&self) {
+        let views = self.views;
+        spawn_local(async move {
+            if let Ok(items) = fetch_smart_views().await {
+                views.set(items);
+            }
+        });
+    }
+
+    /// Save the current query under the entered name
+    pub fn save_current(&self) {
+        let smart_views = *self;
+        let name = self.new_name.get_untracked().trim().to_string();
+        let query = self.query.get_untracked();
+        if name.is_empty() {
+            smart_views.error.set(Some("请输入视图名称".to_string()));
+            return;
+        }
+
+        spawn_local(async move {
+            match save_smart_view(&name, &query).await {
+                Ok(_) => {
+                    smart_views.error.set(None);
+                    smart_views.new_name.set(String::new());
+                    smart_views.reload();
+                }
+                Err(e) => smart_views.error.set(Some(e)),
+            }
+        });
+    }
+
+    pub fn delete(&self, id: i64) {
+        let smart_views = *self;
+        spawn_local(async move {
+            let _ = delete_smart_view(id).await;
+            smart_views.reload();
+        });
+    }
+}
+
+impl Default for SmartViews {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -35,3 +35,14 @@ pub fn parse_positive_int(value: &str) -> Result<i32, &'static str> {
         _ => Err("请输入有效的正整数"),
     }
 }
+
+/// Parse an optional amount field, empty input means "not set"
+pub fn parse_optional_amount(value: &str) -> Result<Option<f64>, &'static str> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    value.parse::<f64>()
+        .map(Some)
+        .map_err(|_| "金额格式错误，请输入有效数字")
+}
@@ -1,217 +1,191 @@
 use leptos::prelude::*;
 use leptos::task::spawn_local;
+use chrono::Datelike;
 
 // Import shared types and API
-use crate::types::{Category, TransactionWithCategory, InstallmentDetail};
-use crate::api::JsValue;
+use crate::types::{Category, TransactionWithCategory, CategoryTotal, MonthlyTrendPoint, MonthTotals};
+use crate::shared::{fetch_category_breakdown, fetch_month_totals, fetch_monthly_trend, month_range, TransactionFeed};
+use crate::charts::{CategoryPieChart, ExpenseTrendChart, IncomeExpenseBarChart};
+use crate::annual_report::AnnualReportView;
+use crate::csv_export::CsvExportView;
 
 
 
 
 #[derive(Clone, Debug)]
 struct MonthGroup {
-    year: i32,
-    month: i32,
+    totals: MonthTotals,
     transactions: Vec<TransactionWithCategory>,
-    installments: Vec<InstallmentDetail>,
-    income: f64,
-    expense: f64,
-    installment_expense: f64,
 }
 
 #[component]
 pub fn SummaryView(
     categories: ReadSignal<Vec<Category>>,
 ) -> impl IntoView {
-    let (all_transactions, set_all_transactions) = create_signal(Vec::<TransactionWithCategory>::new());
-    let (grouped_by_month, set_grouped_by_month) = create_signal(Vec::<MonthGroup>::new());
-    let (total_income, set_total_income) = create_signal(0.0);
-    let (total_expense, set_total_expense) = create_signal(0.0);
-    let (total_balance, set_total_balance) = create_signal(0.0);
-
-    // Load all transactions and installments
-    let load_all_data = {
-        let set_all_transactions = set_all_transactions.clone();
-        let set_grouped_by_month = set_grouped_by_month.clone();
-        let set_total_income = set_total_income.clone();
-        let set_total_expense = set_total_expense.clone();
-        let set_total_balance = set_total_balance.clone();
-
-        move || {
-            let set_all_transactions = set_all_transactions.clone();
-            let set_grouped_by_month = set_grouped_by_month.clone();
-            let set_total_income = set_total_income.clone();
-            let set_total_expense = set_total_expense.clone();
-            let set_total_balance = set_total_balance.clone();
-
-            spawn_local(async move {
-                // Get all transactions
-                let tx_result = match crate::api::invoke_safe("get_transactions", JsValue::NULL).await {
-                    Ok(res) => res,
-                    Err(_) => return, // Fail silently/gracefully if DB not ready
-                };
-                let txs: Vec<TransactionWithCategory> = serde_wasm_bindgen::from_value(tx_result).unwrap_or_default();
-                set_all_transactions.set(txs.clone());
-                
-                // Get all installment details
-                let mut all_installment_details: Vec<InstallmentDetail> = Vec::new();
-
-                if let Ok(inst_result) = crate::api::invoke_safe("get_installments", JsValue::NULL).await {
-                    if let Ok(installments) = serde_wasm_bindgen::from_value::<Vec<crate::types::InstallmentWithCategory>>(inst_result) {
-                        for inst in installments {
-                            let args = serde_wasm_bindgen::to_value(&serde_json::json!({
-                                "installmentId": inst.id
-                            })).unwrap();
-                            
-                            if let Ok(details_result) = crate::api::invoke_safe("get_installment_details", args).await {
-                                if let Ok(details) = serde_wasm_bindgen::from_value::<Vec<InstallmentDetail>>(details_result) {
-                                    all_installment_details.extend(details);
-                                }
-                            }
-                        }
-                    }
-                }
-
+    // Month totals come from the backend; transactions are paged in as the list scrolls
+    let (month_totals, set_month_totals) = create_signal(Vec::<MonthTotals>::new());
+    let feed = TransactionFeed::new();
+    feed.load_on_window_scroll();
+
+    let load_all_data = move || {
+        feed.reset(None);
+        spawn_local(async move {
+            // Fail silently/gracefully if DB not ready
+            if let Ok(totals) = fetch_month_totals().await {
+                set_month_totals.set(totals);
+            }
+        });
+    };
 
-                // Group by month
-                let mut month_map: std::collections::HashMap<(i32, i32), MonthGroup> = std::collections::HashMap::new();
-                let mut total_inc = 0.0;
-                let mut total_exp = 0.0;
-
-                // Process transactions
-                for tx in &txs {
-                    if let Some(date_part) = tx.transaction_date.split('T').next() {
-                        let parts: Vec<&str> = date_part.split('-').collect();
-                        if parts.len() >= 2 {
-                            if let (Ok(year), Ok(month)) = (parts[0].parse::<i32>(), parts[1].parse::<i32>()) {
-                                let key = (year, month);
-                                let group = month_map.entry(key).or_insert(MonthGroup {
-                                    year,
-                                    month,
-                                    transactions: Vec::new(),
-                                    installments: Vec::new(),
-                                    income: 0.0,
-                                    expense: 0.0,
-                                    installment_expense: 0.0,
-                                });
-
-                                group.transactions.push(tx.clone());
-
-                                if tx.amount >= 0.0 {
-                                    group.income += tx.amount;
-                                    total_inc += tx.amount;
-                                } else {
-                                    group.expense += tx.amount.abs();
-                                    total_exp += tx.amount.abs();
-                                }
-                            }
-                        }
-                    }
-                }
-                
-                // Process installment details
-                for detail in all_installment_details {
-                    if let Some(date_part) = detail.due_date.split('T').next() {
-                        let parts: Vec<&str> = date_part.split('-').collect();
-                        if parts.len() >= 2 {
-                            if let (Ok(year), Ok(month)) = (parts[0].parse::<i32>(), parts[1].parse::<i32>()) {
-                                let key = (year, month);
-                                let group = month_map.entry(key).or_insert(MonthGroup {
-                                    year,
-                                    month,
-                                    transactions: Vec::new(),
-                                    installments: Vec::new(),
-                                    income: 0.0,
-                                    expense: 0.0,
-                                    installment_expense: 0.0,
-                                });
-
-                                group.installment_expense += detail.amount;
-                                total_exp += detail.amount;
-                                group.installments.push(detail);
-                            }
-                        }
-                    }
-                }
+    // Load on mount
+    create_effect(move |_| {
+        load_all_data();
+    });
 
-                // Convert to sorted vector
-                let mut groups: Vec<MonthGroup> = month_map.into_values().collect();
-                groups.sort_by(|a, b| {
-                    if a.year != b.year {
-                        b.year.cmp(&a.year)
-                    } else {
-                        b.month.cmp(&a.month)
-                    }
-                });
-
-                // Sort transactions within each group by date
-                for group in &mut groups {
-                    group.transactions.sort_by(|a, b| b.transaction_date.cmp(&a.transaction_date));
+    let total_income = move || month_totals.get().iter().map(|m| m.total_income).sum::<f64>();
+    let total_expense = move || {
+        month_totals.get().iter().map(|m| m.total_expense + m.installment_expense).sum::<f64>()
+    };
+    let total_balance = move || total_income() - total_expense();
+
+    // Months down to the oldest loaded transaction, each with its loaded transactions.
+    // Both lists are newest first, so later pages only extend the last groups.
+    let grouped_by_month = move || {
+        let items = feed.items.get();
+        let loaded_until = if feed.has_more.get() {
+            items.last().map(|tx| tx.transaction_date.chars().take(7).collect::<String>())
+        } else {
+            None
+        };
+
+        month_totals.get().into_iter()
+            .filter_map(|totals| {
+                let ym = format!("{:04}-{:02}", totals.year, totals.month);
+                if loaded_until.as_ref().is_some_and(|oldest| ym < *oldest) {
+                    return None;
                 }
-
-                set_grouped_by_month.set(groups);
-                set_total_income.set(total_inc);
-                set_total_expense.set(total_exp);
-                set_total_balance.set(total_inc - total_exp);
-            });
-        }
+                let transactions = items.iter()
+                    .filter(|tx| tx.transaction_date.starts_with(&ym))
+                    .cloned()
+                    .collect();
+                Some(MonthGroup { totals, transactions })
+            })
+            .collect::<Vec<_>>()
     };
 
-    // Load on mount
+    // Chart data: category share for the chart month, trend for the 12 months ending there
+    let now = chrono::Local::now();
+    let (chart_year, set_chart_year) = create_signal(now.year());
+    let (chart_month, set_chart_month) = create_signal(now.month() as i32);
+    let (category_totals, set_category_totals) = create_signal(Vec::<CategoryTotal>::new());
+    let (trend_points, set_trend_points) = create_signal(Vec::<MonthlyTrendPoint>::new());
+
     create_effect(move |_| {
-        load_all_data();
+        let year = chart_year.get();
+        let month = chart_month.get();
+        spawn_local(async move {
+            let (start, end) = month_range(year, month);
+            if let Ok(totals) = fetch_category_breakdown(&start, &end).await {
+                set_category_totals.set(totals);
+            }
+            if let Ok(points) = fetch_monthly_trend(year, month, 12).await {
+                set_trend_points.set(points);
+            }
+        });
     });
 
+    let shift_chart_month = move |offset: i32| {
+        let total = chart_year.get_untracked() * 12 + (chart_month.get_untracked() - 1) + offset;
+        set_chart_year.set(total.div_euclid(12));
+        set_chart_month.set(total.rem_euclid(12) + 1);
+    };
+
+    let (show_report, set_show_report) = create_signal(false);
+    let (show_export, set_show_export) = create_signal(false);
+
     view! {
         <div class="summary-view" style="display: flex; flex-direction: column; height: 100%;">
-            <h2 style="margin: 0; font-size: 18px; padding: 12px 16px; background: white; border-bottom: 1px solid #e0e0e0; flex-shrink: 0;">"账目汇总"</h2>
-            <div style="flex: 1; overflow-y: auto; padding: 16px;">
+            <h2 style="margin: 0; font-size: 18px; padding: 12px 16px; background: white; border-bottom: 1px solid #e0e0e0; flex-shrink: 0; display: flex; justify-content: space-between; align-items: center;">
+                "账目汇总"
+                <div>
+                    <button class="annual-report-btn" on:click=move |_| set_show_export.set(true)>"📤 导出"</button>
+                    <button class="annual-report-btn" on:click=move |_| set_show_report.set(true)>"📊 年度账单"</button>
+                </div>
+            </h2>
+            <Show when=move || show_report.get()>
+                <div style="flex: 1; overflow-y: auto; padding: 16px;">
+                    <AnnualReportView show_report=set_show_report />
+                </div>
+            </Show>
+            <Show when=move || show_export.get()>
+                <div style="flex: 1; overflow-y: auto; padding: 16px;">
+                    <CsvExportView categories=categories show_export=set_show_export />
+                </div>
+            </Show>
+            <div
+                on:scroll=move |ev| feed.on_scroll(&ev)
+                style=move || format!("flex: 1; overflow-y: auto; padding: 16px; display: {};", if show_report.get() || show_export.get() { "none" } else { "block" })
+            >
 
             // Total summary
             <div class="monthly-summary">
                 <div class="summary-item">
                     <span>"总收入"</span>
-                    <span class="amount-positive">{move || format!("{:.2}", total_income.get())}</span>
+                    <span class="amount-positive">{move || format!("{:.2}", total_income())}</span>
                 </div>
                 <div class="summary-item">
                     <span>"总支出"</span>
-                    <span class="amount-negative">{move || format!("{:.2}", total_expense.get())}</span>
+                    <span class="amount-negative">{move || format!("{:.2}", total_expense())}</span>
                 </div>
                 <div class="summary-item">
                     <span>"总结余"</span>
                     <span class=move || {
-                        if total_balance.get() >= 0.0 { "amount-positive" } else { "amount-negative" }
+                        if total_balance() >= 0.0 { "amount-positive" } else { "amount-negative" }
                     }>
-                        {move || format!("{:.2}", total_balance.get())}
+                        {move || format!("{:.2}", total_balance())}
+                    </span>
+                </div>
+            </div>
+
+            // Charts
+            <div class="summary-charts">
+                <div class="month-selector">
+                    <button on:click=move |_| shift_chart_month(-1)>"◀"</button>
+                    <span class="month-display">
+                        {move || format!("{}年{:02}月", chart_year.get(), chart_month.get())}
                     </span>
+                    <button on:click=move |_| shift_chart_month(1)>"▶"</button>
                 </div>
+                <CategoryPieChart data=category_totals />
+                <ExpenseTrendChart data=trend_points />
+                <IncomeExpenseBarChart data=trend_points />
             </div>
 
             // Monthly breakdown
             <div class="monthly-breakdown">
                 <For
-                    each=move || grouped_by_month.get()
-                    key=|group| format!("{}-{}", group.year, group.month)
+                    each=move || grouped_by_month()
+                    key=|group| format!("{}-{}-{}", group.totals.year, group.totals.month, group.transactions.len())
                     let(group)
                 >
                     <div class="month-group">
                         <div class="month-header">
-                            <h3>{format!("{}年{}月", group.year, group.month)}</h3>
+                            <h3>{format!("{}年{}月", group.totals.year, group.totals.month)}</h3>
                             <div class="month-totals">
                                 <span class="month-income">
-                                    {format!("收入: {:.2}", group.income)}
+                                    {format!("收入: {:.2}", group.totals.total_income)}
                                 </span>
                                 <span class="month-expense">
-                                    {format!("支出: {:.2}", group.expense + group.installment_expense)}
+                                    {format!("支出: {:.2}", group.totals.total_expense + group.totals.installment_expense)}
                                 </span>
                             </div>
                         </div>
 
                         // Show installment summary if any
-                        {if group.installment_expense > 0.0 {
+                        {if group.totals.installment_expense > 0.0 {
                             Some(view! {
                                 <div style="padding: 8px 12px; margin: 4px 0 8px 0; background: #fff3cd; border-radius: 6px; font-size: 13px; color: #856404;">
-                                    {format!("分期还款: {}笔 共 ¥{:.2}", group.installments.len(), group.installment_expense)}
+                                    {format!("分期还款: {}笔 共 ¥{:.2}", group.totals.installment_count, group.totals.installment_expense)}
                                 </div>
                             })
                         } else {
@@ -243,6 +217,9 @@ pub fn SummaryView(
                         </div>
                     </div>
                 </For>
+                <Show when=move || feed.loading.get()>
+                    <div class="load-more-status">"加载中..."</div>
+                </Show>
             </div>
             </div>
         </div>
@@ -63,3 +63,640 @@ pub struct InstallmentDetail {
     pub is_paid: bool,
     pub paid_date: Option<String>,
 }
+
+/// A bill that repeats every few months (rent, subscriptions)
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct RecurringBill {
+    pub id: i64,
+    pub name: String,
+    pub amount: f64,
+    /// Months between two occurrences (1 = monthly, 12 = yearly)
+    pub interval_months: i32,
+    pub start_date: String,
+    /// Last date the bill can fall on; None for open-ended
+    pub end_date: Option<String>,
+    pub created_at: String,
+}
+
+/// Input for creating a recurring bill
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct NewRecurringBill {
+    pub name: String,
+    pub amount: f64,
+    pub interval_months: i32,
+    pub start_date: String,
+    pub end_date: Option<String>,
+}
+
+/// Expense total of one category (图表用)
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct CategoryTotal {
+    pub category_id: i64,
+    pub category_name: String,
+    pub category_icon: Option<String>,
+    pub total: f64,
+    pub transaction_count: i32,
+}
+
+/// One month of income/expense totals for trend charts
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct MonthlyTrendPoint {
+    pub year: i32,
+    pub month: i32,
+    pub total_income: f64,
+    pub total_expense: f64,
+    pub installment_expense: f64,
+}
+
+/// Per-day income/expense totals for the calendar heatmap
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct DailyTotal {
+    pub date: String,
+    pub total_income: f64,
+    pub total_expense: f64,
+    pub transaction_count: i32,
+    pub installment_amount: f64,
+    pub installment_count: i32,
+}
+
+/// Totals of one month in a comparison report (expense includes installments)
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ComparisonPeriod {
+    pub year: i32,
+    pub month: i32,
+    pub total_income: f64,
+    pub total_expense: f64,
+    pub transaction_count: i32,
+}
+
+/// Month-over-month and year-over-year change of one category
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct CategoryComparison {
+    pub category_id: i64,
+    pub category_name: String,
+    pub category_icon: Option<String>,
+    pub current: f64,
+    pub previous: f64,
+    pub last_year: f64,
+    pub mom_change: f64,
+    pub mom_percent: Option<f64>,
+    pub yoy_change: f64,
+    pub yoy_percent: Option<f64>,
+    pub is_driver: bool,
+}
+
+/// This month vs last month vs the same month last year
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct MonthComparison {
+    pub current: ComparisonPeriod,
+    pub previous: ComparisonPeriod,
+    pub last_year: ComparisonPeriod,
+    pub categories: Vec<CategoryComparison>,
+}
+
+/// One month of the annual report
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct AnnualReportMonth {
+    pub month: i32,
+    pub total_income: f64,
+    /// Expense including installment payments
+    pub total_expense: f64,
+    /// Whether the month stayed within the baseline (None when not counted)
+    pub within_budget: Option<bool>,
+}
+
+/// Annual report (年度账单)
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AnnualReport {
+    pub year: i32,
+    pub total_income: f64,
+    pub total_expense: f64,
+    pub installment_expense: f64,
+    pub net_amount: f64,
+    pub transaction_count: i32,
+    pub top_categories: Vec<CategoryTotal>,
+    pub largest_expenses: Vec<TransactionWithCategory>,
+    pub months: Vec<AnnualReportMonth>,
+    pub most_expensive_month: Option<AnnualReportMonth>,
+    pub installments_started: Vec<InstallmentWithCategory>,
+    pub installments_finished: Vec<InstallmentWithCategory>,
+    pub budget: Option<f64>,
+    pub budget_months_within: i32,
+    pub budget_months_counted: i32,
+    pub budget_hit_rate: Option<f64>,
+}
+
+/// Income or expense
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionKind {
+    Income,
+    Expense,
+}
+
+/// Transaction search filter, all set conditions must match
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct TransactionFilter {
+    /// Note keywords separated by spaces
+    pub text: Option<String>,
+    pub category_ids: Vec<i64>,
+    /// Range of the absolute amount
+    pub min_amount: Option<f64>,
+    pub max_amount: Option<f64>,
+    /// Inclusive YYYY-MM-DD date range
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub kind: Option<TransactionKind>,
+}
+
+/// One page of transaction results
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct TransactionPage {
+    pub transactions: Vec<TransactionWithCategory>,
+    pub total_count: i64,
+    pub has_more: bool,
+}
+
+/// Position after the last transaction of a page (date + id)
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct TransactionCursor {
+    pub transaction_date: String,
+    pub id: i64,
+}
+
+/// One cursor-paged slice of transactions
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct TransactionCursorPage {
+    pub transactions: Vec<TransactionWithCategory>,
+    /// None once the last page has been loaded
+    pub next_cursor: Option<TransactionCursor>,
+}
+
+/// Income/expense totals of one month
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct MonthTotals {
+    pub year: i32,
+    pub month: i32,
+    pub total_income: f64,
+    pub total_expense: f64,
+    pub transaction_count: i32,
+    pub installment_expense: f64,
+    pub installment_count: i32,
+}
+
+/// Column of a CSV export
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvColumn {
+    Date,
+    Category,
+    Kind,
+    Amount,
+    Note,
+    CreatedAt,
+}
+
+impl CsvColumn {
+    pub const ALL: [CsvColumn; 6] = [
+        CsvColumn::Date,
+        CsvColumn::Category,
+        CsvColumn::Kind,
+        CsvColumn::Amount,
+        CsvColumn::Note,
+        CsvColumn::CreatedAt,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CsvColumn::Date => "日期",
+            CsvColumn::Category => "分类",
+            CsvColumn::Kind => "收支",
+            CsvColumn::Amount => "金额",
+            CsvColumn::Note => "备注",
+            CsvColumn::CreatedAt => "创建时间",
+        }
+    }
+}
+
+/// Text encoding of a CSV export
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvEncoding {
+    /// UTF-8 with BOM, opens correctly in Excel on Chinese Windows
+    #[default]
+    Utf8Bom,
+    Gb18030,
+}
+
+/// Options of a CSV export; empty fields mean "no limit" / "all columns"
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct CsvExportOptions {
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub category_ids: Vec<i64>,
+    pub columns: Vec<CsvColumn>,
+    pub encoding: CsvEncoding,
+    pub include_installments: bool,
+}
+
+/// Options of an Excel export; empty fields mean "no limit"
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct XlsxExportOptions {
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub category_ids: Vec<i64>,
+}
+
+/// File format of a monthly statement
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementFormat {
+    #[default]
+    Html,
+    /// Desktop only; printed by a locally installed Chrome / Edge / Chromium
+    Pdf,
+}
+
+/// Plain-text accounting format
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlainTextFormat {
+    #[default]
+    Beancount,
+    /// ledger-cli
+    Ledger,
+}
+
+/// Options of a Beancount / ledger-cli export; empty fields use the defaults
+/// (`Assets:Cash`, `CNY`)
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct PlainTextExportOptions {
+    pub format: PlainTextFormat,
+    pub asset_account: Option<String>,
+    pub currency: Option<String>,
+}
+
+/// Options of a calendar export of the unpaid installment periods and recurring bills
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct IcsExportOptions {
+    /// Remind this many days before each due date; None for no reminder
+    pub reminder_days: Option<u32>,
+}
+
+/// A saved filter query (smart view)
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct SmartView {
+    pub id: i64,
+    pub name: String,
+    pub query: String,
+    pub created_at: String,
+}
+
+/// How a backup is restored
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RestoreMode {
+    /// Empty the ledger and restore the backup as-is
+    Replace,
+    /// Add what is missing, keeping existing data
+    Merge,
+}
+
+/// Row counts per table of a backup or restore
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct BackupCounts {
+    pub categories: i64,
+    pub transactions: i64,
+    pub installments: i64,
+    pub installment_details: i64,
+    pub settings: i64,
+    pub smart_views: i64,
+    #[serde(default)]
+    pub rules: i64,
+    #[serde(default)]
+    pub csv_presets: i64,
+    #[serde(default)]
+    pub recurring_bills: i64,
+}
+
+/// `format` of a password-protected backup file
+pub const ENCRYPTED_BACKUP_FORMAT: &str = "keep-accounts-encrypted";
+
+/// What a backup file contains
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct BackupInfo {
+    pub version: u32,
+    pub exported_at: String,
+    pub counts: BackupCounts,
+}
+
+/// Outcome of a restore
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct RestoreSummary {
+    pub imported: BackupCounts,
+    pub skipped: BackupCounts,
+}
+
+/// Why a local snapshot was taken
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotReason {
+    Startup,
+    Daily,
+    Manual,
+    BeforeImport,
+    BeforeRestore,
+    BeforeRuleChanges,
+    BeforeDelete,
+    BeforeMerge,
+    BeforeMigration,
+}
+
+impl SnapshotReason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SnapshotReason::Startup => "启动时",
+            SnapshotReason::Daily => "每日",
+            SnapshotReason::Manual => "手动",
+            SnapshotReason::BeforeImport => "导入前",
+            SnapshotReason::BeforeRestore => "恢复前",
+            SnapshotReason::BeforeRuleChanges => "应用规则前",
+            SnapshotReason::BeforeDelete => "删除分类前",
+            SnapshotReason::BeforeMerge => "合并重复前",
+            SnapshotReason::BeforeMigration => "迁移前",
+        }
+    }
+}
+
+/// A copy of the database kept next to it
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct SnapshotInfo {
+    pub file_name: String,
+    pub reason: SnapshotReason,
+    pub created_at: String,
+    /// Bytes
+    pub size: u64,
+}
+
+/// This device's backups to a synced folder
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct BackupFolderSettings {
+    /// None when folder backups are off
+    pub path: Option<String>,
+    pub keep: u32,
+    pub encrypted: bool,
+}
+
+/// A backup found in the backup folder
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct FolderBackup {
+    pub file_name: String,
+    pub device_id: String,
+    pub created_at: String,
+    /// Bytes
+    pub size: u64,
+    pub from_this_device: bool,
+}
+
+/// How many snapshots rotation keeps
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub struct SnapshotPolicy {
+    pub keep_last: u32,
+    pub keep_daily: u32,
+    pub keep_weekly: u32,
+}
+
+/// Where an imported bill comes from
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    Alipay,
+    Wechat,
+    Ofx,
+    Qif,
+    Beancount,
+    Ledger,
+    /// Any other CSV, read with a `CsvMapping`
+    Csv,
+}
+
+impl ImportSource {
+    pub const ALL: [ImportSource; 7] = [
+        ImportSource::Alipay,
+        ImportSource::Wechat,
+        ImportSource::Ofx,
+        ImportSource::Qif,
+        ImportSource::Beancount,
+        ImportSource::Ledger,
+        ImportSource::Csv,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImportSource::Alipay => "支付宝账单（CSV）",
+            ImportSource::Wechat => "微信支付账单（xlsx / CSV）",
+            ImportSource::Ofx => "银行对账单（OFX）",
+            ImportSource::Qif => "银行对账单（QIF）",
+            ImportSource::Beancount => "Beancount 账本",
+            ImportSource::Ledger => "ledger-cli 账本",
+            ImportSource::Csv => "其他 CSV（自定义列）",
+        }
+    }
+
+    /// `accept` attribute of the file input
+    pub fn accept(&self) -> &'static str {
+        match self {
+            ImportSource::Alipay => ".csv,text/csv",
+            ImportSource::Wechat => ".xlsx,.csv,text/csv",
+            ImportSource::Ofx => ".ofx,.qfx",
+            ImportSource::Qif => ".qif",
+            ImportSource::Beancount => ".beancount,.bean,.txt",
+            ImportSource::Ledger => ".ledger,.journal,.dat,.txt",
+            ImportSource::Csv => ".csv,.txt,text/csv",
+        }
+    }
+}
+
+/// One bill line of an import preview
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ImportRow {
+    /// Line number in the bill file
+    pub line: u64,
+    pub transaction_date: String,
+    /// Negative for expenses
+    pub amount: f64,
+    pub note: Option<String>,
+    pub category_id: Option<i64>,
+    /// Category named in the file that does not exist yet; created on import
+    /// unless `category_id` is set
+    pub category_name: Option<String>,
+    /// The source's id of the line (FITID, order number...)
+    pub external_id: Option<String>,
+    pub account: Option<String>,
+    /// A ledger transaction this row likely duplicates
+    #[serde(default)]
+    pub duplicate_of: Option<String>,
+    /// Categories learned from the ledger, most likely first
+    #[serde(default)]
+    pub suggestions: Vec<CategorySuggestion>,
+    /// Why the row is not imported by default (refund, closed, already imported...)
+    pub skip_reason: Option<String>,
+}
+
+/// Outcome of an import
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct ImportResult {
+    pub imported: i64,
+}
+
+/// How a CSV's amounts tell expenses from income
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SignConvention {
+    /// Negative amounts are expenses
+    #[default]
+    Signed,
+    /// Positive amounts are expenses
+    Inverted,
+    /// Unsigned amounts; a type column says 收入 or 支出
+    TypeColumn,
+}
+
+impl SignConvention {
+    pub const ALL: [SignConvention; 3] = [SignConvention::Signed, SignConvention::Inverted, SignConvention::TypeColumn];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SignConvention::Signed => "负数为支出",
+            SignConvention::Inverted => "正数为支出",
+            SignConvention::TypeColumn => "由收支类型列决定",
+        }
+    }
+}
+
+/// Which CSV columns hold what (0-based)
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct CsvMapping {
+    pub delimiter: char,
+    /// Lines before the header
+    pub skip_rows: usize,
+    pub has_header: bool,
+    pub date_column: usize,
+    /// chrono format, e.g. `%Y-%m-%d`
+    pub date_format: String,
+    pub amount_column: usize,
+    pub sign: SignConvention,
+    pub type_column: Option<usize>,
+    pub category_column: Option<usize>,
+    pub note_column: Option<usize>,
+}
+
+/// Encoding, delimiter and first rows guessed from a CSV file
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct CsvSniff {
+    pub encoding: String,
+    pub delimiter: char,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// A saved CSV mapping
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct CsvPreset {
+    pub id: i64,
+    pub name: String,
+    pub mapping: CsvMapping,
+    pub created_at: String,
+}
+
+/// Outcome of creating a transaction
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct CreateTransactionResult {
+    /// Similar transactions already in the ledger; nothing was saved when not empty
+    pub duplicates: Vec<TransactionWithCategory>,
+}
+
+/// Two transactions that look like the same payment
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct DuplicatePair {
+    /// The earlier one
+    pub first: TransactionWithCategory,
+    pub second: TransactionWithCategory,
+    /// Similarity from 0 to 1
+    pub score: f64,
+}
+
+/// Auto-categorization rule; enabled rules are tried by `priority`, first match wins
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct CategoryRule {
+    pub id: i64,
+    pub name: String,
+    pub priority: i64,
+    pub enabled: bool,
+    /// Text looked for in the note; any note when empty
+    pub pattern: Option<String>,
+    pub is_regex: bool,
+    /// Bounds on the absolute amount
+    pub min_amount: Option<f64>,
+    pub max_amount: Option<f64>,
+    /// Account of imported rows
+    pub account: Option<String>,
+    pub category_id: Option<i64>,
+    /// Appended to the note as `#tag`
+    pub tags: Vec<String>,
+    /// Replaces the note
+    pub note_rewrite: Option<String>,
+    pub created_at: String,
+}
+
+/// A rule being created or edited
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Default)]
+pub struct NewCategoryRule {
+    pub name: String,
+    pub enabled: bool,
+    pub pattern: Option<String>,
+    pub is_regex: bool,
+    pub min_amount: Option<f64>,
+    pub max_amount: Option<f64>,
+    pub account: Option<String>,
+    pub category_id: Option<i64>,
+    pub tags: Vec<String>,
+    pub note_rewrite: Option<String>,
+}
+
+impl From<&CategoryRule> for NewCategoryRule {
+    fn from(rule: &CategoryRule) -> Self {
+        NewCategoryRule {
+            name: rule.name.clone(),
+            enabled: rule.enabled,
+            pattern: rule.pattern.clone(),
+            is_regex: rule.is_regex,
+            min_amount: rule.min_amount,
+            max_amount: rule.max_amount,
+            account: rule.account.clone(),
+            category_id: rule.category_id,
+            tags: rule.tags.clone(),
+            note_rewrite: rule.note_rewrite.clone(),
+        }
+    }
+}
+
+/// What re-running the rules would change on one transaction
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct RuleChange {
+    pub transaction_id: i64,
+    pub transaction_date: String,
+    pub amount: f64,
+    pub rule_name: String,
+    pub old_category_id: i64,
+    pub new_category_id: i64,
+    pub old_note: Option<String>,
+    pub new_note: Option<String>,
+}
+
+/// A category suggested from the ledger's history
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct CategorySuggestion {
+    pub category_id: i64,
+    /// Between 0 and 1
+    pub probability: f64,
+}